@@ -0,0 +1,268 @@
+/*!
+Middleware and extractors for [actix-web](https://docs.rs/actix-web), enabled with the `actix`
+feature.
+
+[`SupabaseAuth`] verifies the access token of every request with [`AuthClient::get_claims`] and
+inserts the [`Claims`] and the [`AccessToken`] into the request extensions, where handlers can
+read them with `web::ReqData<Claims>` or [`SupabaseClaims`]. Requests without a valid token are
+rejected before they reach the handler.
+
+Without the middleware, [`SupabaseClaims`] verifies the token itself. [`SupabaseUser`] fetches
+the user from the server instead, and refreshes an expired cookie session. Both read the token
+from the `Authorization: Bearer` header, or else the `@supabase/ssr` auth cookie, and take the
+[`AuthClient`] from the app data, as `web::Data<AuthClient>`.
+
+# Example
+
+```rust
+async fn me(SupabaseClaims(claims): SupabaseClaims) -> String {
+    claims.email
+}
+
+async fn profile(SupabaseUser(RequestUser { user, .. }): SupabaseUser) -> web::Json<User> {
+    web::Json(user)
+}
+
+let app = App::new()
+    .app_data(web::Data::new(auth_client.clone()))
+    .route("/profile", web::get().to(profile))
+    .service(
+        web::scope("/api")
+            .wrap(SupabaseAuth::new(auth_client))
+            .route("/me", web::get().to(me)),
+    );
+```
+*/
+
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header::WWW_AUTHENTICATE, StatusCode},
+    web, FromRequest, HttpMessage, HttpRequest, HttpResponse, ResponseError,
+};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::{
+    error::Error,
+    models::{AccessToken, AuthClient, Claims, RequestUser},
+};
+
+/// The verified claims of the request's access token, see [`AuthClient::get_claims`]
+///
+/// Taken from the request extensions if [`SupabaseAuth`] already verified the token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupabaseClaims(pub Claims);
+
+/// The user who made the request, see [`AuthClient::get_user_from_headers`]
+///
+/// If an expired cookie session was refreshed, the new session is in
+/// [`RequestUser::refreshed_session`] and must be sent back to the browser.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupabaseUser(pub RequestUser);
+
+/// Why a request was rejected by [`SupabaseAuth`], [`SupabaseClaims`], or [`SupabaseUser`]
+///
+/// Responds with `401 Unauthorized`, unless the auth server couldn't be reached or failed, which
+/// is reported as `503 Service Unavailable`, or no [`AuthClient`] was registered, which is a
+/// `500 Internal Server Error`. The error itself isn't sent to the client.
+#[derive(Debug)]
+pub struct AuthRejection(pub Error);
+
+impl std::fmt::Display for AuthRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<Error> for AuthRejection {
+    fn from(error: Error) -> Self {
+        AuthRejection(error)
+    }
+}
+
+impl ResponseError for AuthRejection {
+    fn status_code(&self) -> StatusCode {
+        match &self.0 {
+            error if error.is_unavailable() => StatusCode::SERVICE_UNAVAILABLE,
+            Error::MissingConfiguration(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+
+        if status == StatusCode::UNAUTHORIZED {
+            return HttpResponse::build(status)
+                .insert_header((WWW_AUTHENTICATE, "Bearer"))
+                .finish();
+        }
+
+        HttpResponse::new(status)
+    }
+}
+
+impl FromRequest for SupabaseClaims {
+    type Error = AuthRejection;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        // Already verified by `SupabaseAuth`
+        if let Some(claims) = request.extensions().get::<Claims>() {
+            let claims = claims.clone();
+            return Box::pin(async move { Ok(SupabaseClaims(claims)) });
+        }
+
+        let auth_client = auth_client(request);
+        let headers = headers(request);
+
+        Box::pin(async move {
+            let (_, claims) = verify(&auth_client?, &headers).await?;
+
+            Ok(SupabaseClaims(claims))
+        })
+    }
+}
+
+impl FromRequest for SupabaseUser {
+    type Error = AuthRejection;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let auth_client = auth_client(request);
+        let headers = headers(request);
+
+        Box::pin(async move {
+            Ok(SupabaseUser(
+                auth_client?.get_user_from_headers(&headers).await?,
+            ))
+        })
+    }
+}
+
+/// Middleware which only passes requests with a valid access token on to the wrapped service
+#[derive(Debug, Clone)]
+pub struct SupabaseAuth {
+    client: AuthClient,
+    allow_anonymous: bool,
+}
+
+impl SupabaseAuth {
+    /// Verify access tokens with `client`
+    pub fn new(client: AuthClient) -> Self {
+        SupabaseAuth {
+            client,
+            allow_anonymous: false,
+        }
+    }
+
+    /// Let requests without a token through, without claims in their extensions. Requests with an
+    /// invalid token are still rejected.
+    pub fn allow_anonymous(mut self, allow_anonymous: bool) -> Self {
+        self.allow_anonymous = allow_anonymous;
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for SupabaseAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = SupabaseAuthMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SupabaseAuthMiddleware {
+            service: Rc::new(service),
+            client: self.client.clone(),
+            allow_anonymous: self.allow_anonymous,
+        }))
+    }
+}
+
+/// The service created by [`SupabaseAuth`]
+#[derive(Debug)]
+pub struct SupabaseAuthMiddleware<S> {
+    service: Rc<S>,
+    client: AuthClient,
+    allow_anonymous: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for SupabaseAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let client = self.client.clone();
+        let allow_anonymous = self.allow_anonymous;
+
+        Box::pin(async move {
+            match verify(&client, &headers(request.request())).await {
+                Ok((access_token, claims)) => {
+                    request.extensions_mut().insert(access_token);
+                    request.extensions_mut().insert(claims);
+                }
+                Err(Error::NotAuthenticated) if allow_anonymous => {}
+                Err(error) => {
+                    return Ok(request
+                        .error_response(AuthRejection(error))
+                        .map_into_right_body());
+                }
+            }
+
+            service
+                .call(request)
+                .await
+                .map(ServiceResponse::map_into_left_body)
+        })
+    }
+}
+
+/// The access token a request was made with, and its verified claims
+async fn verify(client: &AuthClient, headers: &HeaderMap) -> Result<(AccessToken, Claims), Error> {
+    let access_token = client.access_token_from_headers(headers)?;
+    let claims = client.get_claims(&access_token).await?;
+
+    Ok((access_token, claims))
+}
+
+fn auth_client(request: &HttpRequest) -> Result<AuthClient, Error> {
+    request
+        .app_data::<web::Data<AuthClient>>()
+        .map(|client| AuthClient::clone(client))
+        .ok_or(Error::MissingConfiguration(
+            "web::Data<AuthClient> is not registered as app data",
+        ))
+}
+
+/// The request headers, as the `http` 1 types the client works with
+fn headers(request: &HttpRequest) -> HeaderMap {
+    request
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            Some((
+                HeaderName::from_bytes(name.as_str().as_bytes()).ok()?,
+                HeaderValue::from_bytes(value.as_bytes()).ok()?,
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,447 @@
+/*!
+The [`AuthApi`] trait, implemented by [`AuthClient`].
+
+Code taking an `impl AuthApi`, or an `Arc<dyn AuthApi>`, instead of an [`AuthClient`] can be
+tested with a fake answering with canned sessions and users, without a Supabase project. With the
+`mockall` feature, `MockAuthApi` is generated by
+[`mockall::automock`](https://docs.rs/mockall/latest/mockall/attr.automock.html).
+
+Async methods return an [`AuthFuture`], so the trait stays object safe, and the futures can be
+spawned and returned by mocks.
+
+# Example
+
+```rust
+struct Greeter<A> {
+    auth: A,
+}
+
+impl<A: AuthApi> Greeter<A> {
+    async fn greet(&self, access_token: &AccessToken) -> Result<String, Error> {
+        let user = self.auth.get_user(access_token).await?;
+
+        Ok(format!("Hello, {}!", user.email))
+    }
+}
+
+// In production
+let greeter = Greeter { auth: AuthClient::new_from_env()? };
+
+// In tests
+let mut auth = MockAuthApi::new();
+auth.expect_get_user()
+    .returning(|_| Box::pin(async { Ok(User::default()) }));
+
+let greeter = Greeter { auth };
+```
+*/
+
+use std::{borrow::Borrow, future::Future, pin::Pin, time::Duration};
+
+use reqwest::{header::HeaderMap, Url};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    models::{
+        AccessToken, AuthClient, AuthServerHealth, AuthServerSettings, Claims,
+        CompatibilityWarning, CreateOAuthClientParams, EmailSignUpResult, GenerateLinkParams,
+        GeneratedLink, IdTokenCredentials, ListUsersParams, LoginAnonymouslyOptions,
+        LoginEmailOtpParams, LoginWithOAuthOptions, LoginWithPasswordOptions, LoginWithSSO,
+        LogoutScope, OAuthClient, OAuthResponse, OTPResponse, Provider, RefreshToken, RequestUser,
+        ResendParams, ResetPasswordOptions, Session, SignUpWithPasswordOptions, SsoProvider,
+        UpdateSsoProviderParams, UpdatedUser, User, VerifyOtpParams,
+    },
+};
+
+/// The future returned by the async methods of [`AuthApi`]
+///
+/// It owns everything it needs, so mocks can return it and it can be spawned.
+pub type AuthFuture<T> = Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'static>>;
+
+/// An argument of an [`AuthClient`] method, which the [`AuthFuture`] keeps an owned copy of
+trait Arg<'a>: Sized {
+    type Owned: Send + 'static;
+
+    fn owned(self) -> Self::Owned;
+
+    fn arg(owned: &'a Self::Owned) -> Self;
+}
+
+impl<'a, T> Arg<'a> for &'a T
+where
+    T: ToOwned + ?Sized,
+    T::Owned: Send + 'static,
+{
+    type Owned = T::Owned;
+
+    fn owned(self) -> Self::Owned {
+        self.to_owned()
+    }
+
+    fn arg(owned: &'a Self::Owned) -> Self {
+        owned.borrow()
+    }
+}
+
+macro_rules! owned_args {
+    ($($ty:ty),*) => {
+        $(
+            impl Arg<'_> for $ty {
+                type Owned = $ty;
+
+                fn owned(self) -> Self::Owned {
+                    self
+                }
+
+                fn arg(owned: &Self::Owned) -> Self {
+                    owned.clone()
+                }
+            }
+        )*
+    };
+}
+
+owned_args!(
+    Duration,
+    Uuid,
+    Option<Value>,
+    Option<LoginAnonymouslyOptions>,
+    Option<LoginEmailOtpParams>,
+    Option<LogoutScope>,
+    Option<ResetPasswordOptions>,
+    Option<SignUpWithPasswordOptions>,
+    CreateOAuthClientParams,
+    GenerateLinkParams,
+    IdTokenCredentials,
+    ListUsersParams,
+    LoginWithPasswordOptions,
+    LoginWithSSO,
+    ResendParams,
+    UpdateSsoProviderParams,
+    UpdatedUser,
+    VerifyOtpParams
+);
+
+/// The operations of [`AuthClient`], to depend on instead of the client itself, see the
+/// [module docs](self)
+///
+/// Every method behaves like the [`AuthClient`] method with the same name.
+#[cfg_attr(feature = "mockall", mockall::automock)]
+pub trait AuthApi: Send + Sync {
+    /// See [`AuthClient::project_url`]
+    fn project_url(&self) -> &str;
+
+    /// See [`AuthClient::verify_access_token`]
+    fn verify_access_token(&self, access_token: &AccessToken) -> Result<Claims, Error>;
+
+    /// See [`AuthClient::access_token_from_headers`]
+    fn access_token_from_headers(&self, headers: &HeaderMap) -> Result<AccessToken, Error>;
+
+    /// See [`AuthClient::login_with_oauth`]
+    fn login_with_oauth(
+        &self,
+        provider: Provider,
+        options: Option<LoginWithOAuthOptions>,
+    ) -> Result<OAuthResponse, Error>;
+
+    /// See [`AuthClient::get_claims`]
+    fn get_claims(&self, access_token: &AccessToken) -> AuthFuture<Claims>;
+
+    /// See [`AuthClient::login_with_email`]
+    fn login_with_email(&self, email: &str, password: &str) -> AuthFuture<Session>;
+
+    /// See [`AuthClient::login_with_email_with_options`]
+    fn login_with_email_with_options(
+        &self,
+        email: &str,
+        password: &str,
+        options: LoginWithPasswordOptions,
+    ) -> AuthFuture<Session>;
+
+    /// See [`AuthClient::login_with_phone`]
+    fn login_with_phone(&self, phone: &str, password: &str) -> AuthFuture<Session>;
+
+    /// See [`AuthClient::login_with_phone_with_options`]
+    fn login_with_phone_with_options(
+        &self,
+        phone: &str,
+        password: &str,
+        options: LoginWithPasswordOptions,
+    ) -> AuthFuture<Session>;
+
+    /// See [`AuthClient::sign_up_with_email_and_password`]
+    fn sign_up_with_email_and_password(
+        &self,
+        email: &str,
+        password: &str,
+        options: Option<SignUpWithPasswordOptions>,
+    ) -> AuthFuture<EmailSignUpResult>;
+
+    /// See [`AuthClient::sign_up_with_phone_and_password`]
+    fn sign_up_with_phone_and_password(
+        &self,
+        phone: &str,
+        password: &str,
+        options: Option<SignUpWithPasswordOptions>,
+    ) -> AuthFuture<Session>;
+
+    /// See [`AuthClient::login_anonymously`]
+    fn login_anonymously(&self, options: Option<LoginAnonymouslyOptions>) -> AuthFuture<Session>;
+
+    /// See [`AuthClient::send_login_email_with_magic_link`]
+    fn send_login_email_with_magic_link(&self, email: &str) -> AuthFuture<()>;
+
+    /// See [`AuthClient::send_sms_with_otp`]
+    fn send_sms_with_otp(&self, phone: &str) -> AuthFuture<OTPResponse>;
+
+    /// See [`AuthClient::send_email_with_otp`]
+    fn send_email_with_otp(
+        &self,
+        email: &str,
+        options: Option<LoginEmailOtpParams>,
+    ) -> AuthFuture<OTPResponse>;
+
+    /// See [`AuthClient::get_user`]
+    fn get_user(&self, bearer_token: &AccessToken) -> AuthFuture<User>;
+
+    /// See [`AuthClient::get_user_from_headers`]
+    fn get_user_from_headers(&self, headers: &HeaderMap) -> AuthFuture<RequestUser>;
+
+    /// See [`AuthClient::update_user`]
+    fn update_user(
+        &self,
+        updated_user: UpdatedUser,
+        bearer_token: &AccessToken,
+    ) -> AuthFuture<User>;
+
+    /// See [`AuthClient::login_with_id_token`]
+    fn login_with_id_token(&self, credentials: IdTokenCredentials) -> AuthFuture<Session>;
+
+    /// See [`AuthClient::invite_user_by_email`]
+    fn invite_user_by_email(
+        &self,
+        email: &str,
+        data: Option<Value>,
+        bearer_token: &str,
+    ) -> AuthFuture<User>;
+
+    /// See [`AuthClient::verify_otp`]
+    fn verify_otp(&self, params: VerifyOtpParams) -> AuthFuture<Session>;
+
+    /// See [`AuthClient::get_health`]
+    fn get_health(&self) -> AuthFuture<AuthServerHealth>;
+
+    /// See [`AuthClient::wait_until_healthy`]
+    fn wait_until_healthy(
+        &self,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> AuthFuture<AuthServerHealth>;
+
+    /// See [`AuthClient::check_compatibility`]
+    fn check_compatibility(&self) -> AuthFuture<Vec<CompatibilityWarning>>;
+
+    /// See [`AuthClient::get_settings`]
+    fn get_settings(&self) -> AuthFuture<AuthServerSettings>;
+
+    /// See [`AuthClient::refresh_session`]
+    fn refresh_session(&self, refresh_token: &RefreshToken) -> AuthFuture<Session>;
+
+    /// See [`AuthClient::set_session`]
+    fn set_session(
+        &self,
+        access_token: &AccessToken,
+        refresh_token: &RefreshToken,
+    ) -> AuthFuture<Session>;
+
+    /// See [`AuthClient::exchange_code_for_session`]
+    fn exchange_code_for_session(
+        &self,
+        auth_code: &str,
+        code_verifier: &str,
+    ) -> AuthFuture<Session>;
+
+    /// See [`AuthClient::reset_password_for_email`]
+    fn reset_password_for_email(
+        &self,
+        email: &str,
+        options: Option<ResetPasswordOptions>,
+    ) -> AuthFuture<()>;
+
+    /// See [`AuthClient::resend`]
+    fn resend(&self, credentials: ResendParams) -> AuthFuture<()>;
+
+    /// See [`AuthClient::logout`]
+    fn logout(&self, scope: Option<LogoutScope>, bearer_token: &AccessToken) -> AuthFuture<()>;
+
+    /// See [`AuthClient::sso`]
+    fn sso(&self, params: LoginWithSSO) -> AuthFuture<Url>;
+
+    /// See [`AuthClient::get_saml_metadata`]
+    fn get_saml_metadata(&self) -> AuthFuture<String>;
+
+    /// See [`AuthClient::admin_list_sso_providers`]
+    fn admin_list_sso_providers(&self) -> AuthFuture<Vec<SsoProvider>>;
+
+    /// See [`AuthClient::admin_get_sso_provider`]
+    fn admin_get_sso_provider(&self, id: Uuid) -> AuthFuture<SsoProvider>;
+
+    /// See [`AuthClient::admin_update_sso_provider`]
+    fn admin_update_sso_provider(
+        &self,
+        id: Uuid,
+        params: UpdateSsoProviderParams,
+    ) -> AuthFuture<SsoProvider>;
+
+    /// See [`AuthClient::admin_delete_sso_provider`]
+    fn admin_delete_sso_provider(&self, id: Uuid) -> AuthFuture<SsoProvider>;
+
+    /// See [`AuthClient::admin_create_oauth_client`]
+    fn admin_create_oauth_client(&self, params: CreateOAuthClientParams)
+        -> AuthFuture<OAuthClient>;
+
+    /// See [`AuthClient::admin_list_oauth_clients`]
+    fn admin_list_oauth_clients(&self) -> AuthFuture<Vec<OAuthClient>>;
+
+    /// See [`AuthClient::admin_get_oauth_client`]
+    fn admin_get_oauth_client(&self, client_id: &str) -> AuthFuture<OAuthClient>;
+
+    /// See [`AuthClient::admin_delete_oauth_client`]
+    fn admin_delete_oauth_client(&self, client_id: &str) -> AuthFuture<()>;
+
+    /// See [`AuthClient::admin_regenerate_oauth_client_secret`]
+    fn admin_regenerate_oauth_client_secret(&self, client_id: &str) -> AuthFuture<OAuthClient>;
+
+    /// See [`AuthClient::admin_list_users`]
+    fn admin_list_users(&self, params: ListUsersParams) -> AuthFuture<Vec<User>>;
+
+    /// See [`AuthClient::admin_generate_link`]
+    fn admin_generate_link(&self, params: GenerateLinkParams) -> AuthFuture<GeneratedLink>;
+}
+
+/// Implements the async methods of [`AuthApi`] with the inherent methods of [`AuthClient`] of the
+/// same name and arguments
+macro_rules! impl_auth_api {
+    ($(fn $name:ident(&self $(, $arg:ident: $ty:ty)*) -> $ret:ty;)*) => {
+        impl AuthApi for AuthClient {
+            fn project_url(&self) -> &str {
+                AuthClient::project_url(self)
+            }
+
+            fn verify_access_token(&self, access_token: &AccessToken) -> Result<Claims, Error> {
+                AuthClient::verify_access_token(self, access_token)
+            }
+
+            fn access_token_from_headers(&self, headers: &HeaderMap) -> Result<AccessToken, Error> {
+                AuthClient::access_token_from_headers(self, headers)
+            }
+
+            fn login_with_oauth(
+                &self,
+                provider: Provider,
+                options: Option<LoginWithOAuthOptions>,
+            ) -> Result<OAuthResponse, Error> {
+                AuthClient::login_with_oauth(self, provider, options)
+            }
+
+            $(
+                fn $name(&self $(, $arg: $ty)*) -> AuthFuture<$ret> {
+                    let client = self.clone();
+                    $(let $arg = Arg::owned($arg);)*
+
+                    Box::pin(async move { client.$name($(Arg::arg(&$arg)),*).await })
+                }
+            )*
+        }
+    };
+}
+
+impl_auth_api! {
+    fn get_claims(&self, access_token: &AccessToken) -> Claims;
+    fn login_with_email(&self, email: &str, password: &str) -> Session;
+    fn login_with_email_with_options(
+        &self,
+        email: &str,
+        password: &str,
+        options: LoginWithPasswordOptions
+    ) -> Session;
+    fn login_with_phone(&self, phone: &str, password: &str) -> Session;
+    fn login_with_phone_with_options(
+        &self,
+        phone: &str,
+        password: &str,
+        options: LoginWithPasswordOptions
+    ) -> Session;
+    fn sign_up_with_email_and_password(
+        &self,
+        email: &str,
+        password: &str,
+        options: Option<SignUpWithPasswordOptions>
+    ) -> EmailSignUpResult;
+    fn sign_up_with_phone_and_password(
+        &self,
+        phone: &str,
+        password: &str,
+        options: Option<SignUpWithPasswordOptions>
+    ) -> Session;
+    fn login_anonymously(&self, options: Option<LoginAnonymouslyOptions>) -> Session;
+    fn send_login_email_with_magic_link(&self, email: &str) -> ();
+    fn send_sms_with_otp(&self, phone: &str) -> OTPResponse;
+    fn send_email_with_otp(
+        &self,
+        email: &str,
+        options: Option<LoginEmailOtpParams>
+    ) -> OTPResponse;
+    fn get_user(&self, bearer_token: &AccessToken) -> User;
+    fn get_user_from_headers(&self, headers: &HeaderMap) -> RequestUser;
+    fn update_user(&self, updated_user: UpdatedUser, bearer_token: &AccessToken) -> User;
+    fn login_with_id_token(&self, credentials: IdTokenCredentials) -> Session;
+    fn invite_user_by_email(
+        &self,
+        email: &str,
+        data: Option<Value>,
+        bearer_token: &str
+    ) -> User;
+    fn verify_otp(&self, params: VerifyOtpParams) -> Session;
+    fn get_health(&self) -> AuthServerHealth;
+    fn wait_until_healthy(
+        &self,
+        timeout: Duration,
+        poll_interval: Duration
+    ) -> AuthServerHealth;
+    fn check_compatibility(&self) -> Vec<CompatibilityWarning>;
+    fn get_settings(&self) -> AuthServerSettings;
+    fn refresh_session(&self, refresh_token: &RefreshToken) -> Session;
+    fn set_session(
+        &self,
+        access_token: &AccessToken,
+        refresh_token: &RefreshToken
+    ) -> Session;
+    fn exchange_code_for_session(&self, auth_code: &str, code_verifier: &str) -> Session;
+    fn reset_password_for_email(
+        &self,
+        email: &str,
+        options: Option<ResetPasswordOptions>
+    ) -> ();
+    fn resend(&self, credentials: ResendParams) -> ();
+    fn logout(&self, scope: Option<LogoutScope>, bearer_token: &AccessToken) -> ();
+    fn sso(&self, params: LoginWithSSO) -> Url;
+    fn get_saml_metadata(&self) -> String;
+    fn admin_list_sso_providers(&self) -> Vec<SsoProvider>;
+    fn admin_get_sso_provider(&self, id: Uuid) -> SsoProvider;
+    fn admin_update_sso_provider(
+        &self,
+        id: Uuid,
+        params: UpdateSsoProviderParams
+    ) -> SsoProvider;
+    fn admin_delete_sso_provider(&self, id: Uuid) -> SsoProvider;
+    fn admin_create_oauth_client(&self, params: CreateOAuthClientParams) -> OAuthClient;
+    fn admin_list_oauth_clients(&self) -> Vec<OAuthClient>;
+    fn admin_get_oauth_client(&self, client_id: &str) -> OAuthClient;
+    fn admin_delete_oauth_client(&self, client_id: &str) -> ();
+    fn admin_regenerate_oauth_client_secret(&self, client_id: &str) -> OAuthClient;
+    fn admin_list_users(&self, params: ListUsersParams) -> Vec<User>;
+    fn admin_generate_link(&self, params: GenerateLinkParams) -> GeneratedLink;
+}
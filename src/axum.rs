@@ -0,0 +1,170 @@
+/*!
+Extractors for [axum](https://docs.rs/axum) handlers, enabled with the `axum` feature.
+
+[`SupabaseClaims`] verifies the access token a request was made with, locally whenever possible.
+[`SupabaseUser`] fetches the user from the server instead, and refreshes an expired cookie
+session. Both read the token from the `Authorization: Bearer` header, or else the
+`@supabase/ssr` auth cookie, and reject the request with `401 Unauthorized` if it is missing or
+invalid.
+
+The [`AuthClient`] is taken from the router state, through [`FromRef`].
+
+# Example
+
+```rust
+#[derive(Clone, FromRef)]
+struct AppState {
+    auth_client: AuthClient,
+}
+
+async fn me(SupabaseClaims(claims): SupabaseClaims) -> String {
+    claims.email
+}
+
+async fn profile(SupabaseUser(RequestUser { user, .. }): SupabaseUser) -> Json<User> {
+    Json(user)
+}
+
+let app = Router::new()
+    .route("/me", get(me))
+    .route("/profile", get(profile))
+    .with_state(AppState { auth_client });
+```
+*/
+
+use ::axum::{
+    extract::{FromRef, FromRequestParts, OptionalFromRequestParts},
+    http::{header::WWW_AUTHENTICATE, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    error::Error,
+    models::{AuthClient, Claims, RequestUser},
+};
+
+/// The verified claims of the request's access token, see [`AuthClient::get_claims`]
+///
+/// Cheap when the client has the JWT secret or the project uses asymmetric signing keys, since
+/// the token is then verified without contacting the server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupabaseClaims(pub Claims);
+
+/// The user who made the request, see [`AuthClient::get_user_from_headers`]
+///
+/// If an expired cookie session was refreshed, the new session is in
+/// [`RequestUser::refreshed_session`] and must be sent back to the browser.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupabaseUser(pub RequestUser);
+
+/// Why a request was rejected by [`SupabaseClaims`] or [`SupabaseUser`]
+///
+/// Responds with `401 Unauthorized`, unless the auth server couldn't be reached or failed, which
+/// is reported as `503 Service Unavailable`. The error itself isn't sent to the client.
+#[derive(Debug)]
+pub struct AuthRejection(pub Error);
+
+impl AuthRejection {
+    /// The status code the request is rejected with
+    pub fn status(&self) -> StatusCode {
+        if self.0.is_unavailable() {
+            StatusCode::SERVICE_UNAVAILABLE
+        } else {
+            StatusCode::UNAUTHORIZED
+        }
+    }
+}
+
+impl From<Error> for AuthRejection {
+    fn from(error: Error) -> Self {
+        AuthRejection(error)
+    }
+}
+
+impl IntoResponse for AuthRejection {
+    fn into_response(self) -> Response {
+        let status = self.status();
+
+        if status == StatusCode::UNAUTHORIZED {
+            return (status, [(WWW_AUTHENTICATE, "Bearer")]).into_response();
+        }
+
+        status.into_response()
+    }
+}
+
+impl<S> FromRequestParts<S> for SupabaseClaims
+where
+    AuthClient: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Already verified by a middleware, e.g. `SupabaseAuthLayer`
+        if let Some(claims) = parts.extensions.get::<Claims>() {
+            return Ok(SupabaseClaims(claims.clone()));
+        }
+
+        let auth_client = AuthClient::from_ref(state);
+        let access_token = auth_client.access_token_from_headers(&parts.headers)?;
+
+        Ok(SupabaseClaims(auth_client.get_claims(&access_token).await?))
+    }
+}
+
+impl<S> FromRequestParts<S> for SupabaseUser
+where
+    AuthClient: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let auth_client = AuthClient::from_ref(state);
+
+        Ok(SupabaseUser(
+            auth_client.get_user_from_headers(&parts.headers).await?,
+        ))
+    }
+}
+
+/// Extracts `None` for anonymous requests, and still rejects invalid tokens
+impl<S> OptionalFromRequestParts<S> for SupabaseClaims
+where
+    AuthClient: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        match <Self as FromRequestParts<S>>::from_request_parts(parts, state).await {
+            Ok(claims) => Ok(Some(claims)),
+            Err(AuthRejection(Error::NotAuthenticated)) => Ok(None),
+            Err(rejection) => Err(rejection),
+        }
+    }
+}
+
+/// Extracts `None` for anonymous requests, and still rejects invalid tokens
+impl<S> OptionalFromRequestParts<S> for SupabaseUser
+where
+    AuthClient: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        match <Self as FromRequestParts<S>>::from_request_parts(parts, state).await {
+            Ok(user) => Ok(Some(user)),
+            Err(AuthRejection(Error::NotAuthenticated)) => Ok(None),
+            Err(rejection) => Err(rejection),
+        }
+    }
+}
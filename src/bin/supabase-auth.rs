@@ -0,0 +1,249 @@
+/*!
+The `supabase-auth` command line tool, built with the `cli` feature.
+
+```sh
+cargo install supabase-auth --features cli
+
+export SUPABASE_URL=https://YOUR_PROJECT_ID.supabase.co
+export SUPABASE_API_KEY=...
+export SUPABASE_JWT_SECRET=...
+
+supabase-auth login demo@example.com
+supabase-auth whoami
+supabase-auth admin list-users --per-page 10
+```
+
+The session of `login` and `signup` is saved to `~/.supabase-auth/session.json`, or the file
+given with `--session-file`, and used by `whoami`, `refresh`, and `logout`. The `admin`
+commands need the `service_role` key as `SUPABASE_API_KEY`. Output is JSON, to pipe into `jq`.
+*/
+
+use std::{
+    env,
+    error::Error as StdError,
+    io::{self, BufRead},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use serde_json::{json, Value};
+use supabase_auth::{
+    error::Error,
+    managed::ManagedAuthClient,
+    models::{
+        AuthClient, EmailSignUpResult, GenerateLinkParams, GenerateLinkType, ListUsersParams,
+        LogoutScope, SignUpWithPasswordOptions,
+    },
+    store::FileSessionStore,
+};
+
+/// Manage Supabase Auth users and sessions
+#[derive(Debug, Parser)]
+#[command(name = "supabase-auth", version)]
+struct Cli {
+    /// Where the session is saved
+    #[arg(long, global = true, env = "SUPABASE_AUTH_SESSION_FILE")]
+    session_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Sign in with an email and password, and save the session
+    Login {
+        email: String,
+        /// Read from stdin if not given
+        #[arg(long, env = "SUPABASE_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// Sign up with an email and password, and save the session if signed in
+    Signup {
+        email: String,
+        /// Read from stdin if not given
+        #[arg(long, env = "SUPABASE_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+        /// Where to send the user after they confirmed their email
+        #[arg(long)]
+        redirect_to: Option<String>,
+    },
+    /// Show the user of the saved session
+    Whoami,
+    /// Refresh the saved session
+    Refresh,
+    /// Sign out and remove the saved session
+    Logout {
+        /// Sign out of every session of the user
+        #[arg(long)]
+        global: bool,
+    },
+    /// Commands which need the `service_role` key
+    #[command(subcommand)]
+    Admin(AdminCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum AdminCommand {
+    /// List a page of the project's users
+    ListUsers {
+        /// Starts at 1
+        #[arg(long)]
+        page: Option<u32>,
+        #[arg(long)]
+        per_page: Option<u32>,
+    },
+    /// Generate an email link without sending the email
+    GenerateLink {
+        #[arg(value_enum)]
+        link_type: LinkType,
+        email: String,
+        /// Required for signup links
+        #[arg(long, env = "SUPABASE_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+        /// Required for email change links
+        #[arg(long)]
+        new_email: Option<String>,
+        /// JSON user metadata for new users
+        #[arg(long, value_parser = parse_json)]
+        data: Option<Value>,
+        #[arg(long)]
+        redirect_to: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum LinkType {
+    Signup,
+    Invite,
+    Magiclink,
+    Recovery,
+    EmailChangeCurrent,
+    EmailChangeNew,
+}
+
+impl From<LinkType> for GenerateLinkType {
+    fn from(link_type: LinkType) -> Self {
+        match link_type {
+            LinkType::Signup => GenerateLinkType::Signup,
+            LinkType::Invite => GenerateLinkType::Invite,
+            LinkType::Magiclink => GenerateLinkType::Magiclink,
+            LinkType::Recovery => GenerateLinkType::Recovery,
+            LinkType::EmailChangeCurrent => GenerateLinkType::EmailChangeCurrent,
+            LinkType::EmailChangeNew => GenerateLinkType::EmailChangeNew,
+        }
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn StdError>> {
+    let client = AuthClient::new_from_env()?;
+    let store = FileSessionStore::new(match cli.session_file {
+        Some(path) => path,
+        None => default_session_file().ok_or(Error::MissingConfiguration("HOME"))?,
+    });
+    let auth = ManagedAuthClient::with_store(client, store)?;
+
+    match cli.command {
+        Command::Login { email, password } => {
+            let password = password_or_stdin(password)?;
+            print(&auth.login_with_email(&email, &password).await?)
+        }
+        Command::Signup {
+            email,
+            password,
+            redirect_to,
+        } => {
+            let password = password_or_stdin(password)?;
+            let options = redirect_to.map(|redirect_to| {
+                SignUpWithPasswordOptions::default().email_redirect_to(redirect_to)
+            });
+
+            match auth
+                .sign_up_with_email_and_password(&email, &password, options)
+                .await?
+            {
+                EmailSignUpResult::SessionResult(session) => print(&session),
+                EmailSignUpResult::ConfirmationResult(confirmation) => print(&json!({
+                    "id": confirmation.id,
+                    "email": confirmation.email,
+                    "confirmation_sent_at": confirmation.confirmation_sent_at,
+                })),
+            }
+        }
+        Command::Whoami => print(&auth.get_user().await?),
+        Command::Refresh => print(&auth.refresh_session().await?),
+        Command::Logout { global } => {
+            let scope = global.then_some(LogoutScope::Global);
+            Ok(auth.logout(scope).await?)
+        }
+        Command::Admin(AdminCommand::ListUsers { page, per_page }) => {
+            let mut params = ListUsersParams::default();
+            params.page = page;
+            params.per_page = per_page;
+
+            print(&auth.client().admin_list_users(params).await?)
+        }
+        Command::Admin(AdminCommand::GenerateLink {
+            link_type,
+            email,
+            password,
+            new_email,
+            data,
+            redirect_to,
+        }) => {
+            let mut params = GenerateLinkParams::new(link_type.into(), email);
+            params.password = password;
+            params.new_email = new_email;
+            params.data = data;
+            params.redirect_to = redirect_to;
+
+            print(&auth.client().admin_generate_link(params).await?)
+        }
+    }
+}
+
+/// `~/.supabase-auth/session.json`
+fn default_session_file() -> Option<PathBuf> {
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+
+    Some(
+        PathBuf::from(home)
+            .join(".supabase-auth")
+            .join("session.json"),
+    )
+}
+
+fn password_or_stdin(password: Option<String>) -> io::Result<String> {
+    if let Some(password) = password {
+        return Ok(password);
+    }
+
+    let mut password = String::new();
+    io::stdin().lock().read_line(&mut password)?;
+
+    Ok(password.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn parse_json(data: &str) -> Result<Value, serde_json::Error> {
+    serde_json::from_str(data)
+}
+
+fn print(value: &impl Serialize) -> Result<(), Box<dyn StdError>> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
@@ -0,0 +1,251 @@
+/*!
+A synchronous client, enabled with the `blocking` feature.
+
+[`AuthClient`] wraps the async [`AuthClient`](crate::models::AuthClient) together with a
+single threaded Tokio runtime, and blocks the calling thread until each request is done. Useful
+for scripts, build tools, and codebases without an async runtime.
+
+Don't use it from async code: blocking inside a Tokio runtime panics.
+
+# Example
+
+```rust
+let auth_client = supabase_auth::blocking::AuthClient::new_from_env()?;
+
+let session = auth_client.login_with_email(&email, &password)?;
+let user = auth_client.get_user(&session.access_token)?;
+```
+*/
+
+use std::{future::Future, sync::Arc, time::Duration};
+
+use reqwest::{header::HeaderMap, Url};
+use serde_json::Value;
+use tokio::runtime::{Builder, Runtime};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    models::{
+        self, AccessToken, AuthServerHealth, AuthServerSettings, Claims, CompatibilityWarning,
+        CreateOAuthClientParams, EmailSignUpResult, EnvConfig, GenerateLinkParams, GeneratedLink,
+        IdTokenCredentials, ListUsersParams, LoginAnonymouslyOptions, LoginEmailOtpParams,
+        LoginWithOAuthOptions, LoginWithPasswordOptions, LoginWithSSO, LogoutScope, OAuthClient,
+        OAuthResponse, OTPResponse, Provider, RefreshToken, RequestUser, ResendParams,
+        ResetPasswordOptions, Session, SignUpWithPasswordOptions, SsoProvider,
+        UpdateSsoProviderParams, UpdatedUser, User, VerifyOtpParams,
+    },
+};
+
+/// Blocking versions of the async client's methods, see the [module docs](self)
+///
+/// Cloning is cheap, clones share the connection pool and the runtime.
+#[derive(Debug, Clone)]
+pub struct AuthClient {
+    inner: models::AuthClient,
+    runtime: Arc<Runtime>,
+}
+
+/// Wraps async methods of [`models::AuthClient`] with the same name and arguments
+macro_rules! blocking {
+    ($(fn $name:ident(&self $(, $arg:ident: $ty:ty)*) -> $ret:ty;)*) => {
+        $(
+            #[doc = concat!(
+                "Blocking [`AuthClient::", stringify!($name),
+                "`](crate::models::AuthClient::", stringify!($name), ")"
+            )]
+            pub fn $name(&self $(, $arg: $ty)*) -> $ret {
+                self.block_on(self.inner.$name($($arg),*))
+            }
+        )*
+    };
+}
+
+impl AuthClient {
+    /// Create a new blocking client, see [`AuthClient::new`](crate::models::AuthClient::new)
+    ///
+    /// # Panics
+    /// If the runtime can't be created, like `reqwest::blocking::Client::new`.
+    pub fn new(
+        project_url: impl Into<String>,
+        api_key: impl Into<String>,
+        jwt_secret: impl Into<String>,
+    ) -> Self {
+        models::AuthClient::new(project_url, api_key, jwt_secret).into()
+    }
+
+    /// Create a new blocking client from the `SUPABASE_URL`, `SUPABASE_API_KEY`, and
+    /// `SUPABASE_JWT_SECRET` environment variables
+    pub fn new_from_env() -> Result<Self, Error> {
+        models::AuthClient::new_from_env().map(Into::into)
+    }
+
+    /// Create a new blocking client from the environment variables named in `config`
+    pub fn new_from_env_with(config: EnvConfig) -> Result<Self, Error> {
+        models::AuthClient::new_from_env_with(config).map(Into::into)
+    }
+
+    /// The async client requests are made with
+    pub fn inner(&self) -> &models::AuthClient {
+        &self.inner
+    }
+
+    /// Get the project URL
+    pub fn project_url(&self) -> &str {
+        self.inner.project_url()
+    }
+
+    /// Verify an access token locally, see
+    /// [`AuthClient::verify_access_token`](crate::models::AuthClient::verify_access_token)
+    pub fn verify_access_token(&self, access_token: &AccessToken) -> Result<Claims, Error> {
+        self.inner.verify_access_token(access_token)
+    }
+
+    /// The URL to sign in with an OAuth provider at, see
+    /// [`AuthClient::login_with_oauth`](crate::models::AuthClient::login_with_oauth)
+    pub fn login_with_oauth(
+        &self,
+        provider: Provider,
+        options: Option<LoginWithOAuthOptions>,
+    ) -> Result<OAuthResponse, Error> {
+        self.inner.login_with_oauth(provider, options)
+    }
+
+    blocking! {
+        fn get_claims(&self, access_token: &AccessToken) -> Result<Claims, Error>;
+        fn login_with_email(&self, email: &str, password: &str) -> Result<Session, Error>;
+        fn login_with_email_with_options(
+            &self,
+            email: &str,
+            password: &str,
+            options: LoginWithPasswordOptions
+        ) -> Result<Session, Error>;
+        fn login_with_phone(&self, phone: &str, password: &str) -> Result<Session, Error>;
+        fn login_with_phone_with_options(
+            &self,
+            phone: &str,
+            password: &str,
+            options: LoginWithPasswordOptions
+        ) -> Result<Session, Error>;
+        fn sign_up_with_email_and_password(
+            &self,
+            email: &str,
+            password: &str,
+            options: Option<SignUpWithPasswordOptions>
+        ) -> Result<EmailSignUpResult, Error>;
+        fn sign_up_with_phone_and_password(
+            &self,
+            phone: &str,
+            password: &str,
+            options: Option<SignUpWithPasswordOptions>
+        ) -> Result<Session, Error>;
+        fn login_anonymously(
+            &self,
+            options: Option<LoginAnonymouslyOptions>
+        ) -> Result<Session, Error>;
+        fn send_login_email_with_magic_link(&self, email: &str) -> Result<(), Error>;
+        fn send_sms_with_otp(&self, phone: &str) -> Result<OTPResponse, Error>;
+        fn send_email_with_otp(
+            &self,
+            email: &str,
+            options: Option<LoginEmailOtpParams>
+        ) -> Result<OTPResponse, Error>;
+        fn get_user(&self, bearer_token: &AccessToken) -> Result<User, Error>;
+        fn get_user_from_headers(&self, headers: &HeaderMap) -> Result<RequestUser, Error>;
+        fn update_user(
+            &self,
+            updated_user: UpdatedUser,
+            bearer_token: &AccessToken
+        ) -> Result<User, Error>;
+        fn login_with_id_token(&self, credentials: IdTokenCredentials) -> Result<Session, Error>;
+        fn invite_user_by_email(
+            &self,
+            email: &str,
+            data: Option<Value>,
+            bearer_token: &str
+        ) -> Result<User, Error>;
+        fn verify_otp(&self, params: VerifyOtpParams) -> Result<Session, Error>;
+        fn get_health(&self) -> Result<AuthServerHealth, Error>;
+        fn wait_until_healthy(
+            &self,
+            timeout: Duration,
+            poll_interval: Duration
+        ) -> Result<AuthServerHealth, Error>;
+        fn check_compatibility(&self) -> Result<Vec<CompatibilityWarning>, Error>;
+        fn get_settings(&self) -> Result<AuthServerSettings, Error>;
+        fn get_settings_cached(&self) -> Result<AuthServerSettings, Error>;
+        fn refresh_session(&self, refresh_token: &RefreshToken) -> Result<Session, Error>;
+        fn set_session(
+            &self,
+            access_token: &AccessToken,
+            refresh_token: &RefreshToken
+        ) -> Result<Session, Error>;
+        fn exchange_code_for_session(
+            &self,
+            auth_code: &str,
+            code_verifier: &str
+        ) -> Result<Session, Error>;
+        fn reset_password_for_email(
+            &self,
+            email: &str,
+            options: Option<ResetPasswordOptions>
+        ) -> Result<(), Error>;
+        fn resend(&self, credentials: ResendParams) -> Result<(), Error>;
+        fn logout(
+            &self,
+            scope: Option<LogoutScope>,
+            bearer_token: &AccessToken
+        ) -> Result<(), Error>;
+        fn sso(&self, params: LoginWithSSO) -> Result<Url, Error>;
+        fn get_saml_metadata(&self) -> Result<String, Error>;
+        fn admin_list_sso_providers(&self) -> Result<Vec<SsoProvider>, Error>;
+        fn admin_get_sso_provider(&self, id: Uuid) -> Result<SsoProvider, Error>;
+        fn admin_update_sso_provider(
+            &self,
+            id: Uuid,
+            params: UpdateSsoProviderParams
+        ) -> Result<SsoProvider, Error>;
+        fn admin_delete_sso_provider(&self, id: Uuid) -> Result<SsoProvider, Error>;
+        fn admin_create_oauth_client(
+            &self,
+            params: CreateOAuthClientParams
+        ) -> Result<OAuthClient, Error>;
+        fn admin_list_oauth_clients(&self) -> Result<Vec<OAuthClient>, Error>;
+        fn admin_get_oauth_client(&self, client_id: &str) -> Result<OAuthClient, Error>;
+        fn admin_delete_oauth_client(&self, client_id: &str) -> Result<(), Error>;
+        fn admin_regenerate_oauth_client_secret(
+            &self,
+            client_id: &str
+        ) -> Result<OAuthClient, Error>;
+        fn admin_list_users(&self, params: ListUsersParams) -> Result<Vec<User>, Error>;
+        fn admin_generate_link(&self, params: GenerateLinkParams) -> Result<GeneratedLink, Error>;
+    }
+
+    /// Blocking [`AuthClient::invalidate_settings_cache`](crate::models::AuthClient::invalidate_settings_cache)
+    pub fn invalidate_settings_cache(&self) {
+        self.block_on(self.inner.invalidate_settings_cache())
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
+
+impl From<models::AuthClient> for AuthClient {
+    /// Make requests with `client`, e.g. one configured with
+    /// [`AuthClient::builder`](crate::models::AuthClient::builder)
+    ///
+    /// # Panics
+    /// If the runtime can't be created.
+    fn from(client: models::AuthClient) -> Self {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to create the blocking client's runtime");
+
+        AuthClient {
+            inner: client,
+            runtime: Arc::new(runtime),
+        }
+    }
+}
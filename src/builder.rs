@@ -0,0 +1,440 @@
+/*!
+Step by step construction of an [`AuthClient`].
+
+[`AuthClientBuilder`] lets you hand the client a pre-configured [`reqwest::Client`], so the auth
+client shares a connection pool, TLS, and proxy settings with the rest of your application.
+
+# Example
+
+```rust
+let http_client = reqwest::Client::builder()
+    .timeout(Duration::from_secs(10))
+    .build()
+    .unwrap();
+
+let auth_client = AuthClient::builder()
+    .http_client(http_client)
+    .project_url(project_url)
+    .api_key(api_key)
+    .jwt_secret(jwt_secret)
+    .build()
+    .unwrap();
+```
+*/
+
+use std::{fmt, sync::Arc, time::Duration};
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Client, ClientBuilder, Proxy, Url,
+};
+
+use crate::{
+    circuit_breaker::{Circuit, CircuitBreaker},
+    client::{CLIENT_INFO, X_CLIENT_INFO},
+    error::Error,
+    meta::{ResponseHooks, ResponseMeta},
+    models::{redact, AuthClient},
+    rate_limit::{RateLimiter, RateLimits},
+    retry::RetryPolicy,
+    transport::HttpTransport,
+};
+
+/// Builder for [`AuthClient`], created with [`AuthClient::builder`]
+///
+/// `project_url` and `api_key` are required. Without a `jwt_secret`, HS256 access tokens are
+/// checked by the server instead of locally, see [`AuthClient::get_claims`].
+#[derive(Default, Clone)]
+pub struct AuthClientBuilder {
+    http_client: Option<Client>,
+    project_url: Option<String>,
+    api_key: Option<String>,
+    jwt_secret: Option<String>,
+    headers: HeaderMap,
+    timeout: Option<Duration>,
+    retry_policy: Option<RetryPolicy>,
+    rate_limits: Option<RateLimits>,
+    circuit_breaker: Option<CircuitBreaker>,
+    proxies: Vec<Proxy>,
+    pool: PoolSettings,
+    auth_path: Option<String>,
+    skip_api_key: bool,
+    client_info: Option<String>,
+    transport: Option<Arc<dyn HttpTransport>>,
+    response_hooks: ResponseHooks,
+    #[cfg(feature = "logging")]
+    logger: Option<Arc<crate::logging::Logger>>,
+}
+
+impl fmt::Debug for AuthClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("AuthClientBuilder");
+        debug
+            .field("http_client", &self.http_client)
+            .field("project_url", &self.project_url)
+            .field("api_key", &redact(&self.api_key))
+            .field("jwt_secret", &redact(&self.jwt_secret))
+            .field("headers", &self.headers)
+            .field("timeout", &self.timeout)
+            .field("retry_policy", &self.retry_policy)
+            .field("rate_limits", &self.rate_limits)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .field("proxies", &self.proxies)
+            .field("pool", &self.pool)
+            .field("auth_path", &self.auth_path)
+            .field("skip_api_key", &self.skip_api_key)
+            .field("client_info", &self.client_info)
+            .field("transport", &self.transport)
+            .field("response_hooks", &self.response_hooks);
+        #[cfg(feature = "logging")]
+        debug.field("logger", &self.logger.is_some());
+        debug.finish()
+    }
+}
+
+impl AuthClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `http_client` for all requests instead of creating a new one
+    pub fn http_client(mut self, http_client: Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// Send requests through `proxy`, e.g. an egress proxy in an enterprise network. Can be
+    /// called multiple times, the first proxy matching a request is used.
+    ///
+    /// Proxies are configured on the HTTP client created by the builder, so they can't be
+    /// combined with [`AuthClientBuilder::http_client`]; configure the proxy on that client instead.
+    /// SOCKS proxies need the `socks` feature.
+    ///
+    /// # Example
+    /// ```
+    /// let auth_client = AuthClient::builder()
+    ///     .project_url(project_url)
+    ///     .api_key(api_key)
+    ///     .proxy(Proxy::all("http://proxy.internal:3128")?)
+    ///     .build()?;
+    /// ```
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Keep at most `max` idle connections per host, reqwest keeps all of them by default
+    ///
+    /// Like the other connection pool settings, this configures the HTTP client created by the
+    /// builder, so it can't be combined with [`AuthClientBuilder::http_client`] or
+    /// [`AuthClientBuilder::transport`].
+    ///
+    /// # Example
+    /// ```
+    /// let auth_client = AuthClient::builder()
+    ///     .project_url(project_url)
+    ///     .api_key(api_key)
+    ///     .pool_max_idle_per_host(64)
+    ///     .pool_idle_timeout(Duration::from_secs(300))
+    ///     .tcp_keepalive(Duration::from_secs(60))
+    ///     .build()?;
+    /// ```
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool.max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Close connections which were idle for `timeout`, 90 seconds by default
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Send TCP keep-alive probes on idle connections every `interval`, none by default
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.pool.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Talk HTTP/2 to the server without negotiating it first, multiplexing all requests over
+    /// one connection per host. Needs the `http2` feature.
+    #[cfg(feature = "http2")]
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.pool.http2_prior_knowledge = true;
+        self
+    }
+
+    /// Ping HTTP/2 connections every `interval` to keep them alive, closing them if a ping isn't
+    /// answered within `timeout`. Needs the `http2` feature.
+    #[cfg(feature = "http2")]
+    pub fn http2_keep_alive(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.pool.http2_keep_alive = Some((interval, timeout));
+        self
+    }
+
+    /// Send requests with `transport` instead of a `reqwest::Client`, see [`HttpTransport`]
+    ///
+    /// Can't be combined with [`AuthClientBuilder::proxy`], configure proxies on the transport
+    /// instead.
+    pub fn transport(mut self, transport: impl HttpTransport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Your project's URL, e.g. `https://YOUR_PROJECT_ID.supabase.co`
+    pub fn project_url(mut self, project_url: impl Into<String>) -> Self {
+        self.project_url = Some(project_url.into());
+        self
+    }
+
+    /// Your project's anon, publishable, or service role key
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Serve the auth API from `auth_path` relative to the project URL, instead of `/auth/v1`
+    ///
+    /// Self-hosted GoTrue servers are often mounted at the root, use `""` for those.
+    ///
+    /// # Example
+    /// ```
+    /// let auth_client = AuthClient::builder()
+    ///     .project_url("http://localhost:9999")
+    ///     .auth_path("")
+    ///     .without_api_key()
+    ///     .build()?;
+    /// ```
+    pub fn auth_path(mut self, auth_path: impl Into<String>) -> Self {
+        self.auth_path = Some(auth_path.into());
+        self
+    }
+
+    /// Don't send the `apikey` header, which only the Supabase API gateway needs. Makes the api
+    /// key optional.
+    pub fn without_api_key(mut self) -> Self {
+        self.skip_api_key = true;
+        self
+    }
+
+    /// Your project's JWT secret, used to verify HS256 access tokens locally
+    pub fn jwt_secret(mut self, jwt_secret: impl Into<String>) -> Self {
+        self.jwt_secret = Some(jwt_secret.into());
+        self
+    }
+
+    /// Send `headers` with every request, e.g. tenant or tracing headers expected by an API
+    /// gateway. Can be called multiple times, later headers replace earlier ones with the same
+    /// name.
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Send the header `name: value` with every request. See
+    /// [`AuthClientBuilder::default_headers`].
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Identify your application in the `X-Client-Info` header, after this library's name and
+    /// version, e.g. `supabase-auth-rs/0.10.13 my-app/1.2.0`. Shows up in Supabase logs and
+    /// helps support attribute traffic.
+    pub fn client_info(mut self, client_info: impl Into<String>) -> Self {
+        self.client_info = Some(client_info.into());
+        self
+    }
+
+    /// Abort requests which take longer than `timeout`. Individual calls can use a different
+    /// timeout with [`AuthClient::with_timeout`].
+    ///
+    /// Unlike a timeout set on the `reqwest::Client`, this only applies to requests made by the
+    /// auth client.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry requests which failed with a rate limit or server error, see [`RetryPolicy`].
+    /// Requests aren't retried by default.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Pace requests to stay below the server's rate limits, see [`RateLimits`]. The limits are
+    /// shared by all clones of the built client. Requests aren't limited by default.
+    pub fn rate_limits(mut self, rate_limits: RateLimits) -> Self {
+        self.rate_limits = Some(rate_limits);
+        self
+    }
+
+    /// Fail requests right away while the server keeps failing, see [`CircuitBreaker`]. The
+    /// circuit is shared by all clones of the built client. Requests are always sent by default.
+    pub fn circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Call `hook` with the [`ResponseMeta`] of every response the client receives, retries
+    /// included, e.g. to log the `sb-request-id` of each request. Can be called more than once,
+    /// the hooks run in the order they were added.
+    pub fn on_response(mut self, hook: impl Fn(&ResponseMeta) + Send + Sync + 'static) -> Self {
+        self.response_hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Call `logger` with a sanitized summary of every request the client sends, see
+    /// [`logging`](crate::logging). Replaces a logger set before.
+    #[cfg(feature = "logging")]
+    pub fn logger(
+        mut self,
+        logger: impl Fn(&crate::logging::RequestLog) + Send + Sync + 'static,
+    ) -> Self {
+        self.logger = Some(Arc::new(logger));
+        self
+    }
+
+    /// Build the client
+    ///
+    /// Fails with [`Error::MissingConfiguration`] if the project URL or api key weren't set (unless
+    /// [`AuthClientBuilder::without_api_key`] was called), with
+    /// [`Error::ParseUrlError`] if the project URL is invalid, and with
+    /// [`Error::ConflictingConfiguration`] if proxies or connection pool settings were set
+    /// together with an HTTP client or transport.
+    pub fn build(self) -> Result<AuthClient, Error> {
+        let project_url = self
+            .project_url
+            .ok_or(Error::MissingConfiguration("project_url"))?;
+        let api_key = match self.api_key {
+            Some(api_key) => api_key,
+            None if self.skip_api_key => String::new(),
+            None => return Err(Error::MissingConfiguration("api_key")),
+        };
+
+        Url::parse(&project_url).map_err(|_| Error::ParseUrlError)?;
+
+        let client_setting = if !self.proxies.is_empty() {
+            Some("proxy")
+        } else {
+            self.pool.first_set()
+        };
+        if let Some(setting) = client_setting {
+            if self.transport.is_some() || self.http_client.is_some() {
+                return Err(Error::ConflictingConfiguration(setting));
+            }
+        }
+
+        let http_client = match self.http_client {
+            Some(http_client) => http_client,
+            None => {
+                let builder = self
+                    .proxies
+                    .into_iter()
+                    .fold(Client::builder(), |builder, proxy| builder.proxy(proxy));
+                self.pool.apply(builder).build()?
+            }
+        };
+
+        let mut client = AuthClient::from_parts(
+            http_client,
+            project_url,
+            api_key,
+            self.jwt_secret.unwrap_or_default(),
+        );
+        if let Some(client_info) = self.client_info {
+            Arc::make_mut(&mut client.headers).insert(
+                X_CLIENT_INFO,
+                HeaderValue::from_str(&format!("{} {}", CLIENT_INFO, client_info))?,
+            );
+        }
+        Arc::make_mut(&mut client.headers).extend(self.headers);
+        client.timeout = self.timeout;
+        if let Some(transport) = self.transport {
+            client.transport = transport;
+        }
+        client.retry_policy = self.retry_policy.map(Arc::new);
+        client.send_api_key = !self.skip_api_key;
+        if let Some(auth_path) = self.auth_path {
+            let auth_path = auth_path.trim_end_matches('/');
+            client.auth_path = if auth_path.is_empty() || auth_path.starts_with('/') {
+                auth_path.into()
+            } else {
+                format!("/{}", auth_path).into()
+            };
+        }
+        client.rate_limiter = self
+            .rate_limits
+            .map(|rate_limits| Arc::new(RateLimiter::new(rate_limits)));
+        client.circuit = self
+            .circuit_breaker
+            .map(|circuit_breaker| Arc::new(Circuit::new(circuit_breaker)));
+        client.response_hooks = self.response_hooks;
+        #[cfg(feature = "logging")]
+        {
+            client.logger = self.logger;
+        }
+
+        Ok(client)
+    }
+}
+
+/// Connection pool settings of the HTTP client created by [`AuthClientBuilder`]
+#[derive(Debug, Default, Clone)]
+struct PoolSettings {
+    max_idle_per_host: Option<usize>,
+    idle_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+    #[cfg(feature = "http2")]
+    http2_prior_knowledge: bool,
+    #[cfg(feature = "http2")]
+    http2_keep_alive: Option<(Duration, Duration)>,
+}
+
+impl PoolSettings {
+    /// The name of the first setting which was set, for [`Error::ConflictingConfiguration`]
+    fn first_set(&self) -> Option<&'static str> {
+        #[cfg(feature = "http2")]
+        if self.http2_prior_knowledge {
+            return Some("http2_prior_knowledge");
+        } else if self.http2_keep_alive.is_some() {
+            return Some("http2_keep_alive");
+        }
+
+        if self.max_idle_per_host.is_some() {
+            Some("pool_max_idle_per_host")
+        } else if self.idle_timeout.is_some() {
+            Some("pool_idle_timeout")
+        } else if self.tcp_keepalive.is_some() {
+            Some("tcp_keepalive")
+        } else {
+            None
+        }
+    }
+
+    fn apply(self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(max) = self.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        #[cfg(feature = "http2")]
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        #[cfg(feature = "http2")]
+        if let Some((interval, timeout)) = self.http2_keep_alive {
+            builder = builder
+                .http2_keep_alive_interval(interval)
+                .http2_keep_alive_timeout(timeout)
+                .http2_keep_alive_while_idle(true);
+        }
+
+        builder
+    }
+}
@@ -0,0 +1,213 @@
+/*!
+A circuit breaker, to fail fast while Supabase Auth is down.
+
+Without one, every request to an unreachable or failing server waits for its timeout, so an
+outage piles up login requests until the caller runs out of connections or tasks. With
+[`CircuitBreaker`] configured, the client counts consecutive failures: requests which couldn't
+reach the server, and responses with a `5xx` status. Once the count reaches the
+`failure_threshold`, the circuit opens and requests fail right away with
+[`Error::CircuitOpen`](crate::error::Error::CircuitOpen), without contacting the server.
+
+After `open_duration`, the circuit is half-open: up to `half_open_probes` requests are let
+through as probes, while others keep failing fast. If they all succeed, the circuit closes again;
+if any fails, it opens for another `open_duration`.
+
+Responses rejecting a request, e.g. `400 Bad Request` or `429 Too Many Requests`, count as
+successes, as the server is answering.
+
+# Example
+
+```rust
+let auth_client = AuthClient::builder()
+    .project_url(project_url)
+    .api_key(api_key)
+    .timeout(Duration::from_secs(5))
+    .circuit_breaker(CircuitBreaker::default())
+    .build()
+    .unwrap();
+
+match auth_client.login_with_email(&email, &password).await {
+    Err(Error::CircuitOpen { retry_after }) => {
+        return service_unavailable(retry_after);
+    }
+    result => result?,
+}
+```
+*/
+
+use std::{
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::error::Error;
+
+/// When the circuit opens and how it recovers, see the [module docs](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    /// Consecutive failures which open the circuit
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before probe requests are let through
+    pub open_duration: Duration,
+    /// Probe requests let through while the circuit is half-open, all of which must succeed to
+    /// close it
+    pub half_open_probes: u32,
+}
+
+impl Default for CircuitBreaker {
+    /// Open after 5 consecutive failures for 30 seconds, then probe with a single request
+    fn default() -> Self {
+        CircuitBreaker {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+            half_open_probes: 1,
+        }
+    }
+}
+
+/// The state of a client's circuit, see
+/// [`AuthClient::circuit_state`](crate::models::AuthClient::circuit_state)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent
+    Closed,
+    /// Requests fail without being sent
+    Open,
+    /// Probe requests are sent to find out whether the server recovered
+    HalfOpen,
+}
+
+/// The circuit of an [`AuthClient`](crate::models::AuthClient), shared by its clones
+#[derive(Debug)]
+pub(crate) struct Circuit {
+    config: CircuitBreaker,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen(Probes),
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Probes {
+    /// Probes sent but not answered yet
+    in_flight: u32,
+    /// Probes which succeeded
+    succeeded: u32,
+}
+
+impl Circuit {
+    pub(crate) fn new(config: CircuitBreaker) -> Self {
+        Circuit {
+            config,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    pub(crate) fn config(&self) -> CircuitBreaker {
+        self.config
+    }
+
+    pub(crate) fn state(&self) -> CircuitState {
+        match *self.lock() {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { until } if Instant::now() < until => CircuitState::Open,
+            State::Open { .. } | State::HalfOpen(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Ask to send a request, failing with [`Error::CircuitOpen`] if the circuit doesn't allow it
+    pub(crate) fn acquire(&self) -> Result<Permit<'_>, Error> {
+        let mut state = self.lock();
+        let now = Instant::now();
+
+        if let State::Open { until } = *state {
+            if now < until {
+                return Err(Error::CircuitOpen {
+                    retry_after: until - now,
+                });
+            }
+            *state = State::HalfOpen(Probes::default());
+        }
+
+        let probe = match &mut *state {
+            State::HalfOpen(probes) => {
+                if probes.in_flight + probes.succeeded >= self.config.half_open_probes.max(1) {
+                    return Err(Error::CircuitOpen {
+                        retry_after: Duration::ZERO,
+                    });
+                }
+                probes.in_flight += 1;
+                true
+            }
+            _ => false,
+        };
+
+        Ok(Permit {
+            circuit: self,
+            probe,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+/// Permission to send a request, report its outcome with [`Permit::record`]
+///
+/// A probe dropped without an outcome, e.g. because the request was cancelled, frees its slot for
+/// another probe.
+#[must_use]
+pub(crate) struct Permit<'a> {
+    circuit: &'a Circuit,
+    probe: bool,
+}
+
+impl Permit<'_> {
+    /// Record whether the request reached a working server
+    pub(crate) fn record(mut self, success: bool) {
+        let config = self.circuit.config;
+        let mut state = self.circuit.lock();
+
+        *state = match (&*state, success) {
+            (State::Closed { .. }, true) => State::Closed { failures: 0 },
+            (State::Closed { failures }, false) if failures + 1 < config.failure_threshold => {
+                State::Closed {
+                    failures: failures + 1,
+                }
+            }
+            (State::HalfOpen(probes), true) if self.probe => {
+                if probes.succeeded + 1 >= config.half_open_probes {
+                    State::Closed { failures: 0 }
+                } else {
+                    State::HalfOpen(Probes {
+                        in_flight: probes.in_flight.saturating_sub(1),
+                        succeeded: probes.succeeded + 1,
+                    })
+                }
+            }
+            // The outcome of a request sent before the circuit opened
+            (State::Open { .. } | State::HalfOpen(_), _) if !self.probe => return,
+            _ => State::Open {
+                until: Instant::now() + config.open_duration,
+            },
+        };
+        self.probe = false;
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if !self.probe {
+            return;
+        }
+
+        if let State::HalfOpen(probes) = &mut *self.circuit.lock() {
+            probes.in_flight = probes.in_flight.saturating_sub(1);
+        }
+    }
+}
@@ -13,32 +13,151 @@ through the [`AuthClient`] struct.
 - Properly handle token expiration and refresh cycles
 */
 
-use std::env;
+use std::{
+    collections::HashMap,
+    env,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use chrono::{DateTime, TimeDelta, Utc};
+use jsonwebtoken::{decode_header, Algorithm};
 use reqwest::{
-    header::{self, HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE},
-    Client, Url,
+    header::{self, HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE, COOKIE},
+    Client, IntoUrl, Method, Request, RequestBuilder, Response, StatusCode, Url,
 };
-use serde_json::{from_str, Value};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::{sync::Mutex, time::sleep};
+use uuid::Uuid;
 
 use crate::{
-    error::{
-        Error::{self, AuthError},
-        SupabaseHTTPError,
-    },
+    builder::AuthClientBuilder,
+    circuit_breaker::{Circuit, CircuitState},
+    cookies,
+    error::{decode_response, Error, ErrorCode},
+    jwks::JwksCache,
+    jwt::{decode_unverified, JwtValidationOptions},
+    meta::{ResponseHooks, ResponseMeta},
     models::{
-        AuthClient, AuthServerHealth, AuthServerSettings, EmailSignUpConfirmation,
-        EmailSignUpResult, ExchangeCodeForSessionPayload, IdTokenCredentials, InviteParams,
-        LoginAnonymouslyOptions, LoginAnonymouslyPayload, LoginEmailOtpParams,
-        LoginWithEmailAndPasswordPayload, LoginWithEmailOtpPayload, LoginWithOAuthOptions,
-        LoginWithPhoneAndPasswordPayload, LoginWithSSO, LogoutScope, OAuthResponse, OTPResponse,
-        Provider, RefreshSessionPayload, RequestMagicLinkPayload, ResendParams,
-        ResetPasswordForEmailPayload, ResetPasswordOptions, SendSMSOtpPayload, Session,
+        AccessToken, ApiKeyClaims, AuthClient, AuthServerHealth, AuthServerSettings, Claims,
+        CompatibilityWarning, CreateOAuthClientParams, EmailSignUpResult, EnvConfig,
+        ExchangeCodeForSessionPayload, ExpiryClaims, GenerateLinkParams, GeneratedLink,
+        IdTokenCredentials, InviteUserPayload, ListUsersParams, LoginAnonymouslyOptions,
+        LoginAnonymouslyPayload, LoginEmailOtpParams, LoginWithEmailAndPasswordPayload,
+        LoginWithEmailOtpPayload, LoginWithOAuthOptions, LoginWithPasswordOptions,
+        LoginWithPhoneAndPasswordPayload, LoginWithSSO, LogoutScope, OAuthClient,
+        OAuthClientsResponse, OAuthResponse, OTPResponse, Provider, RefreshSessionPayload,
+        RefreshToken, RequestMagicLinkPayload, RequestUser, ResendParams,
+        ResetPasswordForEmailPayload, ResetPasswordOptions, Role, SendSMSOtpPayload, Session,
         SignUpWithEmailAndPasswordPayload, SignUpWithPasswordOptions,
-        SignUpWithPhoneAndPasswordPayload, UpdatedUser, User, VerifyOtpParams, AUTH_V1,
+        SignUpWithPhoneAndPasswordPayload, SsoProvider, SsoProvidersResponse,
+        UpdateSsoProviderParams, UpdatedUser, User, UsersResponse, VerifyOtpParams, AUTH_V1,
+        MIN_SUPPORTED_VERSION, VERSION_CHANGES,
     },
+    rate_limit::RateLimiter,
+    retry::retry_after,
+    stream::UserStream,
+    user_client::UserClient,
 };
 
+/// Identifies this library in the `X-Client-Info` header, like the other Supabase SDKs
+pub(crate) const CLIENT_INFO: &str = concat!("supabase-auth-rs/", env!("CARGO_PKG_VERSION"));
+pub(crate) const X_CLIENT_INFO: &str = "x-client-info";
+
+/// How long [`AuthClient::get_settings_cached`] reuses fetched settings unless configured otherwise
+pub(crate) const DEFAULT_SETTINGS_TTL: Duration = Duration::from_secs(300);
+
+/// The server settings cached for an [`AuthClient`], shared by its clones
+#[derive(Debug, Default)]
+pub(crate) struct SettingsCache {
+    cached: Mutex<Option<Cached<AuthServerSettings>>>,
+}
+
+/// How long a stale copy is served after a failed revalidation before the next attempt
+const STALE_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A copy of a document kept by [`AuthClient::get_settings_cached`] or [`JwksCache`]
+///
+/// Copies are revalidated with their `ETag`, and honor the `max-age` of the `Cache-Control`
+/// header they were served with. When a revalidation fails because the server couldn't be reached
+/// or was failing, the stale copy keeps being served.
+#[derive(Debug)]
+pub(crate) struct Cached<T> {
+    pub(crate) value: T,
+    pub(crate) etag: Option<HeaderValue>,
+    fetched_at: Instant,
+    /// How long the copy is fresh if shorter than the cache TTL: the `max-age` it was served
+    /// with, or the wait before retrying a failed revalidation
+    fresh_for: Option<Duration>,
+}
+
+impl<T> Cached<T> {
+    /// A copy of `value`, fetched just now with the response `headers`
+    pub(crate) fn new(value: T, headers: &HeaderMap) -> Self {
+        Cached {
+            value,
+            etag: headers.get(header::ETAG).cloned(),
+            fetched_at: Instant::now(),
+            fresh_for: max_age(headers),
+        }
+    }
+
+    /// Whether the copy can be used without revalidating it, given the cache `ttl`
+    pub(crate) fn is_fresh(&self, ttl: Duration) -> bool {
+        self.fetched_at.elapsed() < self.fresh_for.map_or(ttl, |fresh_for| fresh_for.min(ttl))
+    }
+
+    /// Mark the copy fresh again after the server answered `304 Not Modified` with `headers`
+    pub(crate) fn revalidated(&mut self, headers: &HeaderMap) {
+        self.fetched_at = Instant::now();
+        self.fresh_for = max_age(headers);
+    }
+
+    /// Keep serving the stale copy after a revalidation failed with `error`, or return the error
+    /// if the server rejected the request rather than failing
+    pub(crate) fn serve_stale(&mut self, error: Error) -> Result<(), Error> {
+        let transient = match &error {
+            Error::NetworkError(_) => true,
+            error => error.status().is_some_and(|status| {
+                status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+            }),
+        };
+        if !transient {
+            return Err(error);
+        }
+
+        self.fetched_at = Instant::now();
+        self.fresh_for = Some(STALE_RETRY_INTERVAL);
+
+        Ok(())
+    }
+}
+
+/// How long a response may be reused according to its `Cache-Control` header: its `max-age`, or
+/// zero if it must be revalidated every time
+fn max_age(headers: &HeaderMap) -> Option<Duration> {
+    let cache_control = headers.get(header::CACHE_CONTROL)?.to_str().ok()?;
+
+    cache_control.split(',').find_map(|directive| {
+        let directive = directive.trim();
+        if directive.eq_ignore_ascii_case("no-cache") || directive.eq_ignore_ascii_case("no-store")
+        {
+            return Some(Duration::ZERO);
+        }
+
+        let (name, seconds) = directive.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("max-age")
+            .then(|| seconds.trim().trim_matches('"').parse().ok())
+            .flatten()
+            .map(Duration::from_secs)
+    })
+}
+
+/// Message GoTrue returns when a rotated refresh token is reused
+const REFRESH_TOKEN_ALREADY_USED_MESSAGE: &str = "Invalid Refresh Token: Already Used";
+
 impl AuthClient {
     /// Create a new Auth Client
     /// You can find your project url and keys at `https://supabase.com/dashboard/project/YOUR_PROJECT_ID/settings/api`
@@ -51,12 +170,26 @@ impl AuthClient {
         api_key: impl Into<String>,
         jwt_secret: impl Into<String>,
     ) -> Self {
-        AuthClient {
-            client: Client::new(),
-            project_url: project_url.into(),
-            api_key: api_key.into(),
-            jwt_secret: jwt_secret.into(),
-        }
+        AuthClient::from_parts(
+            Client::new(),
+            project_url.into(),
+            api_key.into(),
+            jwt_secret.into(),
+        )
+    }
+
+    /// Configure a new Auth Client, e.g. to share a `reqwest::Client` with the rest of your app
+    /// # Example
+    /// ```
+    /// let auth_client = AuthClient::builder()
+    ///     .http_client(http_client.clone())
+    ///     .project_url(project_url)
+    ///     .api_key(api_key)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder() -> AuthClientBuilder {
+        AuthClientBuilder::new()
     }
 
     /// Create a new AuthClient from environment variables
@@ -68,16 +201,459 @@ impl AuthClient {
     /// assert!(auth_client.project_url == env::var("SUPABASE_URL").unwrap())
     /// ```
     pub fn new_from_env() -> Result<AuthClient, Error> {
-        let project_url = env::var("SUPABASE_URL")?;
-        let api_key = env::var("SUPABASE_API_KEY")?;
-        let jwt_secret = env::var("SUPABASE_JWT_SECRET")?;
+        AuthClient::new_from_env_with(EnvConfig::default())
+    }
+
+    /// Create a new AuthClient from the environment variables named in `config`
+    /// # Example
+    /// ```
+    /// let auth_client = AuthClient::new_from_env_with(EnvConfig {
+    ///     url_var: "NEXT_PUBLIC_SUPABASE_URL".to_string(),
+    ///     api_key_var: "SUPABASE_ANON_KEY".to_string(),
+    ///     ..Default::default()
+    /// })
+    /// .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// [`Error::InvalidEnvironment`] naming every variable which is unset, empty, or invalid. The
+    /// project URL must be a valid URL.
+    pub fn new_from_env_with(config: EnvConfig) -> Result<AuthClient, Error> {
+        let mut missing = Vec::new();
+        let mut invalid = Vec::new();
+
+        let mut read = |name: &str| match env::var(name) {
+            Ok(value) if !value.trim().is_empty() => value,
+            Ok(_) | Err(env::VarError::NotPresent) => {
+                missing.push(name.to_string());
+                String::new()
+            }
+            Err(env::VarError::NotUnicode(_)) => {
+                invalid.push(name.to_string());
+                String::new()
+            }
+        };
+
+        let project_url = read(&config.url_var);
+        let api_key = read(&config.api_key_var);
+        let jwt_secret = match &config.jwt_secret_var {
+            Some(jwt_secret_var) => read(jwt_secret_var),
+            None => String::new(),
+        };
+
+        if !project_url.is_empty() && Url::parse(&project_url).is_err() {
+            invalid.push(config.url_var.clone());
+        }
+
+        if !missing.is_empty() || !invalid.is_empty() {
+            return Err(Error::InvalidEnvironment { missing, invalid });
+        }
 
-        Ok(AuthClient {
-            client: Client::new(),
+        Ok(AuthClient::from_parts(
+            Client::new(),
             project_url,
             api_key,
             jwt_secret,
-        })
+        ))
+    }
+
+    pub(crate) fn from_parts(
+        client: Client,
+        project_url: String,
+        api_key: String,
+        jwt_secret: String,
+    ) -> Self {
+        AuthClient {
+            transport: Arc::new(client.clone()),
+            client,
+            project_url: project_url.into(),
+            key_role: api_key_role(&api_key).map(Arc::new),
+            api_key: api_key.into(),
+            jwt_secret: jwt_secret.into(),
+            headers: Arc::new(HeaderMap::from_iter([(
+                HeaderName::from_static(X_CLIENT_INFO),
+                HeaderValue::from_static(CLIENT_INFO),
+            )])),
+            timeout: None,
+            auth_path: AUTH_V1.into(),
+            send_api_key: true,
+            retry_policy: None,
+            rate_limiter: None,
+            circuit: None,
+            jwks: Arc::default(),
+            settings: Arc::default(),
+            settings_ttl: DEFAULT_SETTINGS_TTL,
+            jwt_validation: Arc::default(),
+            response_hooks: ResponseHooks::default(),
+            #[cfg(feature = "logging")]
+            logger: None,
+        }
+    }
+
+    /// A copy of this client which adds `headers` to every request, on top of the headers it
+    /// already sends
+    ///
+    /// Use it to annotate a single call, e.g. for a multi-tenant proxy. Set headers for every
+    /// call with [`AuthClientBuilder::default_headers`].
+    /// # Example
+    /// ```
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("x-tenant-id", HeaderValue::from_static("acme"));
+    ///
+    /// let session = auth_client
+    ///     .with_headers(headers)
+    ///     .login_with_email(&demo_email, &demo_password)
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub fn with_headers(&self, headers: HeaderMap) -> AuthClient {
+        let mut client = self.clone();
+        Arc::make_mut(&mut client.headers).extend(headers);
+        client
+    }
+
+    /// A client for another Supabase project, sharing this client's connection pool and settings
+    ///
+    /// Lets a server proxying auth for many tenants serve them all from one HTTP client. Headers,
+    /// timeout, retry policy, rate limits, and circuit breaker configuration carry over.
+    ///
+    /// The returned client starts with an empty JWKS and settings cache, a full rate limit
+    /// bucket, and a closed circuit, which its clones share. Keep it for as long as the project is
+    /// served, e.g. in an [`AuthClientPool`](crate::pool::AuthClientPool), rather than calling
+    /// this per request: a client created per request refetches the signing keys every time, and
+    /// its rate limits and circuit breaker never take effect.
+    /// # Example
+    /// ```
+    /// let tenant = tenants.get(&tenant_id).unwrap();
+    /// let tenant_client = auth_client.for_project(
+    ///     &tenant.project_url,
+    ///     &tenant.api_key,
+    ///     &tenant.jwt_secret,
+    /// );
+    /// tenant_clients.insert(tenant_id, tenant_client.clone());
+    ///
+    /// let user = tenant_client.get_user(&access_token).await.unwrap();
+    /// ```
+    pub fn for_project(
+        &self,
+        project_url: impl Into<String>,
+        api_key: impl Into<String>,
+        jwt_secret: impl Into<String>,
+    ) -> AuthClient {
+        let api_key = api_key.into();
+
+        AuthClient {
+            project_url: project_url.into().into(),
+            key_role: api_key_role(&api_key).map(Arc::new),
+            api_key: api_key.into(),
+            jwt_secret: jwt_secret.into().into(),
+            jwks: Arc::default(),
+            settings: Arc::default(),
+            rate_limiter: self
+                .rate_limiter
+                .as_ref()
+                .map(|rate_limiter| Arc::new(RateLimiter::new(rate_limiter.limits()))),
+            circuit: self
+                .circuit
+                .as_ref()
+                .map(|circuit| Arc::new(Circuit::new(circuit.config()))),
+            ..self.clone()
+        }
+    }
+
+    /// A copy of this client whose requests fail with [`Error::NetworkError`] if they take
+    /// longer than `timeout`
+    ///
+    /// Overrides the timeout set with [`AuthClientBuilder::timeout`].
+    /// # Example
+    /// ```
+    /// let session = auth_client
+    ///     .with_timeout(Duration::from_secs(2))
+    ///     .login_with_email(&demo_email, &demo_password)
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub fn with_timeout(&self, timeout: Duration) -> AuthClient {
+        let mut client = self.clone();
+        client.timeout = Some(timeout);
+        client
+    }
+
+    /// A copy of this client which calls `hook` with the [`ResponseMeta`] of every response it
+    /// receives, retries included
+    ///
+    /// Hooks added to a copy don't affect the original, and run after the hooks set with
+    /// [`AuthClientBuilder::on_response`](crate::builder::AuthClientBuilder::on_response).
+    /// # Example
+    /// ```
+    /// let user = auth_client
+    ///     .on_response(|meta| {
+    ///         tracing::debug!(request_id = ?meta.request_id, latency = ?meta.latency, "get user");
+    ///     })
+    ///     .get_user(&access_token)
+    ///     .await?;
+    /// ```
+    pub fn on_response(&self, hook: impl Fn(&ResponseMeta) + Send + Sync + 'static) -> AuthClient {
+        let mut client = self.clone();
+        client.response_hooks.push(Arc::new(hook));
+        client
+    }
+
+    /// The state of the client's circuit breaker, `None` if it has none, see
+    /// [`AuthClientBuilder::circuit_breaker`]
+    /// # Example
+    /// ```
+    /// let ready = auth_client.circuit_state() != Some(CircuitState::Open);
+    /// ```
+    pub fn circuit_state(&self) -> Option<CircuitState> {
+        self.circuit.as_ref().map(|circuit| circuit.state())
+    }
+
+    /// Execute a single attempt of `request` with the client's transport, in a client span with
+    /// the `otel` feature, recording it with the `metrics` feature, and logging it with the
+    /// `logging` feature
+    ///
+    /// Responses are passed to the hooks added with [`AuthClient::on_response`]. Fails without
+    /// sending the request while the circuit breaker is open.
+    async fn execute(&self, request: Request) -> Result<Response, Error> {
+        let permit = self
+            .circuit
+            .as_ref()
+            .map(|circuit| circuit.acquire())
+            .transpose()?;
+        let method = request.method().clone();
+        let path = request.url().path().to_string();
+        #[cfg(feature = "metrics")]
+        let endpoint = crate::transport::route(request.url());
+        #[cfg(feature = "logging")]
+        let url = request.url().clone();
+        let started = Instant::now();
+
+        #[cfg(feature = "otel")]
+        let result = crate::otel::execute(self.transport.as_ref(), request).await;
+        #[cfg(not(feature = "otel"))]
+        let result = self.transport.execute(request).await;
+
+        let latency = started.elapsed();
+
+        if let Some(permit) = permit {
+            permit.record(match &result {
+                Ok(response) => !response.status().is_server_error(),
+                Err(_) => false,
+            });
+        }
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_request(&method, endpoint, latency, &result);
+
+        if let Ok(response) = &result {
+            if !self.response_hooks.is_empty() {
+                let meta = ResponseMeta::new(method.clone(), path, latency, response);
+                self.response_hooks.run(&meta);
+            }
+        }
+
+        #[cfg(feature = "logging")]
+        if let Some(logger) = &self.logger {
+            return crate::logging::log(logger, method, &url, latency, result).await;
+        }
+
+        result
+    }
+
+    /// Send a request started with [`AuthClient::request`], retrying it according to the
+    /// client's [`RetryPolicy`](crate::retry::RetryPolicy). Every attempt waits for the client's
+    /// [`RateLimits`](crate::rate_limit::RateLimits).
+    pub(crate) async fn send(&self, request: RequestBuilder) -> Result<Response, Error> {
+        let mut request = request.build()?;
+
+        if !self.send_api_key {
+            request.headers_mut().remove("apikey");
+        }
+
+        let Some(policy) = self
+            .retry_policy
+            .as_ref()
+            .filter(|policy| policy.retries_method(request.method()))
+        else {
+            self.rate_limit(&request).await;
+            return self.execute(request).await;
+        };
+
+        let mut attempt = 1;
+
+        loop {
+            let next = request.try_clone();
+            self.rate_limit(&request).await;
+            let result = self.execute(request).await;
+
+            let delay = match &result {
+                Ok(response) if policy.retries_status(response.status()) => {
+                    policy.delay(attempt, retry_after(response.headers()))
+                }
+                Err(Error::NetworkError(error)) if error.is_connect() => {
+                    policy.delay(attempt, None)
+                }
+                _ => None,
+            };
+
+            match (delay, next) {
+                (Some(delay), Some(next)) => {
+                    sleep(delay).await;
+                    request = next;
+                    attempt += 1;
+                }
+                _ => return result,
+            }
+        }
+    }
+
+    /// Revalidate the `cached` copy of a document with `request`, made conditional on its `ETag`
+    ///
+    /// If the request fails and `serve_stale` is set, the stale copy is returned instead as long
+    /// as the failure is transient, see [`Cached`].
+    pub(crate) async fn revalidate<'a, T: DeserializeOwned>(
+        &self,
+        cached: &'a mut Option<Cached<T>>,
+        mut request: RequestBuilder,
+        serve_stale: bool,
+    ) -> Result<&'a T, Error> {
+        if let Some(etag) = cached.as_ref().and_then(|cached| cached.etag.clone()) {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+
+        let result = match self.send(request).await {
+            Ok(response) if response.status() == StatusCode::NOT_MODIFIED => {
+                let cached = cached.as_mut().ok_or(Error::InternalError)?;
+                cached.revalidated(response.headers());
+                Ok(())
+            }
+            Ok(response) => {
+                let headers = response.headers().clone();
+                decode_response(response)
+                    .await
+                    .map(|value| *cached = Some(Cached::new(value, &headers)))
+            }
+            Err(error) => Err(error),
+        };
+
+        match (result, cached.as_mut()) {
+            (Ok(_), _) => {}
+            (Err(error), Some(stale)) if serve_stale => stale.serve_stale(error)?,
+            (Err(error), _) => return Err(error),
+        }
+
+        cached
+            .as_ref()
+            .map(|cached| &cached.value)
+            .ok_or(Error::InternalError)
+    }
+
+    /// Wait until `request` may be sent without exceeding the client's rate limits
+    async fn rate_limit(&self, request: &Request) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire(request.url().path()).await;
+        }
+    }
+
+    /// Start a request to the auth server, with the client's custom headers and timeout applied
+    pub(crate) fn request(&self, method: Method, url: impl IntoUrl) -> RequestBuilder {
+        let request = self
+            .client
+            .request(method, url)
+            .headers(HeaderMap::clone(&self.headers));
+
+        match self.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        }
+    }
+
+    /// Bind this client to a user's access token, so it doesn't need to be passed to every call
+    /// # Example
+    /// ```
+    /// let user = auth_client.with_token(&access_token).get_user().await.unwrap();
+    /// ```
+    pub fn with_token(&self, access_token: impl Into<AccessToken>) -> UserClient {
+        UserClient::new(self.clone(), access_token)
+    }
+
+    /// Verify an access token locally with this client's JWT secret, without contacting the
+    /// server. See [`verify_access_token`](crate::jwt::verify_access_token) and
+    /// [`AuthClient::with_jwt_validation`].
+    /// # Example
+    /// ```
+    /// let claims = auth_client.verify_access_token(&access_token).unwrap();
+    ///
+    /// assert!(claims.email == demo_email)
+    /// ```
+    pub fn verify_access_token(&self, access_token: &AccessToken) -> Result<Claims, Error> {
+        self.jwt_validation
+            .verify(access_token.as_str(), &self.jwt_secret)
+    }
+
+    /// Fail with [`Error::TokenExpired`] if `access_token` expired longer ago than the leeway of
+    /// [`AuthClient::with_jwt_validation`], rather than sending a request the server would reject
+    ///
+    /// Tokens whose expiry can't be read are left for the server to check.
+    fn check_expiry(&self, access_token: &AccessToken) -> Result<(), Error> {
+        let Some(expired_at) = decode_unverified::<ExpiryClaims>(access_token.as_str())
+            .ok()
+            .and_then(|claims| DateTime::from_timestamp(claims.exp, 0))
+        else {
+            return Ok(());
+        };
+
+        let leeway = TimeDelta::from_std(self.jwt_validation.leeway).unwrap_or_default();
+        if expired_at + leeway <= Utc::now() {
+            return Err(Error::TokenExpired { expired_at });
+        }
+
+        Ok(())
+    }
+
+    /// Set how access tokens are validated by [`AuthClient::verify_access_token`],
+    /// [`AuthClient::get_claims`], and [`JwksCache`]
+    /// # Example
+    /// ```
+    /// let auth_client = AuthClient::new_from_env()?.with_jwt_validation(JwtValidationOptions {
+    ///     leeway: Duration::from_secs(120),
+    ///     ..Default::default()
+    /// });
+    /// ```
+    pub fn with_jwt_validation(mut self, options: JwtValidationOptions) -> Self {
+        self.jwt_validation = Arc::new(options);
+        self
+    }
+
+    /// Verify an access token and return its claims, like `getClaims` in supabase-js
+    ///
+    /// Tokens signed with an asymmetric key are verified locally against the project's JWKS,
+    /// which is cached on this client. HS256 tokens are verified locally with the JWT secret if
+    /// one was configured. Otherwise the token is checked by fetching its user from the server.
+    /// # Example
+    /// ```
+    /// let claims = auth_client.get_claims(&access_token).await.unwrap();
+    ///
+    /// assert!(claims.email == demo_email)
+    /// ```
+    pub async fn get_claims(&self, access_token: &AccessToken) -> Result<Claims, Error> {
+        match decode_header(access_token.as_str())?.alg {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                if self.jwt_secret.is_empty() {
+                    self.get_user(access_token).await?;
+
+                    return decode_unverified(access_token.as_str());
+                }
+
+                self.verify_access_token(access_token)
+            }
+            _ => {
+                JwksCache::new(self.clone())
+                    .verify_access_token(access_token.as_str())
+                    .await
+            }
+        }
     }
 
     /// Sign in a user with an email and password
@@ -91,43 +667,54 @@ impl AuthClient {
     /// assert!(session.user.email == demo_email)
     /// ```
     pub async fn login_with_email(&self, email: &str, password: &str) -> Result<Session, Error> {
-        let payload = LoginWithEmailAndPasswordPayload { email, password };
+        self.login_with_email_with_options(email, password, LoginWithPasswordOptions::default())
+            .await
+    }
+
+    /// Sign in a user with an email and password, e.g. with the token of a solved captcha for
+    /// projects with captcha protection enabled
+    /// # Example
+    /// ```
+    /// let session = auth_client
+    ///     .login_with_email_with_options(
+    ///         demo_email,
+    ///         demo_password,
+    ///         LoginWithPasswordOptions::default().captcha_token(captcha_token),
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn login_with_email_with_options(
+        &self,
+        email: &str,
+        password: &str,
+        options: LoginWithPasswordOptions,
+    ) -> Result<Session, Error> {
+        let payload = LoginWithEmailAndPasswordPayload {
+            email,
+            password,
+            gotrue_meta_security: options.gotrue_meta_security(),
+        };
 
         let mut headers = header::HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
         headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
         let body = serde_json::to_string(&payload)?;
 
-        let response = self
-            .client
-            .post(format!(
-                "{}{}/token?grant_type=password",
-                self.project_url, AUTH_V1
-            ))
+        let request = self
+            .request(
+                Method::POST,
+                format!(
+                    "{}{}/token?grant_type=password",
+                    self.project_url, self.auth_path
+                ),
+            )
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
+            .body(body);
 
-        let res_status = response.status();
-        let res_body = response.text().await?;
-
-        if let Ok(session) = from_str(&res_body) {
-            return Ok(session);
-        }
+        let response = self.send(request).await?;
 
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
-
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        decode_response(response).await
     }
 
     /// Sign in a user with phone number and password
@@ -141,7 +728,34 @@ impl AuthClient {
     /// assert!(session.user.phone == demo_phone)
     /// ```
     pub async fn login_with_phone(&self, phone: &str, password: &str) -> Result<Session, Error> {
-        let payload = LoginWithPhoneAndPasswordPayload { phone, password };
+        self.login_with_phone_with_options(phone, password, LoginWithPasswordOptions::default())
+            .await
+    }
+
+    /// Sign in a user with phone number and password, e.g. with the token of a solved captcha
+    /// for projects with captcha protection enabled
+    /// # Example
+    /// ```
+    /// let session = auth_client
+    ///     .login_with_phone_with_options(
+    ///         demo_phone,
+    ///         demo_password,
+    ///         LoginWithPasswordOptions::default().captcha_token(captcha_token),
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn login_with_phone_with_options(
+        &self,
+        phone: &str,
+        password: &str,
+        options: LoginWithPasswordOptions,
+    ) -> Result<Session, Error> {
+        let payload = LoginWithPhoneAndPasswordPayload {
+            phone,
+            password,
+            gotrue_meta_security: options.gotrue_meta_security(),
+        };
 
         let mut headers = header::HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
@@ -149,36 +763,20 @@ impl AuthClient {
 
         let body = serde_json::to_string(&payload)?;
 
-        let response = self
-            .client
-            .post(format!(
-                "{}{}/token?grant_type=password",
-                self.project_url, AUTH_V1
-            ))
+        let request = self
+            .request(
+                Method::POST,
+                format!(
+                    "{}{}/token?grant_type=password",
+                    self.project_url, self.auth_path
+                ),
+            )
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
+            .body(body);
 
-        if let Ok(session) = from_str(&res_body) {
-            return Ok(session);
-        }
-
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
+        let response = self.send(request).await?;
 
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        decode_response(response).await
     }
 
     /// Sign up a new user with an email and password
@@ -199,12 +797,12 @@ impl AuthClient {
     ) -> Result<EmailSignUpResult, Error> {
         let redirect_to = options
             .as_ref()
-            .and_then(|o| o.email_redirect_to.as_deref().map(str::to_owned));
+            .and_then(|o| o.email_redirect_to.as_deref());
 
         let payload = SignUpWithEmailAndPasswordPayload {
             email,
             password,
-            options,
+            options: options.as_ref(),
         };
 
         let mut headers = header::HeaderMap::new();
@@ -213,39 +811,20 @@ impl AuthClient {
 
         let body = serde_json::to_string(&payload)?;
 
-        let response = self
-            .client
-            .post(format!("{}{}/signup", self.project_url, AUTH_V1))
-            .query(&[("redirect_to", redirect_to.as_deref())])
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/signup", self.project_url, self.auth_path),
+            )
+            .query(&[("redirect_to", redirect_to)])
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
+            .body(body);
 
-        let res_status = response.status();
-        let res_body = response.text().await?;
+        let response = self.send(request).await?;
 
-        if let Ok(session) = from_str::<Session>(&res_body) {
-            return Ok(EmailSignUpResult::SessionResult(session));
-        }
-
-        if let Ok(result) = from_str::<EmailSignUpConfirmation>(&res_body) {
-            return Ok(EmailSignUpResult::ConfirmationResult(result));
-        }
-
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
-
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
-    }
+        // Sessions are returned when email confirmation is disabled
+        decode_response(response).await
+    }
 
     /// Sign up a new user with an email and password
     /// # Example
@@ -265,12 +844,12 @@ impl AuthClient {
     ) -> Result<Session, Error> {
         let redirect_to = options
             .as_ref()
-            .and_then(|o| o.email_redirect_to.as_deref().map(str::to_owned));
+            .and_then(|o| o.email_redirect_to.as_deref());
 
         let payload = SignUpWithPhoneAndPasswordPayload {
             phone,
             password,
-            options,
+            options: options.as_ref(),
         };
 
         let mut headers = header::HeaderMap::new();
@@ -279,34 +858,18 @@ impl AuthClient {
 
         let body = serde_json::to_string(&payload)?;
 
-        let response = self
-            .client
-            .post(format!("{}{}/signup", self.project_url, AUTH_V1))
-            .query(&[("email_redirect_to", redirect_to.as_deref())])
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/signup", self.project_url, self.auth_path),
+            )
+            .query(&[("email_redirect_to", redirect_to)])
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
+            .body(body);
 
-        if let Ok(session) = from_str(&res_body) {
-            return Ok(session);
-        }
-
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
+        let response = self.send(request).await?;
 
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        decode_response(response).await
     }
 
     /// Sign in a new user anonymously. This actually signs up a user, but it's
@@ -337,33 +900,17 @@ impl AuthClient {
 
         let body = serde_json::to_string(&payload)?;
 
-        let response = self
-            .client
-            .post(format!("{}{}/signup", self.project_url, AUTH_V1))
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/signup", self.project_url, self.auth_path),
+            )
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
-
-        if let Ok(session) = from_str(&res_body) {
-            return Ok(session);
-        }
+            .body(body);
 
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
+        let response = self.send(request).await?;
 
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        decode_response(response).await
     }
 
     /// Sends a login email containing a magic link
@@ -383,32 +930,24 @@ impl AuthClient {
 
         let body = serde_json::to_string(&payload)?;
 
-        let response = self
-            .client
-            .post(format!("{}{}/magiclink", self.project_url, AUTH_V1))
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/magiclink", self.project_url, self.auth_path),
+            )
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
+            .body(body);
+
+        let response = self.send(request).await?;
 
         let res_status = response.status();
+        let res_headers = response.headers().clone();
         let res_body = response.text().await?;
 
         if res_status.is_success() {
             Ok(())
         } else {
-            if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-                return Err(AuthError {
-                    status: res_status,
-                    message: error.message,
-                });
-            }
-
-            // Fallback: return raw error
-            return Err(AuthError {
-                status: res_status,
-                message: res_body,
-            });
+            Err(Error::from_body(res_status, res_body, res_headers))
         }
     }
 
@@ -427,34 +966,17 @@ impl AuthClient {
 
         let body = serde_json::to_string(&payload)?;
 
-        let response = self
-            .client
-            .post(format!("{}{}/otp", self.project_url, AUTH_V1))
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/otp", self.project_url, self.auth_path),
+            )
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
+            .body(body);
 
-        if res_status.is_success() {
-            let message = serde_json::from_str(&res_body)?;
-            Ok(message)
-        } else {
-            if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-                return Err(AuthError {
-                    status: res_status,
-                    message: error.message,
-                });
-            }
+        let response = self.send(request).await?;
 
-            // Fallback: return raw error
-            return Err(AuthError {
-                status: res_status,
-                message: res_body,
-            });
-        }
+        decode_response(response).await
     }
 
     /// Send a Login OTP via email
@@ -477,49 +999,27 @@ impl AuthClient {
 
         let body = serde_json::to_string(&payload)?;
 
-        let response = self
-            .client
-            .post(format!("{}{}/otp", self.project_url, AUTH_V1))
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/otp", self.project_url, self.auth_path),
+            )
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
+            .body(body);
 
-        if res_status.is_success() {
-            let message = serde_json::from_str(&res_body)?;
-            Ok(message)
-        } else {
-            if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-                return Err(AuthError {
-                    status: res_status,
-                    message: error.message,
-                });
-            }
+        let response = self.send(request).await?;
 
-            // Fallback: return raw error
-            return Err(AuthError {
-                status: res_status,
-                message: res_body,
-            });
-        }
+        decode_response(response).await
     }
 
     /// Sign in a user using an OAuth provider.
     /// # Example
     /// ```
-    /// // You can add custom parameters using a HashMap<String, String>
-    /// let mut params = HashMap::new();
-    /// params.insert("key".to_string(), "value".to_string());
-    ///
-    /// let options = LoginWithOAuthOptions {
-    ///     query_params: Some(params),
-    ///     redirect_to: Some("localhost".to_string()),
-    ///     scopes: Some("repo gist notifications".to_string()),
-    ///     skip_browser_redirect: Some(true),
-    /// };
+    /// let options = LoginWithOAuthOptions::default()
+    ///     .query_param("key", "value")
+    ///     .redirect_to("localhost")
+    ///     .scopes("repo gist notifications")
+    ///     .skip_browser_redirect(true);
     ///
     /// let response = auth_client
     ///     .login_with_oauth(supabase_auth::models::Provider::Github, Some(options))
@@ -530,25 +1030,21 @@ impl AuthClient {
         provider: Provider,
         options: Option<LoginWithOAuthOptions>,
     ) -> Result<OAuthResponse, Error> {
-        let query_params = options.as_ref().map_or_else(
-            || vec![("provider", provider.to_string())],
-            |o| {
-                let mut params = vec![("provider", provider.to_string())];
-
-                if let Some(ref redirect) = o.redirect_to {
-                    params.push(("redirect_to", redirect.to_string()));
-                }
+        let provider_name = provider.to_string();
+        let mut query_params = vec![("provider", provider_name.as_str())];
 
-                if let Some(ref extra) = o.query_params {
-                    params.extend(extra.iter().map(|(k, v)| (k.as_str(), v.to_string())));
-                }
+        if let Some(options) = &options {
+            if let Some(redirect_to) = &options.redirect_to {
+                query_params.push(("redirect_to", redirect_to));
+            }
 
-                params
-            },
-        );
+            if let Some(extra) = &options.query_params {
+                query_params.extend(extra.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+            }
+        }
 
         let url = Url::parse_with_params(
-            format!("{}{}/authorize", self.project_url, AUTH_V1).as_str(),
+            format!("{}{}/authorize", self.project_url, self.auth_path).as_str(),
             query_params,
         )
         .map_err(|_| Error::ParseUrlError)?;
@@ -559,16 +1055,11 @@ impl AuthClient {
     /// Sign up a user using an OAuth provider.
     /// # Example
     /// ```
-    /// // You can add custom parameters using a HashMap<String, String>
-    /// let mut params = HashMap::new();
-    /// params.insert("key".to_string(), "value".to_string());
-    ///
-    /// let options = LoginWithOAuthOptions {
-    ///     query_params: Some(params),
-    ///     redirect_to: Some("localhost".to_string()),
-    ///     scopes: Some("repo gist notifications".to_string()),
-    ///     skip_browser_redirect: Some(true),
-    /// };
+    /// let options = LoginWithOAuthOptions::default()
+    ///     .query_param("key", "value")
+    ///     .redirect_to("localhost")
+    ///     .scopes("repo gist notifications")
+    ///     .skip_browser_redirect(true);
     ///
     /// let response = auth_client
     ///     .sign_up_with_oauth(supabase_auth::models::Provider::Github, Some(options))
@@ -583,107 +1074,161 @@ impl AuthClient {
     }
 
     /// Return the signed in User
+    ///
+    /// Fails with [`Error::TokenExpired`] without contacting the server if the access token
+    /// expired.
     /// # Example
     /// ```
     /// let user = auth_client
-    ///     .get_user(session.unwrap().access_token)
+    ///     .get_user(&session.unwrap().access_token)
     ///     .await
     ///     .unwrap();
     ///
     /// assert!(user.email == demo_email)
     /// ```
-    pub async fn get_user(&self, bearer_token: &str) -> Result<User, Error> {
+    pub async fn get_user(&self, bearer_token: &AccessToken) -> Result<User, Error> {
+        self.check_expiry(bearer_token)?;
+
         let mut headers = header::HeaderMap::new();
         headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
         headers.insert(
             AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", bearer_token))?,
+            HeaderValue::from_str(&format!("Bearer {}", bearer_token.as_str()))?,
         );
 
-        let response = self
-            .client
-            .get(format!("{}{}/user", self.project_url, AUTH_V1))
-            .headers(headers)
-            .send()
-            .await?;
+        let request = self
+            .request(
+                Method::GET,
+                format!("{}{}/user", self.project_url, self.auth_path),
+            )
+            .headers(headers);
 
-        let res_status = response.status();
-        let res_body = response.text().await?;
+        let response = self.send(request).await?;
 
-        if let Ok(user) = from_str(&res_body) {
-            return Ok(user);
+        decode_response(response).await
+    }
+
+    /// Find the user who made a request from its headers
+    ///
+    /// Uses the `Authorization: Bearer` header if present, otherwise the `@supabase/ssr` auth
+    /// cookie. An expired cookie session is refreshed, in which case the new session is returned
+    /// in [`RequestUser::refreshed_session`] and must be sent back to the browser.
+    ///
+    /// Returns [`Error::NotAuthenticated`] if the request carries neither.
+    ///
+    /// # Example
+    /// ```
+    /// let RequestUser { user, refreshed_session } = auth_client
+    ///     .get_user_from_headers(request.headers())
+    ///     .await?;
+    /// ```
+    pub async fn get_user_from_headers(&self, headers: &HeaderMap) -> Result<RequestUser, Error> {
+        if let Some(bearer_token) = bearer_token(headers) {
+            return Ok(RequestUser {
+                user: self.get_user(&bearer_token).await?,
+                refreshed_session: None,
+            });
         }
 
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
+        let session = self
+            .cookie_session(headers)?
+            .ok_or(Error::NotAuthenticated)?;
+
+        if session.is_expired() {
+            let session = self.refresh_session(&session.refresh_token).await?;
+
+            return Ok(RequestUser {
+                user: session.user.clone(),
+                refreshed_session: Some(session),
             });
         }
 
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
+        Ok(RequestUser {
+            user: self.get_user(&session.access_token).await?,
+            refreshed_session: None,
         })
     }
 
-    /// Update the user, such as changing email or password. Each field (email, password, and data) is optional
+    /// The access token a request was made with, read from its headers without verifying it
+    ///
+    /// Uses the `Authorization: Bearer` header if present, otherwise the `@supabase/ssr` auth
+    /// cookie. Returns [`Error::NotAuthenticated`] if the request carries neither.
+    ///
+    /// # Example
+    /// ```
+    /// let access_token = auth_client.access_token_from_headers(request.headers())?;
+    /// let claims = auth_client.get_claims(&access_token).await?;
+    /// ```
+    pub fn access_token_from_headers(&self, headers: &HeaderMap) -> Result<AccessToken, Error> {
+        if let Some(bearer_token) = bearer_token(headers) {
+            return Ok(bearer_token);
+        }
+
+        self.cookie_session(headers)?
+            .map(|session| session.access_token)
+            .ok_or(Error::NotAuthenticated)
+    }
+
+    /// The session stored in the `@supabase/ssr` auth cookie, if the request carries one
+    fn cookie_session(&self, headers: &HeaderMap) -> Result<Option<Session>, Error> {
+        let cookie_header = headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        let cookie_name = cookies::cookie_name(&self.project_url)?;
+
+        cookies::parse_session_cookie(&cookie_name, &cookie_header)
+    }
+
+    /// Update the user, such as changing email or password. Fields left
+    /// [`Patch::Unchanged`](crate::models::Patch::Unchanged) aren't sent, see [`UpdatedUser`]
+    ///
+    /// Fails with [`Error::TokenExpired`] without contacting the server if the access token
+    /// expired.
     /// # Example
     /// ```
-    /// let updated_user_data = UpdateUserPayload {
-    ///     email: Some("demo@demo.com".to_string()),
-    ///     password: Some("demo_password".to_string()),
-    ///     data: None, // This field can hold any valid JSON value
+    /// let updated_user_data = UpdatedUser {
+    ///     email: Patch::Set("demo@demo.com".to_string()),
+    ///     password: Patch::Set("demo_password".to_string()),
+    ///     ..Default::default()
     /// };
     ///
     /// let user = auth_client
-    ///     .update_user(updated_user_data, access_token)
+    ///     .update_user(updated_user_data, &access_token)
     ///     .await
     ///     .unwrap();
     /// ```
     pub async fn update_user(
         &self,
         updated_user: UpdatedUser,
-        bearer_token: &str,
+        bearer_token: &AccessToken,
     ) -> Result<User, Error> {
+        self.check_expiry(bearer_token)?;
+
         let mut headers = header::HeaderMap::new();
         headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
         headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
         headers.insert(
             AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", bearer_token))?,
+            HeaderValue::from_str(&format!("Bearer {}", bearer_token.as_str()))?,
         );
 
         let body = serde_json::to_string::<UpdatedUser>(&updated_user)?;
 
-        let response = self
-            .client
-            .put(format!("{}{}/user", self.project_url, AUTH_V1))
+        let request = self
+            .request(
+                Method::PUT,
+                format!("{}{}/user", self.project_url, self.auth_path),
+            )
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
-
-        if let Ok(user) = from_str(&res_body) {
-            return Ok(user);
-        }
+            .body(body);
 
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
+        let response = self.send(request).await?;
 
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        decode_response(response).await
     }
 
     /// Allows signing in with an OIDC ID token. The authentication provider used should be enabled and configured.
@@ -709,36 +1254,20 @@ impl AuthClient {
 
         let body = serde_json::to_string(&credentials)?;
 
-        let response = self
-            .client
-            .post(format!(
-                "{}{}/token?grant_type=id_token",
-                self.project_url, AUTH_V1
-            ))
+        let request = self
+            .request(
+                Method::POST,
+                format!(
+                    "{}{}/token?grant_type=id_token",
+                    self.project_url, self.auth_path
+                ),
+            )
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
+            .body(body);
 
-        if let Ok(session) = from_str(&res_body) {
-            return Ok(session);
-        }
-
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
+        let response = self.send(request).await?;
 
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        decode_response(response).await
     }
 
     /// Sends an invite link to an email address.
@@ -768,50 +1297,30 @@ impl AuthClient {
             HeaderValue::from_str(&format!("Bearer {}", bearer_token))?,
         );
 
-        let invite_payload = InviteParams {
-            email: email.into(),
-            data,
+        let invite_payload = InviteUserPayload {
+            email,
+            data: data.as_ref(),
         };
 
         let body = serde_json::to_string(&invite_payload)?;
 
-        let response = self
-            .client
-            .post(format!("{}{}/invite", self.project_url, AUTH_V1))
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/invite", self.project_url, self.auth_path),
+            )
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
-
-        if let Ok(user) = from_str(&res_body) {
-            return Ok(user);
-        }
+            .body(body);
 
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
+        let response = self.send(request).await?;
 
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        decode_response(response).await
     }
 
     /// Verify the OTP sent to the user
     /// # Example
     /// ```
-    /// let params = VerifyEmailOtpParams {
-    ///     token: "abc123",
-    ///     otp_type: OtpType::EmailChange,
-    ///     options: None,
-    /// };
+    /// let params = VerifyOtpParams::email(&demo_email, "123456", OtpType::EmailChange);
     ///
     /// let session = auth_client
     ///     .verify_otp(params)
@@ -825,447 +1334,1042 @@ impl AuthClient {
 
         let body = serde_json::to_string(&params)?;
 
-        let response = self
-            .client
-            .post(&format!("{}{}/verify", self.project_url, AUTH_V1))
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/verify", self.project_url, self.auth_path),
+            )
             .headers(headers)
-            .body(body)
-            .send()
+            .body(body);
+
+        let response = self.send(request).await?;
+
+        decode_response(response).await
+    }
+
+    /// Check the Health Status of the Auth Server
+    /// # Example
+    /// ```
+    /// let health = auth_client
+    ///     .get_health()
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn get_health(&self) -> Result<AuthServerHealth, Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+
+        let request = self
+            .request(
+                Method::GET,
+                format!("{}{}/health", self.project_url, self.auth_path),
+            )
+            .headers(headers);
+
+        let response = self.send(request).await?;
+
+        decode_response::<AuthServerHealth>(response).await
+    }
+
+    /// Poll the health endpoint every `poll_interval` until the server responds, for up to
+    /// `timeout`
+    ///
+    /// Useful in integration tests and local development, where the auth server may still be
+    /// starting up. Fails with [`Error::Unhealthy`] if the server isn't healthy in time.
+    /// # Example
+    /// ```
+    /// let health = auth_client
+    ///     .wait_until_healthy(Duration::from_secs(30), Duration::from_millis(500))
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn wait_until_healthy(
+        &self,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<AuthServerHealth, Error> {
+        let deadline = Instant::now() + timeout;
+        let mut last_error = None;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            match tokio::time::timeout(remaining, self.get_health()).await {
+                Ok(Ok(health)) => return Ok(health),
+                Ok(Err(error)) => last_error = Some(Box::new(error)),
+                Err(_) => break,
+            }
+
+            if deadline.saturating_duration_since(Instant::now()) <= poll_interval {
+                break;
+            }
+
+            sleep(poll_interval).await;
+        }
+
+        Err(Error::Unhealthy {
+            timeout,
+            last_error,
+        })
+    }
+
+    /// Compare the server's version against the versions this crate supports
+    ///
+    /// Returns a warning for every known API change the server predates, so that an outdated
+    /// server is noticed at startup rather than by a failing call. An empty list means no
+    /// problems are known. Fails only if the health endpoint can't be reached.
+    /// # Example
+    /// ```
+    /// for warning in auth_client.check_compatibility().await? {
+    ///     eprintln!("{warning}");
+    /// }
+    /// ```
+    pub async fn check_compatibility(&self) -> Result<Vec<CompatibilityWarning>, Error> {
+        let health = self.get_health().await?;
+
+        let Some(version) = health.server_version() else {
+            return Ok(vec![CompatibilityWarning::UnknownVersion(health.version)]);
+        };
+
+        let mut warnings = Vec::new();
+        if version < MIN_SUPPORTED_VERSION {
+            warnings.push(CompatibilityWarning::Unsupported {
+                version,
+                minimum: MIN_SUPPORTED_VERSION,
+            });
+        }
+
+        warnings.extend(
+            VERSION_CHANGES
+                .iter()
+                .filter(|(since, _)| version < *since)
+                .map(|&(since, change)| CompatibilityWarning::MissingChange {
+                    version,
+                    since,
+                    change,
+                }),
+        );
+
+        Ok(warnings)
+    }
+
+    /// Retrieve the public settings of the server
+    /// # Example
+    /// ```
+    /// let settings = auth_client
+    ///     .get_settings()
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn get_settings(&self) -> Result<AuthServerSettings, Error> {
+        let response = self.send(self.settings_request()?).await?;
+
+        decode_response(response).await
+    }
+
+    /// Retrieve the public settings of the server, reusing the last fetched copy until it is
+    /// older than the cache TTL
+    ///
+    /// The cache is shared by every clone of the client. Settings are cached for 5 minutes unless
+    /// configured otherwise with [`AuthClient::with_settings_ttl`], or for less if the server
+    /// sends a shorter `Cache-Control: max-age`. Expired settings are revalidated with their
+    /// `ETag`, so unchanged settings cost the server nothing but a `304 Not Modified`. If the
+    /// server can't be reached, is rate limiting, or fails with a `5xx` status, the expired
+    /// settings are returned instead of an error, and revalidated again 30 seconds later.
+    /// # Example
+    /// ```
+    /// // Only the first call contacts the server
+    /// let settings = auth_client.get_settings_cached().await.unwrap();
+    /// let settings = auth_client.get_settings_cached().await.unwrap();
+    ///
+    /// if settings.external.github {
+    ///     render_github_button();
+    /// }
+    /// ```
+    pub async fn get_settings_cached(&self) -> Result<AuthServerSettings, Error> {
+        let mut cached = self.settings.cached.lock().await;
+
+        if let Some(settings) = cached.as_ref() {
+            if settings.is_fresh(self.settings_ttl) {
+                return Ok(settings.value.clone());
+            }
+        }
+
+        let settings = self
+            .revalidate(&mut cached, self.settings_request()?, true)
             .await?;
 
-        let res_status = response.status();
-        let res_body = response.text().await?;
+        Ok(settings.clone())
+    }
+
+    fn settings_request(&self) -> Result<RequestBuilder, Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+
+        Ok(self
+            .request(
+                Method::GET,
+                format!("{}{}/settings", self.project_url, self.auth_path),
+            )
+            .headers(headers))
+    }
+
+    /// Drop the settings cached by [`AuthClient::get_settings_cached`], so the next call fetches
+    /// them again, e.g. after changing the project's configuration
+    pub async fn invalidate_settings_cache(&self) {
+        *self.settings.cached.lock().await = None;
+    }
+
+    /// Set how long [`AuthClient::get_settings_cached`] reuses fetched settings
+    /// # Example
+    /// ```
+    /// let auth_client = AuthClient::new_from_env()?.with_settings_ttl(Duration::from_secs(60));
+    /// ```
+    pub fn with_settings_ttl(mut self, ttl: Duration) -> Self {
+        self.settings_ttl = ttl;
+        self
+    }
+
+    /// Exchange refresh token for a new session
+    /// # Example
+    /// ```
+    /// // When a user signs in they get a session
+    /// let original_session = auth_client
+    ///     .login_with_email_and_password(demo_email.as_ref(), demo_password)
+    ///     .await
+    ///     .unwrap();
+    ///
+    /// // Exchange the refresh token from the original session to create a new session
+    /// let new_session = auth_client
+    ///     .refresh_session(&original_session.refresh_token)
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn exchange_token_for_session(
+        &self,
+        refresh_token: &RefreshToken,
+    ) -> Result<Session, Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
+
+        let body = serde_json::to_string(&RefreshSessionPayload {
+            refresh_token: refresh_token.as_str(),
+        })?;
+
+        let request = self
+            .request(
+                Method::POST,
+                format!(
+                    "{}{}/token?grant_type=refresh_token",
+                    self.project_url, self.auth_path
+                ),
+            )
+            .headers(headers)
+            .body(body);
+
+        let result = match self.send(request).await {
+            Ok(response) => decode_response(response).await,
+            Err(error) => Err(error),
+        };
+
+        let result = result.map_err(|error| match error {
+            Error::AuthError {
+                code: Some(ErrorCode::RefreshTokenAlreadyUsed),
+                ..
+            } => Error::RefreshTokenAlreadyUsed,
+            // Older GoTrue versions don't send an error code
+            Error::AuthError { message, .. }
+                if message.contains(REFRESH_TOKEN_ALREADY_USED_MESSAGE) =>
+            {
+                Error::RefreshTokenAlreadyUsed
+            }
+            error => error,
+        });
+
+        #[cfg(feature = "metrics")]
+        if let Err(error) = &result {
+            crate::metrics::record_refresh_failure(error);
+        }
+
+        result
+    }
+
+    /// Exchange a refresh token for a new session
+    ///
+    /// Refresh tokens can only be used once. Reusing a token which was already exchanged returns
+    /// [`Error::RefreshTokenAlreadyUsed`], in which case the session is gone and the user has to
+    /// sign in again.
+    pub async fn refresh_session(&self, refresh_token: &RefreshToken) -> Result<Session, Error> {
+        self.exchange_token_for_session(refresh_token).await
+    }
+
+    /// Build a session from an access token and refresh token, e.g. ones received from a
+    /// supabase-js frontend
+    ///
+    /// If the access token has expired, the refresh token is used to get a new session.
+    /// Otherwise the access token is validated by fetching the user it belongs to.
+    ///
+    /// # Example
+    /// ```
+    /// let session = auth_client
+    ///     .set_session(&access_token, &refresh_token)
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn set_session(
+        &self,
+        access_token: &AccessToken,
+        refresh_token: &RefreshToken,
+    ) -> Result<Session, Error> {
+        let claims: ExpiryClaims = decode_unverified(access_token.as_str())?;
+
+        let expires_at = DateTime::from_timestamp(claims.exp, 0).ok_or(Error::WrongToken)?;
+        let now = Utc::now();
+
+        if expires_at <= now {
+            return self.refresh_session(refresh_token).await;
+        }
+
+        let user = self.get_user(access_token).await?;
+
+        Ok(Session {
+            provider_token: None,
+            provider_refresh_token: None,
+            access_token: access_token.clone(),
+            token_type: "bearer".to_string(),
+            expires_in: (expires_at - now).num_seconds(),
+            expires_at,
+            refresh_token: refresh_token.clone(),
+            user,
+            weak_password: None,
+            extra: HashMap::new(),
+        })
+    }
+
+    /// Exchange code for a new session
+    /// # Example
+    /// ```
+    /// // When a user signs in they get a session
+    /// let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+    ///
+    /// let options = LoginWithOAuthOptions::default()
+    ///     .query_param("redirect_to", "http://localhost:3000/auth/callback")
+    ///     .query_param("response_type", "code")
+    ///     .query_param("skip_browser_redirect", "true")
+    ///     .query_param("code_challenge", pkce_challenge.as_str())
+    ///     .query_param("code_challenge_method", "S256");
+    ///
+    /// let oauth_res = auth_client
+    ///     .login_with_oauth(Provider::Github, Some(options))?;
+    ///
+    /// // Exchange the code to create a new session
+    /// let new_session = auth_client
+    ///     .exchange_code_for_session(auth_code, pkce_verifier)
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn exchange_code_for_session(
+        &self,
+        auth_code: &str,
+        code_verifier: &str,
+    ) -> Result<Session, Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
+
+        let body = serde_json::to_string(&ExchangeCodeForSessionPayload {
+            auth_code,
+            code_verifier,
+        })?;
+
+        let request = self
+            .request(
+                Method::POST,
+                format!(
+                    "{}{}/token?grant_type=pkce",
+                    self.project_url, self.auth_path
+                ),
+            )
+            .headers(headers)
+            .body(body);
+
+        let response = self.send(request).await?;
+
+        decode_response(response).await
+    }
+
+    /// Send a password recovery email. Invalid Email addresses will return Error Code 400.
+    /// Valid email addresses that are not registered as users will not return an error.
+    /// # Example
+    /// ```
+    /// let response = auth_client.reset_password_for_email(demo_email, None).await.unwrap();
+    /// ```
+    pub async fn reset_password_for_email(
+        &self,
+        email: &str,
+        options: Option<ResetPasswordOptions>,
+    ) -> Result<(), Error> {
+        let redirect_to = options
+            .as_ref()
+            .and_then(|o| o.email_redirect_to.as_deref());
+
+        let payload = ResetPasswordForEmailPayload {
+            email,
+            options: options.as_ref(),
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
+
+        let body = serde_json::to_string(&payload)?;
+
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/recover", self.project_url, self.auth_path),
+            )
+            .query(&[("redirect_to", redirect_to)])
+            .headers(headers)
+            .body(body);
+
+        let response = self.send(request).await?;
+
+        let res_status = response.status();
+        let res_headers = response.headers().clone();
+        let res_body = response.text().await?;
+
+        if res_status.is_success() {
+            return Ok(());
+        }
+
+        Err(Error::from_body(res_status, res_body, res_headers))
+    }
+
+    /// Resends emails for existing signup confirmation, email change, SMS OTP, or phone change OTP.
+    /// # Example
+    /// ```
+    /// let resend = auth_client
+    ///     .resend(ResendParams::new(OtpType::Signup, &demo_email))
+    ///     .await;
+    /// ```
+    pub async fn resend(&self, credentials: ResendParams) -> Result<(), Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
+
+        let body = serde_json::to_string(&credentials)?;
+
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/resend", self.project_url, self.auth_path),
+            )
+            .headers(headers)
+            .body(body);
+
+        let response = self.send(request).await?;
+
+        let res_status = response.status();
+        let res_headers = response.headers().clone();
+        let res_body = response.text().await?;
+
+        if res_status.is_success() {
+            return Ok(());
+        }
+
+        Err(Error::from_body(res_status, res_body, res_headers))
+    }
+
+    /// Logs out a user with a given scope
+    ///
+    /// Fails with [`Error::TokenExpired`] without contacting the server if the access token
+    /// expired.
+    /// # Example
+    /// ```
+    /// auth_client.logout(Some(LogoutScope::Global), &session.access_token).await.unwrap();
+    /// ```
+    pub async fn logout(
+        &self,
+        scope: Option<LogoutScope>,
+        bearer_token: &AccessToken,
+    ) -> Result<(), Error> {
+        self.check_expiry(bearer_token)?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", bearer_token.as_str()))?,
+        );
+
+        let body = serde_json::to_string(&scope)?;
+
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/logout", self.project_url, self.auth_path),
+            )
+            .headers(headers)
+            .body(body);
+
+        let response = self.send(request).await?;
+
+        let res_status = response.status();
+        let res_headers = response.headers().clone();
+        let res_body = response.text().await?;
+
+        if res_status.is_success() {
+            return Ok(());
+        }
+
+        Err(Error::from_body(res_status, res_body, res_headers))
+    }
+
+    /// Initiates an SSO Login Flow
+    /// Returns the URL where the user must authenticate with the SSO Provider
+    ///
+    /// WARNING: Requires an SSO Provider and Supabase Pro plan
+    ///
+    /// # Example
+    /// ```
+    /// let url = auth_client.sso(params).await.unwrap();
+    ///
+    /// println!("{}", url.to_string());
+    /// ```
+    pub async fn sso(&self, params: LoginWithSSO) -> Result<Url, Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+
+        let body = serde_json::to_string::<LoginWithSSO>(&params)?;
+
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/sso", self.project_url, self.auth_path),
+            )
+            .headers(headers)
+            .body(body);
+
+        let response = self.send(request).await?;
+
+        let res_status = response.status();
+        let res_headers = response.headers().clone();
+        let url = response.url().clone();
+        let res_body = response.text().await?;
+
+        if res_status.is_server_error() || res_status.is_client_error() {
+            return Err(Error::from_body(res_status, res_body, res_headers));
+        }
+
+        Ok(url)
+    }
+
+    /// Retrieves the SAML 2.0 Service Provider metadata of the project as raw XML
+    /// Hand this to the administrator of an enterprise identity provider to register your project
+    ///
+    /// WARNING: Requires SAML to be enabled on the project
+    ///
+    /// # Example
+    /// ```
+    /// let metadata = auth_client.get_saml_metadata().await.unwrap();
+    ///
+    /// std::fs::write("sp-metadata.xml", metadata).unwrap();
+    /// ```
+    pub async fn get_saml_metadata(&self) -> Result<String, Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+
+        let request = self
+            .request(
+                Method::GET,
+                format!("{}{}/sso/saml/metadata", self.project_url, self.auth_path),
+            )
+            .headers(headers);
+
+        let response = self.send(request).await?;
+
+        let res_status = response.status();
+        let res_headers = response.headers().clone();
+        let res_body = response.text().await?;
+
+        if res_status.is_success() {
+            return Ok(res_body);
+        }
+
+        Err(Error::from_body(res_status, res_body, res_headers))
+    }
+
+    /// Lists all SSO providers registered on the project
+    /// Requires the `service_role` key as the client's API key
+    ///
+    /// WARNING: Requires an SSO Provider and Supabase Pro plan
+    ///
+    /// # Example
+    /// ```
+    /// let providers = auth_client.admin_list_sso_providers().await.unwrap();
+    /// ```
+    pub async fn admin_list_sso_providers(&self) -> Result<Vec<SsoProvider>, Error> {
+        self.require_service_role()?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", &self.api_key))?,
+        );
+
+        let request = self
+            .request(
+                Method::GET,
+                format!("{}{}/admin/sso/providers", self.project_url, self.auth_path),
+            )
+            .headers(headers);
+
+        let response = self.send(request).await?;
+
+        decode_response::<SsoProvidersResponse>(response)
+            .await
+            .map(|providers| providers.items)
+    }
+
+    /// Retrieves a single SSO provider by its ID
+    /// Requires the `service_role` key as the client's API key
+    ///
+    /// WARNING: Requires an SSO Provider and Supabase Pro plan
+    ///
+    /// # Example
+    /// ```
+    /// let provider = auth_client.admin_get_sso_provider(provider_id).await.unwrap();
+    ///
+    /// println!("{:?}", provider.saml);
+    /// ```
+    pub async fn admin_get_sso_provider(&self, id: Uuid) -> Result<SsoProvider, Error> {
+        self.require_service_role()?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", &self.api_key))?,
+        );
+
+        let request = self
+            .request(
+                Method::GET,
+                format!(
+                    "{}{}/admin/sso/providers/{}",
+                    self.project_url, self.auth_path, id
+                ),
+            )
+            .headers(headers);
+
+        let response = self.send(request).await?;
+
+        decode_response(response).await
+    }
+
+    /// Updates an SSO provider, e.g. to rotate its metadata or change the domains routed to it
+    /// Requires the `service_role` key as the client's API key
+    ///
+    /// WARNING: Requires an SSO Provider and Supabase Pro plan
+    ///
+    /// # Example
+    /// ```
+    /// let provider = auth_client.admin_get_sso_provider(provider_id).await.unwrap();
+    ///
+    /// let params = UpdateSsoProviderParams::from_provider(&provider)
+    ///     .add_domain("new.example.com")
+    ///     .remove_domain("old.example.com");
+    ///
+    /// let provider = auth_client
+    ///     .admin_update_sso_provider(provider_id, params)
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn admin_update_sso_provider(
+        &self,
+        id: Uuid,
+        params: UpdateSsoProviderParams,
+    ) -> Result<SsoProvider, Error> {
+        self.require_service_role()?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", &self.api_key))?,
+        );
+
+        let body = serde_json::to_string(&params)?;
+
+        let request = self
+            .request(
+                Method::PUT,
+                format!(
+                    "{}{}/admin/sso/providers/{}",
+                    self.project_url, self.auth_path, id
+                ),
+            )
+            .headers(headers)
+            .body(body);
+
+        let response = self.send(request).await?;
+
+        decode_response(response).await
+    }
+
+    /// Deletes an SSO provider along with its domains
+    /// Returns the deleted provider
+    /// Requires the `service_role` key as the client's API key
+    ///
+    /// WARNING: Requires an SSO Provider and Supabase Pro plan
+    ///
+    /// # Example
+    /// ```
+    /// let deleted = auth_client.admin_delete_sso_provider(provider_id).await.unwrap();
+    /// ```
+    pub async fn admin_delete_sso_provider(&self, id: Uuid) -> Result<SsoProvider, Error> {
+        self.require_service_role()?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", &self.api_key))?,
+        );
 
-        if let Ok(session) = from_str(&res_body) {
-            return Ok(session);
-        }
+        let request = self
+            .request(
+                Method::DELETE,
+                format!(
+                    "{}{}/admin/sso/providers/{}",
+                    self.project_url, self.auth_path, id
+                ),
+            )
+            .headers(headers);
 
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
+        let response = self.send(request).await?;
 
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        decode_response(response).await
     }
 
-    /// Check the Health Status of the Auth Server
+    /// Registers a new third-party client with the project's OAuth 2.1 server
+    /// The returned client includes the `client_secret`, which cannot be retrieved again later
+    /// Requires the `service_role` key as the client's API key
+    ///
     /// # Example
     /// ```
-    /// let health = auth_client
-    ///     .get_health()
-    ///     .await
-    ///     .unwrap();
+    /// let params = CreateOAuthClientParams {
+    ///     client_name: "My App".to_string(),
+    ///     redirect_uris: vec!["https://myapp.example.com/callback".to_string()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let client = auth_client.admin_create_oauth_client(params).await.unwrap();
     /// ```
-    pub async fn get_health(&self) -> Result<AuthServerHealth, Error> {
+    pub async fn admin_create_oauth_client(
+        &self,
+        params: CreateOAuthClientParams,
+    ) -> Result<OAuthClient, Error> {
+        self.require_service_role()?;
+
         let mut headers = HeaderMap::new();
         headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", &self.api_key))?,
+        );
 
-        let response = self
-            .client
-            .get(&format!("{}{}/health", self.project_url, AUTH_V1))
-            .headers(headers)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
+        let body = serde_json::to_string(&params)?;
 
-        if let Ok(health) = from_str::<AuthServerHealth>(&res_body) {
-            return Ok(health);
-        }
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/admin/oauth/clients", self.project_url, self.auth_path),
+            )
+            .headers(headers)
+            .body(body);
 
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
+        let response = self.send(request).await?;
 
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        decode_response(response).await
     }
 
-    /// Retrieve the public settings of the server
+    /// Lists the third-party clients registered with the project's OAuth 2.1 server
+    /// Requires the `service_role` key as the client's API key
+    ///
     /// # Example
     /// ```
-    /// let settings = auth_client
-    ///     .get_settings()
-    ///     .await
-    ///     .unwrap();
+    /// let clients = auth_client.admin_list_oauth_clients().await.unwrap();
     /// ```
-    pub async fn get_settings(&self) -> Result<AuthServerSettings, Error> {
+    pub async fn admin_list_oauth_clients(&self) -> Result<Vec<OAuthClient>, Error> {
+        self.require_service_role()?;
+
         let mut headers = HeaderMap::new();
         headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", &self.api_key))?,
+        );
 
-        let response = self
-            .client
-            .get(&format!("{}{}/settings", self.project_url, AUTH_V1))
-            .headers(headers)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
-
-        if let Ok(settings) = from_str(&res_body) {
-            return Ok(settings);
-        }
+        let request = self
+            .request(
+                Method::GET,
+                format!("{}{}/admin/oauth/clients", self.project_url, self.auth_path),
+            )
+            .headers(headers);
 
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
+        let response = self.send(request).await?;
 
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        decode_response::<OAuthClientsResponse>(response)
+            .await
+            .map(|clients| clients.clients)
     }
 
-    /// Exchange refresh token for a new session
+    /// Retrieves a third-party OAuth client by its client ID
+    /// Requires the `service_role` key as the client's API key
+    ///
     /// # Example
     /// ```
-    /// // When a user signs in they get a session
-    /// let original_session = auth_client
-    ///     .login_with_email_and_password(demo_email.as_ref(), demo_password)
-    ///     .await
-    ///     .unwrap();
-    ///
-    /// // Exchange the refresh token from the original session to create a new session
-    /// let new_session = auth_client
-    ///     .refresh_session(original_session.refresh_token)
-    ///     .await
-    ///     .unwrap();
+    /// let client = auth_client.admin_get_oauth_client(&client_id).await.unwrap();
     /// ```
-    pub async fn exchange_token_for_session(&self, refresh_token: &str) -> Result<Session, Error> {
+    pub async fn admin_get_oauth_client(&self, client_id: &str) -> Result<OAuthClient, Error> {
+        self.require_service_role()?;
+
         let mut headers = HeaderMap::new();
         headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
-        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
-
-        let body = serde_json::to_string(&RefreshSessionPayload { refresh_token })?;
-
-        let response = self
-            .client
-            .post(&format!(
-                "{}{}/token?grant_type=refresh_token",
-                self.project_url, AUTH_V1
-            ))
-            .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
-
-        if let Ok(session) = from_str(&res_body) {
-            return Ok(session);
-        }
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", &self.api_key))?,
+        );
 
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
+        let request = self
+            .request(Method::GET, self.oauth_client_url(client_id, &[])?)
+            .headers(headers);
 
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
-    }
+        let response = self.send(request).await?;
 
-    pub async fn refresh_session(&self, refresh_token: &str) -> Result<Session, Error> {
-        self.exchange_token_for_session(refresh_token).await
+        decode_response(response).await
     }
 
-    /// Exchange code for a new session
+    /// Deletes a third-party OAuth client. Tokens already issued to the client stop working.
+    /// Requires the `service_role` key as the client's API key
+    ///
     /// # Example
     /// ```
-    /// // When a user signs in they get a session
-    /// let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
-    ///
-    /// let options = LoginWithOAuthOptions {
-    ///     query_params: Some(HashMap::from([
-    ///         (
-    ///             "redirect_to".to_owned(),
-    ///             "http://localhost:3000/auth/callback".to_owned(),
-    ///         ),
-    ///         ("response_type".to_owned(), "code".to_owned()),
-    ///         ("skip_browser_redirect".to_owned(), "true".to_owned()),
-    ///         (
-    ///             "code_challenge".to_owned(),
-    ///             pkce_challenge.as_str().to_owned(),
-    ///         ),
-    ///         ("code_challenge_method".to_owned(), "S256".to_owned()),
-    ///     ])),
-    ///     ..Default::default()
-    /// };
-    ///
-    /// let oauth_res = auth_client
-    ///     .login_with_oauth(Provider::Github, Some(options))?;
-    ///
-    /// // Exchange the code to create a new session
-    /// let new_session = auth_client
-    ///     .exchange_code_for_session(auth_code, pkce_verifier)
-    ///     .await
-    ///     .unwrap();
+    /// auth_client.admin_delete_oauth_client(&client_id).await.unwrap();
     /// ```
-    pub async fn exchange_code_for_session(
-        &self,
-        auth_code: &str,
-        code_verifier: &str,
-    ) -> Result<Session, Error> {
+    pub async fn admin_delete_oauth_client(&self, client_id: &str) -> Result<(), Error> {
+        self.require_service_role()?;
+
         let mut headers = HeaderMap::new();
         headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
-        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", &self.api_key))?,
+        );
 
-        let body = serde_json::to_string(&ExchangeCodeForSessionPayload {
-            auth_code,
-            code_verifier,
-        })?;
+        let request = self
+            .request(Method::DELETE, self.oauth_client_url(client_id, &[])?)
+            .headers(headers);
 
-        let response = self
-            .client
-            .post(&format!(
-                "{}{}/token?grant_type=pkce",
-                self.project_url, AUTH_V1
-            ))
-            .headers(headers)
-            .body(body)
-            .send()
-            .await?;
+        let response = self.send(request).await?;
 
         let res_status = response.status();
+        let res_headers = response.headers().clone();
         let res_body = response.text().await?;
 
-        if let Ok(session) = from_str(&res_body) {
-            return Ok(session);
-        }
-
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
+        if res_status.is_success() {
+            return Ok(());
         }
 
-        // Fallback: return raw error
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        Err(Error::from_body(res_status, res_body, res_headers))
     }
 
-    /// Send a password recovery email. Invalid Email addresses will return Error Code 400.
-    /// Valid email addresses that are not registered as users will not return an error.
+    /// Issues a new `client_secret` for a third-party OAuth client, invalidating the previous one
+    /// Requires the `service_role` key as the client's API key
+    ///
     /// # Example
     /// ```
-    /// let response = auth_client.reset_password_for_email(demo_email, None).await.unwrap();
+    /// let client = auth_client
+    ///     .admin_regenerate_oauth_client_secret(&client_id)
+    ///     .await
+    ///     .unwrap();
+    ///
+    /// let new_secret = client.client_secret.unwrap();
     /// ```
-    pub async fn reset_password_for_email(
+    pub async fn admin_regenerate_oauth_client_secret(
         &self,
-        email: &str,
-        options: Option<ResetPasswordOptions>,
-    ) -> Result<(), Error> {
-        let redirect_to = options
-            .as_ref()
-            .and_then(|o| o.email_redirect_to.as_deref().map(str::to_owned));
-
-        let payload = ResetPasswordForEmailPayload {
-            email: String::from(email),
-            options,
-        };
+        client_id: &str,
+    ) -> Result<OAuthClient, Error> {
+        self.require_service_role()?;
 
         let mut headers = HeaderMap::new();
         headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
-        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", &self.api_key))?,
+        );
 
-        let body = serde_json::to_string(&payload)?;
+        let request = self
+            .request(
+                Method::POST,
+                self.oauth_client_url(client_id, &["regenerate_secret"])?,
+            )
+            .headers(headers);
 
-        let response = self
-            .client
-            .post(&format!("{}{}/recover", self.project_url, AUTH_V1))
-            .query(&[("redirect_to", redirect_to.as_deref())])
-            .headers(headers)
-            .body(body)
-            .send()
-            .await?;
+        let response = self.send(request).await?;
 
-        let res_status = response.status();
-        let res_body = response.text().await?;
+        decode_response(response).await
+    }
 
-        if res_status.is_success() {
-            return Ok(());
+    /// The URL of the OAuth client `client_id`, followed by the path segments `rest`
+    ///
+    /// The id is percent encoded, so that it can't reach another endpoint. Fails with
+    /// [`Error::ParseUrlError`] for ids which are empty, `.` or `..`.
+    fn oauth_client_url(&self, client_id: &str, rest: &[&str]) -> Result<Url, Error> {
+        if matches!(client_id, "" | "." | "..") {
+            return Err(Error::ParseUrlError);
         }
 
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
+        let mut url = Url::parse(&format!(
+            "{}{}/admin/oauth/clients",
+            self.project_url, self.auth_path
+        ))
+        .map_err(|_| Error::ParseUrlError)?;
+        url.path_segments_mut()
+            .map_err(|_| Error::ParseUrlError)?
+            .push(client_id)
+            .extend(rest);
 
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        Ok(url)
     }
 
-    /// Resends emails for existing signup confirmation, email change, SMS OTP, or phone change OTP.
+    /// Lists a page of the project's users
+    /// Requires the `service_role` key as the client's API key
+    ///
     /// # Example
     /// ```
-    /// // Resend can also take MobileResendParams
-    /// let credentials = DesktopResendParams {
-    ///     otp_type: supabase_auth::models::EmailOtpType::Email,
-    ///     email: demo_email.to_owned(),
-    ///     options: None,
-    /// };
+    /// let params = ListUsersParams::default().page(2).per_page(100);
     ///
-    /// let resend = auth_client.resend(ResendParams::Desktop(credentials)).await;
+    /// let users = auth_client.admin_list_users(params).await.unwrap();
     /// ```
-    pub async fn resend(&self, credentials: ResendParams) -> Result<(), Error> {
+    pub async fn admin_list_users(&self, params: ListUsersParams) -> Result<Vec<User>, Error> {
+        self.require_service_role()?;
+
         let mut headers = HeaderMap::new();
         headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
-        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
-
-        let body = serde_json::to_string(&credentials)?;
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", &self.api_key))?,
+        );
 
-        let response = self
-            .client
-            .post(&format!("{}{}/resend", self.project_url, AUTH_V1))
+        let request = self
+            .request(
+                Method::GET,
+                format!("{}{}/admin/users", self.project_url, self.auth_path),
+            )
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
-
-        if res_status.is_success() {
-            return Ok(());
-        }
+            .query(&params);
 
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
-        }
+        let response = self.send(request).await?;
 
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        decode_response::<UsersResponse>(response)
+            .await
+            .map(|users| users.users)
     }
 
-    /// Logs out a user with a given scope
+    /// Lists a page of the project's users like [`AuthClient::admin_list_users`], decoding them
+    /// one at a time as the response arrives instead of buffering the page, see
+    /// [`stream`](crate::stream)
+    /// Requires the `service_role` key as the client's API key
+    ///
     /// # Example
     /// ```
-    /// auth_client.logout(Some(LogoutScope::Global), session.access_token).await.unwrap();
+    /// let params = ListUsersParams::default().per_page(10_000);
+    /// let mut users = auth_client.admin_list_users_stream(params).await?;
+    ///
+    /// while let Some(user) = users.next().await {
+    ///     println!("{}", user?.email);
+    /// }
     /// ```
-    pub async fn logout(
+    pub async fn admin_list_users_stream(
         &self,
-        scope: Option<LogoutScope>,
-        bearer_token: &str,
-    ) -> Result<(), Error> {
+        params: ListUsersParams,
+    ) -> Result<UserStream, Error> {
+        self.require_service_role()?;
+
         let mut headers = HeaderMap::new();
         headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
-        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
         headers.insert(
             AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", bearer_token))?,
+            HeaderValue::from_str(&format!("Bearer {}", &self.api_key))?,
         );
 
-        let body = serde_json::to_string(&scope)?;
-
-        let response = self
-            .client
-            .post(&format!("{}{}/logout", self.project_url, AUTH_V1))
+        let request = self
+            .request(
+                Method::GET,
+                format!("{}{}/admin/users", self.project_url, self.auth_path),
+            )
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let res_status = response.status();
-        let res_body = response.text().await?;
+            .query(&params);
 
-        if res_status.is_success() {
-            return Ok(());
-        }
+        let response = self.send(request).await?;
 
-        if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-            return Err(Error::AuthError {
-                status: res_status,
-                message: error.message,
-            });
+        let status = response.status();
+        if !status.is_success() {
+            let headers = response.headers().clone();
+            let body = response.text().await?;
+            return Err(Error::from_body(status, body, headers));
         }
 
-        Err(Error::AuthError {
-            status: res_status,
-            message: res_body,
-        })
+        Ok(UserStream::new(response))
     }
 
-    /// Initiates an SSO Login Flow
-    /// Returns the URL where the user must authenticate with the SSO Provider
-    ///
-    /// WARNING: Requires an SSO Provider and Supabase Pro plan
+    /// Generates an email link, e.g. a magic link or an invite, without sending the email
+    /// Requires the `service_role` key as the client's API key
     ///
     /// # Example
     /// ```
-    /// let url = auth_client.sso(params).await.unwrap();
+    /// let params = GenerateLinkParams::new(GenerateLinkType::Magiclink, "demo@example.com");
     ///
-    /// println!("{}", url.to_string());
+    /// let link = auth_client.admin_generate_link(params).await.unwrap();
+    ///
+    /// println!("{}", link.action_link);
     /// ```
-    pub async fn sso(&self, params: LoginWithSSO) -> Result<Url, Error> {
+    pub async fn admin_generate_link(
+        &self,
+        params: GenerateLinkParams,
+    ) -> Result<GeneratedLink, Error> {
+        self.require_service_role()?;
+
         let mut headers = HeaderMap::new();
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
         headers.insert("apikey", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", &self.api_key))?,
+        );
 
-        let body = serde_json::to_string::<crate::models::LoginWithSSO>(&params)?;
+        let body = serde_json::to_string(&params)?;
 
-        let response = self
-            .client
-            .post(&format!("{}{}/sso", self.project_url, AUTH_V1))
+        let request = self
+            .request(
+                Method::POST,
+                format!("{}{}/admin/generate_link", self.project_url, self.auth_path),
+            )
             .headers(headers)
-            .body(body)
-            .send()
-            .await?;
+            .body(body);
 
-        let res_status = response.status();
-        let url = response.url().clone();
-        let res_body = response.text().await?;
-
-        if res_status.is_server_error() || res_status.is_client_error() {
-            if let Ok(error) = from_str::<SupabaseHTTPError>(&res_body) {
-                return Err(AuthError {
-                    status: res_status,
-                    message: error.message,
-                });
-            }
-
-            // Fallback: return raw error
-            return Err(AuthError {
-                status: res_status,
-                message: res_body,
-            });
-        }
+        let response = self.send(request).await?;
 
-        Ok(url)
+        decode_response(response).await
     }
 
     /// Get the project URL from an AuthClient
@@ -1273,6 +2377,12 @@ impl AuthClient {
         &self.project_url
     }
 
+    /// The path of the auth API relative to the project URL, `/auth/v1` unless configured with
+    /// [`AuthClientBuilder::auth_path`](crate::builder::AuthClientBuilder::auth_path)
+    pub fn auth_path(&self) -> &str {
+        &self.auth_path
+    }
+
     /// Get the API Key from an AuthClient
     pub fn api_key(&self) -> &str {
         &self.api_key
@@ -1282,4 +2392,59 @@ impl AuthClient {
     pub fn jwt_secret(&self) -> &str {
         &self.jwt_secret
     }
+
+    /// The role of the API key, if it could be determined
+    ///
+    /// Legacy API keys are JWTs carrying a `role` claim. The newer `sb_publishable_` and
+    /// `sb_secret_` keys are recognized by their prefix. `None` for anything else.
+    /// # Example
+    /// ```
+    /// if auth_client.key_role().is_some_and(|role| role.is_service_role()) {
+    ///     panic!("Don't ship the service role key to browsers");
+    /// }
+    /// ```
+    pub fn key_role(&self) -> Option<&Role> {
+        self.key_role.as_deref()
+    }
+
+    /// Fail early with [`Error::ServiceRoleKeyRequired`] when the API key is known not to be a
+    /// service role key, instead of sending a request the server will reject
+    fn require_service_role(&self) -> Result<(), Error> {
+        match &self.key_role {
+            Some(role) if !role.is_service_role() => Err(Error::ServiceRoleKeyRequired),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The token of an `Authorization: Bearer` header
+fn bearer_token(headers: &HeaderMap) -> Option<AccessToken> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_bearer)
+}
+
+/// The token of an `Authorization` header value using the `Bearer` scheme, whose name is case
+/// insensitive. A header without a token, like `Bearer `, has none.
+pub(crate) fn parse_bearer(value: &str) -> Option<AccessToken> {
+    let (scheme, token) = value.trim_start().split_once(' ')?;
+    let token = token.trim();
+
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then(|| AccessToken::new(token))
+}
+
+/// The role of `api_key`, see [`AuthClient::key_role`]
+fn api_key_role(api_key: &str) -> Option<Role> {
+    if api_key.starts_with("sb_secret_") {
+        return Some(Role::SERVICE_ROLE.into());
+    }
+
+    if api_key.starts_with("sb_publishable_") {
+        return Some(Role::ANON.into());
+    }
+
+    decode_unverified::<ApiKeyClaims>(api_key)
+        .ok()
+        .map(|claims| claims.role)
 }
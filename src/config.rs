@@ -0,0 +1,228 @@
+/*!
+Client settings loaded from a TOML file with named profiles (requires the `config` feature).
+
+Keeping one profile per environment lets the same binary talk to a local Supabase stack, staging,
+and production by switching the profile name instead of recompiling or juggling environment
+variables.
+
+```toml
+[profiles.local]
+project_url = "http://localhost:54321"
+api_key = "your-local-anon-key"
+jwt_secret = "super-secret-jwt-token-with-at-least-32-characters-long"
+
+[profiles.prod]
+project_url = "https://YOUR_PROJECT_ID.supabase.co"
+api_key = "sb_publishable_..."
+timeout_secs = 10
+
+[profiles.prod.retry]
+max_attempts = 4
+initial_backoff_secs = 0.5
+
+[profiles.prod.refresh]
+refresh_margin_secs = 120
+```
+
+Durations are given in seconds, fractions are allowed.
+
+# Example
+
+```rust
+let config = ConfigFile::load("supabase.toml")?;
+let profile = config.profile(&env::var("APP_ENV")?)?;
+
+let auth_client = profile.build()?;
+let refresher =
+    SessionRefresher::spawn_with_policy(auth_client, session, profile.refresh_policy()?);
+```
+*/
+
+use std::{collections::HashMap, fmt, fs, path::Path, str::FromStr, time::Duration};
+
+use serde::Deserialize;
+
+use crate::{
+    builder::AuthClientBuilder,
+    error::Error,
+    models::{redact, AuthClient},
+    refresher::RefreshPolicy,
+    retry::RetryPolicy,
+};
+
+/// A configuration file, holding one [`Profile`] per environment
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+impl ConfigFile {
+    /// Read and parse the configuration file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let contents =
+            fs::read_to_string(path).map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+        contents.parse()
+    }
+
+    /// The profile called `name`, failing with [`Error::UnknownProfile`] if there is none
+    pub fn profile(&self, name: &str) -> Result<&Profile, Error> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| Error::UnknownProfile(name.to_string()))
+    }
+}
+
+impl FromStr for ConfigFile {
+    type Err = Error;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        toml::from_str(contents).map_err(|error| Error::InvalidConfig(error.to_string()))
+    }
+}
+
+/// Settings of a client for one environment
+///
+/// Only `project_url` is required. Leave out `api_key` when connecting to a self-hosted server
+/// which doesn't expect the `apikey` header.
+#[derive(Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub project_url: String,
+    pub api_key: Option<String>,
+    pub jwt_secret: Option<String>,
+    /// See [`AuthClientBuilder::auth_path`]
+    pub auth_path: Option<String>,
+    /// See [`AuthClientBuilder::timeout`]
+    pub timeout_secs: Option<f64>,
+    /// Retry requests which failed with a rate limit or server error, see [`RetryPolicy`]
+    pub retry: Option<RetrySettings>,
+    /// When sessions are refreshed, see [`RefreshPolicy`]
+    #[serde(default)]
+    pub refresh: RefreshSettings,
+}
+
+impl fmt::Debug for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Profile")
+            .field("project_url", &self.project_url)
+            .field("api_key", &redact(&self.api_key))
+            .field("jwt_secret", &redact(&self.jwt_secret))
+            .field("auth_path", &self.auth_path)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("retry", &self.retry)
+            .field("refresh", &self.refresh)
+            .finish()
+    }
+}
+
+/// The `retry` table of a [`Profile`]. Unset values keep the [`RetryPolicy`] defaults.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RetrySettings {
+    pub max_attempts: Option<u32>,
+    pub initial_backoff_secs: Option<f64>,
+    pub max_backoff_secs: Option<f64>,
+    pub jitter: Option<bool>,
+}
+
+/// The `refresh` table of a [`Profile`]. Unset values keep the [`RefreshPolicy`] defaults.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RefreshSettings {
+    pub refresh_margin_secs: Option<f64>,
+    pub max_jitter_secs: Option<f64>,
+    pub retry_interval_secs: Option<f64>,
+}
+
+impl Profile {
+    /// A builder configured with this profile, for settings the file doesn't cover
+    pub fn builder(&self) -> Result<AuthClientBuilder, Error> {
+        let mut builder = AuthClient::builder().project_url(&self.project_url);
+
+        builder = match &self.api_key {
+            Some(api_key) => builder.api_key(api_key),
+            None => builder.without_api_key(),
+        };
+
+        if let Some(jwt_secret) = &self.jwt_secret {
+            builder = builder.jwt_secret(jwt_secret);
+        }
+
+        if let Some(auth_path) = &self.auth_path {
+            builder = builder.auth_path(auth_path);
+        }
+
+        if let Some(timeout) = self.timeout_secs {
+            builder = builder.timeout(seconds("timeout_secs", timeout)?);
+        }
+
+        if let Some(retry) = &self.retry {
+            builder = builder.retry_policy(retry.policy()?);
+        }
+
+        Ok(builder)
+    }
+
+    /// Build a client configured with this profile
+    pub fn build(&self) -> Result<AuthClient, Error> {
+        self.builder()?.build()
+    }
+
+    /// The refresh policy of this profile, for a
+    /// [`SessionRefresher`](crate::refresher::SessionRefresher)
+    pub fn refresh_policy(&self) -> Result<RefreshPolicy, Error> {
+        let defaults = RefreshPolicy::default();
+        let refresh = &self.refresh;
+
+        Ok(RefreshPolicy {
+            refresh_margin: or_default(
+                "refresh_margin_secs",
+                refresh.refresh_margin_secs,
+                defaults.refresh_margin,
+            )?,
+            max_jitter: or_default(
+                "max_jitter_secs",
+                refresh.max_jitter_secs,
+                defaults.max_jitter,
+            )?,
+            retry_interval: or_default(
+                "retry_interval_secs",
+                refresh.retry_interval_secs,
+                defaults.retry_interval,
+            )?,
+        })
+    }
+}
+
+impl RetrySettings {
+    fn policy(&self) -> Result<RetryPolicy, Error> {
+        let defaults = RetryPolicy::default();
+
+        Ok(RetryPolicy {
+            max_attempts: self.max_attempts.unwrap_or(defaults.max_attempts),
+            initial_backoff: or_default(
+                "initial_backoff_secs",
+                self.initial_backoff_secs,
+                defaults.initial_backoff,
+            )?,
+            max_backoff: or_default(
+                "max_backoff_secs",
+                self.max_backoff_secs,
+                defaults.max_backoff,
+            )?,
+            jitter: self.jitter.unwrap_or(defaults.jitter),
+            ..defaults
+        })
+    }
+}
+
+fn or_default(name: &str, value: Option<f64>, default: Duration) -> Result<Duration, Error> {
+    value.map_or(Ok(default), |value| seconds(name, value))
+}
+
+fn seconds(name: &str, value: f64) -> Result<Duration, Error> {
+    Duration::try_from_secs_f64(value)
+        .map_err(|_| Error::InvalidConfig(format!("{} must be a positive number", name)))
+}
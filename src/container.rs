@@ -0,0 +1,239 @@
+/*!
+A self-hosted Supabase Auth server for integration tests, enabled with the `testcontainers`
+feature.
+
+[`GoTrueContainer::start`] runs Postgres and GoTrue in Docker with
+[testcontainers](https://docs.rs/testcontainers), signs up [`USER_EMAIL`] and returns once the
+server is healthy. Tests get a real server without a Supabase project, demo credentials or
+waiting out rate limits: emails and phone numbers are confirmed automatically and rate limits
+are raised far beyond what tests hit.
+
+Both containers are removed when the [`GoTrueContainer`] is dropped.
+
+# Example
+
+```rust
+#[tokio::test]
+async fn signs_in() {
+    let gotrue = GoTrueContainer::start().await;
+
+    let session = gotrue
+        .client()
+        .login_with_email(USER_EMAIL, USER_PASSWORD)
+        .await
+        .unwrap();
+
+    assert_eq!(session.user.id, gotrue.user().id);
+}
+```
+*/
+
+use std::time::Duration;
+
+use chrono::Utc;
+use jsonwebtoken::{EncodingKey, Header};
+use serde_json::{json, Value};
+use testcontainers::{
+    core::{IntoContainerPort, WaitFor},
+    runners::AsyncRunner,
+    ContainerAsync, GenericImage, ImageExt,
+};
+use uuid::Uuid;
+
+use crate::models::{AuthClient, User};
+
+/// The GoTrue image the server runs
+pub const GOTRUE_IMAGE: (&str, &str) = ("supabase/gotrue", "v2.170.0");
+
+/// The Postgres image the server stores users in
+pub const POSTGRES_IMAGE: (&str, &str) = ("postgres", "15-alpine");
+
+/// The JWT secret the server signs access tokens with, the default of local Supabase
+pub const JWT_SECRET: &str = "super-secret-jwt-token-with-at-least-32-characters-long";
+
+/// The email address of the user signed up on start
+pub const USER_EMAIL: &str = "demo@example.com";
+
+/// The password of the user signed up on start
+pub const USER_PASSWORD: &str = "demo-password";
+
+/// The port GoTrue listens on inside its container
+const GOTRUE_PORT: u16 = 9999;
+
+/// How long GoTrue may take to migrate the database and become healthy
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Creates the roles and schema GoTrue expects, like Supabase's Postgres image does
+const INIT_SQL: &str = "
+CREATE USER supabase_admin LOGIN CREATEROLE CREATEDB REPLICATION BYPASSRLS;
+CREATE USER supabase_auth_admin NOINHERIT CREATEROLE LOGIN NOREPLICATION PASSWORD 'root';
+CREATE SCHEMA IF NOT EXISTS auth AUTHORIZATION supabase_auth_admin;
+GRANT CREATE ON DATABASE postgres TO supabase_auth_admin;
+ALTER USER supabase_auth_admin SET search_path = 'auth';
+";
+
+/// A GoTrue server running in Docker, see the [module docs](self)
+#[derive(Debug)]
+pub struct GoTrueContainer {
+    gotrue: ContainerAsync<GenericImage>,
+    /// Kept so the database lives as long as the server
+    _postgres: ContainerAsync<GenericImage>,
+    url: String,
+    user: User,
+}
+
+impl GoTrueContainer {
+    /// Start the containers, wait until GoTrue is healthy and sign up [`USER_EMAIL`]
+    ///
+    /// # Panics
+    ///
+    /// If Docker isn't available, or the server doesn't start within a minute. The panic
+    /// message includes GoTrue's logs.
+    pub async fn start() -> Self {
+        let id = Uuid::new_v4().simple().to_string();
+        let network = format!("supabase-auth-{id}");
+        let database_host = format!("supabase-auth-db-{id}");
+
+        let postgres = GenericImage::new(POSTGRES_IMAGE.0, POSTGRES_IMAGE.1)
+            .with_wait_for(WaitFor::message_on_stdout(
+                "PostgreSQL init process complete",
+            ))
+            .with_wait_for(WaitFor::seconds(1))
+            .with_network(&network)
+            .with_container_name(&database_host)
+            .with_env_var("POSTGRES_PASSWORD", "postgres")
+            .with_copy_to(
+                "/docker-entrypoint-initdb.d/init.sql",
+                INIT_SQL.as_bytes().to_vec(),
+            )
+            .start()
+            .await
+            .expect("the Postgres container starts");
+
+        let gotrue = GenericImage::new(GOTRUE_IMAGE.0, GOTRUE_IMAGE.1)
+            .with_exposed_port(GOTRUE_PORT.tcp())
+            .with_network(&network)
+            .with_env_var("GOTRUE_API_HOST", "0.0.0.0")
+            .with_env_var("PORT", GOTRUE_PORT.to_string())
+            .with_env_var("API_EXTERNAL_URL", "http://localhost:9999")
+            .with_env_var("GOTRUE_SITE_URL", "http://localhost:3000")
+            .with_env_var("GOTRUE_DB_DRIVER", "postgres")
+            .with_env_var(
+                "GOTRUE_DB_DATABASE_URL",
+                format!("postgres://supabase_auth_admin:root@{database_host}:5432/postgres"),
+            )
+            .with_env_var("GOTRUE_JWT_SECRET", JWT_SECRET)
+            .with_env_var("GOTRUE_JWT_EXP", "3600")
+            .with_env_var("GOTRUE_JWT_AUD", "authenticated")
+            .with_env_var("GOTRUE_JWT_DEFAULT_GROUP_NAME", "authenticated")
+            .with_env_var("GOTRUE_JWT_ADMIN_ROLES", "service_role")
+            .with_env_var("GOTRUE_DISABLE_SIGNUP", "false")
+            .with_env_var("GOTRUE_EXTERNAL_EMAIL_ENABLED", "true")
+            .with_env_var("GOTRUE_MAILER_AUTOCONFIRM", "true")
+            .with_env_var("GOTRUE_EXTERNAL_PHONE_ENABLED", "true")
+            .with_env_var("GOTRUE_SMS_AUTOCONFIRM", "true")
+            .with_env_var("GOTRUE_EXTERNAL_ANONYMOUS_USERS_ENABLED", "true")
+            .with_env_var("GOTRUE_RATE_LIMIT_EMAIL_SENT", "10000")
+            .with_env_var("GOTRUE_RATE_LIMIT_SMS_SENT", "10000")
+            .with_env_var("GOTRUE_RATE_LIMIT_TOKEN_REFRESH", "10000")
+            .with_env_var("GOTRUE_RATE_LIMIT_VERIFY", "10000")
+            .with_env_var("GOTRUE_RATE_LIMIT_OTP", "10000")
+            .with_env_var("GOTRUE_RATE_LIMIT_ANONYMOUS_USERS", "10000")
+            .start()
+            .await
+            .expect("the GoTrue container starts");
+
+        let host = gotrue.get_host().await.expect("GoTrue has a host");
+        let port = gotrue
+            .get_host_port_ipv4(GOTRUE_PORT)
+            .await
+            .expect("GoTrue's port is mapped");
+        let url = format!("http://{host}:{port}");
+
+        if !wait_until_healthy(&url).await {
+            let logs = gotrue.stderr_to_vec().await.unwrap_or_default();
+            panic!(
+                "GoTrue didn't become healthy within {STARTUP_TIMEOUT:?}:\n{}",
+                String::from_utf8_lossy(&logs)
+            );
+        }
+
+        let mut container = GoTrueContainer {
+            gotrue,
+            _postgres: postgres,
+            url,
+            user: User::default(),
+        };
+
+        container.user = container
+            .client()
+            .sign_up_with_email_and_password(USER_EMAIL, USER_PASSWORD, None)
+            .await
+            .expect("the user signs up")
+            .into_session()
+            .expect("the sign up doesn't need confirmation, as autoconfirm is on")
+            .user;
+
+        container
+    }
+
+    /// The project URL of the server
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The GoTrue container, e.g. to read its logs
+    pub fn container(&self) -> &ContainerAsync<GenericImage> {
+        &self.gotrue
+    }
+
+    /// The user signed up with [`USER_EMAIL`] and [`USER_PASSWORD`] on start
+    pub fn user(&self) -> &User {
+        &self.user
+    }
+
+    /// A client for the server, with an anon key and [`JWT_SECRET`]
+    pub fn client(&self) -> AuthClient {
+        AuthClient::new(self.url.clone(), sign_key("anon"), JWT_SECRET)
+    }
+
+    /// A client for the server, with a service role key for the admin methods
+    pub fn admin_client(&self) -> AuthClient {
+        AuthClient::new(self.url.clone(), sign_key("service_role"), JWT_SECRET)
+    }
+}
+
+/// Poll `/health` until GoTrue answers, it migrates the database before it listens
+async fn wait_until_healthy(url: &str) -> bool {
+    let deadline = tokio::time::Instant::now() + STARTUP_TIMEOUT;
+
+    while tokio::time::Instant::now() < deadline {
+        if let Ok(response) = reqwest::get(format!("{url}/health")).await {
+            if response.status().is_success() {
+                return true;
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+
+    false
+}
+
+/// An API key of `role`, like the ones Supabase issues
+fn sign_key(role: &str) -> String {
+    let now = Utc::now().timestamp();
+    let claims: Value = json!({
+        "iss": "supabase-demo",
+        "role": role,
+        "iat": now,
+        "exp": now + 10 * 365 * 24 * 3600,
+    });
+
+    jsonwebtoken::encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+    )
+    .expect("API keys can be signed")
+}
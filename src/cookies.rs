@@ -0,0 +1,244 @@
+/*!
+Build and parse the `sb-<project-ref>-auth-token` cookies used by `@supabase/ssr`.
+
+Server rendered apps share the session with the browser through cookies. This module writes
+sessions in the same format as `@supabase/ssr`, so either side can read what the other wrote:
+
+- The cookie value is the supabase-js session JSON, base64url encoded and prefixed with `base64-`.
+- Values longer than [`MAX_CHUNK_SIZE`] are split over `<name>.0`, `<name>.1`, ... cookies.
+
+# Example
+
+```rust
+let cookie_name = cookies::cookie_name(auth_client.project_url())?;
+
+// After signing in, send the session to the browser
+for set_cookie in cookies::build_session_cookies(&cookie_name, &session, &CookieOptions::default())? {
+    response.headers_mut().append(SET_COOKIE, set_cookie.parse()?);
+}
+
+// On the next request, read it back
+let session = cookies::parse_session_cookie(&cookie_name, request_cookie_header)?;
+```
+*/
+
+use std::fmt::Write;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use reqwest::Url;
+
+use crate::{error::Error, models::Session};
+
+/// Longest cookie value written before the session is split into chunks, same as `@supabase/ssr`
+pub const MAX_CHUNK_SIZE: usize = 3180;
+
+/// Prefix `@supabase/ssr` puts in front of base64url encoded values
+const BASE64_PREFIX: &str = "base64-";
+
+/// The `SameSite` attribute of a cookie
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SameSite {
+    Strict,
+    #[default]
+    Lax,
+    None,
+}
+
+/// Attributes of the auth cookies. Defaults match `@supabase/ssr`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieOptions {
+    pub path: String,
+    pub domain: Option<String>,
+    /// Lifetime of the cookie in seconds. Defaults to 400 days, the longest browsers allow.
+    pub max_age: u64,
+    pub same_site: SameSite,
+    pub secure: bool,
+    /// `@supabase/ssr` reads the session in the browser, so this is off by default
+    pub http_only: bool,
+}
+
+impl Default for CookieOptions {
+    fn default() -> Self {
+        CookieOptions {
+            path: "/".to_string(),
+            domain: None,
+            max_age: 400 * 24 * 60 * 60,
+            same_site: SameSite::Lax,
+            secure: false,
+            http_only: false,
+        }
+    }
+}
+
+/// The cookie name supabase-js uses for a project: `sb-<project-ref>-auth-token`
+///
+/// The project ref is the first label of the project URL's host name.
+pub fn cookie_name(project_url: &str) -> Result<String, Error> {
+    let url = Url::parse(project_url).map_err(|_| Error::ParseUrlError)?;
+    let host = url.host_str().ok_or(Error::ParseUrlError)?;
+    let project_ref = host.split('.').next().unwrap_or(host);
+
+    Ok(format!("sb-{}-auth-token", project_ref))
+}
+
+/// `Set-Cookie` header values storing `session` under `name`, split into chunks if needed
+///
+/// When a session shrinks, chunks written for the previous session may be left behind. Send
+/// [`build_removal_cookies`] for the cookies present in the request first to clear them.
+pub fn build_session_cookies(
+    name: &str,
+    session: &Session,
+    options: &CookieOptions,
+) -> Result<Vec<String>, Error> {
+    let value = format!(
+        "{}{}",
+        BASE64_PREFIX,
+        URL_SAFE_NO_PAD.encode(session.to_supabase_js_json()?)
+    );
+
+    if value.len() <= MAX_CHUNK_SIZE {
+        return Ok(vec![set_cookie(name, &value, options)]);
+    }
+
+    // The value is ASCII, so splitting on byte boundaries is safe
+    let cookies = value
+        .as_bytes()
+        .chunks(MAX_CHUNK_SIZE)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+            set_cookie(&format!("{}.{}", name, i), chunk, options)
+        })
+        .collect();
+
+    Ok(cookies)
+}
+
+/// `Set-Cookie` header values which delete the session cookie and all of its chunks found in
+/// `cookie_header`, e.g. on logout
+pub fn build_removal_cookies(
+    name: &str,
+    cookie_header: &str,
+    options: &CookieOptions,
+) -> Vec<String> {
+    let options = CookieOptions {
+        max_age: 0,
+        ..options.clone()
+    };
+
+    parse_cookie_header(cookie_header)
+        .filter(|(cookie, _)| is_session_cookie(name, cookie))
+        .map(|(cookie, _)| set_cookie(cookie, "", &options))
+        .collect()
+}
+
+/// Read the session stored under `name` from the value of a `Cookie` request header
+///
+/// Returns `None` if the cookie is not present. Both chunked and single cookies are supported,
+/// as are the base64url and plain JSON encodings `@supabase/ssr` has used.
+pub fn parse_session_cookie(name: &str, cookie_header: &str) -> Result<Option<Session>, Error> {
+    let cookies: Vec<(&str, &str)> = parse_cookie_header(cookie_header).collect();
+    let find = |cookie: &str| {
+        cookies
+            .iter()
+            .find(|(n, _)| *n == cookie)
+            .map(|(_, value)| *value)
+    };
+
+    let value = match find(name) {
+        Some(value) => value.to_string(),
+        None => {
+            let mut value = String::new();
+
+            for i in 0.. {
+                match find(&format!("{}.{}", name, i)) {
+                    Some(chunk) => value.push_str(chunk),
+                    None => break,
+                }
+            }
+
+            value
+        }
+    };
+
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let json = match value.strip_prefix(BASE64_PREFIX) {
+        Some(encoded) => {
+            let decoded = URL_SAFE_NO_PAD
+                .decode(encoded.trim_end_matches('='))
+                .map_err(|_| Error::InvalidCookie)?;
+
+            String::from_utf8(decoded).map_err(|_| Error::InvalidCookie)?
+        }
+        None => percent_decode(&value)?,
+    };
+
+    Session::from_supabase_js_json(&json).map(Some)
+}
+
+fn is_session_cookie(name: &str, cookie: &str) -> bool {
+    cookie == name
+        || cookie
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .is_some_and(|index| index.parse::<usize>().is_ok())
+}
+
+fn parse_cookie_header(cookie_header: &str) -> impl Iterator<Item = (&str, &str)> {
+    cookie_header.split(';').filter_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        let value = value.trim();
+
+        Some((name.trim(), value.trim_matches('"')))
+    })
+}
+
+fn set_cookie(name: &str, value: &str, options: &CookieOptions) -> String {
+    let mut cookie = format!(
+        "{}={}; Path={}; Max-Age={}",
+        name, value, options.path, options.max_age
+    );
+
+    if let Some(domain) = &options.domain {
+        let _ = write!(cookie, "; Domain={}", domain);
+    }
+
+    cookie.push_str(match options.same_site {
+        SameSite::Strict => "; SameSite=Strict",
+        SameSite::Lax => "; SameSite=Lax",
+        SameSite::None => "; SameSite=None",
+    });
+
+    if options.secure {
+        cookie.push_str("; Secure");
+    }
+
+    if options.http_only {
+        cookie.push_str("; HttpOnly");
+    }
+
+    cookie
+}
+
+/// Decode a value written with JavaScript's `encodeURIComponent`
+fn percent_decode(value: &str) -> Result<String, Error> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3).ok_or(Error::InvalidCookie)?;
+            decoded.push(u8::from_str_radix(hex, 16).map_err(|_| Error::InvalidCookie)?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|_| Error::InvalidCookie)
+}
@@ -4,18 +4,38 @@ Provides error types and handling for Supabase authentication operations.
 This module defines the primary [`Error`] enum and supporting types for handling various
 error conditions that may occur during authentication operations.
 
-The most common error is [`Error::Supabase`] which wraps [`SupabaseHTTPError`]. This is a transparent error which gives you the `status` as a [`StatusCode`] and `message` from the Supabase response.
+Failed requests end in one of three variants, so callers can decide what to retry and what to
+alert on:
+
+- [`Error::NetworkError`]: the server couldn't be reached or the connection broke. Usually worth
+  retrying.
+- [`Error::AuthError`] (and the more specific [`Error::RateLimited`] and [`Error::WeakPassword`]):
+  the server rejected the request. Its `status`, `code`, and `message` say why.
+- [`Error::DecodeError`]: the server accepted the request, but its response couldn't be decoded.
+  The `endpoint` and the offending `body` are kept for debugging, this usually means the server
+  and this library disagree on the API version.
+
+The underlying `reqwest` and `serde_json` errors are available through
+[`std::error::Error::source`].
 */
 
 use std::{
     env,
     fmt::{self, Display},
+    time::Duration,
 };
 
-use reqwest::StatusCode;
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use reqwest::{header::HeaderMap, Response, StatusCode, Url};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
+use crate::{
+    meta::request_id,
+    models::{WeakPassword, WeakPasswordReason},
+    retry::retry_after,
+};
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("User Already Exists")]
@@ -28,28 +48,433 @@ pub enum Error {
     NotAuthenticated,
     #[error("Missing Refresh Token")]
     MissingRefreshToken,
+    /// The refresh token was already exchanged for a new session, usually by another client
+    /// sharing the same session. The token can't be used again, the user has to sign in again.
+    #[error("Refresh Token Already Used")]
+    RefreshTokenAlreadyUsed,
     #[error("JWT Is Invalid")]
     WrongToken,
+    #[error("JWT Verification Failed")]
+    InvalidJwt(#[from] jsonwebtoken::errors::Error),
+    #[error("This Operation Requires the Service Role Key")]
+    ServiceRoleKeyRequired,
+    #[error("Expected Role {expected}, Got {actual}")]
+    RoleMismatch { expected: String, actual: String },
+    #[error("Signing Key {0} Not Found")]
+    UnknownSigningKey(String),
     #[error("Internal Error")]
     InternalError,
     #[error("Network Error")]
     NetworkError(#[from] reqwest::Error),
     #[error("Failed to Parse")]
     ParseError(#[from] serde_json::Error),
+    /// The successful response of `endpoint` (the URL path) isn't what the API should return,
+    /// usually because the server runs a different GoTrue version. `body` holds the first 4 KiB
+    /// of the response, to report or work around the mismatch.
+    #[error("Failed to Decode Response of {endpoint}: {source}")]
+    DecodeError {
+        endpoint: String,
+        body: String,
+        #[source]
+        source: serde_json::Error,
+    },
     #[error("Header Value is Invalid")]
     InvalidHeaderValue(#[from] reqwest::header::InvalidHeaderValue),
     #[error("Environment Variable Unreadable")]
     InvalidEnvironmentVariable(#[from] env::VarError),
+    /// Environment variables needed to create a client are unset or empty (`missing`), or not
+    /// valid Unicode or not a valid URL (`invalid`)
+    #[error("Environment Variables Missing: {missing:?}, Invalid: {invalid:?}")]
+    InvalidEnvironment {
+        missing: Vec<String>,
+        invalid: Vec<String>,
+    },
     #[error("Failed to parse URL")]
     ParseUrlError,
+    #[error("Invalid Version: {0}")]
+    InvalidVersion(String),
+    #[error("Missing Client Configuration: {0}")]
+    MissingConfiguration(&'static str),
+    /// Two builder options were set which can't be used together
+    #[error("Conflicting Client Configuration: {0}")]
+    ConflictingConfiguration(&'static str),
+    #[error("Invalid Configuration File: {0}")]
+    InvalidConfig(String),
+    #[error("Profile {0} Not Found")]
+    UnknownProfile(String),
+    #[error("Session Store Unavailable")]
+    SessionStoreError(#[from] std::io::Error),
+    #[error("Failed to Decrypt Session")]
+    DecryptionError,
+    #[error("Session Cookie Is Invalid")]
+    InvalidCookie,
+    /// A recorded fixture couldn't be read or written, or holds no response for a request
+    #[error("Recording Fixture Unusable: {0}")]
+    FixtureError(String),
     #[error("{0}")]
-    Supabase(SupabaseHTTPError),
+    Supabase(Box<SupabaseHTTPError>),
+    /// The server rejected the request. `code` is set when the server returned a machine readable
+    /// error code. `headers` are the response headers, e.g. `Retry-After` on `429` responses.
     #[error("Error: {status}: {message}")]
-    AuthError { status: StatusCode, message: String },
+    AuthError {
+        status: StatusCode,
+        code: Option<ErrorCode>,
+        message: String,
+        headers: Box<HeaderMap>,
+    },
+    /// The new password doesn't meet the project's password requirements. `reasons` lists the
+    /// requirements it failed, `message` describes them.
+    #[error("Weak Password: {message}")]
+    WeakPassword {
+        status: StatusCode,
+        reasons: Vec<WeakPasswordReason>,
+        message: String,
+        headers: Box<HeaderMap>,
+    },
+    /// The server answered `429 Too Many Requests`. Wait for `retry_after` before trying again,
+    /// when the server said how long.
+    #[error("Rate Limited: {message}")]
+    RateLimited {
+        retry_after: Option<Duration>,
+        code: Option<ErrorCode>,
+        message: String,
+        headers: Box<HeaderMap>,
+    },
+    /// The server didn't report healthy within `timeout`, see
+    /// [`AuthClient::wait_until_healthy`](crate::models::AuthClient::wait_until_healthy).
+    /// `last_error` is why the last completed health check failed, if any.
+    #[error("Auth Server Not Healthy After {timeout:?}")]
+    Unhealthy {
+        timeout: Duration,
+        #[source]
+        last_error: Option<Box<Error>>,
+    },
+    /// The request wasn't sent, as the client's circuit breaker opened after repeated failures to
+    /// reach the server. Wait for `retry_after` before trying again, see
+    /// [`CircuitBreaker`](crate::circuit_breaker::CircuitBreaker).
+    #[error("Circuit Breaker Open, Retry After {retry_after:?}")]
+    CircuitOpen { retry_after: Duration },
+    /// The access token expired at `expired_at`, so the request wasn't sent. Refresh the session
+    /// and try again with its new access token.
+    #[error("Access Token Expired at {expired_at}")]
+    TokenExpired { expired_at: DateTime<Utc> },
+    /// A refresh this call waited for, see
+    /// [`SingleFlightRefresh`](crate::refresher::SingleFlightRefresh), failed with an error which
+    /// can't be copied, e.g. a network error. `message` describes it.
+    #[error("Refresh Failed: {message}")]
+    RefreshFailed { message: String },
 }
 
+impl Error {
+    /// The error for a response the server rejected with `status` and `body`
+    ///
+    /// The message falls back to the raw body when it isn't a known error shape, and to the
+    /// status' reason phrase when the body is empty.
+    pub(crate) fn from_body(status: StatusCode, body: String, headers: HeaderMap) -> Self {
+        let error = Error::parse_body(status, body, headers);
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_error(status, &error);
+
+        error
+    }
+
+    fn parse_body(status: StatusCode, body: String, headers: HeaderMap) -> Self {
+        if let Ok(mut error) = serde_json::from_str::<SupabaseHTTPError>(&body) {
+            if error.code == 0 {
+                error.code = status.as_u16().into();
+            }
+
+            return Error::from_http_error(status, error, headers);
+        }
+
+        let message = if body.trim().is_empty() {
+            status
+                .canonical_reason()
+                .unwrap_or("Unknown Error")
+                .to_string()
+        } else {
+            body
+        };
+
+        Error::from_response(status, None, message, headers)
+    }
+
+    /// The error for a response the server rejected with `status` and the JSON body `error`
+    pub(crate) fn from_http_error(
+        status: StatusCode,
+        error: SupabaseHTTPError,
+        headers: HeaderMap,
+    ) -> Self {
+        if error.code() == Some(ErrorCode::WeakPassword) {
+            return Error::WeakPassword {
+                status,
+                reasons: error
+                    .weak_password
+                    .map(|weak_password| weak_password.reasons)
+                    .unwrap_or_default(),
+                message: error.message,
+                headers: Box::new(headers),
+            };
+        }
+
+        Error::from_response(status, error.code(), error.message, headers)
+    }
+
+    /// The error for a response the server rejected with `status`
+    pub(crate) fn from_response(
+        status: StatusCode,
+        code: Option<ErrorCode>,
+        message: String,
+        headers: HeaderMap,
+    ) -> Self {
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Error::RateLimited {
+                retry_after: retry_after(&headers),
+                code,
+                message,
+                headers: Box::new(headers),
+            };
+        }
+
+        Error::AuthError {
+            status,
+            code,
+            message,
+            headers: Box::new(headers),
+        }
+    }
+
+    /// The error Supabase Auth reported by redirecting to `url`, if any
+    ///
+    /// OAuth sign ins, magic links, and email confirmations that fail redirect back with
+    /// `error`, `error_code`, and `error_description` in the query or the fragment. These become
+    /// the same [`Error::AuthError`] failed requests return. Redirects don't carry an HTTP
+    /// status, so `status` is derived from the OAuth `error`: `403` for `access_denied`, `500`
+    /// for `server_error`, `503` for `temporarily_unavailable`, and `400` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// let url = Url::parse(
+    ///     "https://example.com/callback#error=access_denied&error_code=otp_expired&error_description=Email+link+is+invalid+or+has+expired",
+    /// )?;
+    ///
+    /// if let Some(error) = Error::from_redirect_url(&url) {
+    ///     assert_eq!(error.code(), Some(&ErrorCode::OtpExpired));
+    /// }
+    /// ```
+    pub fn from_redirect_url(url: &Url) -> Option<Self> {
+        let params = url
+            .query_pairs()
+            .chain(form_urlencoded::parse(
+                url.fragment().unwrap_or_default().as_bytes(),
+            ))
+            .filter(|(_, value)| !value.trim().is_empty());
+
+        let mut error = None;
+        let mut error_code = None;
+        let mut error_description = None;
+        for (name, value) in params {
+            match name.as_ref() {
+                "error" => error = error.or(Some(value.into_owned())),
+                "error_code" => error_code = error_code.or(Some(value.into_owned())),
+                "error_description" => {
+                    error_description = error_description.or(Some(value.into_owned()))
+                }
+                _ => {}
+            }
+        }
+
+        if error.is_none() && error_code.is_none() {
+            return None;
+        }
+
+        // Older GoTrue versions send the HTTP status as the error code
+        let (status, code) = match error_code.as_deref().map(str::parse::<u16>) {
+            Some(Ok(status)) => (StatusCode::from_u16(status).ok(), None),
+            _ => (None, error_code.as_deref().map(ErrorCode::from)),
+        };
+        let status = status.unwrap_or(match error.as_deref() {
+            Some("access_denied") => StatusCode::FORBIDDEN,
+            Some("server_error") => StatusCode::INTERNAL_SERVER_ERROR,
+            Some("temporarily_unavailable") => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::BAD_REQUEST,
+        });
+
+        let message = error_description
+            .or(error)
+            .or(error_code)
+            .unwrap_or_default();
+
+        Some(Error::from_response(
+            status,
+            code,
+            message,
+            HeaderMap::new(),
+        ))
+    }
+
+    /// The HTTP status of the response, if the server rejected the request
+    ///
+    /// # Example
+    /// ```
+    /// match auth_client.login_with_email(&email, &password).await {
+    ///     Err(error) if error.status() == Some(StatusCode::TOO_MANY_REQUESTS) => {
+    ///         println!("Slow down");
+    ///     }
+    ///     result => { result?; }
+    /// }
+    /// ```
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Error::AuthError { status, .. } | Error::WeakPassword { status, .. } => Some(*status),
+            Error::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            Error::NetworkError(error) => error.status(),
+            _ => None,
+        }
+    }
+
+    /// The machine readable error code returned by the server, if any
+    pub fn code(&self) -> Option<&ErrorCode> {
+        match self {
+            Error::AuthError { code, .. } | Error::RateLimited { code, .. } => code.as_ref(),
+            Error::WeakPassword { .. } => Some(&WEAK_PASSWORD),
+            _ => None,
+        }
+    }
+
+    /// The headers of the response, if the server rejected the request
+    pub fn headers(&self) -> Option<&HeaderMap> {
+        match self {
+            Error::AuthError { headers, .. }
+            | Error::RateLimited { headers, .. }
+            | Error::WeakPassword { headers, .. } => Some(headers),
+            _ => None,
+        }
+    }
+
+    /// The `sb-request-id` of the response, if the server rejected the request
+    ///
+    /// Include it when reporting an error to Supabase support, it identifies the request in the
+    /// server logs.
+    /// # Example
+    /// ```
+    /// if let Err(error) = auth_client.login_with_email(&demo_email, &demo_password).await {
+    ///     tracing::error!(request_id = ?error.request_id(), "sign in failed: {error}");
+    /// }
+    /// ```
+    pub fn request_id(&self) -> Option<&str> {
+        self.headers().and_then(request_id)
+    }
+
+    /// A copy of this error for another caller waiting on the same request
+    ///
+    /// Errors holding a `reqwest` or `serde_json` error can't be copied, they become
+    /// [`Error::RefreshFailed`] with their description.
+    pub(crate) fn shared_copy(&self) -> Error {
+        match self {
+            Error::MissingRefreshToken => Error::MissingRefreshToken,
+            Error::RefreshTokenAlreadyUsed => Error::RefreshTokenAlreadyUsed,
+            Error::NotAuthenticated => Error::NotAuthenticated,
+            Error::InternalError => Error::InternalError,
+            Error::AuthError {
+                status,
+                code,
+                message,
+                headers,
+            } => Error::AuthError {
+                status: *status,
+                code: code.clone(),
+                message: message.clone(),
+                headers: headers.clone(),
+            },
+            Error::RateLimited {
+                retry_after,
+                code,
+                message,
+                headers,
+            } => Error::RateLimited {
+                retry_after: *retry_after,
+                code: code.clone(),
+                message: message.clone(),
+                headers: headers.clone(),
+            },
+            Error::CircuitOpen { retry_after } => Error::CircuitOpen {
+                retry_after: *retry_after,
+            },
+            Error::RefreshFailed { message } => Error::RefreshFailed {
+                message: message.clone(),
+            },
+            error => Error::RefreshFailed {
+                message: match std::error::Error::source(error) {
+                    Some(source) => format!("{error}: {source}"),
+                    None => error.to_string(),
+                },
+            },
+        }
+    }
+
+    /// Whether the request failed because Supabase Auth is unreachable or failing, rather than
+    /// because it rejected the credentials. Server integrations answer these with
+    /// `503 Service Unavailable` instead of `401 Unauthorized`.
+    #[cfg(any(feature = "actix", feature = "axum", feature = "tower"))]
+    pub(crate) fn is_unavailable(&self) -> bool {
+        match self {
+            Error::NetworkError(_) | Error::RateLimited { .. } | Error::CircuitOpen { .. } => true,
+            Error::AuthError { status, .. } => status.is_server_error(),
+            _ => false,
+        }
+    }
+}
+
+/// The code of every [`Error::WeakPassword`]
+static WEAK_PASSWORD: ErrorCode = ErrorCode::WeakPassword;
+
+/// How much of an undecodable response body is kept in [`Error::DecodeError`]
+const MAX_DECODE_ERROR_BODY: usize = 4096;
+
+/// Decode the JSON body of a successful response, or the error of a failed one
+///
+/// Bodies are decoded straight from the received bytes, JSON is always UTF-8 so they aren't
+/// copied into a `String` first. Only bodies that fail to decode are.
+pub(crate) async fn decode_response<T: DeserializeOwned>(response: Response) -> Result<T, Error> {
+    let endpoint = response.url().path().to_string();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let bytes = response.bytes().await?;
+
+    if !status.is_success() {
+        let body = String::from_utf8_lossy(&bytes).into_owned();
+        return Err(Error::from_body(status, body, headers));
+    }
+
+    serde_json::from_slice(&bytes).map_err(|source| {
+        let mut body = String::from_utf8_lossy(&bytes).into_owned();
+        if body.len() > MAX_DECODE_ERROR_BODY {
+            let mut end = MAX_DECODE_ERROR_BODY;
+            while !body.is_char_boundary(end) {
+                end -= 1;
+            }
+            body.truncate(end);
+        }
+
+        Error::DecodeError {
+            endpoint,
+            body,
+            source,
+        }
+    })
+}
+
+/// The JSON body of an error response
+///
+/// Depending on the endpoint and API version, GoTrue puts the message in `msg`, `message`,
+/// `error_description`, or `error`, and the error code in `error_code`, `code`, or `error`. All
+/// of these shapes are accepted; bodies without any message are rejected.
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(try_from = "ErrorPayload")]
 pub struct SupabaseHTTPError {
+    /// The HTTP status, taken from the response when the body doesn't include it
     pub code: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_code: Option<String>,
@@ -61,6 +486,83 @@ pub struct SupabaseHTTPError {
     pub internal_message: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_id: Option<String>,
+    /// Why the password was rejected, for `weak_password` errors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weak_password: Option<WeakPassword>,
+}
+
+impl SupabaseHTTPError {
+    /// The parsed `error_code`, if the server returned one
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.error_code.as_deref().map(ErrorCode::from)
+    }
+}
+
+/// Every shape of error body returned by GoTrue, see [`SupabaseHTTPError`]
+#[derive(Deserialize)]
+struct ErrorPayload {
+    #[serde(default)]
+    code: Option<serde_json::Value>,
+    #[serde(default)]
+    error_code: Option<String>,
+    #[serde(default)]
+    msg: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+    #[serde(default)]
+    error_description: Option<String>,
+    #[serde(default)]
+    internal_error: Option<serde_json::Value>,
+    #[serde(default)]
+    internal_message: Option<serde_json::Value>,
+    #[serde(default)]
+    error_id: Option<String>,
+    #[serde(default)]
+    weak_password: Option<WeakPassword>,
+}
+
+impl TryFrom<ErrorPayload> for SupabaseHTTPError {
+    type Error = &'static str;
+
+    fn try_from(payload: ErrorPayload) -> Result<Self, Self::Error> {
+        let non_empty = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+
+        let error = match payload.error {
+            Some(serde_json::Value::String(error)) => non_empty(Some(error)),
+            _ => None,
+        };
+        let (status, code) = match payload.code {
+            Some(serde_json::Value::Number(status)) => (status.as_i64(), None),
+            Some(serde_json::Value::String(code)) => (None, non_empty(Some(code))),
+            _ => (None, None),
+        };
+
+        // OAuth style bodies put the code in `error` and the message in `error_description`
+        let (error_as_code, error_as_message) = match payload.error_description {
+            Some(_) => (error, None),
+            None => (None, error),
+        };
+
+        let message = non_empty(payload.msg)
+            .or(non_empty(payload.message))
+            .or(non_empty(payload.error_description))
+            .or(error_as_message)
+            .ok_or("error body without a message")?;
+
+        Ok(SupabaseHTTPError {
+            code: status
+                .and_then(|status| i32::try_from(status).ok())
+                .unwrap_or_default(),
+            error_code: non_empty(payload.error_code).or(code).or(error_as_code),
+            message,
+            internal_error: payload.internal_error,
+            internal_message: payload.internal_message,
+            error_id: payload.error_id,
+            weak_password: payload.weak_password,
+        })
+    }
 }
 
 impl Display for SupabaseHTTPError {
@@ -86,3 +588,158 @@ impl Display for SupabaseHTTPError {
         write!(f, "\nMessage: {}", self.message)
     }
 }
+
+macro_rules! error_codes {
+    ($($variant:ident => $code:literal,)*) => {
+        /// A machine readable error code returned by Supabase Auth in the `error_code` field
+        ///
+        /// Branch on codes rather than messages, which may change between server versions. Codes
+        /// this version of the library doesn't know yet are kept as [`ErrorCode::Unknown`].
+        ///
+        /// See <https://supabase.com/docs/guides/auth/debugging/error-codes> for what each code
+        /// means.
+        ///
+        /// # Example
+        /// ```
+        /// match auth_client.login_with_email(&email, &password).await {
+        ///     Err(Error::AuthError { code: Some(ErrorCode::EmailNotConfirmed), .. }) => {
+        ///         println!("Check your inbox to confirm your email address");
+        ///     }
+        ///     result => { result?; }
+        /// }
+        /// ```
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum ErrorCode {
+            $(
+                #[doc = concat!("`", $code, "`")]
+                $variant,
+            )*
+            /// A code this version of the library doesn't know
+            Unknown(String),
+        }
+
+        impl ErrorCode {
+            /// The code as returned by the server
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(ErrorCode::$variant => $code,)*
+                    ErrorCode::Unknown(code) => code,
+                }
+            }
+        }
+
+        impl From<&str> for ErrorCode {
+            fn from(code: &str) -> Self {
+                match code {
+                    $($code => ErrorCode::$variant,)*
+                    _ => ErrorCode::Unknown(code.to_string()),
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    UnexpectedFailure => "unexpected_failure",
+    ValidationFailed => "validation_failed",
+    BadJson => "bad_json",
+    EmailExists => "email_exists",
+    PhoneExists => "phone_exists",
+    BadJwt => "bad_jwt",
+    NotAdmin => "not_admin",
+    NoAuthorization => "no_authorization",
+    UserNotFound => "user_not_found",
+    SessionNotFound => "session_not_found",
+    SessionExpired => "session_expired",
+    RefreshTokenNotFound => "refresh_token_not_found",
+    RefreshTokenAlreadyUsed => "refresh_token_already_used",
+    FlowStateNotFound => "flow_state_not_found",
+    FlowStateExpired => "flow_state_expired",
+    SignupDisabled => "signup_disabled",
+    UserBanned => "user_banned",
+    ProviderEmailNeedsVerification => "provider_email_needs_verification",
+    InviteNotFound => "invite_not_found",
+    BadOauthState => "bad_oauth_state",
+    BadOauthCallback => "bad_oauth_callback",
+    OauthProviderNotSupported => "oauth_provider_not_supported",
+    UnexpectedAudience => "unexpected_audience",
+    SingleIdentityNotDeletable => "single_identity_not_deletable",
+    EmailConflictIdentityNotDeletable => "email_conflict_identity_not_deletable",
+    IdentityAlreadyExists => "identity_already_exists",
+    IdentityNotFound => "identity_not_found",
+    EmailProviderDisabled => "email_provider_disabled",
+    PhoneProviderDisabled => "phone_provider_disabled",
+    ProviderDisabled => "provider_disabled",
+    AnonymousProviderDisabled => "anonymous_provider_disabled",
+    ManualLinkingDisabled => "manual_linking_disabled",
+    TooManyEnrolledMfaFactors => "too_many_enrolled_mfa_factors",
+    MfaFactorNameConflict => "mfa_factor_name_conflict",
+    MfaFactorNotFound => "mfa_factor_not_found",
+    MfaIpAddressMismatch => "mfa_ip_address_mismatch",
+    MfaChallengeExpired => "mfa_challenge_expired",
+    MfaVerificationFailed => "mfa_verification_failed",
+    MfaVerificationRejected => "mfa_verification_rejected",
+    MfaVerifiedFactorExists => "mfa_verified_factor_exists",
+    MfaPhoneEnrollNotEnabled => "mfa_phone_enroll_not_enabled",
+    MfaPhoneVerifyNotEnabled => "mfa_phone_verify_not_enabled",
+    MfaTotpEnrollNotEnabled => "mfa_totp_enroll_not_enabled",
+    MfaTotpVerifyNotEnabled => "mfa_totp_verify_not_enabled",
+    MfaWebauthnEnrollNotEnabled => "mfa_webauthn_enroll_not_enabled",
+    MfaWebauthnVerifyNotEnabled => "mfa_webauthn_verify_not_enabled",
+    InsufficientAal => "insufficient_aal",
+    CaptchaFailed => "captcha_failed",
+    SamlProviderDisabled => "saml_provider_disabled",
+    SamlRelayStateNotFound => "saml_relay_state_not_found",
+    SamlRelayStateExpired => "saml_relay_state_expired",
+    SamlIdpNotFound => "saml_idp_not_found",
+    SamlIdpAlreadyExists => "saml_idp_already_exists",
+    SamlAssertionNoUserId => "saml_assertion_no_user_id",
+    SamlAssertionNoEmail => "saml_assertion_no_email",
+    SamlMetadataFetchFailed => "saml_metadata_fetch_failed",
+    SamlEntityIdMismatch => "saml_entity_id_mismatch",
+    SsoProviderNotFound => "sso_provider_not_found",
+    SsoDomainAlreadyExists => "sso_domain_already_exists",
+    UserSsoManaged => "user_sso_managed",
+    SmsSendFailed => "sms_send_failed",
+    EmailNotConfirmed => "email_not_confirmed",
+    PhoneNotConfirmed => "phone_not_confirmed",
+    EmailAddressInvalid => "email_address_invalid",
+    EmailAddressNotAuthorized => "email_address_not_authorized",
+    ReauthNonceMissing => "reauth_nonce_missing",
+    ReauthenticationNeeded => "reauthentication_needed",
+    ReauthenticationNotValid => "reauthentication_not_valid",
+    SamePassword => "same_password",
+    WeakPassword => "weak_password",
+    InvalidCredentials => "invalid_credentials",
+    UserAlreadyExists => "user_already_exists",
+    Conflict => "conflict",
+    OtpExpired => "otp_expired",
+    OtpDisabled => "otp_disabled",
+    BadCodeVerifier => "bad_code_verifier",
+    OverRequestRateLimit => "over_request_rate_limit",
+    OverEmailSendRateLimit => "over_email_send_rate_limit",
+    OverSmsSendRateLimit => "over_sms_send_rate_limit",
+    HookTimeout => "hook_timeout",
+    HookTimeoutAfterRetry => "hook_timeout_after_retry",
+    HookPayloadOverSizeLimit => "hook_payload_over_size_limit",
+    HookPayloadInvalidContentType => "hook_payload_invalid_content_type",
+    RequestTimeout => "request_timeout",
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrorCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(ErrorCode::from(String::deserialize(deserializer)?.as_str()))
+    }
+}
@@ -0,0 +1,161 @@
+/*!
+Auth state change notifications for [`ManagedAuthClient`](crate::managed::ManagedAuthClient).
+
+# Example
+
+```rust
+let subscription = auth_client.on_auth_state_change(|event, session| {
+    if event == AuthEvent::SignedOut {
+        cache.clear();
+    }
+});
+
+// Stop receiving events
+subscription.unsubscribe();
+
+// Or observe events from an independent task
+let mut events = auth_client.subscribe_auth_events();
+
+tokio::spawn(async move {
+    while let Ok(change) = events.recv().await {
+        metrics.record(change.event);
+    }
+});
+```
+*/
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock, Weak,
+    },
+};
+
+use tokio::sync::broadcast;
+
+use crate::models::Session;
+
+/// Number of events buffered per broadcast receiver before the oldest are dropped
+const EVENT_CHANNEL_CAPACITY: usize = 32;
+
+/// A change of the signed in state of a [`ManagedAuthClient`](crate::managed::ManagedAuthClient)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuthEvent {
+    /// A user signed in, or a session was set
+    SignedIn,
+    /// The user signed out, or the session was cleared
+    SignedOut,
+    /// The session was refreshed
+    TokenRefreshed,
+    /// The signed in user was updated
+    UserUpdated,
+    /// A user signed in through a password recovery link or OTP
+    PasswordRecovery,
+}
+
+/// An [`AuthEvent`] together with the session after the change
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthStateChange {
+    pub event: AuthEvent,
+    /// The current session, `None` after signing out
+    pub session: Option<Session>,
+}
+
+type Listener = dyn Fn(AuthEvent, Option<&Session>) + Send + Sync;
+type ListenerList = RwLock<Vec<(u64, Arc<Listener>)>>;
+
+/// Registered auth state change callbacks and broadcast subscribers
+#[derive(Clone)]
+pub(crate) struct Listeners {
+    list: Arc<ListenerList>,
+    next_id: Arc<AtomicU64>,
+    sender: broadcast::Sender<AuthStateChange>,
+}
+
+impl Default for Listeners {
+    fn default() -> Self {
+        Listeners {
+            list: Arc::default(),
+            next_id: Arc::default(),
+            sender: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+        }
+    }
+}
+
+impl Listeners {
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<AuthStateChange> {
+        self.sender.subscribe()
+    }
+
+    pub(crate) fn add(
+        &self,
+        listener: impl Fn(AuthEvent, Option<&Session>) + Send + Sync + 'static,
+    ) -> AuthStateSubscription {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        self.list
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((id, Arc::new(listener)));
+
+        AuthStateSubscription {
+            id,
+            list: Arc::downgrade(&self.list),
+        }
+    }
+
+    pub(crate) fn emit(&self, event: AuthEvent, session: Option<&Session>) {
+        // Call the listeners without holding the lock, so they may (un)subscribe
+        let listeners: Vec<Arc<Listener>> = self
+            .list
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(_, listener)| listener.clone())
+            .collect();
+
+        for listener in listeners {
+            listener(event, session);
+        }
+
+        // Only fails when nobody is subscribed
+        let _ = self.sender.send(AuthStateChange {
+            event,
+            session: session.cloned(),
+        });
+    }
+}
+
+impl fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.list.read().unwrap_or_else(|e| e.into_inner()).len();
+
+        f.debug_struct("Listeners")
+            .field("count", &count)
+            .field("receivers", &self.sender.receiver_count())
+            .finish()
+    }
+}
+
+/// Handle to a callback registered with
+/// [`ManagedAuthClient::on_auth_state_change`](crate::managed::ManagedAuthClient::on_auth_state_change)
+///
+/// The callback stays registered when the handle is dropped; call
+/// [`AuthStateSubscription::unsubscribe`] to remove it.
+#[derive(Debug)]
+pub struct AuthStateSubscription {
+    id: u64,
+    list: Weak<ListenerList>,
+}
+
+impl AuthStateSubscription {
+    /// Stop calling the callback
+    pub fn unsubscribe(self) {
+        if let Some(list) = self.list.upgrade() {
+            list.write()
+                .unwrap_or_else(|e| e.into_inner())
+                .retain(|(id, _)| *id != self.id);
+        }
+    }
+}
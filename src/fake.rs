@@ -0,0 +1,315 @@
+/*!
+Realistic users and sessions for tests, enabled with the `test-utils` feature.
+
+[`User::fake`] and [`Session::fake`] return a confirmed email user and a valid session of one.
+[`FakeUser`] and [`FakeSession`] override the fields a test cares about, everything else is
+filled in like Supabase Auth would. Access tokens are real JWTs, signed with [`JWT_SECRET`]
+unless [`FakeSession::jwt_secret`] says otherwise, so they pass
+[`AuthClient::verify_access_token`](crate::models::AuthClient::verify_access_token).
+
+# Example
+
+```rust
+let admin = FakeUser::new()
+    .email("admin@example.com")
+    .app_metadata("roles", json!(["admin"]))
+    .build();
+
+let expired = FakeSession::new().user(admin).expired().build();
+assert!(expired.is_expired());
+```
+*/
+
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use jsonwebtoken::{EncodingKey, Header};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::models::{
+    AccessToken, AppMetadata, Identity, IdentityData, RefreshToken, Session, User, UserMetadata,
+};
+
+/// The JWT secret fake access tokens are signed with by default, the default of local Supabase
+pub const JWT_SECRET: &str = "super-secret-jwt-token-with-at-least-32-characters-long";
+
+impl User {
+    /// A confirmed user who signed up with a random email address, see [`FakeUser`]
+    pub fn fake() -> User {
+        FakeUser::new().build()
+    }
+}
+
+impl Session {
+    /// A valid session of a [`User::fake`], see [`FakeSession`]
+    pub fn fake() -> Session {
+        FakeSession::new().build()
+    }
+}
+
+/// Builds a fake [`User`], see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct FakeUser {
+    user: User,
+}
+
+impl Default for FakeUser {
+    fn default() -> Self {
+        FakeUser::new()
+    }
+}
+
+impl FakeUser {
+    /// A confirmed user who signed up with a random email address
+    pub fn new() -> Self {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+
+        let user = User {
+            id,
+            aud: "authenticated".to_string(),
+            role: "authenticated".to_string(),
+            email: format!("user-{}@example.com", &id.simple().to_string()[..8]),
+            email_confirmed_at: Some(now),
+            confirmed_at: Some(now),
+            last_sign_in_at: Some(now),
+            app_metadata: AppMetadata {
+                provider: Some("email".to_string()),
+                providers: Some(vec!["email".to_string()]),
+                custom: HashMap::new(),
+            },
+            created_at: now,
+            updated_at: now,
+            ..Default::default()
+        };
+
+        FakeUser { user }
+    }
+
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.user.id = id;
+        self
+    }
+
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.user.email = email.into();
+        self
+    }
+
+    /// Set a confirmed phone number, the user signed up with it if the email is empty
+    pub fn phone(mut self, phone: impl Into<String>) -> Self {
+        self.user.phone = phone.into();
+        self.user.phone_confirmed_at = Some(Utc::now());
+        self
+    }
+
+    /// The Postgres role, `authenticated` unless overridden
+    pub fn role(mut self, role: impl Into<String>) -> Self {
+        self.user.role = role.into();
+        self
+    }
+
+    /// Make the user anonymous, without an email address
+    pub fn anonymous(mut self) -> Self {
+        self.user.email = String::new();
+        self.user.email_confirmed_at = None;
+        self.user.confirmed_at = None;
+        self.user.is_anonymous = true;
+        self.user.app_metadata.provider = None;
+        self.user.app_metadata.providers = None;
+        self
+    }
+
+    /// Leave the email address unconfirmed
+    pub fn unconfirmed(mut self) -> Self {
+        self.user.email_confirmed_at = None;
+        self.user.confirmed_at = None;
+        self.user.confirmation_sent_at = Some(Utc::now());
+        self
+    }
+
+    /// Set a field of `app_metadata`, e.g. a role or a tenant
+    pub fn app_metadata(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.user.app_metadata = with_field(&self.user.app_metadata, key.into(), value);
+        self
+    }
+
+    /// Set a field of `user_metadata`, typed fields like `name` included
+    pub fn user_metadata(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.user.user_metadata = with_field(&self.user.user_metadata, key.into(), value);
+        self
+    }
+
+    pub fn build(self) -> User {
+        let mut user = self.user;
+
+        if !user.is_anonymous && user.identities.is_empty() {
+            let email = (!user.email.is_empty()).then(|| user.email.clone());
+            let provider = if email.is_none() && !user.phone.is_empty() {
+                user.email_confirmed_at = None;
+                user.confirmed_at = user.phone_confirmed_at;
+                user.app_metadata.provider = Some("phone".to_string());
+                user.app_metadata.providers = Some(vec!["phone".to_string()]);
+                "phone"
+            } else {
+                "email"
+            };
+
+            user.user_metadata = UserMetadata {
+                email: email.clone(),
+                email_verified: Some(user.email_confirmed_at.is_some()),
+                phone_verified: Some(user.phone_confirmed_at.is_some()),
+                ..user.user_metadata
+            };
+
+            let mut identity_data = IdentityData {
+                email: email.clone(),
+                sub: user.id.to_string(),
+                ..Default::default()
+            };
+            if provider == "phone" {
+                identity_data
+                    .extra
+                    .insert("phone".to_string(), Value::String(user.phone.clone()));
+            }
+
+            user.identities.push(Identity {
+                identity_id: Uuid::new_v4(),
+                id: user.id.to_string(),
+                user_id: user.id,
+                identity_data,
+                provider: provider.to_string(),
+                last_sign_in_at: user.last_sign_in_at,
+                created_at: user.created_at,
+                updated_at: user.updated_at,
+                email,
+            });
+        }
+
+        user
+    }
+}
+
+/// Builds a fake [`Session`], see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct FakeSession {
+    user: Option<User>,
+    expires_in: Duration,
+    expires_at: Option<DateTime<Utc>>,
+    session_id: Option<Uuid>,
+    jwt_secret: String,
+}
+
+impl Default for FakeSession {
+    fn default() -> Self {
+        FakeSession::new()
+    }
+}
+
+impl FakeSession {
+    /// A session of a [`User::fake`], valid for an hour
+    pub fn new() -> Self {
+        FakeSession {
+            user: None,
+            expires_in: Duration::from_secs(3600),
+            expires_at: None,
+            session_id: None,
+            jwt_secret: JWT_SECRET.to_string(),
+        }
+    }
+
+    /// The user the session belongs to
+    pub fn user(mut self, user: User) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    /// Shorthand for a session of a [`FakeUser`] with `email`
+    pub fn email(self, email: impl Into<String>) -> Self {
+        self.user(FakeUser::new().email(email).build())
+    }
+
+    /// How long the access token is valid after it was issued, an hour unless overridden
+    pub fn expires_in(mut self, expires_in: Duration) -> Self {
+        self.expires_in = expires_in;
+        self
+    }
+
+    /// When the access token expires, `expires_in` from now unless overridden
+    pub fn expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Make the access token expired an hour ago, well past the leeway verification allows
+    pub fn expired(self) -> Self {
+        self.expires_at(Utc::now() - TimeDelta::hours(1))
+    }
+
+    /// The `session_id` claim of the access token, random unless overridden
+    pub fn session_id(mut self, session_id: Uuid) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    /// Sign the access token with `jwt_secret` instead of [`JWT_SECRET`]
+    pub fn jwt_secret(mut self, jwt_secret: impl Into<String>) -> Self {
+        self.jwt_secret = jwt_secret.into();
+        self
+    }
+
+    pub fn build(self) -> Session {
+        let user = self.user.unwrap_or_else(User::fake);
+        let expires_in = TimeDelta::from_std(self.expires_in).expect("expires_in is in range");
+        let expires_at = self.expires_at.unwrap_or_else(|| Utc::now() + expires_in);
+        let issued_at = expires_at - expires_in;
+
+        let method = if user.is_anonymous {
+            "anonymous"
+        } else {
+            "password"
+        };
+        let claims = json!({
+            "iss": "http://127.0.0.1:54321/auth/v1",
+            "sub": user.id,
+            "aud": user.aud,
+            "exp": expires_at.timestamp(),
+            "iat": issued_at.timestamp(),
+            "email": user.email,
+            "phone": user.phone,
+            "app_metadata": user.app_metadata,
+            "user_metadata": user.user_metadata,
+            "role": user.role,
+            "aal": "aal1",
+            "amr": [{ "method": method, "timestamp": issued_at.timestamp() }],
+            "session_id": self.session_id.unwrap_or_else(Uuid::new_v4),
+            "is_anonymous": user.is_anonymous,
+        });
+
+        let access_token = jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(self.jwt_secret.as_bytes()),
+        )
+        .expect("fake claims can be signed");
+
+        Session {
+            access_token: AccessToken::new(access_token),
+            token_type: "bearer".to_string(),
+            expires_in: expires_in.num_seconds(),
+            expires_at,
+            refresh_token: RefreshToken::new(&Uuid::new_v4().simple().to_string()[..12]),
+            user,
+            ..Default::default()
+        }
+    }
+}
+
+/// `metadata` with the field `key` set to `value`, so typed fields are set like GoTrue's JSON does
+fn with_field<T: Serialize + DeserializeOwned>(metadata: &T, key: String, value: Value) -> T {
+    let mut json = serde_json::to_value(metadata).expect("metadata serializes");
+    json[key] = value;
+    serde_json::from_value(json).expect("metadata fields have the type GoTrue returns")
+}
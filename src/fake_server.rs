@@ -0,0 +1,733 @@
+/*!
+An in-memory Supabase Auth server for tests without any network, enabled with the `test-utils`
+feature.
+
+[`FakeAuthServer`] is an [`HttpTransport`] that answers requests from a user table it keeps in
+memory instead of sending them anywhere. A [`FakeAuthClient`] is an [`AuthClient`] wired to it, so
+apps are tested end-to-end through the real client, including its error handling.
+
+The server implements the flows apps depend on, following GoTrue's semantics:
+
+- Sign ups with email, phone or anonymously, rejecting taken emails and weak passwords. Emails are
+  confirmed right away unless [`FakeAuthServer::require_email_confirmation`] is set.
+- Password sign ins, rejecting wrong passwords and unconfirmed emails.
+- Refreshing sessions with rotation: each refresh token works once, reusing it fails with
+  `refresh_token_already_used`.
+- OTPs and magic links, whose codes tests read with [`FakeAuthServer::otp`], and verifying them.
+- Password recovery, getting and updating the user, and signing out with each scope.
+
+Other endpoints answer `404`. Access tokens are signed with
+[`JWT_SECRET`](crate::fake::JWT_SECRET), so they pass
+[`AuthClient::verify_access_token`] too.
+
+# Example
+
+```rust
+let server = FakeAuthServer::new();
+server.add_user("demo@example.com", "password");
+
+let auth_client = server.client();
+let session = auth_client
+    .client()
+    .login_with_email("demo@example.com", "password")
+    .await?;
+
+auth_client.client().logout(None, &session.access_token).await?;
+assert_eq!(server.active_sessions(session.user.id), 0);
+```
+*/
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+use http::StatusCode;
+use jsonwebtoken::{DecodingKey, Validation};
+use reqwest::{header::AUTHORIZATION, Request, Response, ResponseBuilderExt};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ErrorCode,
+    fake::{FakeSession, FakeUser, JWT_SECRET},
+    models::{AuthClient, User, AUTH_V1},
+    transport::{HttpTransport, TransportFuture},
+};
+
+/// The project URL of [`FakeAuthClient`]s, requests never leave the process
+const PROJECT_URL: &str = "http://fake-auth.invalid";
+
+/// The shortest password GoTrue accepts by default
+const MIN_PASSWORD_LENGTH: usize = 6;
+
+/// An in-memory Supabase Auth server, see the [module docs](self)
+///
+/// Clones share the same users and sessions.
+#[derive(Clone, Default)]
+pub struct FakeAuthServer {
+    state: Arc<Mutex<State>>,
+}
+
+impl fmt::Debug for FakeAuthServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+
+        f.debug_struct("FakeAuthServer")
+            .field("users", &state.users.len())
+            .field("sessions", &state.sessions.len())
+            .field(
+                "require_email_confirmation",
+                &state.require_email_confirmation,
+            )
+            .finish()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    users: Vec<StoredUser>,
+    /// Sessions by id, with the id of the user they belong to
+    sessions: HashMap<Uuid, Uuid>,
+    /// Refresh tokens with the session they belong to, and whether they were used already
+    refresh_tokens: HashMap<String, (Uuid, bool)>,
+    /// The last OTP issued to each email address or phone number
+    otps: HashMap<String, String>,
+    require_email_confirmation: bool,
+}
+
+struct StoredUser {
+    user: User,
+    password: Option<String>,
+}
+
+/// The response to a request, before it is turned into a [`Response`]
+type Answer = (StatusCode, Option<Value>);
+
+impl FakeAuthServer {
+    /// A server without any users, confirming emails right away
+    pub fn new() -> Self {
+        FakeAuthServer::default()
+    }
+
+    /// Make email sign ups confirm their address with an OTP before they can sign in
+    pub fn require_email_confirmation(self, require: bool) -> Self {
+        self.state.lock().unwrap().require_email_confirmation = require;
+        self
+    }
+
+    /// A client for this server
+    pub fn client(&self) -> FakeAuthClient {
+        FakeAuthClient::with_server(self.clone())
+    }
+
+    /// Add a confirmed user who signs in with `email` and `password`
+    pub fn add_user(&self, email: &str, password: &str) -> User {
+        self.insert_user(FakeUser::new().email(email).build(), Some(password))
+    }
+
+    /// Add `user`, who signs in with `password` if any, e.g. a [`FakeUser`] with custom metadata
+    pub fn insert_user(&self, user: User, password: Option<&str>) -> User {
+        self.state.lock().unwrap().users.push(StoredUser {
+            user: user.clone(),
+            password: password.map(str::to_string),
+        });
+        user
+    }
+
+    /// Every user, in the order they signed up
+    pub fn users(&self) -> Vec<User> {
+        let state = self.state.lock().unwrap();
+        state
+            .users
+            .iter()
+            .map(|stored| stored.user.clone())
+            .collect()
+    }
+
+    /// The user with the email address or phone number `email_or_phone`
+    pub fn user(&self, email_or_phone: &str) -> Option<User> {
+        let state = self.state.lock().unwrap();
+        state
+            .find(Some(email_or_phone), Some(email_or_phone))
+            .map(|index| state.users[index].user.clone())
+    }
+
+    /// The last OTP sent to `email_or_phone`, if it wasn't verified yet
+    pub fn otp(&self, email_or_phone: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.otps.get(&email_or_phone.to_lowercase()).cloned()
+    }
+
+    /// How many sessions of the user with `user_id` weren't signed out
+    pub fn active_sessions(&self, user_id: Uuid) -> usize {
+        let state = self.state.lock().unwrap();
+        state
+            .sessions
+            .values()
+            .filter(|owner| **owner == user_id)
+            .count()
+    }
+
+    fn answer(&self, request: &Request) -> Answer {
+        let path = request.url().path();
+        let Some(endpoint) = path.strip_prefix(AUTH_V1) else {
+            return not_found();
+        };
+
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .and_then(|body| serde_json::from_slice::<Value>(body).ok())
+            .unwrap_or_else(|| json!({}));
+        let query = |name: &str| {
+            request
+                .url()
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        let bearer_token = request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::to_string);
+
+        let mut state = self.state.lock().unwrap();
+
+        match (request.method().as_str(), endpoint) {
+            ("GET", "/health") => (
+                StatusCode::OK,
+                Some(json!({
+                    "version": "v2.170.0",
+                    "name": "GoTrue",
+                    "description": "GoTrue is a user registration and authentication API",
+                })),
+            ),
+            ("POST", "/signup") => state.sign_up(&body),
+            ("POST", "/token") => match query("grant_type").as_deref() {
+                Some("password") => state.login(&body),
+                Some("refresh_token") => state.refresh(&body),
+                _ => error(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCode::ValidationFailed,
+                    "unsupported_grant_type",
+                ),
+            },
+            ("POST", "/otp") | ("POST", "/magiclink") => state.send_otp(&body),
+            ("POST", "/recover") | ("POST", "/resend") => state.resend(&body),
+            ("POST", "/verify") => state.verify(&body),
+            ("GET", "/user") => match state.authenticate(bearer_token.as_deref()) {
+                Ok((index, _)) => ok(&state.users[index].user),
+                Err(answer) => answer,
+            },
+            ("PUT", "/user") => match state.authenticate(bearer_token.as_deref()) {
+                Ok((index, _)) => state.update_user(index, &body),
+                Err(answer) => answer,
+            },
+            ("POST", "/logout") => match state.authenticate(bearer_token.as_deref()) {
+                Ok((index, session_id)) => {
+                    let user_id = state.users[index].user.id;
+                    // The client sends the scope as the body, GoTrue reads the query
+                    let scope = query("scope")
+                        .or_else(|| body.as_str().map(str::to_string))
+                        .unwrap_or_else(|| "global".to_string());
+                    state.sessions.retain(|id, owner| {
+                        *owner != user_id
+                            || match scope.as_str() {
+                                "local" => *id != session_id,
+                                "others" => *id == session_id,
+                                _ => false,
+                            }
+                    });
+                    (StatusCode::NO_CONTENT, None)
+                }
+                Err(answer) => answer,
+            },
+            _ => not_found(),
+        }
+    }
+}
+
+impl HttpTransport for FakeAuthServer {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let (status, body) = self.answer(&request);
+
+            let response = http::Response::builder()
+                .status(status)
+                .url(request.url().clone())
+                .header("content-type", "application/json")
+                .body(body.map(|body| body.to_string()).unwrap_or_default())
+                .expect("fake responses are valid");
+
+            Ok(Response::from(response))
+        })
+    }
+}
+
+impl State {
+    /// The index of the user with `email` or `phone`
+    fn find(&self, email: Option<&str>, phone: Option<&str>) -> Option<usize> {
+        self.users.iter().position(|stored| {
+            email.is_some_and(|email| {
+                !stored.user.email.is_empty() && stored.user.email.eq_ignore_ascii_case(email)
+            }) || phone
+                .is_some_and(|phone| !stored.user.phone.is_empty() && stored.user.phone == phone)
+        })
+    }
+
+    fn sign_up(&mut self, body: &Value) -> Answer {
+        let email = field(body, "email");
+        let phone = field(body, "phone");
+        let password = field(body, "password");
+
+        if email.is_none() && phone.is_none() {
+            let user = with_data(FakeUser::new().anonymous(), body).build();
+            self.users.push(StoredUser {
+                user,
+                password: None,
+            });
+            return self.issue_session(self.users.len() - 1);
+        }
+
+        if self.find(email, phone).is_some() {
+            return error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                ErrorCode::UserAlreadyExists,
+                "User already registered",
+            );
+        }
+
+        if password.is_none_or(|password| password.len() < MIN_PASSWORD_LENGTH) {
+            let mut answer = error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                ErrorCode::WeakPassword,
+                "Password should be at least 6 characters.",
+            );
+            if let Some(body) = &mut answer.1 {
+                body["weak_password"] = json!({ "reasons": ["length"] });
+            }
+            return answer;
+        }
+
+        let user = match (email, phone) {
+            (Some(email), _) => FakeUser::new().email(email),
+            (None, Some(phone)) => FakeUser::new().email("").phone(phone),
+            (None, None) => unreachable!("anonymous sign ups returned above"),
+        };
+        let confirm = email.is_some() && self.require_email_confirmation;
+        let user = with_data(if confirm { user.unconfirmed() } else { user }, body).build();
+
+        self.users.push(StoredUser {
+            user: user.clone(),
+            password: password.map(str::to_string),
+        });
+
+        if confirm {
+            self.issue_otp(&user.email);
+            return ok(&user);
+        }
+
+        self.issue_session(self.users.len() - 1)
+    }
+
+    fn login(&mut self, body: &Value) -> Answer {
+        let index = self
+            .find(field(body, "email"), field(body, "phone"))
+            .filter(|index| {
+                let stored = &self.users[*index];
+                stored.password.is_some() && stored.password.as_deref() == field(body, "password")
+            });
+
+        let Some(index) = index else {
+            return error(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidCredentials,
+                "Invalid login credentials",
+            );
+        };
+
+        if self.users[index].user.confirmed_at.is_none() {
+            return error(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::EmailNotConfirmed,
+                "Email not confirmed",
+            );
+        }
+
+        self.issue_session(index)
+    }
+
+    fn refresh(&mut self, body: &Value) -> Answer {
+        let token = field(body, "refresh_token").unwrap_or_default();
+
+        let session = match self.refresh_tokens.get_mut(token) {
+            Some((_, true)) => {
+                return error(
+                    StatusCode::BAD_REQUEST,
+                    ErrorCode::RefreshTokenAlreadyUsed,
+                    "Invalid Refresh Token: Already Used",
+                )
+            }
+            Some((session_id, used)) => {
+                *used = true;
+                *session_id
+            }
+            None => return refresh_token_not_found(),
+        };
+
+        let Some(index) = self.sessions.get(&session).and_then(|user_id| {
+            self.users
+                .iter()
+                .position(|stored| stored.user.id == *user_id)
+        }) else {
+            return refresh_token_not_found();
+        };
+
+        self.session(index, session)
+    }
+
+    fn send_otp(&mut self, body: &Value) -> Answer {
+        let email = field(body, "email");
+        let phone = field(body, "phone");
+
+        if self.find(email, phone).is_none() {
+            if body.get("create_user") == Some(&Value::Bool(false)) {
+                return error(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    ErrorCode::OtpDisabled,
+                    "Signups not allowed for otp",
+                );
+            }
+
+            let user = match (email, phone) {
+                (Some(email), _) => FakeUser::new().email(email).unconfirmed(),
+                (None, Some(phone)) => {
+                    let mut user = FakeUser::new().email("").phone(phone).build();
+                    user.phone_confirmed_at = None;
+                    user.confirmed_at = None;
+                    self.users.push(StoredUser {
+                        user,
+                        password: None,
+                    });
+                    return self.otp_sent(phone, true);
+                }
+                (None, None) => {
+                    return error(
+                        StatusCode::BAD_REQUEST,
+                        ErrorCode::ValidationFailed,
+                        "An email address or phone number is required",
+                    )
+                }
+            };
+            self.users.push(StoredUser {
+                user: with_data(user, body).build(),
+                password: None,
+            });
+        }
+
+        match (email, phone) {
+            (Some(email), _) => self.otp_sent(email, false),
+            (None, Some(phone)) => self.otp_sent(phone, true),
+            (None, None) => unreachable!("users are found by email or phone"),
+        }
+    }
+
+    /// Recovery emails and resent codes, which don't reveal whether the user exists
+    fn resend(&mut self, body: &Value) -> Answer {
+        let email = field(body, "email");
+        let phone = field(body, "phone");
+
+        if self.find(email, phone).is_some() {
+            if let Some(recipient) = email.or(phone) {
+                self.issue_otp(recipient);
+            }
+        }
+
+        (StatusCode::OK, Some(json!({})))
+    }
+
+    fn verify(&mut self, body: &Value) -> Answer {
+        let recipient = field(body, "email")
+            .or_else(|| field(body, "phone"))
+            .map(str::to_lowercase);
+        let token = field(body, "token").or_else(|| field(body, "token_hash"));
+
+        let recipient = match (recipient, token) {
+            (Some(recipient), Some(token))
+                if self.otps.get(&recipient).map(String::as_str) == Some(token) =>
+            {
+                recipient
+            }
+            (None, Some(token)) => match self.otps.iter().find(|(_, otp)| *otp == token) {
+                Some((recipient, _)) => recipient.clone(),
+                None => return otp_expired(),
+            },
+            _ => return otp_expired(),
+        };
+        self.otps.remove(&recipient);
+
+        let Some(index) = self.find(Some(&recipient), Some(&recipient)) else {
+            return otp_expired();
+        };
+
+        let now = Utc::now();
+        let user = &mut self.users[index].user;
+        if user.email.eq_ignore_ascii_case(&recipient) {
+            user.email_confirmed_at.get_or_insert(now);
+            user.user_metadata.email_verified = Some(true);
+        } else {
+            user.phone_confirmed_at.get_or_insert(now);
+            user.user_metadata.phone_verified = Some(true);
+        }
+        user.confirmed_at.get_or_insert(now);
+
+        self.issue_session(index)
+    }
+
+    fn update_user(&mut self, index: usize, body: &Value) -> Answer {
+        if let Some(email) = field(body, "email") {
+            if self
+                .find(Some(email), None)
+                .is_some_and(|other| other != index)
+            {
+                return error(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    ErrorCode::EmailExists,
+                    "A user with this email address has already been registered",
+                );
+            }
+        }
+
+        if let Some(password) = field(body, "password") {
+            if password.len() < MIN_PASSWORD_LENGTH {
+                return error(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    ErrorCode::WeakPassword,
+                    "Password should be at least 6 characters.",
+                );
+            }
+            self.users[index].password = Some(password.to_string());
+        }
+
+        let stored = &mut self.users[index];
+        if let Some(email) = field(body, "email") {
+            stored.user.email = email.to_string();
+            stored.user.user_metadata.email = Some(email.to_string());
+        }
+        if let Some(Value::Object(data)) = body.get("data") {
+            let custom = &mut stored.user.user_metadata.custom;
+            for (key, value) in data {
+                match value {
+                    Value::Null => custom.remove(key),
+                    value => custom.insert(key.clone(), value.clone()),
+                };
+            }
+        }
+        stored.user.updated_at = Utc::now();
+
+        ok(&stored.user)
+    }
+
+    /// The user and session the bearer token belongs to
+    fn authenticate(&self, bearer_token: Option<&str>) -> Result<(usize, Uuid), Answer> {
+        let Some(token) = bearer_token else {
+            return Err(error(
+                StatusCode::UNAUTHORIZED,
+                ErrorCode::NoAuthorization,
+                "This endpoint requires a Bearer token",
+            ));
+        };
+
+        let mut validation = Validation::default();
+        validation.set_audience(&["authenticated"]);
+        let claims = jsonwebtoken::decode::<Value>(
+            token,
+            &DecodingKey::from_secret(JWT_SECRET.as_bytes()),
+            &validation,
+        )
+        .map_err(|_| {
+            error(
+                StatusCode::FORBIDDEN,
+                ErrorCode::BadJwt,
+                "invalid JWT: unable to parse or verify signature, token is expired",
+            )
+        })?
+        .claims;
+
+        let session_id = claims["session_id"]
+            .as_str()
+            .and_then(|id| id.parse::<Uuid>().ok());
+        let user_id = session_id.and_then(|id| self.sessions.get(&id));
+
+        match (session_id, user_id) {
+            (Some(session_id), Some(user_id)) => self
+                .users
+                .iter()
+                .position(|stored| stored.user.id == *user_id)
+                .map(|index| (index, session_id))
+                .ok_or_else(|| {
+                    error(
+                        StatusCode::FORBIDDEN,
+                        ErrorCode::UserNotFound,
+                        "User from sub claim in JWT does not exist",
+                    )
+                }),
+            _ => Err(error(
+                StatusCode::FORBIDDEN,
+                ErrorCode::SessionNotFound,
+                "Session from session_id claim in JWT does not exist",
+            )),
+        }
+    }
+
+    /// Sign in the user at `index` with a new session
+    fn issue_session(&mut self, index: usize) -> Answer {
+        let session_id = Uuid::new_v4();
+        self.sessions.insert(session_id, self.users[index].user.id);
+        self.users[index].user.last_sign_in_at = Some(Utc::now());
+
+        self.session(index, session_id)
+    }
+
+    /// New tokens for the session `session_id` of the user at `index`
+    fn session(&mut self, index: usize, session_id: Uuid) -> Answer {
+        let session = FakeSession::new()
+            .user(self.users[index].user.clone())
+            .session_id(session_id)
+            .build();
+
+        self.refresh_tokens.insert(
+            session.refresh_token.as_str().to_string(),
+            (session_id, false),
+        );
+
+        ok(&session)
+    }
+
+    fn issue_otp(&mut self, recipient: &str) {
+        let code = format!("{:06}", Uuid::new_v4().as_u128() % 1_000_000);
+        self.otps.insert(recipient.to_lowercase(), code);
+    }
+
+    fn otp_sent(&mut self, recipient: &str, sms: bool) -> Answer {
+        self.issue_otp(recipient);
+
+        let body = if sms {
+            json!({ "message_id": format!("SM{}", Uuid::new_v4().simple()) })
+        } else {
+            json!({})
+        };
+        (StatusCode::OK, Some(body))
+    }
+}
+
+/// An [`AuthClient`] answered by a [`FakeAuthServer`], see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct FakeAuthClient {
+    client: AuthClient,
+    server: FakeAuthServer,
+}
+
+impl Default for FakeAuthClient {
+    fn default() -> Self {
+        FakeAuthClient::new()
+    }
+}
+
+impl FakeAuthClient {
+    /// A client for a new [`FakeAuthServer`]
+    pub fn new() -> Self {
+        FakeAuthClient::with_server(FakeAuthServer::new())
+    }
+
+    /// A client for `server`
+    pub fn with_server(server: FakeAuthServer) -> Self {
+        let client = AuthClient::builder()
+            .project_url(PROJECT_URL)
+            .api_key("fake-anon-key")
+            .jwt_secret(JWT_SECRET)
+            .transport(server.clone())
+            .build()
+            .expect("the fake client is configured");
+
+        FakeAuthClient { client, server }
+    }
+
+    /// The client, to hand to the code under test
+    pub fn client(&self) -> &AuthClient {
+        &self.client
+    }
+
+    /// The server answering the client
+    pub fn server(&self) -> &FakeAuthServer {
+        &self.server
+    }
+
+    pub fn into_client(self) -> AuthClient {
+        self.client
+    }
+}
+
+/// The string field `name` of a request body, if set
+fn field<'a>(body: &'a Value, name: &str) -> Option<&'a str> {
+    body.get(name)
+        .and_then(Value::as_str)
+        .filter(|value| !value.is_empty())
+}
+
+/// Apply the `data` of a sign up to the user's metadata
+fn with_data(mut user: FakeUser, body: &Value) -> FakeUser {
+    if let Some(Value::Object(data)) = body.get("data") {
+        for (key, value) in data {
+            user = user.user_metadata(key.clone(), value.clone());
+        }
+    }
+    user
+}
+
+fn ok(value: &impl serde::Serialize) -> Answer {
+    (
+        StatusCode::OK,
+        Some(serde_json::to_value(value).expect("fake responses serialize")),
+    )
+}
+
+/// An error response in GoTrue's format
+fn error(status: StatusCode, code: ErrorCode, message: &str) -> Answer {
+    (
+        status,
+        Some(json!({
+            "code": status.as_u16(),
+            "error_code": code.as_str(),
+            "msg": message,
+        })),
+    )
+}
+
+fn not_found() -> Answer {
+    (
+        StatusCode::NOT_FOUND,
+        Some(json!({ "code": 404, "msg": "Not Found" })),
+    )
+}
+
+fn otp_expired() -> Answer {
+    error(
+        StatusCode::FORBIDDEN,
+        ErrorCode::OtpExpired,
+        "Token has expired or is invalid",
+    )
+}
+
+fn refresh_token_not_found() -> Answer {
+    error(
+        StatusCode::BAD_REQUEST,
+        ErrorCode::RefreshTokenNotFound,
+        "Invalid Refresh Token: Refresh Token Not Found",
+    )
+}
@@ -0,0 +1,189 @@
+/*!
+Fetches and caches the public keys Supabase Auth signs access tokens with.
+
+Projects using asymmetric signing keys publish them at `/auth/v1/.well-known/jwks.json`.
+[`JwksCache`] keeps a copy of that key set, refetching it once it is older than the configured
+TTL, or than a shorter `Cache-Control: max-age` it was served with. Refetches send the `ETag` of
+the cached copy, so an unchanged key set costs the server nothing but a `304 Not Modified`. If a
+refetch fails because the server can't be reached, is rate limiting, or fails with a `5xx`
+status, the expired key set keeps being used, and is refetched again 30 seconds later. A token
+signed with a key missing from the cache, e.g. right after a key rotation, triggers an early
+refetch.
+
+# Example
+
+```rust
+let jwks = JwksCache::new(auth_client.clone());
+
+// Fetches the key set on first use, then serves it from the cache
+let claims = jwks.verify_access_token(&access_token).await?;
+```
+*/
+
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::{
+    decode_header,
+    jwk::{Jwk, JwkSet},
+};
+use reqwest::{
+    header::{HeaderMap, HeaderValue},
+    Method,
+};
+use tokio::sync::Mutex;
+
+use crate::{
+    client::Cached,
+    error::Error,
+    models::{AuthClient, Claims},
+};
+
+/// How long a fetched key set is used before it is revalidated
+const DEFAULT_TTL: Duration = Duration::from_secs(600);
+/// Shortest wait between refetches caused by tokens with an unknown `kid`
+const MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(30);
+/// Longest wait between refetches caused by tokens with an unknown `kid`
+const MAX_REFETCH_INTERVAL: Duration = Duration::from_secs(300);
+
+/// A cached copy of a project's JSON Web Key Set
+///
+/// The cache is shared by every clone of the [`AuthClient`] it was created from, including the one
+/// used by [`AuthClient::get_claims`]. Concurrent callers wait for a single fetch instead of each
+/// requesting the key set.
+#[derive(Debug, Clone)]
+pub struct JwksCache {
+    client: AuthClient,
+    ttl: Duration,
+    min_refetch_interval: Duration,
+}
+
+/// The key set cached for an [`AuthClient`], shared by its clones
+#[derive(Debug, Default)]
+pub(crate) struct JwksState {
+    inner: Mutex<JwksInner>,
+}
+
+#[derive(Debug, Default)]
+struct JwksInner {
+    cached: Option<Cached<JwkSet>>,
+    /// When a token with an unknown `kid` last caused a refetch
+    last_refetch: Option<Instant>,
+    /// How long to wait after `last_refetch` before refetching for an unknown `kid` again
+    refetch_backoff: Duration,
+}
+
+impl JwksCache {
+    /// Create a cache for the project `client` points to. Keys are cached for 10 minutes.
+    pub fn new(client: AuthClient) -> Self {
+        JwksCache {
+            client,
+            ttl: DEFAULT_TTL,
+            min_refetch_interval: MIN_REFETCH_INTERVAL,
+        }
+    }
+
+    /// Set how long a fetched key set is used before it is revalidated with the server
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set how long to wait between refetches caused by tokens with an unknown `kid`. Defaults
+    /// to 30 seconds.
+    ///
+    /// The wait doubles, up to 5 minutes, each time a refetch doesn't turn up the missing key.
+    pub fn with_min_refetch_interval(mut self, interval: Duration) -> Self {
+        self.min_refetch_interval = interval;
+        self
+    }
+
+    /// The project's key set, fetched from the server if the cached copy is missing or expired
+    pub async fn keys(&self) -> Result<JwkSet, Error> {
+        let mut state = self.client.jwks.inner.lock().await;
+        let (keys, _) = self.load(&mut state, false).await?;
+
+        Ok(keys)
+    }
+
+    /// The key with id `kid`
+    ///
+    /// If the cached key set doesn't contain the key, e.g. because the project's signing keys
+    /// were rotated, the key set is fetched again before failing with
+    /// [`Error::UnknownSigningKey`]. Refetches are rate limited, see
+    /// [`JwksCache::with_min_refetch_interval`].
+    pub async fn key(&self, kid: &str) -> Result<Jwk, Error> {
+        let mut state = self.client.jwks.inner.lock().await;
+        let unknown_key = || Error::UnknownSigningKey(kid.to_string());
+
+        let (keys, fetched) = self.load(&mut state, false).await?;
+        if let Some(key) = keys.find(kid) {
+            return Ok(key.clone());
+        }
+
+        let backoff = state.refetch_backoff.max(self.min_refetch_interval);
+        let recently_refetched = state
+            .last_refetch
+            .is_some_and(|refetched_at| refetched_at.elapsed() < backoff);
+
+        if fetched || recently_refetched {
+            return Err(unknown_key());
+        }
+
+        state.last_refetch = Some(Instant::now());
+        let (keys, _) = self.load(&mut state, true).await?;
+
+        match keys.find(kid) {
+            Some(key) => {
+                state.refetch_backoff = Duration::ZERO;
+                Ok(key.clone())
+            }
+            None => {
+                state.refetch_backoff = (backoff * 2).min(MAX_REFETCH_INTERVAL);
+                Err(unknown_key())
+            }
+        }
+    }
+
+    /// Verify an access token against the key named by its `kid` header
+    ///
+    /// Checks the signature, then validates the claims according to the client's
+    /// [`JwtValidationOptions`](crate::jwt::JwtValidationOptions).
+    pub async fn verify_access_token(&self, token: &str) -> Result<Claims, Error> {
+        let kid = decode_header(token)?.kid.ok_or(Error::WrongToken)?;
+        let jwk = self.key(&kid).await?;
+
+        self.client.jwt_validation.verify_with_jwk(token, &jwk)
+    }
+
+    /// The cached key set, fetched first if it is missing, expired, or `force` is set. Also
+    /// returns whether the server was contacted.
+    async fn load(&self, state: &mut JwksInner, force: bool) -> Result<(JwkSet, bool), Error> {
+        if let Some(jwks) = &state.cached {
+            if !force && jwks.is_fresh(self.ttl) {
+                return Ok((jwks.value.clone(), false));
+            }
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", HeaderValue::from_str(&self.client.api_key)?);
+
+        let request = self
+            .client
+            .request(
+                Method::GET,
+                format!(
+                    "{}{}/.well-known/jwks.json",
+                    self.client.project_url, self.client.auth_path
+                ),
+            )
+            .headers(headers);
+
+        // A forced refetch looks for a key the cached copy is missing, a stale copy won't do
+        let keys = self
+            .client
+            .revalidate(&mut state.cached, request, !force)
+            .await?;
+
+        Ok((keys.clone(), true))
+    }
+}
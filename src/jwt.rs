@@ -0,0 +1,184 @@
+/*!
+Helpers for reading the JSON Web Tokens issued by Supabase Auth.
+
+[`verify_access_token`] validates an access token locally with the project's JWT secret, which
+avoids a round trip to the auth server for every request a backend handles. Projects using
+asymmetric signing keys can verify tokens with [`verify_access_token_with_jwk`], or with a
+[`JwksCache`](crate::jwks::JwksCache) which looks up the right key automatically.
+
+# Example
+
+```rust
+let claims = verify_access_token(&access_token, &jwt_secret)?;
+
+println!("Request made by {}", claims.sub);
+```
+*/
+
+use std::{str::FromStr, time::Duration};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{
+    decode,
+    errors::ErrorKind,
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk},
+    Algorithm, DecodingKey, Validation,
+};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    error::Error,
+    models::{Claims, Role},
+};
+
+/// The `aud` claim of access tokens issued to signed in users
+const AUTHENTICATED_AUDIENCE: &str = "authenticated";
+
+/// How access tokens are validated once their signature checks out
+///
+/// The defaults accept tokens issued to signed in users by any Supabase project, allowing 60
+/// seconds of clock skew.
+///
+/// # Example
+/// ```
+/// let options = JwtValidationOptions {
+///     leeway: Duration::from_secs(5),
+///     issuer: Some(format!("{}/auth/v1", project_url)),
+///     ..Default::default()
+/// };
+///
+/// let claims = options.verify(&access_token, &jwt_secret)?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JwtValidationOptions {
+    /// Clock skew tolerated when checking `exp` and `nbf`
+    pub leeway: Duration,
+    /// Accepted values of the `aud` claim. Any audience is accepted when empty.
+    pub audience: Vec<String>,
+    /// The expected `iss` claim, not checked when `None`
+    pub issuer: Option<String>,
+    /// The required `role` claim, not checked when `None`
+    pub role: Option<Role>,
+}
+
+impl Default for JwtValidationOptions {
+    fn default() -> Self {
+        JwtValidationOptions {
+            leeway: Duration::from_secs(60),
+            audience: vec![AUTHENTICATED_AUDIENCE.to_string()],
+            issuer: None,
+            role: None,
+        }
+    }
+}
+
+impl JwtValidationOptions {
+    /// Verify an access token signed with the project's JWT secret (HS256) against these
+    /// options. See [`verify_access_token`].
+    pub fn verify(&self, token: &str, secret: &str) -> Result<Claims, Error> {
+        self.verify_with_key(
+            token,
+            &DecodingKey::from_secret(secret.as_bytes()),
+            Algorithm::HS256,
+        )
+    }
+
+    /// Verify an access token signed with the private half of `jwk` against these options. See
+    /// [`verify_access_token_with_jwk`].
+    pub fn verify_with_jwk(&self, token: &str, jwk: &Jwk) -> Result<Claims, Error> {
+        self.verify_with_key(token, &DecodingKey::from_jwk(jwk)?, jwk_algorithm(jwk)?)
+    }
+
+    fn verify_with_key(
+        &self,
+        token: &str,
+        key: &DecodingKey,
+        algorithm: Algorithm,
+    ) -> Result<Claims, Error> {
+        let mut validation = Validation::new(algorithm);
+        validation.leeway = self.leeway.as_secs();
+
+        if self.audience.is_empty() {
+            validation.validate_aud = false;
+        } else {
+            validation.set_audience(&self.audience);
+        }
+
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+
+        let claims = decode::<Claims>(token, key, &validation)?.claims;
+
+        if let Some(role) = &self.role {
+            claims.require_role(role)?;
+        }
+
+        Ok(claims)
+    }
+}
+
+/// Verify an access token signed with the project's JWT secret (HS256)
+///
+/// Checks the signature, that the token hasn't expired, and that its audience is
+/// `authenticated`. Fails with [`Error::InvalidJwt`] otherwise. Use
+/// [`JwtValidationOptions::verify`] to validate differently.
+///
+/// This doesn't detect tokens of sessions which were revoked since the token was issued, use
+/// [`AuthClient::get_user`](crate::models::AuthClient::get_user) when that matters.
+pub fn verify_access_token(token: &str, secret: &str) -> Result<Claims, Error> {
+    JwtValidationOptions::default().verify(token, secret)
+}
+
+/// Verify an access token signed with the private half of `jwk`
+///
+/// RSA (`RS256`), P-256 (`ES256`), and Ed25519 (`EdDSA`) keys are supported, as published in a
+/// project's JWKS. The algorithm is taken from the key's `alg` parameter, or inferred from its key
+/// type and curve when `alg` is missing. Tokens signed with any other algorithm are rejected.
+///
+/// # Example
+/// ```
+/// let jwk: Jwk = serde_json::from_str(&public_key)?;
+///
+/// let claims = verify_access_token_with_jwk(&access_token, &jwk)?;
+/// ```
+pub fn verify_access_token_with_jwk(token: &str, jwk: &Jwk) -> Result<Claims, Error> {
+    JwtValidationOptions::default().verify_with_jwk(token, jwk)
+}
+
+/// The algorithm tokens signed by `jwk` use
+fn jwk_algorithm(jwk: &Jwk) -> Result<Algorithm, Error> {
+    if let Some(algorithm) = jwk.common.key_algorithm {
+        return Ok(Algorithm::from_str(&algorithm.to_string())?);
+    }
+
+    match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => Ok(Algorithm::RS256),
+        AlgorithmParameters::EllipticCurve(params) if params.curve == EllipticCurve::P256 => {
+            Ok(Algorithm::ES256)
+        }
+        AlgorithmParameters::OctetKeyPair(params) if params.curve == EllipticCurve::Ed25519 => {
+            Ok(Algorithm::EdDSA)
+        }
+        _ => Err(jsonwebtoken::errors::Error::from(ErrorKind::MissingAlgorithm).into()),
+    }
+}
+
+/// Decode the payload of a JWT without verifying its signature
+///
+/// Only use the result for decisions that don't need to be trusted, such as when to refresh.
+pub(crate) fn decode_unverified<T: DeserializeOwned>(token: &str) -> Result<T, Error> {
+    let mut parts = token.split('.');
+
+    let (Some(_header), Some(payload), Some(_signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(Error::WrongToken);
+    };
+
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|_| Error::WrongToken)?;
+
+    serde_json::from_slice(&payload).map_err(|_| Error::WrongToken)
+}
@@ -0,0 +1,171 @@
+/*!
+Session helpers for [Leptos](https://leptos.dev) server functions served with `leptos_axum`,
+enabled with the `leptos` feature.
+
+The session is kept in the same `@supabase/ssr` cookies as in the [`cookies`](crate::cookies)
+module, so the browser client and the server share it. The helpers read the request from the
+Leptos context and add `Set-Cookie` headers to its [`ResponseOptions`] whenever the session
+changes, including when [`get_session`] refreshes an expired one.
+
+Cookie attributes are taken from a [`CookieOptions`] provided as context, or else the defaults.
+
+# Example
+
+```rust
+#[server]
+async fn login(email: String, password: String) -> Result<(), ServerFnError> {
+    let auth_client = expect_context::<AuthClient>();
+    let session = auth_client.login_with_email(&email, &password).await?;
+
+    supabase_auth::leptos::set_session(&auth_client, &session)?;
+    Ok(())
+}
+
+#[server]
+async fn whoami() -> Result<Option<String>, ServerFnError> {
+    let auth_client = expect_context::<AuthClient>();
+    let user = supabase_auth::leptos::get_user(&auth_client).await?;
+
+    Ok(user.map(|user| user.email))
+}
+```
+*/
+
+use http::{
+    header::{COOKIE, SET_COOKIE},
+    request::Parts,
+    HeaderValue,
+};
+use leptos::prelude::use_context;
+use leptos_axum::ResponseOptions;
+
+use crate::{
+    cookies::{self, CookieOptions},
+    error::Error,
+    models::{AuthClient, RequestUser, Session, User},
+};
+
+/// The session stored in the request's auth cookies, refreshed if it has expired
+///
+/// Returns `None` if the request carries no session. The session isn't verified with the
+/// server, use [`get_user`] or [`AuthClient::get_claims`] before trusting it.
+pub async fn get_session(auth_client: &AuthClient) -> Result<Option<Session>, Error> {
+    let context = RequestContext::current(auth_client)?;
+
+    let Some(session) = cookies::parse_session_cookie(&context.cookie_name, &context.cookies())?
+    else {
+        return Ok(None);
+    };
+
+    if !session.is_expired() {
+        return Ok(Some(session));
+    }
+
+    let session = auth_client.refresh_session(&session.refresh_token).await?;
+    context.write_session(&session)?;
+
+    Ok(Some(session))
+}
+
+/// The user the request was made by, see [`AuthClient::get_user_from_headers`]
+///
+/// Returns `None` if the request carries no access token. A refreshed cookie session is sent
+/// back to the browser.
+pub async fn get_user(auth_client: &AuthClient) -> Result<Option<User>, Error> {
+    let context = RequestContext::current(auth_client)?;
+
+    let RequestUser {
+        user,
+        refreshed_session,
+    } = match auth_client
+        .get_user_from_headers(&context.parts.headers)
+        .await
+    {
+        Ok(request_user) => request_user,
+        Err(Error::NotAuthenticated) => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    if let Some(session) = refreshed_session {
+        context.write_session(&session)?;
+    }
+
+    Ok(Some(user))
+}
+
+/// Store `session` in the auth cookies of the response, e.g. after signing in
+pub fn set_session(auth_client: &AuthClient, session: &Session) -> Result<(), Error> {
+    RequestContext::current(auth_client)?.write_session(session)
+}
+
+/// Remove the auth cookies sent with the request, e.g. after signing out
+pub fn clear_session(auth_client: &AuthClient) -> Result<(), Error> {
+    let context = RequestContext::current(auth_client)?;
+
+    let removals =
+        cookies::build_removal_cookies(&context.cookie_name, &context.cookies(), &context.options);
+
+    context.append_cookies(removals)
+}
+
+/// What the helpers need from the Leptos context, captured before the first `await`
+struct RequestContext {
+    parts: Parts,
+    response: ResponseOptions,
+    options: CookieOptions,
+    cookie_name: String,
+}
+
+impl RequestContext {
+    fn current(auth_client: &AuthClient) -> Result<Self, Error> {
+        Ok(RequestContext {
+            parts: use_context::<Parts>()
+                .ok_or(Error::MissingConfiguration("leptos_axum request context"))?,
+            response: use_context::<ResponseOptions>()
+                .ok_or(Error::MissingConfiguration("leptos_axum response context"))?,
+            options: use_context::<CookieOptions>().unwrap_or_default(),
+            cookie_name: cookies::cookie_name(auth_client.project_url())?,
+        })
+    }
+
+    /// The `Cookie` headers of the request, joined into one
+    fn cookies(&self) -> String {
+        self.parts
+            .headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Replace the session cookies of the request with `session`
+    fn write_session(&self, session: &Session) -> Result<(), Error> {
+        let set = cookies::build_session_cookies(&self.cookie_name, session, &self.options)?;
+
+        // Chunks of a larger previous session which aren't overwritten
+        let stale =
+            cookies::build_removal_cookies(&self.cookie_name, &self.cookies(), &self.options)
+                .into_iter()
+                .filter(|removal| {
+                    !set.iter()
+                        .any(|cookie| cookie_name_of(cookie) == cookie_name_of(removal))
+                })
+                .collect::<Vec<_>>();
+
+        self.append_cookies(stale.into_iter().chain(set))
+    }
+
+    fn append_cookies(&self, cookies: impl IntoIterator<Item = String>) -> Result<(), Error> {
+        for cookie in cookies {
+            self.response
+                .append_header(SET_COOKIE, HeaderValue::from_str(&cookie)?);
+        }
+
+        Ok(())
+    }
+}
+
+fn cookie_name_of(set_cookie: &str) -> &str {
+    set_cookie.split('=').next().unwrap_or_default()
+}
@@ -31,6 +31,15 @@ Or use cargo-add:
 cargo add supabase-auth
 ```
 
+### TLS
+
+Requests use [rustls](https://github.com/rustls/rustls) by default, which needs no system libraries and builds for musl targets. To use the platform's TLS library (OpenSSL on Linux) instead, disable the default features and enable `native-tls`:
+
+```toml
+[dependencies]
+supabase-auth = { version = "0.1.0", default-features = false, features = ["native-tls"] }
+```
+
 ## Usage
 
 ### Create an Auth Client
@@ -42,8 +51,26 @@ let auth_client = AuthClient::new(project_url, api_key, jwt_secret).unwrap();
 // Or you can use environment variables
 // Requires `SUPABASE_URL`, `SUPABASE_API_KEY`, and `SUPABASE_JWT_SECRET` environment variables
 let auth_client = AuthClient::new_from_env().unwrap();
+
+// Or pick the environment variables to read
+let auth_client = AuthClient::new_from_env_with(EnvConfig {
+    url_var: "NEXT_PUBLIC_SUPABASE_URL".to_string(),
+    api_key_var: "SUPABASE_ANON_KEY".to_string(),
+    jwt_secret_var: None,
+})
+.unwrap();
+
+// Or use the builder to share a `reqwest::Client` with the rest of your app
+let auth_client = AuthClient::builder()
+    .http_client(http_client)
+    .project_url(project_url)
+    .api_key(api_key)
+    .build()
+    .unwrap();
 ```
 
+`AuthClient` is cheap to clone, and clones share the connection pool and caches. Clone it into every task or handler rather than wrapping it in an `Arc<Mutex<_>>`.
+
 ### Sign Up
 
 ```rust
@@ -86,17 +113,12 @@ let oauth_response = auth_client
     .unwrap();
 
 // You can also customize the options like so:
-let mut query_params = HashMap::new();
-query_params.insert("key".to_string(), "value".to_string());
-query_params.insert("second_key".to_string(), "second_value".to_string());
-query_params.insert("third_key".to_string(), "third_value".to_string());
-
-let options = SignInWithOAuthOptions {
-    query_params: Some(query_params),
-    redirect_to: Some("your-redirect-url".to_string()),
-    scopes: Some("repo gist notifications".to_string()),
-    skip_browser_redirect: Some(true),
-};
+let options = LoginWithOAuthOptions::default()
+    .query_param("key", "value")
+    .query_param("second_key", "second_value")
+    .redirect_to("your-redirect-url")
+    .scopes("repo gist notifications")
+    .skip_browser_redirect(true);
 
 let response = auth_client
     .login_with_oauth(Provider::Github, Some(options))
@@ -109,10 +131,13 @@ let response = auth_client
 NOTE: Requires an SSO Provider and Supabase Pro plan
 
 ```rust
-let params = SignInWithSSO {
-    domain: Some(demo_domain),
+// Route the user by their email domain...
+let params = LoginWithSSO::domain(demo_domain);
+
+// ...or target a specific provider
+let params = LoginWithSSO {
+    identifier: SsoIdentifier::ProviderId(provider_id),
     options: None,
-    provider_id: None,
 };
 
 // Returns the URL where the user will continue the auth flow with your SSO provider
@@ -136,8 +161,94 @@ let url = auth_client.sso(params).await.unwrap();
 * ✓ Reset Password
 * ✓ Change User Data (e.g., Email or password)
 * ✓ SSO
+* ✓ Background Session Refresh
+* ✓ Stateful Client (Managed Session)
+* ✓ Auth State Change Events
+* ✓ SSR Auth Cookies (compatible with `@supabase/ssr`)
+* ✓ Session Persistence (Memory, File with the `file-store` feature, Encrypted with the `encrypted-store` feature, OS Keyring with the `keyring-store` feature)
+* ✓ Manage SSO Providers (Admin)
+* ✓ Manage OAuth Server Clients (Admin)
+* ✓ List Users and Generate Email Links (Admin)
+* ✓ Token-Scoped User Client
+* ✓ `AuthApi` Trait for Mocking the Client (generated mocks with the `mockall` feature)
+* ✓ Local JWT Verification (HS256, RS256, ES256, EdDSA)
+* ✓ JWKS and Server Settings Caching (revalidated with `ETag`, served stale on errors)
+* ✓ Automatic Retries with Exponential Backoff
+* ✓ Client-Side Rate Limiting
+* ✓ Circuit Breaker to Fail Fast During Outages
+* ✓ HTTP(S) and SOCKS Proxies (SOCKS with the `socks` feature)
+* ✓ Configuration Profiles Loaded from TOML (with the `config` feature)
+* ✓ Multi-Project Client Pool
+* ✓ Blocking Client (with the `blocking` feature)
+* ✓ Axum Extractors (with the `axum` feature)
+* ✓ Tower Middleware for Token Verification (with the `tower` feature)
+* ✓ Actix-web Middleware and Extractors (with the `actix` feature)
+* ✓ Tonic Interceptor for gRPC Services (with the `tonic` feature)
+* ✓ Leptos SSR Session Helpers (with the `leptos` feature)
+* ✓ OS Keyring Session Store and Deep Link OAuth for Tauri Apps (with the `tauri` feature)
+* ✓ `supabase-auth` Command Line Tool (with the `cli` feature)
+* ✓ Mock Auth Server for Tests (with the `testing` feature)
+* ✓ Fake Users and Sessions for Tests (with the `test-utils` feature)
+* ✓ In-Memory Fake Auth Server and Client for Tests (with the `test-utils` feature)
+* ✓ Self-Hosted GoTrue in Docker for Integration Tests (with the `testcontainers` feature)
+* ✓ Record and Replay HTTP Fixtures for Deterministic Tests (with the `recording` feature)
+* ✓ OpenTelemetry Spans and W3C Trace Context Propagation (with the `otel` feature)
+* ✓ Request, Latency, Error and Refresh Failure Metrics (with the `metrics` feature)
+* ✓ Request IDs (`sb-request-id`) and Latency of Responses
+* ✓ Redacted Request Logs for `slog`, `log4rs` and Audit Trails (with the `logging` feature)
+* ✓ Streaming Admin User Listings
+* ✓ Connection Pool, Keep-Alive and HTTP/2 Settings (HTTP/2 with the `http2` feature)
 */
 
+#[cfg(feature = "actix")]
+pub mod actix;
+pub mod api;
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod builder;
+pub mod circuit_breaker;
 pub mod client;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "testcontainers")]
+pub mod container;
+pub mod cookies;
 pub mod error;
+pub mod events;
+#[cfg(feature = "test-utils")]
+pub mod fake;
+#[cfg(feature = "test-utils")]
+pub mod fake_server;
+pub mod jwks;
+pub mod jwt;
+#[cfg(feature = "leptos")]
+pub mod leptos;
+#[cfg(feature = "logging")]
+pub mod logging;
+pub mod managed;
+pub mod meta;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod models;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod pool;
+pub mod rate_limit;
+#[cfg(feature = "recording")]
+pub mod recording;
+pub mod refresher;
+pub mod retry;
+pub mod store;
+pub mod stream;
+#[cfg(feature = "tauri")]
+pub mod tauri;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "tonic")]
+pub mod tonic;
+#[cfg(feature = "tower")]
+pub mod tower;
+pub mod transport;
+pub mod user_client;
@@ -0,0 +1,181 @@
+/*!
+A logging callback for requests to Supabase Auth, enabled with the `logging` feature.
+
+For apps logging with `slog`, `log4rs`, or an audit trail of their own rather than `tracing`.
+Register a callback with
+[`AuthClientBuilder::logger`](crate::builder::AuthClientBuilder::logger) and it receives a
+[`RequestLog`] for every request the client sends, retries included, whether or not a response
+arrived.
+
+Entries are sanitized: they hold no headers and no bodies, so no tokens, passwords, or api keys.
+Query parameters other than [`LOGGED_QUERY_PARAMS`] have their values replaced with
+`[REDACTED]`, as they may hold tokens, codes, or redirect URLs. Rejected requests are logged with
+the error code from the response body.
+
+# Example
+
+```rust
+let log = slog::Logger::root(drain, slog::o!());
+
+let auth_client = AuthClient::builder()
+    .project_url(project_url)
+    .api_key(api_key)
+    .logger(move |entry| match entry.status {
+        Some(status) if status.is_success() => slog::info!(log, "{entry}"),
+        _ => slog::warn!(log, "{entry}"; "error_code" => entry.error_code.as_deref()),
+    })
+    .build()?;
+```
+*/
+
+use std::{fmt, sync::Arc, time::Duration};
+
+use reqwest::{Method, Response, ResponseBuilderExt, StatusCode, Url};
+
+use crate::{
+    error::{Error, SupabaseHTTPError},
+    meta::request_id,
+    models::REDACTED,
+};
+
+/// Query parameters whose values are logged, the values of all others are redacted
+pub const LOGGED_QUERY_PARAMS: &[&str] = &["grant_type", "scope", "type", "page", "per_page"];
+
+/// A sanitized summary of a request, see the [module docs](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RequestLog {
+    pub method: Method,
+    /// The path of the request, e.g. `/auth/v1/token`
+    pub path: String,
+    /// The query of the request with redacted values, e.g. `grant_type=password`
+    pub query: Option<String>,
+    /// The response status, `None` if no response arrived
+    pub status: Option<StatusCode>,
+    /// How long the request took until the response headers arrived, or it failed
+    pub duration: Duration,
+    /// The error code of a rejected request, e.g. `invalid_credentials`, or of a request without
+    /// a response: `timeout`, `connect`, or `network`
+    pub error_code: Option<String>,
+    /// The `sb-request-id` of the response, if the server sent one
+    pub request_id: Option<String>,
+}
+
+impl fmt::Display for RequestLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.path)?;
+        if let Some(query) = &self.query {
+            write!(f, "?{query}")?;
+        }
+        match self.status {
+            Some(status) => write!(f, " {}", status.as_u16())?,
+            None => write!(f, " -")?,
+        }
+        if let Some(error_code) = &self.error_code {
+            write!(f, " {error_code}")?;
+        }
+        write!(f, " {}ms", self.duration.as_millis())?;
+        if let Some(request_id) = &self.request_id {
+            write!(f, " request_id={request_id}")?;
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) type Logger = dyn Fn(&RequestLog) + Send + Sync;
+
+/// Log a request to `url`, which took `duration` and ended in `result`
+///
+/// The body of a rejected request is read for its error code, so its response is rebuilt from the
+/// read body and returned in place of the original.
+pub(crate) async fn log(
+    logger: &Arc<Logger>,
+    method: Method,
+    url: &Url,
+    duration: Duration,
+    result: Result<Response, Error>,
+) -> Result<Response, Error> {
+    let mut entry = RequestLog {
+        method,
+        path: url.path().to_string(),
+        query: redact_query(url),
+        status: None,
+        duration,
+        error_code: None,
+        request_id: None,
+    };
+
+    let result = match result {
+        Ok(response) => {
+            entry.status = Some(response.status());
+            entry.request_id = request_id(response.headers()).map(str::to_string);
+
+            if response.status().is_success() {
+                Ok(response)
+            } else {
+                let (response, error_code) = read_error_code(response).await?;
+                entry.error_code = error_code;
+                Ok(response)
+            }
+        }
+        Err(error) => {
+            entry.error_code = Some(
+                match &error {
+                    Error::NetworkError(error) if error.is_timeout() => "timeout",
+                    Error::NetworkError(error) if error.is_connect() => "connect",
+                    _ => "network",
+                }
+                .to_string(),
+            );
+            Err(error)
+        }
+    };
+
+    logger(&entry);
+
+    result
+}
+
+/// The error code in the body of the rejected `response`, and the response rebuilt around it
+async fn read_error_code(response: Response) -> Result<(Response, Option<String>), Error> {
+    let status = response.status();
+    let version = response.version();
+    let url = response.url().clone();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?;
+
+    let error_code = serde_json::from_slice::<SupabaseHTTPError>(&body)
+        .ok()
+        .and_then(|error| error.error_code);
+
+    let mut builder = http::Response::builder()
+        .status(status)
+        .version(version)
+        .url(url);
+    if let Some(response_headers) = builder.headers_mut() {
+        *response_headers = headers;
+    }
+    let response = builder.body(body).map_err(|_| Error::InternalError)?;
+
+    Ok((response.into(), error_code))
+}
+
+/// The query of `url` with the values of all but [`LOGGED_QUERY_PARAMS`] redacted
+fn redact_query(url: &Url) -> Option<String> {
+    url.query()?;
+
+    let query = url
+        .query_pairs()
+        .map(|(name, value)| {
+            if LOGGED_QUERY_PARAMS.contains(&name.as_ref()) {
+                format!("{name}={value}")
+            } else {
+                format!("{name}={REDACTED}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+
+    Some(query)
+}
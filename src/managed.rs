@@ -0,0 +1,453 @@
+/*!
+A stateful wrapper around [`AuthClient`] which remembers the current session.
+
+[`ManagedAuthClient`] stores the session returned by sign in, sign up, and refresh calls, so that
+methods acting on the signed in user don't need an `access_token` passed in, matching the
+ergonomics of supabase-js.
+
+# Example
+
+```rust
+let auth_client = ManagedAuthClient::new(AuthClient::new_from_env().unwrap());
+
+auth_client
+    .login_with_email(&demo_email, &demo_password)
+    .await
+    .unwrap();
+
+// No access token needed
+let user = auth_client.get_user().await.unwrap();
+
+auth_client.logout(None).await.unwrap();
+```
+*/
+
+use std::{
+    future::Future,
+    sync::{Arc, RwLock},
+};
+
+use reqwest::StatusCode;
+use tokio::sync::broadcast;
+
+use crate::{
+    error::{Error, ErrorCode},
+    events::{AuthEvent, AuthStateChange, AuthStateSubscription, Listeners},
+    models::{
+        AccessToken, AuthClient, EmailSignUpResult, IdTokenCredentials, LoginAnonymouslyOptions,
+        LoginWithPasswordOptions, LogoutScope, OtpType, RefreshToken, Session,
+        SignUpWithPasswordOptions, UpdatedUser, User, VerifyOtpParams,
+    },
+    refresher::SingleFlightRefresh,
+    store::SessionStore,
+};
+
+/// An [`AuthClient`] which keeps track of the current session
+///
+/// Clones share the same session. Concurrent calls to [`ManagedAuthClient::refresh_session`]
+/// are coalesced into a single request, see [`SingleFlightRefresh`].
+///
+/// Methods acting on the signed in user refresh the session first if it expired, and refresh it
+/// and retry once when the server rejects the access token as expired.
+#[derive(Debug, Clone)]
+pub struct ManagedAuthClient {
+    client: AuthClient,
+    session: Arc<RwLock<Option<Session>>>,
+    refresh: SingleFlightRefresh,
+    store: Option<Arc<dyn SessionStore>>,
+    listeners: Listeners,
+}
+
+impl ManagedAuthClient {
+    /// Wrap an [`AuthClient`], starting without a session
+    pub fn new(client: AuthClient) -> Self {
+        ManagedAuthClient {
+            refresh: SingleFlightRefresh::new(client.clone()),
+            client,
+            session: Arc::new(RwLock::new(None)),
+            store: None,
+            listeners: Listeners::default(),
+        }
+    }
+
+    /// Wrap an [`AuthClient`], starting with an existing session
+    pub fn with_session(client: AuthClient, session: Session) -> Self {
+        ManagedAuthClient {
+            refresh: SingleFlightRefresh::new(client.clone()),
+            client,
+            session: Arc::new(RwLock::new(Some(session))),
+            store: None,
+            listeners: Listeners::default(),
+        }
+    }
+
+    /// Wrap an [`AuthClient`], starting with the session saved in `store`, if any
+    ///
+    /// The session is saved to `store` whenever it changes, and cleared on logout.
+    ///
+    /// # Example
+    /// ```
+    /// let store = FileSessionStore::new(home_dir.join(".my-cli/session.json"));
+    ///
+    /// let auth_client = ManagedAuthClient::with_store(AuthClient::new_from_env()?, store)?;
+    ///
+    /// if auth_client.session().is_none() {
+    ///     auth_client.login_with_email(&email, &password).await?;
+    /// }
+    /// ```
+    pub fn with_store(
+        client: AuthClient,
+        store: impl SessionStore + 'static,
+    ) -> Result<Self, Error> {
+        let session = store.load()?;
+
+        Ok(ManagedAuthClient {
+            refresh: SingleFlightRefresh::new(client.clone()),
+            client,
+            session: Arc::new(RwLock::new(session)),
+            store: Some(Arc::new(store)),
+            listeners: Listeners::default(),
+        })
+    }
+
+    /// The underlying stateless client
+    pub fn client(&self) -> &AuthClient {
+        &self.client
+    }
+
+    /// The current session, if a user is signed in
+    pub fn session(&self) -> Option<Session> {
+        self.session
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Replace the current session
+    pub fn replace_session(&self, session: Session) -> Result<(), Error> {
+        self.store(AuthEvent::SignedIn, Some(session))
+    }
+
+    /// Build a session from an access token and refresh token, e.g. ones received from a
+    /// supabase-js frontend, and store it. See [`AuthClient::set_session`].
+    pub async fn set_session(
+        &self,
+        access_token: &AccessToken,
+        refresh_token: &RefreshToken,
+    ) -> Result<Session, Error> {
+        let session = self.client.set_session(access_token, refresh_token).await?;
+        self.store(AuthEvent::SignedIn, Some(session.clone()))?;
+
+        Ok(session)
+    }
+
+    /// Forget the current session without contacting the server
+    pub fn clear_session(&self) -> Result<(), Error> {
+        self.store(AuthEvent::SignedOut, None)
+    }
+
+    /// Sign in a user with an email and password, and store the session
+    pub async fn login_with_email(&self, email: &str, password: &str) -> Result<Session, Error> {
+        let session = self.client.login_with_email(email, password).await?;
+        self.store(AuthEvent::SignedIn, Some(session.clone()))?;
+
+        Ok(session)
+    }
+
+    /// Sign in a user with an email and password and `options`, e.g. a captcha token, and store
+    /// the session
+    pub async fn login_with_email_with_options(
+        &self,
+        email: &str,
+        password: &str,
+        options: LoginWithPasswordOptions,
+    ) -> Result<Session, Error> {
+        let session = self
+            .client
+            .login_with_email_with_options(email, password, options)
+            .await?;
+        self.store(AuthEvent::SignedIn, Some(session.clone()))?;
+
+        Ok(session)
+    }
+
+    /// Sign in a user with phone number and password, and store the session
+    pub async fn login_with_phone(&self, phone: &str, password: &str) -> Result<Session, Error> {
+        let session = self.client.login_with_phone(phone, password).await?;
+        self.store(AuthEvent::SignedIn, Some(session.clone()))?;
+
+        Ok(session)
+    }
+
+    /// Sign in a user with phone number and password and `options`, e.g. a captcha token, and
+    /// store the session
+    pub async fn login_with_phone_with_options(
+        &self,
+        phone: &str,
+        password: &str,
+        options: LoginWithPasswordOptions,
+    ) -> Result<Session, Error> {
+        let session = self
+            .client
+            .login_with_phone_with_options(phone, password, options)
+            .await?;
+        self.store(AuthEvent::SignedIn, Some(session.clone()))?;
+
+        Ok(session)
+    }
+
+    /// Sign in a new anonymous user, and store the session
+    pub async fn login_anonymously(
+        &self,
+        options: Option<LoginAnonymouslyOptions>,
+    ) -> Result<Session, Error> {
+        let session = self.client.login_anonymously(options).await?;
+        self.store(AuthEvent::SignedIn, Some(session.clone()))?;
+
+        Ok(session)
+    }
+
+    /// Sign in with an OIDC ID token, and store the session
+    pub async fn login_with_id_token(
+        &self,
+        credentials: IdTokenCredentials,
+    ) -> Result<Session, Error> {
+        let session = self.client.login_with_id_token(credentials).await?;
+        self.store(AuthEvent::SignedIn, Some(session.clone()))?;
+
+        Ok(session)
+    }
+
+    /// Sign up a new user with an email and password
+    ///
+    /// The session is stored when email confirmation is disabled and the server signs the user in.
+    pub async fn sign_up_with_email_and_password(
+        &self,
+        email: &str,
+        password: &str,
+        options: Option<SignUpWithPasswordOptions>,
+    ) -> Result<EmailSignUpResult, Error> {
+        let result = self
+            .client
+            .sign_up_with_email_and_password(email, password, options)
+            .await?;
+
+        if let Some(session) = result.session() {
+            self.store(AuthEvent::SignedIn, Some(session.clone()))?;
+        }
+
+        Ok(result)
+    }
+
+    /// Sign up a new user with a phone number and password, and store the session
+    pub async fn sign_up_with_phone_and_password(
+        &self,
+        phone: &str,
+        password: &str,
+        options: Option<SignUpWithPasswordOptions>,
+    ) -> Result<Session, Error> {
+        let session = self
+            .client
+            .sign_up_with_phone_and_password(phone, password, options)
+            .await?;
+        self.store(AuthEvent::SignedIn, Some(session.clone()))?;
+
+        Ok(session)
+    }
+
+    /// Verify an OTP, and store the session
+    ///
+    /// Recovery OTPs are reported as [`AuthEvent::PasswordRecovery`].
+    pub async fn verify_otp(&self, params: VerifyOtpParams) -> Result<Session, Error> {
+        let otp_type = match &params {
+            VerifyOtpParams::Mobile(params) => &params.otp_type,
+            VerifyOtpParams::Email(params) => &params.otp_type,
+            VerifyOtpParams::TokenHash(params) => &params.otp_type,
+        };
+        let event = match otp_type {
+            OtpType::Recovery => AuthEvent::PasswordRecovery,
+            _ => AuthEvent::SignedIn,
+        };
+
+        let session = self.client.verify_otp(params).await?;
+        self.store(event, Some(session.clone()))?;
+
+        Ok(session)
+    }
+
+    /// Exchange a PKCE auth code for a session, and store it
+    pub async fn exchange_code_for_session(
+        &self,
+        auth_code: &str,
+        code_verifier: &str,
+    ) -> Result<Session, Error> {
+        let session = self
+            .client
+            .exchange_code_for_session(auth_code, code_verifier)
+            .await?;
+        self.store(AuthEvent::SignedIn, Some(session.clone()))?;
+
+        Ok(session)
+    }
+
+    /// Exchange the stored refresh token for a new session, and store it
+    ///
+    /// If the server reports that the refresh token was already used, the session can't be
+    /// recovered: it is cleared, [`AuthEvent::SignedOut`] is emitted, and
+    /// [`Error::RefreshTokenAlreadyUsed`] is returned so the caller can ask the user to sign in
+    /// again.
+    pub async fn refresh_session(&self) -> Result<Session, Error> {
+        let refresh_token = self
+            .session()
+            .map(|s| s.refresh_token)
+            .filter(|t| !t.is_empty())
+            .ok_or(Error::MissingRefreshToken)?;
+
+        let session = match self.refresh.refresh(&refresh_token).await {
+            Ok(session) => session,
+            Err(Error::RefreshTokenAlreadyUsed) => {
+                self.store(AuthEvent::SignedOut, None)?;
+                return Err(Error::RefreshTokenAlreadyUsed);
+            }
+            Err(error) => return Err(error),
+        };
+        self.store(AuthEvent::TokenRefreshed, Some(session.clone()))?;
+
+        Ok(session)
+    }
+
+    /// Return the signed in User
+    pub async fn get_user(&self) -> Result<User, Error> {
+        self.with_access_token(
+            |access_token| async move { self.client.get_user(&access_token).await },
+        )
+        .await
+    }
+
+    /// Update the signed in user. The stored session is updated with the returned user.
+    pub async fn update_user(&self, updated_user: UpdatedUser) -> Result<User, Error> {
+        let user = self
+            .with_access_token(|access_token| {
+                let updated_user = updated_user.clone();
+                async move { self.client.update_user(updated_user, &access_token).await }
+            })
+            .await?;
+
+        if let Some(mut session) = self.session() {
+            session.user = user.clone();
+            self.store(AuthEvent::UserUpdated, Some(session))?;
+        }
+
+        Ok(user)
+    }
+
+    /// Log out the signed in user and clear the stored session
+    pub async fn logout(&self, scope: Option<LogoutScope>) -> Result<(), Error> {
+        self.with_access_token(|access_token| {
+            let scope = scope.clone();
+            async move { self.client.logout(scope, &access_token).await }
+        })
+        .await?;
+
+        self.store(AuthEvent::SignedOut, None)
+    }
+
+    /// Run `call` with the current access token, refreshing the session first if it expired. If
+    /// the server rejects the token as expired, refresh the session once and run `call` again
+    /// with the new token.
+    async fn with_access_token<T, F, Fut>(&self, call: F) -> Result<T, Error>
+    where
+        F: Fn(AccessToken) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let access_token = match self.session() {
+            Some(session) if session.is_expired() => self.refresh_session().await?.access_token,
+            Some(session) => session.access_token,
+            None => return Err(Error::NotAuthenticated),
+        };
+
+        match call(access_token).await {
+            Err(error) if is_expired_token_error(&error) => {
+                let session = self.refresh_session().await?;
+
+                call(session.access_token).await
+            }
+            result => result,
+        }
+    }
+
+    /// Register a callback which is called whenever a user signs in or out, the session is
+    /// refreshed, or the user is updated
+    ///
+    /// # Example
+    /// ```
+    /// let subscription = auth_client.on_auth_state_change(|event, session| {
+    ///     println!("{:?}: {:?}", event, session.map(|s| &s.user.email));
+    /// });
+    ///
+    /// subscription.unsubscribe();
+    /// ```
+    pub fn on_auth_state_change(
+        &self,
+        callback: impl Fn(AuthEvent, Option<&Session>) + Send + Sync + 'static,
+    ) -> AuthStateSubscription {
+        self.listeners.add(callback)
+    }
+
+    /// Receive every auth state change on a channel, independently of other subscribers
+    ///
+    /// A receiver which falls behind by more than 32 events skips the oldest ones and gets
+    /// [`RecvError::Lagged`](broadcast::error::RecvError::Lagged). Wrap the receiver in
+    /// `tokio_stream::wrappers::BroadcastStream` to use it as a `Stream`.
+    ///
+    /// # Example
+    /// ```
+    /// let mut events = auth_client.subscribe_auth_events();
+    ///
+    /// while let Ok(change) = events.recv().await {
+    ///     println!("{:?}", change.event);
+    /// }
+    /// ```
+    pub fn subscribe_auth_events(&self) -> broadcast::Receiver<AuthStateChange> {
+        self.listeners.subscribe()
+    }
+
+    fn store(&self, event: AuthEvent, session: Option<Session>) -> Result<(), Error> {
+        if let Some(store) = &self.store {
+            match &session {
+                Some(session) => store.save(session)?,
+                None => store.clear()?,
+            }
+        }
+
+        *self.session.write().unwrap_or_else(|e| e.into_inner()) = session.clone();
+
+        self.listeners.emit(event, session.as_ref());
+
+        Ok(())
+    }
+}
+
+/// Whether a request failed because the access token expired
+///
+/// The client fails expired tokens it can read with [`Error::TokenExpired`]. Otherwise GoTrue
+/// answers with the `bad_jwt` or `session_expired` error code. Only for servers which send no
+/// error code is the message checked for the expiry.
+fn is_expired_token_error(error: &Error) -> bool {
+    match error {
+        Error::TokenExpired { .. } => true,
+        Error::AuthError {
+            code: Some(code), ..
+        } => matches!(code, ErrorCode::BadJwt | ErrorCode::SessionExpired),
+        Error::AuthError {
+            status,
+            code: None,
+            message,
+            ..
+        } => {
+            matches!(*status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+                && message.to_ascii_lowercase().contains("expired")
+        }
+        _ => false,
+    }
+}
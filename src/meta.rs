@@ -0,0 +1,100 @@
+/*!
+Metadata of the responses an [`AuthClient`](crate::models::AuthClient) receives.
+
+Supabase tags every response with an `sb-request-id` header, the id Supabase support looks up
+server logs by. Register a hook with
+[`AuthClient::on_response`](crate::models::AuthClient::on_response) for a single call, or with
+[`AuthClientBuilder::on_response`](crate::builder::AuthClientBuilder::on_response) for every call,
+to receive a [`ResponseMeta`] with the request id and latency of each response, retries
+included. Failed requests carry the id too, see [`Error::request_id`](crate::error::Error::request_id).
+
+# Example
+
+```rust
+let request_ids = Arc::new(Mutex::new(Vec::new()));
+let ids = request_ids.clone();
+
+let session = auth_client
+    .on_response(move |meta| {
+        ids.lock().unwrap().extend(meta.request_id.clone());
+    })
+    .login_with_email(&demo_email, &demo_password)
+    .await?;
+
+tracing::info!(request_ids = ?request_ids.lock().unwrap(), "signed in");
+```
+*/
+
+use std::{fmt, sync::Arc, time::Duration};
+
+use reqwest::{header::HeaderMap, Method, Response, StatusCode};
+
+/// The header Supabase returns the id of a request in
+pub const SB_REQUEST_ID: &str = "sb-request-id";
+
+/// What a hook registered with
+/// [`AuthClient::on_response`](crate::models::AuthClient::on_response) is told about a response
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ResponseMeta {
+    pub method: Method,
+    /// The path of the request, without the query, e.g. `/auth/v1/token`
+    pub path: String,
+    pub status: StatusCode,
+    /// The `sb-request-id` of the response, if the server sent one
+    pub request_id: Option<String>,
+    /// How long the request took until the response headers arrived
+    pub latency: Duration,
+}
+
+impl ResponseMeta {
+    pub(crate) fn new(
+        method: Method,
+        path: String,
+        latency: Duration,
+        response: &Response,
+    ) -> Self {
+        ResponseMeta {
+            method,
+            path,
+            status: response.status(),
+            request_id: request_id(response.headers()).map(str::to_string),
+            latency,
+        }
+    }
+}
+
+pub(crate) type Hook = dyn Fn(&ResponseMeta) + Send + Sync;
+
+/// The hooks of an [`AuthClient`](crate::models::AuthClient), run in the order they were registered
+#[derive(Clone, Default)]
+pub(crate) struct ResponseHooks(Vec<Arc<Hook>>);
+
+impl ResponseHooks {
+    pub(crate) fn push(&mut self, hook: Arc<Hook>) {
+        self.0.push(hook);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn run(&self, meta: &ResponseMeta) {
+        for hook in &self.0 {
+            hook(meta);
+        }
+    }
+}
+
+impl fmt::Debug for ResponseHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ResponseHooks").field(&self.0.len()).finish()
+    }
+}
+
+/// The `sb-request-id` in `headers`, if any
+pub(crate) fn request_id(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(SB_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+}
@@ -0,0 +1,117 @@
+/*!
+Client-side metrics of requests to Supabase Auth, enabled with the `metrics` feature.
+
+Metrics are emitted with the [`metrics`](https://docs.rs/metrics) crate, so they go to whichever
+recorder the app installed, e.g. a Prometheus exporter. Without a recorder they are discarded.
+
+| Metric | Type | Labels |
+|---|---|---|
+| [`REQUESTS_TOTAL`] | counter | `method`, `endpoint`, `status` |
+| [`REQUEST_DURATION_SECONDS`] | histogram | `method`, `endpoint` |
+| [`ERRORS_TOTAL`] | counter | `status`, `code` |
+| [`REFRESH_FAILURES_TOTAL`] | counter | `reason` |
+
+`endpoint` is the path of the request with ids replaced by `{id}`, e.g.
+`/auth/v1/admin/users/{id}`. `status` is the response status, or `network` when no response was
+received. Each retry counts as a request. `code` is the [`ErrorCode`] the server rejected a
+request with, or `unknown`. `reason` is the error code of a failed session refresh, `network`, or
+`other`.
+
+# Example
+
+```rust
+PrometheusBuilder::new().install()?;
+supabase_auth::metrics::describe();
+
+// Alert on degradation, e.g.
+// rate(supabase_auth_refresh_failures_total[5m]) > 0
+// rate(supabase_auth_requests_total{status=~"5.."}[5m]) > 0
+```
+*/
+
+use std::time::Duration;
+
+use ::metrics::{counter, describe_counter, describe_histogram, histogram, Unit};
+use reqwest::{Method, Response, StatusCode};
+
+use crate::error::{Error, ErrorCode};
+
+/// Requests sent, by method, endpoint and response status
+pub const REQUESTS_TOTAL: &str = "supabase_auth_requests_total";
+
+/// How long requests took until the response headers arrived, by method and endpoint
+pub const REQUEST_DURATION_SECONDS: &str = "supabase_auth_request_duration_seconds";
+
+/// Requests the server rejected, by status and error code
+pub const ERRORS_TOTAL: &str = "supabase_auth_errors_total";
+
+/// Sessions that couldn't be refreshed, by reason
+pub const REFRESH_FAILURES_TOTAL: &str = "supabase_auth_refresh_failures_total";
+
+/// Describe the metrics to the installed recorder, call it once after installing one
+pub fn describe() {
+    describe_counter!(REQUESTS_TOTAL, "Requests sent to Supabase Auth");
+    describe_histogram!(
+        REQUEST_DURATION_SECONDS,
+        Unit::Seconds,
+        "Latency of requests to Supabase Auth"
+    );
+    describe_counter!(ERRORS_TOTAL, "Requests Supabase Auth rejected");
+    describe_counter!(
+        REFRESH_FAILURES_TOTAL,
+        "Sessions that couldn't be refreshed"
+    );
+}
+
+/// Record a request to `endpoint`, which took `duration` and ended in `result`
+pub(crate) fn record_request(
+    method: &Method,
+    endpoint: String,
+    duration: Duration,
+    result: &Result<Response, Error>,
+) {
+    let status = match result {
+        Ok(response) => response.status().as_u16().to_string(),
+        Err(_) => "network".to_string(),
+    };
+
+    counter!(
+        REQUESTS_TOTAL,
+        "method" => method.to_string(),
+        "endpoint" => endpoint.clone(),
+        "status" => status
+    )
+    .increment(1);
+    histogram!(
+        REQUEST_DURATION_SECONDS,
+        "method" => method.to_string(),
+        "endpoint" => endpoint
+    )
+    .record(duration);
+}
+
+/// Record a request the server rejected with `status` and `error`
+pub(crate) fn record_error(status: StatusCode, error: &Error) {
+    let code = match error {
+        Error::WeakPassword { .. } => ErrorCode::WeakPassword.as_str(),
+        error => error.code().map_or("unknown", ErrorCode::as_str),
+    };
+
+    counter!(
+        ERRORS_TOTAL,
+        "status" => status.as_u16().to_string(),
+        "code" => code.to_string()
+    )
+    .increment(1);
+}
+
+/// Record a session refresh which failed with `error`
+pub(crate) fn record_refresh_failure(error: &Error) {
+    let reason = match error {
+        Error::RefreshTokenAlreadyUsed => ErrorCode::RefreshTokenAlreadyUsed.as_str(),
+        Error::NetworkError(_) => "network",
+        error => error.code().map_or("other", ErrorCode::as_str),
+    };
+
+    counter!(REFRESH_FAILURES_TOTAL, "reason" => reason.to_string()).increment(1);
+}
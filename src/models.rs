@@ -1,27 +1,187 @@
 #![cfg(not(doctest))]
 
+use chrono::{DateTime, TimeDelta, Utc};
 use core::fmt;
-use reqwest::{Client, Url};
-use serde::{Deserialize, Serialize};
+use reqwest::{header::HeaderMap, Client, Url};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
-use std::{collections::HashMap, fmt::Display};
+use std::{collections::HashMap, fmt::Display, sync::Arc, time::Duration};
 use schemars::JsonSchema;
 use uuid::Uuid;
 
+use crate::{
+    circuit_breaker::Circuit, client::SettingsCache, error::Error, jwks::JwksState,
+    jwt::JwtValidationOptions, meta::ResponseHooks, rate_limit::RateLimiter, retry::RetryPolicy,
+    transport::HttpTransport,
+};
+
 /// Supabase Auth Client
+///
+/// Cloning is cheap: the connection pool, configuration, JWKS and settings caches, and rate
+/// limiter are reference counted and shared by all clones. All methods take `&self`, so there is
+/// no need to wrap the client in an `Arc` or `Mutex`; clone it into every task or request handler
+/// instead.
 #[derive(Clone)]
 pub struct AuthClient {
+    /// Builds requests
     pub(crate) client: Client,
+    /// Sends requests, `client` unless configured otherwise
+    pub(crate) transport: Arc<dyn HttpTransport>,
     /// REST endpoint for querying and managing your database
     /// Example: `https://YOUR_PROJECT_ID.supabase.co`
-    pub(crate) project_url: String,
+    pub(crate) project_url: Arc<str>,
     /// WARN: The `service role` key has the ability to bypass Row Level Security. Never share it publicly.
-    pub(crate) api_key: String,
+    pub(crate) api_key: Arc<str>,
     /// Used to decode your JWTs. You can also use this to mint your own JWTs.
-    pub(crate) jwt_secret: String,
+    pub(crate) jwt_secret: Arc<str>,
+    /// Custom headers sent with every request
+    pub(crate) headers: Arc<HeaderMap>,
+    /// How long a request may take before it is aborted
+    pub(crate) timeout: Option<Duration>,
+    /// Which failed requests are retried, none if `None`
+    pub(crate) retry_policy: Option<Arc<RetryPolicy>>,
+    /// Path of the auth API relative to `project_url`, [`AUTH_V1`] unless self-hosted
+    pub(crate) auth_path: Arc<str>,
+    /// Whether the `apikey` header is sent, which self-hosted servers may not need
+    pub(crate) send_api_key: bool,
+    /// Paces requests to stay below the server's rate limits, unlimited if `None`
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+    /// Fails requests fast while the server is down, disabled if `None`
+    pub(crate) circuit: Option<Arc<Circuit>>,
+    /// Signing keys fetched by [`JwksCache`](crate::jwks::JwksCache)
+    pub(crate) jwks: Arc<JwksState>,
+    /// Server settings cached by [`AuthClient::get_settings_cached`]
+    pub(crate) settings: Arc<SettingsCache>,
+    /// How long cached server settings are reused
+    pub(crate) settings_ttl: Duration,
+    /// How access tokens are validated by [`AuthClient::get_claims`]
+    pub(crate) jwt_validation: Arc<JwtValidationOptions>,
+    /// The role of `api_key`, see [`AuthClient::key_role`]
+    pub(crate) key_role: Option<Arc<Role>>,
+    /// Called with the metadata of every response, see [`AuthClient::on_response`]
+    pub(crate) response_hooks: ResponseHooks,
+    /// Called with a summary of every request, see
+    /// [`AuthClientBuilder::logger`](crate::builder::AuthClientBuilder::logger)
+    #[cfg(feature = "logging")]
+    pub(crate) logger: Option<Arc<crate::logging::Logger>>,
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+/// Names of the environment variables read by [`AuthClient::new_from_env_with`]
+///
+/// Defaults to `SUPABASE_URL`, `SUPABASE_API_KEY`, and `SUPABASE_JWT_SECRET`.
+///
+/// # Example
+/// ```
+/// let auth_client = AuthClient::new_from_env_with(EnvConfig {
+///     url_var: "NEXT_PUBLIC_SUPABASE_URL".to_string(),
+///     api_key_var: "SUPABASE_ANON_KEY".to_string(),
+///     jwt_secret_var: None,
+/// })?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvConfig {
+    /// The variable holding the project URL
+    pub url_var: String,
+    /// The variable holding the anon, publishable, or service role key
+    pub api_key_var: String,
+    /// The variable holding the JWT secret. When `None`, the client is created without one.
+    pub jwt_secret_var: Option<String>,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            url_var: "SUPABASE_URL".to_string(),
+            api_key_var: "SUPABASE_API_KEY".to_string(),
+            jwt_secret_var: Some("SUPABASE_JWT_SECRET".to_string()),
+        }
+    }
+}
+
+macro_rules! tokens {
+    ($($(#[$doc:meta])* $name:ident;)*) => {
+        $(
+            $(#[$doc])*
+            ///
+            /// The token is redacted in `Debug` output and has no `Display` implementation, use
+            /// [`as_str`](Self::as_str) to read it.
+            #[derive(Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+            #[serde(transparent)]
+            pub struct $name(String);
+
+            impl $name {
+                pub fn new(token: impl Into<String>) -> Self {
+                    $name(token.into())
+                }
+
+                pub fn as_str(&self) -> &str {
+                    &self.0
+                }
+
+                pub fn into_string(self) -> String {
+                    self.0
+                }
+
+                pub fn is_empty(&self) -> bool {
+                    self.0.is_empty()
+                }
+            }
+
+            impl fmt::Debug for $name {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.debug_tuple(stringify!($name)).field(&REDACTED).finish()
+                }
+            }
+
+            impl From<String> for $name {
+                fn from(token: String) -> Self {
+                    $name(token)
+                }
+            }
+
+            impl From<&str> for $name {
+                fn from(token: &str) -> Self {
+                    $name(token.to_string())
+                }
+            }
+
+            impl From<&$name> for $name {
+                fn from(token: &$name) -> Self {
+                    token.clone()
+                }
+            }
+
+            impl AsRef<str> for $name {
+                fn as_ref(&self) -> &str {
+                    &self.0
+                }
+            }
+
+            impl PartialEq<str> for $name {
+                fn eq(&self, other: &str) -> bool {
+                    self.0 == other
+                }
+            }
+
+            impl PartialEq<&str> for $name {
+                fn eq(&self, other: &&str) -> bool {
+                    self.0 == *other
+                }
+            }
+        )*
+    };
+}
+
+tokens! {
+    /// A short lived JWT authenticating requests on behalf of a user, see
+    /// [`Session::access_token`]
+    AccessToken;
+    /// A single use token which is exchanged for a new session, see [`Session::refresh_token`]
+    RefreshToken;
+}
+
+#[derive(Default, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(from = "SessionPayload")]
 pub struct Session {
     /// The oauth provider token. If present, this can be used to make external API requests to the oauth provider used.
     pub provider_token: Option<String>,
@@ -30,15 +190,197 @@ pub struct Session {
     /// Not all oauth providers return a provider refresh token. If the provider_refresh_token is missing, please refer to the oauth provider's documentation for information on how to obtain the provider refresh token.
     pub provider_refresh_token: Option<String>,
     /// The access token jwt. It is recommended to set the JWT_EXPIRY to a shorter expiry value.
-    pub access_token: String,
+    pub access_token: AccessToken,
     pub token_type: String,
     /// The number of seconds until the token expires (since it was issued). Returned when a login is confirmed.
     pub expires_in: i64,
     /// A timestamp of when the token will expire. Returned when a login is confirmed.
-    pub expires_at: u64,
+    ///
+    /// Serialized as seconds since the Unix epoch. When the server omits it, it is computed
+    /// from `expires_in` at the time the session is parsed.
+    #[serde(with = "chrono::serde::ts_seconds")]
+    #[schemars(with = "i64")]
+    pub expires_at: DateTime<Utc>,
     /// A one-time used refresh token that never expires.
-    pub refresh_token: String,
+    pub refresh_token: RefreshToken,
+    pub user: User,
+    /// Why the password the user signed in with is considered weak. Only returned by password
+    /// sign-ins, when the project enforces password strength.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weak_password: Option<WeakPassword>,
+    /// Fields this version of the library doesn't know about, e.g. ones added by newer GoTrue
+    /// versions. They are kept, and serialized again.
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("provider_token", &redact(&self.provider_token))
+            .field("provider_refresh_token", &redact(&self.provider_refresh_token))
+            .field("access_token", &self.access_token)
+            .field("token_type", &self.token_type)
+            .field("expires_in", &self.expires_in)
+            .field("expires_at", &self.expires_at)
+            .field("refresh_token", &self.refresh_token)
+            .field("user", &self.user)
+            .field("weak_password", &self.weak_password)
+            .field("extra", &self.extra)
+            .finish()
+    }
+}
+
+impl Session {
+    /// Whether the access token has expired
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+
+    /// Whether the access token expires within `duration` from now. Also true if it already expired.
+    ///
+    /// # Example
+    /// ```
+    /// if session.expires_within(Duration::from_secs(60)) {
+    ///     session = auth_client.refresh_session(&session.refresh_token).await.unwrap();
+    /// }
+    /// ```
+    pub fn expires_within(&self, duration: std::time::Duration) -> bool {
+        self.time_until_expiry() <= duration
+    }
+
+    /// Time left until the access token expires, zero if it already expired
+    pub fn time_until_expiry(&self) -> std::time::Duration {
+        (self.expires_at - Utc::now()).to_std().unwrap_or_default()
+    }
+
+    /// Serialize the session in the format supabase-js keeps in localStorage and cookies
+    ///
+    /// # Example
+    /// ```
+    /// let json = session.to_supabase_js_json().unwrap();
+    ///
+    /// // Hand `json` to the frontend, which can store it under `sb-<project-ref>-auth-token`
+    /// ```
+    pub fn to_supabase_js_json(&self) -> Result<String, Error> {
+        let session = SupabaseJsSession {
+            access_token: self.access_token.as_str(),
+            token_type: &self.token_type,
+            expires_in: self.expires_in,
+            expires_at: self.expires_at.timestamp(),
+            refresh_token: self.refresh_token.as_str(),
+            user: &self.user,
+            provider_token: self.provider_token.as_deref(),
+            provider_refresh_token: self.provider_refresh_token.as_deref(),
+        };
+
+        Ok(serde_json::to_string(&session)?)
+    }
+
+    /// Parse a session stored by supabase-js
+    ///
+    /// Accepts the current format as well as the `{ "currentSession": ..., "expiresAt": ... }`
+    /// wrapper written by supabase-js v1, and camelCase keys.
+    pub fn from_supabase_js_json(json: &str) -> Result<Session, Error> {
+        match serde_json::from_str(json)? {
+            SupabaseJsStorage::Legacy {
+                mut current_session,
+                expires_at,
+            } => {
+                if let Some(expires_at) = expires_at.and_then(|t| DateTime::from_timestamp(t, 0)) {
+                    current_session.expires_at = expires_at;
+                }
+
+                Ok(current_session)
+            }
+            SupabaseJsStorage::Current(session) => Ok(session),
+        }
+    }
+}
+
+/// Session as serialized by supabase-js
+#[derive(Serialize)]
+struct SupabaseJsSession<'a> {
+    access_token: &'a str,
+    token_type: &'a str,
+    expires_in: i64,
+    expires_at: i64,
+    refresh_token: &'a str,
+    user: &'a User,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provider_token: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provider_refresh_token: Option<&'a str>,
+}
+
+/// The formats supabase-js has used to store a session
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SupabaseJsStorage {
+    Legacy {
+        #[serde(rename = "currentSession")]
+        current_session: Session,
+        #[serde(rename = "expiresAt")]
+        expires_at: Option<i64>,
+    },
+    Current(Session),
+}
+
+/// The user a request was made by, see [`AuthClient::get_user_from_headers`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestUser {
     pub user: User,
+    /// The new session, if the session in the auth cookie had expired and was refreshed.
+    ///
+    /// The old refresh token can't be used again, so send this back to the browser, e.g. with
+    /// [`build_session_cookies`](crate::cookies::build_session_cookies).
+    pub refreshed_session: Option<Session>,
+}
+
+/// Wire format of [`Session`], where `expires_at` may be missing
+#[derive(Deserialize, JsonSchema)]
+struct SessionPayload {
+    #[serde(alias = "providerToken")]
+    provider_token: Option<String>,
+    #[serde(alias = "providerRefreshToken")]
+    provider_refresh_token: Option<String>,
+    #[serde(alias = "accessToken")]
+    access_token: AccessToken,
+    #[serde(alias = "tokenType")]
+    token_type: String,
+    #[serde(alias = "expiresIn")]
+    expires_in: i64,
+    #[serde(default, alias = "expiresAt", with = "chrono::serde::ts_seconds_option")]
+    #[schemars(with = "Option<i64>")]
+    expires_at: Option<DateTime<Utc>>,
+    #[serde(alias = "refreshToken")]
+    refresh_token: RefreshToken,
+    user: User,
+    #[serde(default, alias = "weakPassword")]
+    weak_password: Option<WeakPassword>,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+impl From<SessionPayload> for Session {
+    fn from(payload: SessionPayload) -> Self {
+        let expires_at = payload
+            .expires_at
+            .unwrap_or_else(|| Utc::now() + TimeDelta::seconds(payload.expires_in));
+
+        Session {
+            provider_token: payload.provider_token,
+            provider_refresh_token: payload.provider_refresh_token,
+            access_token: payload.access_token,
+            token_type: payload.token_type,
+            expires_in: payload.expires_in,
+            expires_at,
+            refresh_token: payload.refresh_token,
+            user: payload.user,
+            weak_password: payload.weak_password,
+            extra: payload.extra,
+        }
+    }
 }
 
 /// User respresents a registered user
@@ -49,34 +391,147 @@ pub struct User {
     pub role: String,
     pub email: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub invited_at: Option<String>,
+    pub invited_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub confirmation_sent_at: Option<String>,
+    pub confirmation_sent_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub email_confirmed_at: Option<String>,
+    pub email_confirmed_at: Option<DateTime<Utc>>,
     pub phone: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub phone_confirmed_at: Option<String>,
+    pub phone_confirmed_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub confirmed_at: Option<String>,
+    pub confirmed_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub recovery_sent_at: Option<String>,
+    pub recovery_sent_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_sign_in_at: Option<String>,
+    pub last_sign_in_at: Option<DateTime<Utc>>,
+    /// Until when the user is banned from signing in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub banned_until: Option<DateTime<Utc>>,
+    /// When the user was soft deleted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
     pub app_metadata: AppMetadata,
     pub user_metadata: UserMetadata,
+    /// The provider accounts linked to the user. Empty if the server omitted them.
+    #[serde(default, deserialize_with = "null_as_default")]
     pub identities: Vec<Identity>,
-    pub created_at: String,
-    pub updated_at: String,
+    /// The MFA factors the user enrolled, including unverified ones
+    #[serde(
+        default,
+        deserialize_with = "null_as_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub factors: Vec<Factor>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Whether the user signed in anonymously and hasn't been converted to a permanent user yet.
+    /// Servers predating anonymous sign-ins omit the field, so it defaults to `false`.
+    #[serde(default)]
     pub is_anonymous: bool,
+    /// Fields this version of the library doesn't know about, e.g. ones added by newer GoTrue
+    /// versions. They are kept, and serialized again.
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl User {
+    /// Whether the user is currently banned from signing in
+    pub fn is_banned(&self) -> bool {
+        self.banned_until.is_some_and(|banned_until| banned_until > Utc::now())
+    }
+
+    /// The names of the providers linked to the user, e.g. `["email", "github"]`
+    pub fn providers(&self) -> Vec<&str> {
+        self.identities
+            .iter()
+            .map(|identity| identity.provider.as_str())
+            .collect()
+    }
+
+    /// The user's identity at `provider`, if one is linked
+    pub fn identity(&self, provider: &str) -> Option<&Identity> {
+        self.identities
+            .iter()
+            .find(|identity| identity.provider == provider)
+    }
+
+    /// The user's verified MFA factors. If there are any, signing in requires a second factor to
+    /// reach [`Aal::Aal2`].
+    pub fn verified_factors(&self) -> impl Iterator<Item = &Factor> {
+        self.factors.iter().filter(|factor| factor.is_verified())
+    }
+
+    /// Whether the user has a verified MFA factor
+    pub fn has_verified_factor(&self) -> bool {
+        self.verified_factors().next().is_some()
+    }
+
+    /// Whether the user was soft deleted
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// Whether the user confirmed their email address or phone number
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed_at.is_some()
+            || self.email_confirmed_at.is_some()
+            || self.phone_confirmed_at.is_some()
+    }
+
+    /// Deserialize the user's metadata into an application defined type
+    ///
+    /// # Example
+    /// ```
+    /// #[derive(Deserialize)]
+    /// struct Profile {
+    ///     full_name: String,
+    ///     plan: Plan,
+    /// }
+    ///
+    /// let profile: Profile = user.metadata_as()?;
+    /// ```
+    pub fn metadata_as<M: DeserializeOwned>(&self) -> Result<M, Error> {
+        self.user_metadata.deserialize_into()
+    }
+
+    /// Deserialize the user's app metadata into an application defined type
+    ///
+    /// # Example
+    /// ```
+    /// #[derive(Deserialize)]
+    /// struct Tenancy {
+    ///     tenant_id: Uuid,
+    ///     roles: Vec<String>,
+    /// }
+    ///
+    /// let tenancy: Tenancy = user.app_metadata_as()?;
+    /// ```
+    pub fn app_metadata_as<M: DeserializeOwned>(&self) -> Result<M, Error> {
+        self.app_metadata.deserialize_into()
+    }
 }
 
+/// Metadata only the server and the service role can change, so it's where roles and tenancy
+/// usually live
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Default)]
 pub struct AppMetadata {
+    /// The provider the user first signed up with, e.g. `email` or `github`
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider: Option<String>,
+    /// All providers linked to the user
     #[serde(skip_serializing_if = "Option::is_none")]
     pub providers: Option<Vec<String>>,
+    /// Any other fields, e.g. ones set with the admin API
+    #[serde(flatten)]
+    pub custom: HashMap<String, Value>,
+}
+
+impl AppMetadata {
+    /// Deserialize the metadata, including `provider` and `providers`, into `M`
+    pub fn deserialize_into<M: DeserializeOwned>(&self) -> Result<M, Error> {
+        Ok(serde_json::from_value(serde_json::to_value(self)?)?)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Default)]
@@ -99,12 +554,77 @@ pub struct UserMetadata {
     pub custom: HashMap<String, Value>,
 }
 
-#[derive(Debug)]
+impl UserMetadata {
+    /// Deserialize the metadata, including the standard fields, into `M`
+    pub fn deserialize_into<M: DeserializeOwned>(&self) -> Result<M, Error> {
+        Ok(serde_json::from_value(serde_json::to_value(self)?)?)
+    }
+}
+
+/// The result of signing up with an email and password
+///
+/// The user is signed in right away, unless the project requires new users to confirm their
+/// email, in which case a confirmation email was sent instead.
+/// # Example
+/// ```
+/// let result = auth_client
+///     .sign_up_with_email_and_password(&email, &password, None)
+///     .await?;
+///
+/// match result.into_session() {
+///     Some(session) => store.save(&session)?,
+///     None => println!("Check your inbox to confirm {email}"),
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum EmailSignUpResult {
     SessionResult(Session),
     ConfirmationResult(EmailSignUpConfirmation),
 }
 
+impl EmailSignUpResult {
+    /// The session of the new user, `None` if they need to confirm their email first
+    pub fn session(&self) -> Option<&Session> {
+        match self {
+            EmailSignUpResult::SessionResult(session) => Some(session),
+            EmailSignUpResult::ConfirmationResult(_) => None,
+        }
+    }
+
+    /// The session of the new user, `None` if they need to confirm their email first
+    pub fn into_session(self) -> Option<Session> {
+        match self {
+            EmailSignUpResult::SessionResult(session) => Some(session),
+            EmailSignUpResult::ConfirmationResult(_) => None,
+        }
+    }
+
+    /// Whether the user needs to confirm their email before they can sign in
+    pub fn needs_confirmation(&self) -> bool {
+        matches!(self, EmailSignUpResult::ConfirmationResult(_))
+    }
+
+    /// The pending confirmation, `None` if the user was signed in right away
+    pub fn confirmation(&self) -> Option<&EmailSignUpConfirmation> {
+        match self {
+            EmailSignUpResult::SessionResult(_) => None,
+            EmailSignUpResult::ConfirmationResult(confirmation) => Some(confirmation),
+        }
+    }
+
+    /// The id of the new user
+    pub fn user_id(&self) -> Uuid {
+        match self {
+            EmailSignUpResult::SessionResult(session) => session.user.id,
+            EmailSignUpResult::ConfirmationResult(confirmation) => confirmation.id,
+        }
+    }
+}
+
+/// A user who signed up and was sent an email to confirm their address, see
+/// [`EmailSignUpResult::confirmation`]
 #[derive(Clone, Debug, Deserialize, PartialEq, Default)]
 pub struct EmailSignUpConfirmation {
     pub id: Uuid,
@@ -112,13 +632,13 @@ pub struct EmailSignUpConfirmation {
     pub role: String,
     pub email: Option<String>,
     pub phone: Option<String>,
-    pub confirmation_sent_at: String,
-    pub created_at: String,
-    pub updated_at: String,
+    pub confirmation_sent_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
     pub is_anonymous: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct IdTokenCredentials {
     /// Provider name or OIDC `iss` value identifying which provider should be used to verify the provided token.
     pub provider: Provider,
@@ -133,7 +653,20 @@ pub struct IdTokenCredentials {
     pub gotrue_meta_security: Option<GotrueMetaSecurity>,
 }
 
+impl fmt::Debug for IdTokenCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdTokenCredentials")
+            .field("provider", &self.provider)
+            .field("token", &REDACTED)
+            .field("access_token", &redact(&self.access_token))
+            .field("nonce", &self.nonce)
+            .field("gotrue_meta_security", &self.gotrue_meta_security)
+            .finish()
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, PartialEq, Default)]
+#[non_exhaustive]
 pub struct LoginWithOAuthOptions {
     pub query_params: Option<HashMap<String, String>>,
     pub redirect_to: Option<String>,
@@ -141,6 +674,34 @@ pub struct LoginWithOAuthOptions {
     pub skip_browser_redirect: Option<bool>,
 }
 
+impl LoginWithOAuthOptions {
+    /// Add a query parameter to the provider's authorization URL
+    pub fn query_param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query_params
+            .get_or_insert_with(HashMap::new)
+            .insert(name.into(), value.into());
+        self
+    }
+
+    /// Where to send the user after they signed in with the provider
+    pub fn redirect_to(mut self, redirect_to: impl Into<String>) -> Self {
+        self.redirect_to = Some(redirect_to.into());
+        self
+    }
+
+    /// Space separated scopes to request from the provider
+    pub fn scopes(mut self, scopes: impl Into<String>) -> Self {
+        self.scopes = Some(scopes.into());
+        self
+    }
+
+    /// Whether to skip redirecting the browser to the provider
+    pub fn skip_browser_redirect(mut self, skip_browser_redirect: bool) -> Self {
+        self.skip_browser_redirect = Some(skip_browser_redirect);
+        self
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct OAuthResponse {
     pub url: Url,
@@ -155,14 +716,17 @@ pub struct GotrueMetaSecurity {
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Identity {
-    pub identity_id: String,
+    pub identity_id: Uuid,
+    /// The user's id at the provider, e.g. their GitHub user id
     pub id: String,
-    pub user_id: String,
+    pub user_id: Uuid,
+    #[serde(default)]
     pub identity_data: IdentityData,
     pub provider: String,
-    pub last_sign_in_at: String,
-    pub created_at: String,
-    pub updated_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_sign_in_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
 }
@@ -171,9 +735,62 @@ pub struct Identity {
 pub struct IdentityData {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
+    #[serde(default)]
     pub email_verified: bool,
+    #[serde(default)]
     pub phone_verified: bool,
+    #[serde(default)]
     pub sub: String,
+    /// Provider specific claims, e.g. `name` or `avatar_url`
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// An MFA factor enrolled by a user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Factor {
+    pub id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub friendly_name: Option<String>,
+    pub factor_type: FactorType,
+    pub status: FactorStatus,
+    /// The phone number codes are sent to, for [`FactorType::Phone`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_challenged_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Factor {
+    /// Whether the factor finished enrollment and can be used to sign in
+    pub fn is_verified(&self) -> bool {
+        self.status == FactorStatus::Verified
+    }
+}
+
+/// The kind of an MFA [`Factor`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FactorType {
+    Totp,
+    Phone,
+    Webauthn,
+    /// A factor type this version of the crate doesn't know about
+    #[serde(other)]
+    Unknown,
+}
+
+/// Whether an MFA [`Factor`] finished enrollment
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FactorStatus {
+    Verified,
+    Unverified,
+    /// A status this version of the crate doesn't know about
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -186,30 +803,34 @@ pub enum LoginOptions {
 pub(crate) struct LoginWithEmailAndPasswordPayload<'a> {
     pub(crate) email: &'a str,
     pub(crate) password: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) gotrue_meta_security: Option<GotrueMetaSecurity>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub(crate) struct LoginWithPhoneAndPasswordPayload<'a> {
     pub(crate) phone: &'a str,
     pub(crate) password: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) gotrue_meta_security: Option<GotrueMetaSecurity>,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, JsonSchema)]
 pub(crate) struct SignUpWithEmailAndPasswordPayload<'a> {
     pub(crate) email: &'a str,
     pub(crate) password: &'a str,
     #[serde(flatten)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(crate) options: Option<SignUpWithPasswordOptions>,
+    pub(crate) options: Option<&'a SignUpWithPasswordOptions>,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, JsonSchema)]
 pub(crate) struct SignUpWithPhoneAndPasswordPayload<'a> {
     pub(crate) phone: &'a str,
     pub(crate) password: &'a str,
     #[serde(flatten)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(crate) options: Option<SignUpWithPasswordOptions>,
+    pub(crate) options: Option<&'a SignUpWithPasswordOptions>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -220,6 +841,7 @@ pub(crate) struct LoginAnonymouslyPayload {
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
 pub struct SignUpWithPasswordOptions {
     /// The redirect url embedded in the email link
     #[serde(skip)]
@@ -232,7 +854,28 @@ pub struct SignUpWithPasswordOptions {
     pub captcha_token: Option<String>,
 }
 
+impl SignUpWithPasswordOptions {
+    /// The redirect url embedded in the email link
+    pub fn email_redirect_to(mut self, email_redirect_to: impl Into<String>) -> Self {
+        self.email_redirect_to = Some(email_redirect_to.into());
+        self
+    }
+
+    /// Metadata to store in `auth.users.raw_user_meta_data`
+    pub fn data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Verification token received when the user completes the captcha on the site
+    pub fn captcha_token(mut self, captcha_token: impl Into<String>) -> Self {
+        self.captcha_token = Some(captcha_token.into());
+        self
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
 pub struct ResetPasswordOptions {
     /// The redirect url embedded in the email link
     #[serde(skip)]
@@ -242,7 +885,48 @@ pub struct ResetPasswordOptions {
     pub captcha_token: Option<String>,
 }
 
+impl ResetPasswordOptions {
+    /// The redirect url embedded in the email link
+    pub fn email_redirect_to(mut self, email_redirect_to: impl Into<String>) -> Self {
+        self.email_redirect_to = Some(email_redirect_to.into());
+        self
+    }
+
+    /// Verification token received when the user completes the captcha on the site
+    pub fn captcha_token(mut self, captcha_token: impl Into<String>) -> Self {
+        self.captcha_token = Some(captcha_token.into());
+        self
+    }
+}
+
+/// Options of [`AuthClient::login_with_email_with_options`] and
+/// [`AuthClient::login_with_phone_with_options`]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
+pub struct LoginWithPasswordOptions {
+    /// Verification token received when the user completes the captcha on the site.
+    pub captcha_token: Option<String>,
+}
+
+impl LoginWithPasswordOptions {
+    /// Verification token received when the user completes the captcha on the site
+    pub fn captcha_token(mut self, captcha_token: impl Into<String>) -> Self {
+        self.captcha_token = Some(captcha_token.into());
+        self
+    }
+
+    /// The `gotrue_meta_security` object the captcha token is sent in
+    pub(crate) fn gotrue_meta_security(&self) -> Option<GotrueMetaSecurity> {
+        self.captcha_token
+            .clone()
+            .map(|captcha_token| GotrueMetaSecurity {
+                captcha_token: Some(captcha_token),
+            })
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
 pub struct LoginAnonymouslyOptions {
     /// The `data` should be a JSON object that includes user-specific info, such as their first and last name.
     pub data: Option<Value>,
@@ -250,16 +934,159 @@ pub struct LoginAnonymouslyOptions {
     pub captcha_token: Option<String>,
 }
 
+impl LoginAnonymouslyOptions {
+    /// Metadata to store in `auth.users.raw_user_meta_data`
+    pub fn data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Verification token received when the user completes the captcha on the site
+    pub fn captcha_token(mut self, captcha_token: impl Into<String>) -> Self {
+        self.captcha_token = Some(captcha_token.into());
+        self
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub(crate) struct RequestMagicLinkPayload<'a> {
     pub(crate) email: &'a str,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+/// An update to a field: leave it [`Unchanged`](Patch::Unchanged), [`Set`](Patch::Set) it, or
+/// [`Clear`](Patch::Clear) it
+///
+/// Unchanged fields are left out of the request, cleared ones are sent as `null`.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub enum Patch<T> {
+    #[default]
+    Unchanged,
+    Set(T),
+    Clear,
+}
+
+impl<T> Patch<T> {
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self, Patch::Unchanged)
+    }
+
+    /// The new value, if the field is set
+    pub fn as_set(&self) -> Option<&T> {
+        match self {
+            Patch::Set(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Patch<U> {
+        match self {
+            Patch::Unchanged => Patch::Unchanged,
+            Patch::Set(value) => Patch::Set(f(value)),
+            Patch::Clear => Patch::Clear,
+        }
+    }
+
+    pub fn as_ref(&self) -> Patch<&T> {
+        match self {
+            Patch::Unchanged => Patch::Unchanged,
+            Patch::Set(value) => Patch::Set(value),
+            Patch::Clear => Patch::Clear,
+        }
+    }
+}
+
+impl<T> From<T> for Patch<T> {
+    fn from(value: T) -> Self {
+        Patch::Set(value)
+    }
+}
+
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_set().serialize(serializer)
+    }
+}
+
+/// `null` is [`Patch::Clear`], missing fields need `#[serde(default)]` to be [`Patch::Unchanged`]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Option::deserialize(deserializer)? {
+            Some(value) => Patch::Set(value),
+            None => Patch::Clear,
+        })
+    }
+}
+
+/// The changes [`AuthClient::update_user`] makes to a user
+///
+/// Fields are [`Patch::Unchanged`] by default. Supabase merges `data` into the user's metadata,
+/// keys set to `null` are removed and others are kept, see [`UpdatedUser::remove_data`].
+///
+/// # Example
+/// ```
+/// let updated_user = UpdatedUser::default()
+///     .email("new@example.com")
+///     .data(json!({ "theme": "dark" }))
+///     .remove_data("nickname");
+/// ```
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct UpdatedUser {
-    pub email: Option<String>,
-    pub password: Option<String>,
-    pub data: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Patch::is_unchanged")]
+    #[schemars(with = "Option<String>")]
+    pub email: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_unchanged")]
+    #[schemars(with = "Option<String>")]
+    pub phone: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_unchanged")]
+    #[schemars(with = "Option<String>")]
+    pub password: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_unchanged")]
+    #[schemars(with = "Option<Value>")]
+    pub data: Patch<Value>,
+}
+
+impl UpdatedUser {
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.email = Patch::Set(email.into());
+        self
+    }
+
+    pub fn phone(mut self, phone: impl Into<String>) -> Self {
+        self.phone = Patch::Set(phone.into());
+        self
+    }
+
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = Patch::Set(password.into());
+        self
+    }
+
+    /// Set the keys of the JSON object `data` in the user's metadata, merged with keys set before
+    pub fn data(mut self, data: Value) -> Self {
+        match (&mut self.data, data) {
+            (Patch::Set(Value::Object(current)), Value::Object(data)) => current.extend(data),
+            (_, data) => self.data = Patch::Set(data),
+        }
+        self
+    }
+
+    /// Remove `key` from the user's metadata
+    pub fn remove_data(self, key: impl Into<String>) -> Self {
+        let mut data = serde_json::Map::new();
+        data.insert(key.into(), Value::Null);
+        self.data(Value::Object(data))
+    }
+}
+
+impl fmt::Debug for UpdatedUser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UpdatedUser")
+            .field("email", &self.email)
+            .field("phone", &self.phone)
+            .field("password", &self.password.as_ref().map(|_| REDACTED))
+            .field("data", &self.data)
+            .finish()
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -281,6 +1108,36 @@ pub enum VerifyOtpParams {
     TokenHash(VerifyTokenHashParams),
 }
 
+impl VerifyOtpParams {
+    /// Verify the one time password sent to `email`
+    pub fn email(email: impl Into<String>, token: impl Into<String>, otp_type: OtpType) -> Self {
+        VerifyOtpParams::Email(VerifyEmailOtpParams {
+            email: email.into(),
+            token: token.into(),
+            otp_type,
+            options: None,
+        })
+    }
+
+    /// Verify the one time password sent to `phone`
+    pub fn mobile(phone: impl Into<String>, token: impl Into<String>, otp_type: OtpType) -> Self {
+        VerifyOtpParams::Mobile(VerifyMobileOtpParams {
+            phone: phone.into(),
+            token: token.into(),
+            otp_type,
+            options: None,
+        })
+    }
+
+    /// Verify the `token_hash` of an email link
+    pub fn token_hash(token_hash: impl Into<String>, otp_type: OtpType) -> Self {
+        VerifyOtpParams::TokenHash(VerifyTokenHashParams {
+            token_hash: token_hash.into(),
+            otp_type,
+        })
+    }
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct VerifyMobileOtpParams {
     /// The user's phone number.
@@ -333,12 +1190,21 @@ pub enum OtpType {
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[non_exhaustive]
 pub struct VerifyOtpOptions {
     /// A URL to send the user to after they are confirmed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub redirect_to: Option<String>,
 }
 
+impl VerifyOtpOptions {
+    /// A URL to send the user to after they are confirmed
+    pub fn redirect_to(mut self, redirect_to: impl Into<String>) -> Self {
+        self.redirect_to = Some(redirect_to.into());
+        self
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Default)]
 pub(crate) struct LoginWithEmailOtpPayload<'a> {
     pub email: &'a str,
@@ -349,6 +1215,7 @@ pub(crate) struct LoginWithEmailOtpPayload<'a> {
 
 // align json field's name with https://github.com/supabase/auth/blob/1f7de6c65f31ef0bbb80899369989b13ab5a517f/openapi.yaml#L559
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[non_exhaustive]
 pub struct LoginEmailOtpParams {
     /// Verification token received when the user completes the captcha on the site.
     pub captcha_token: Option<String>,
@@ -361,12 +1228,39 @@ pub struct LoginEmailOtpParams {
     pub should_create_user: Option<bool>,
 }
 
-// align json field's name with https://github.com/supabase/auth/blob/1f7de6c65f31ef0bbb80899369989b13ab5a517f/openapi.yaml#L559
-#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
-pub struct LoginMobileOtpParams {
-    /// Verification token received when the user completes the captcha on the site.
-    pub captcha_token: Option<String>,
-    /// A custom data object to store the user's metadata. This maps to the `auth.users.raw_user_meta_data` column.
+impl LoginEmailOtpParams {
+    /// Verification token received when the user completes the captcha on the site
+    pub fn captcha_token(mut self, captcha_token: impl Into<String>) -> Self {
+        self.captcha_token = Some(captcha_token.into());
+        self
+    }
+
+    /// Metadata to store in `auth.users.raw_user_meta_data`
+    pub fn data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// The redirect url embedded in the email link
+    pub fn email_redirect_to(mut self, email_redirect_to: impl Into<String>) -> Self {
+        self.email_redirect_to = Some(email_redirect_to.into());
+        self
+    }
+
+    /// Whether to create a new user if none exists. Defaults to true.
+    pub fn should_create_user(mut self, should_create_user: bool) -> Self {
+        self.should_create_user = Some(should_create_user);
+        self
+    }
+}
+
+// align json field's name with https://github.com/supabase/auth/blob/1f7de6c65f31ef0bbb80899369989b13ab5a517f/openapi.yaml#L559
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[non_exhaustive]
+pub struct LoginMobileOtpParams {
+    /// Verification token received when the user completes the captcha on the site.
+    pub captcha_token: Option<String>,
+    /// A custom data object to store the user's metadata. This maps to the `auth.users.raw_user_meta_data` column.
     pub data: Option<serde_json::Value>,
     /// The redirect url embedded in the email link
     pub channel: Option<Channel>,
@@ -375,6 +1269,335 @@ pub struct LoginMobileOtpParams {
     pub should_create_user: Option<bool>,
 }
 
+impl LoginMobileOtpParams {
+    /// Verification token received when the user completes the captcha on the site
+    pub fn captcha_token(mut self, captcha_token: impl Into<String>) -> Self {
+        self.captcha_token = Some(captcha_token.into());
+        self
+    }
+
+    /// Metadata to store in `auth.users.raw_user_meta_data`
+    pub fn data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// The channel to send the one time password with
+    pub fn channel(mut self, channel: Channel) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// Whether to create a new user if none exists. Defaults to true.
+    pub fn should_create_user(mut self, should_create_user: bool) -> Self {
+        self.should_create_user = Some(should_create_user);
+        self
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ExpiryClaims {
+    pub(crate) exp: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ApiKeyClaims {
+    pub(crate) role: Role,
+}
+
+/// The claims of an access token issued by Supabase Auth
+///
+/// Returned by [`verify_access_token`](crate::jwt::verify_access_token) once the token's
+/// signature, expiry, and audience have been checked.
+///
+/// Claims added by a custom access token hook are collected in `custom`. Use
+/// [`Claims::into_custom`] to read them into your own type.
+///
+/// # Example
+/// ```
+/// #[derive(Deserialize)]
+/// struct TenantClaims {
+///     tenant_id: Uuid,
+/// }
+///
+/// let claims = auth_client
+///     .get_claims(&access_token)
+///     .await?
+///     .into_custom::<TenantClaims>()?;
+///
+/// println!("{} belongs to {}", claims.sub, claims.custom.tenant_id);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Claims<T = Value> {
+    /// The id of the user the token was issued to
+    pub sub: Uuid,
+    /// `authenticated` for signed in users
+    pub aud: String,
+    /// When the token expires, as a Unix timestamp in seconds
+    pub exp: i64,
+    /// When the token was issued, as a Unix timestamp in seconds
+    #[serde(default)]
+    pub iat: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// The Postgres role used for Row Level Security, usually `authenticated`
+    pub role: Role,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub phone: String,
+    /// Authenticator assurance level, `aal2` when MFA was used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aal: Option<Aal>,
+    /// The methods the user authenticated with during this session
+    #[serde(default)]
+    pub amr: Vec<AmrEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<Uuid>,
+    #[serde(default)]
+    pub is_anonymous: bool,
+    #[serde(default)]
+    pub app_metadata: AppMetadata,
+    #[serde(default)]
+    pub user_metadata: UserMetadata,
+    /// Any other claims, e.g. ones added by a custom access token hook
+    #[serde(flatten)]
+    pub custom: T,
+}
+
+impl<T> Claims<T> {
+    /// The highest assurance level the user reached in this session
+    ///
+    /// Takes the larger of the `aal` claim and the level implied by the `amr` methods.
+    ///
+    /// # Example
+    /// ```
+    /// if claims.highest_aal() < Aal::Aal2 {
+    ///     return Err(StatusCode::FORBIDDEN);
+    /// }
+    /// ```
+    pub fn highest_aal(&self) -> Aal {
+        let from_amr = if self.amr.iter().any(|entry| entry.method.is_mfa()) {
+            Aal::Aal2
+        } else {
+            Aal::Aal1
+        };
+
+        self.aal.unwrap_or(Aal::Aal1).max(from_amr)
+    }
+
+    /// Whether the user authenticated with `method` during this session
+    pub fn authenticated_with(&self, method: AmrMethod) -> bool {
+        self.amr.iter().any(|entry| entry.method == method)
+    }
+
+    /// Fail with [`Error::RoleMismatch`] unless the token was issued for `role`
+    ///
+    /// # Example
+    /// ```
+    /// let claims = auth_client.get_claims(&access_token).await?;
+    ///
+    /// claims.require_role(Role::SERVICE_ROLE)?;
+    /// ```
+    pub fn require_role(&self, role: impl AsRef<str>) -> Result<(), Error> {
+        let role = role.as_ref();
+
+        if self.role != role {
+            return Err(Error::RoleMismatch {
+                expected: role.to_string(),
+                actual: self.role.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Claims {
+    /// Deserialize the custom claims into `T`
+    pub fn into_custom<T: DeserializeOwned>(self) -> Result<Claims<T>, Error> {
+        Ok(Claims {
+            custom: serde_json::from_value(self.custom)?,
+            sub: self.sub,
+            aud: self.aud,
+            exp: self.exp,
+            iat: self.iat,
+            iss: self.iss,
+            role: self.role,
+            email: self.email,
+            phone: self.phone,
+            aal: self.aal,
+            amr: self.amr,
+            session_id: self.session_id,
+            is_anonymous: self.is_anonymous,
+            app_metadata: self.app_metadata,
+            user_metadata: self.user_metadata,
+        })
+    }
+}
+
+/// The Postgres role an access token grants, see [`Claims::require_role`]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct Role(String);
+
+impl Role {
+    /// Signed in users
+    pub const AUTHENTICATED: &'static str = "authenticated";
+    /// Requests made with the anon key, without a signed in user
+    pub const ANON: &'static str = "anon";
+    /// Requests made with the service role key, which bypasses Row Level Security
+    pub const SERVICE_ROLE: &'static str = "service_role";
+
+    pub fn new(role: impl Into<String>) -> Self {
+        Role(role.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self == Role::AUTHENTICATED
+    }
+
+    pub fn is_anon(&self) -> bool {
+        self == Role::ANON
+    }
+
+    pub fn is_service_role(&self) -> bool {
+        self == Role::SERVICE_ROLE
+    }
+}
+
+impl AsRef<str> for Role {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Role {
+    fn from(role: &str) -> Self {
+        Role(role.to_string())
+    }
+}
+
+impl From<String> for Role {
+    fn from(role: String) -> Self {
+        Role(role)
+    }
+}
+
+impl PartialEq<str> for Role {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Role {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+/// Authenticator assurance level of a session
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Aal {
+    /// Signed in with a single factor, such as a password or magic link
+    Aal1,
+    /// Verified a second factor, such as a TOTP code
+    Aal2,
+    Aal3,
+}
+
+/// An entry of the `amr` (authentication method reference) claim
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct AmrEntry {
+    pub method: AmrMethod,
+    /// When the method was used, as a Unix timestamp in seconds
+    pub timestamp: i64,
+    /// The SSO provider id, for `sso/saml`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+}
+
+/// Why a password was found to be weak
+///
+/// Returned in [`Error::WeakPassword`](crate::error::Error::WeakPassword) when signing up or
+/// changing the password, and in [`Session::weak_password`] when signing in with a password that
+/// no longer meets the project's requirements.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct WeakPassword {
+    #[serde(default)]
+    pub reasons: Vec<WeakPasswordReason>,
+    /// A description of the requirements the password failed, suitable for users
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// A requirement a weak password failed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WeakPasswordReason {
+    /// The password is too short
+    Length,
+    /// The password lacks a required kind of character, e.g. digits or symbols
+    Characters,
+    /// The password appeared in a data breach
+    Pwned,
+    /// A reason this version of the crate doesn't know about
+    #[serde(other)]
+    Unknown,
+}
+
+/// How a user authenticated, as recorded in the `amr` claim
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AmrMethod {
+    Password,
+    Otp,
+    #[serde(rename = "oauth")]
+    OAuth,
+    IdToken,
+    Totp,
+    #[serde(rename = "mfa/phone")]
+    MfaPhone,
+    #[serde(rename = "mfa/webauthn")]
+    MfaWebauthn,
+    Recovery,
+    Invite,
+    #[serde(rename = "sso/saml")]
+    SsoSaml,
+    #[serde(rename = "magiclink")]
+    MagicLink,
+    #[serde(rename = "email/signup")]
+    EmailSignup,
+    EmailChange,
+    TokenRefresh,
+    Anonymous,
+    Web3,
+    /// A method this version of the crate doesn't know about
+    #[serde(other)]
+    Unknown,
+}
+
+impl AmrMethod {
+    /// Whether the method is a second factor, raising the session to [`Aal::Aal2`]
+    pub fn is_mfa(&self) -> bool {
+        matches!(
+            self,
+            AmrMethod::Totp | AmrMethod::MfaPhone | AmrMethod::MfaWebauthn
+        )
+    }
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub(crate) struct RefreshSessionPayload<'a> {
     pub refresh_token: &'a str,
@@ -386,12 +1609,12 @@ pub(crate) struct ExchangeCodeForSessionPayload<'a> {
     pub code_verifier: &'a str,
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
-pub(crate) struct ResetPasswordForEmailPayload {
-    pub email: String,
+#[derive(Default, Debug, Clone, Serialize, JsonSchema, PartialEq)]
+pub(crate) struct ResetPasswordForEmailPayload<'a> {
+    pub email: &'a str,
     #[serde(flatten)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(crate) options: Option<ResetPasswordOptions>,
+    pub(crate) options: Option<&'a ResetPasswordOptions>,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
@@ -404,18 +1627,73 @@ pub struct ResendParams {
     pub options: Option<DesktopResendOptions>,
 }
 
+impl ResendParams {
+    /// Resend the email of type `otp_type` to `email`
+    pub fn new(otp_type: OtpType, email: impl Into<String>) -> Self {
+        ResendParams {
+            otp_type,
+            email: email.into(),
+            options: None,
+        }
+    }
+
+    /// Where the link in the email leads, and the captcha token
+    pub fn options(mut self, options: DesktopResendOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct InviteParams {
     pub email: String,
     pub data: Option<Value>,
 }
 
+impl InviteParams {
+    /// Invite the user with `email`
+    pub fn new(email: impl Into<String>) -> Self {
+        InviteParams {
+            email: email.into(),
+            data: None,
+        }
+    }
+
+    /// Metadata to store in `auth.users.raw_user_meta_data`
+    pub fn data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+/// The body of [`AuthClient::invite_user_by_email`], borrowing its arguments
+#[derive(Debug, Serialize)]
+pub(crate) struct InviteUserPayload<'a> {
+    pub(crate) email: &'a str,
+    pub(crate) data: Option<&'a Value>,
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[non_exhaustive]
 pub struct DesktopResendOptions {
     pub email_redirect_to: Option<String>,
     pub captcha_token: Option<String>,
 }
 
+impl DesktopResendOptions {
+    /// The redirect url embedded in the email link
+    pub fn email_redirect_to(mut self, email_redirect_to: impl Into<String>) -> Self {
+        self.email_redirect_to = Some(email_redirect_to.into());
+        self
+    }
+
+    /// Verification token received when the user completes the captcha on the site
+    pub fn captcha_token(mut self, captcha_token: impl Into<String>) -> Self {
+        self.captcha_token = Some(captcha_token.into());
+        self
+    }
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct MobileResendParams {
     #[serde(rename = "type")]
@@ -426,11 +1704,37 @@ pub struct MobileResendParams {
     pub options: Option<MobileResendOptions>,
 }
 
+impl MobileResendParams {
+    /// Resend the SMS of type `otp_type` to `phone`
+    pub fn new(otp_type: OtpType, phone: impl Into<String>) -> Self {
+        MobileResendParams {
+            otp_type,
+            phone: phone.into(),
+            options: None,
+        }
+    }
+
+    /// The captcha token
+    pub fn options(mut self, options: MobileResendOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[non_exhaustive]
 pub struct MobileResendOptions {
     captcha_token: Option<String>,
 }
 
+impl MobileResendOptions {
+    /// Verification token received when the user completes the captcha on the site
+    pub fn captcha_token(mut self, captcha_token: impl Into<String>) -> Self {
+        self.captcha_token = Some(captcha_token.into());
+        self
+    }
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum Channel {
@@ -451,52 +1755,251 @@ impl Display for Channel {
 /// Health status of the Auth Server
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct AuthServerHealth {
-    /// Version of the service
+    /// Version of the service, e.g. `v2.170.0`
+    #[serde(default)]
     pub version: String,
     /// Name of the service
+    #[serde(default)]
     pub name: String,
     /// Description of the service
+    #[serde(default)]
     pub description: String,
 }
 
+impl AuthServerHealth {
+    /// The parsed [`version`](AuthServerHealth::version), `None` if it isn't a release version,
+    /// e.g. for servers built from a branch
+    ///
+    /// # Example
+    /// ```
+    /// let health = auth_client.get_health().await?;
+    ///
+    /// if health.server_version().is_some_and(|version| version < ServerVersion::new(2, 150, 0)) {
+    ///     panic!("GoTrue {} is too old", health.version);
+    /// }
+    /// ```
+    pub fn server_version(&self) -> Option<ServerVersion> {
+        self.version.parse().ok()
+    }
+}
+
+/// A GoTrue release version, ordered by major, minor, and patch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ServerVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl ServerVersion {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        ServerVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl std::str::FromStr for ServerVersion {
+    type Err = Error;
+
+    /// Parse versions like `v2.170.0` or `2.170.0-rc.1`. Pre-release and build suffixes are
+    /// ignored.
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidVersion(version.to_string());
+
+        let release = version.trim().trim_start_matches('v');
+        let release = release.split(['-', '+']).next().unwrap_or_default();
+
+        let parts = release
+            .split('.')
+            .map(|part| part.parse::<u64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match parts[..] {
+            [major, minor, patch] => Ok(ServerVersion::new(major, minor, patch)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The oldest GoTrue version this crate is tested against
+pub const MIN_SUPPORTED_VERSION: ServerVersion = ServerVersion::new(2, 100, 0);
+
+/// Server versions which changed an API this crate relies on, and what older servers lack
+pub(crate) const VERSION_CHANGES: &[(ServerVersion, &str)] = &[
+    (
+        ServerVersion::new(2, 122, 0),
+        "error responses carry machine readable `error_code`s",
+    ),
+    (
+        ServerVersion::new(2, 127, 0),
+        "identities carry an `identity_id` separate from the provider's user id",
+    ),
+    (
+        ServerVersion::new(2, 143, 0),
+        "anonymous sign ins and `is_anonymous` on users",
+    ),
+];
+
+/// A reason the server may not work with this version of the crate, returned by
+/// [`AuthClient::check_compatibility`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompatibilityWarning {
+    /// The server reported a version which isn't a release version, so it couldn't be checked
+    UnknownVersion(String),
+    /// The server is older than [`MIN_SUPPORTED_VERSION`]
+    Unsupported {
+        version: ServerVersion,
+        minimum: ServerVersion,
+    },
+    /// The server predates a change of an API this crate relies on, described by `change`
+    MissingChange {
+        version: ServerVersion,
+        since: ServerVersion,
+        change: &'static str,
+    },
+}
+
+impl Display for CompatibilityWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompatibilityWarning::UnknownVersion(version) => {
+                write!(f, "GoTrue version {version:?} can't be checked")
+            }
+            CompatibilityWarning::Unsupported { version, minimum } => {
+                write!(f, "GoTrue {version} is older than the minimum supported {minimum}")
+            }
+            CompatibilityWarning::MissingChange {
+                version,
+                since,
+                change,
+            } => write!(f, "GoTrue {version} predates {since}: {change}"),
+        }
+    }
+}
+
 /// Settings of the Auth Server
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AuthServerSettings {
+    /// Which sign in methods are enabled
+    #[serde(default)]
     pub external: External,
+    /// Whether new users can sign up
+    #[serde(default)]
     pub disable_signup: bool,
+    /// Whether email sign ups are confirmed without sending a confirmation email
+    #[serde(default)]
     pub mailer_autoconfirm: bool,
+    /// Whether phone sign ups are confirmed without sending an SMS
+    #[serde(default)]
     pub phone_autoconfirm: bool,
+    /// The SMS provider, e.g. `twilio`. Empty if phone sign in isn't configured.
+    #[serde(default)]
     pub sms_provider: String,
+    /// Whether users can enroll MFA factors
+    #[serde(default)]
+    pub mfa_enabled: bool,
+    #[serde(default)]
     pub saml_enabled: bool,
+    /// Display names of external providers, keyed by provider name
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub external_labels: HashMap<String, String>,
+    /// Fields this version of the library doesn't know about, e.g. ones added by newer GoTrue
+    /// versions. They are kept, and serialized again.
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
+/// Which sign in methods are enabled on the server
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct External {
+    #[serde(default)]
     pub anonymous_users: bool,
+    #[serde(default)]
     pub apple: bool,
+    #[serde(default)]
     pub azure: bool,
+    #[serde(default)]
     pub bitbucket: bool,
+    #[serde(default)]
     pub discord: bool,
+    #[serde(default)]
     pub facebook: bool,
+    #[serde(default)]
     pub figma: bool,
+    #[serde(default)]
     pub fly: bool,
+    #[serde(default)]
     pub github: bool,
+    #[serde(default)]
     pub gitlab: bool,
+    #[serde(default)]
     pub google: bool,
+    #[serde(default)]
     pub keycloak: bool,
+    #[serde(default)]
     pub kakao: bool,
+    #[serde(default)]
     pub linkedin: bool,
+    #[serde(default)]
     pub linkedin_oidc: bool,
+    #[serde(default)]
     pub notion: bool,
+    #[serde(default)]
     pub spotify: bool,
+    #[serde(default)]
     pub slack: bool,
+    #[serde(default)]
     pub slack_oidc: bool,
+    #[serde(default)]
     pub workos: bool,
+    #[serde(default)]
     pub twitch: bool,
+    #[serde(default)]
     pub twitter: bool,
+    #[serde(default)]
     pub email: bool,
+    #[serde(default)]
     pub phone: bool,
+    #[serde(default)]
     pub zoom: bool,
+    /// Providers this version of the library doesn't have a field for
+    #[serde(flatten)]
+    pub other: HashMap<String, bool>,
+}
+
+impl External {
+    /// Every provider flag, keyed by provider name
+    pub fn to_map(&self) -> HashMap<String, bool> {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether the provider named `provider` is enabled, e.g. `"github"`
+    pub fn is_enabled(&self, provider: &str) -> bool {
+        self.to_map().get(provider).copied().unwrap_or(false)
+    }
+
+    /// The names of the enabled providers
+    pub fn enabled(&self) -> Vec<String> {
+        let mut enabled: Vec<String> = self
+            .to_map()
+            .into_iter()
+            .filter_map(|(provider, enabled)| enabled.then_some(provider))
+            .collect();
+        enabled.sort();
+        enabled
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -572,26 +2075,459 @@ pub enum LogoutScope {
     Others,
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
-pub struct LoginWithSSO {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    /// UUID of the SSO provider to invoke single-sign on to
-    pub provider_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+/// Identifies which SSO provider a sign on should be routed to
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SsoIdentifier {
     /// Domain of the SSO provider where users can initiate sign on
-    pub domain: Option<String>,
+    Domain(String),
+    /// UUID of the SSO provider to invoke single-sign on to
+    ProviderId(Uuid),
+}
+
+/// Parameters for [`AuthClient::sso`](crate::models::AuthClient::sso)
+///
+/// # Example
+/// ```
+/// let params = LoginWithSSO::domain("example.com");
+///
+/// let params = LoginWithSSO {
+///     identifier: SsoIdentifier::ProviderId(provider_id),
+///     options: Some(SSOLoginOptions::default().redirect_to("https://example.com/welcome")),
+/// };
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct LoginWithSSO {
+    #[serde(flatten)]
+    pub identifier: SsoIdentifier,
+    #[serde(flatten)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub options: Option<SSOLoginOptions>,
 }
 
+impl LoginWithSSO {
+    /// Sign on with the SSO provider registered for `domain`
+    pub fn domain(domain: impl Into<String>) -> Self {
+        LoginWithSSO {
+            identifier: SsoIdentifier::Domain(domain.into()),
+            options: None,
+        }
+    }
+
+    /// Sign on with the SSO provider identified by `provider_id`
+    pub fn provider_id(provider_id: Uuid) -> Self {
+        LoginWithSSO {
+            identifier: SsoIdentifier::ProviderId(provider_id),
+            options: None,
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[non_exhaustive]
 pub struct SSOLoginOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     /// Verification token received when the user completes the captcha on the site.
-    captcha_token: Option<String>,
+    pub captcha_token: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     /// A URL to send the user to after they have signed-in.
-    redirect_to: Option<String>,
+    pub redirect_to: Option<String>,
+}
+
+impl SSOLoginOptions {
+    /// Verification token received when the user completes the captcha on the site
+    pub fn captcha_token(mut self, captcha_token: impl Into<String>) -> Self {
+        self.captcha_token = Some(captcha_token.into());
+        self
+    }
+
+    /// A URL to send the user to after they have signed-in
+    pub fn redirect_to(mut self, redirect_to: impl Into<String>) -> Self {
+        self.redirect_to = Some(redirect_to.into());
+        self
+    }
+}
+
+/// An SSO identity provider registered on the project
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SsoProvider {
+    pub id: Uuid,
+    /// Optional user-defined identifier, unique per project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_id: Option<String>,
+    /// SAML configuration of the provider
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saml: Option<SamlProviderConfig>,
+    /// Email domains routed to this provider
+    #[serde(default)]
+    pub domains: Vec<SsoDomain>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SamlProviderConfig {
+    /// The SAML EntityID of the identity provider
+    pub entity_id: String,
+    /// URL the identity provider metadata is fetched from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata_url: Option<String>,
+    /// Raw XML metadata of the identity provider
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata_xml: Option<String>,
+    /// Mapping of SAML assertion attributes to user metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribute_mapping: Option<SamlAttributeMapping>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_id_format: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SamlAttributeMapping {
+    #[serde(default)]
+    pub keys: HashMap<String, SamlAttribute>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SamlAttribute {
+    /// Name of the SAML attribute to read the value from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Names of SAML attributes to try in order, the first one present is used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub names: Option<Vec<String>>,
+    /// Value used when none of the attributes are present in the assertion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    /// Collect all values of the attribute into an array
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub array: Option<bool>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SsoDomain {
+    pub domain: String,
+}
+
+/// Changes to apply to an existing SSO provider. Fields left as `None` are not modified.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct UpdateSsoProviderParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// URL to fetch the identity provider metadata from. Mutually exclusive with `metadata_xml`.
+    pub metadata_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Raw XML metadata of the identity provider. Mutually exclusive with `metadata_url`.
+    pub metadata_xml: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// The complete list of email domains routed to this provider.
+    ///
+    /// This replaces the existing list, so include the current domains to keep them.
+    /// See [`UpdateSsoProviderParams::add_domain`] and [`UpdateSsoProviderParams::remove_domain`].
+    pub domains: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Mapping of SAML assertion attributes to user metadata
+    pub attribute_mapping: Option<SamlAttributeMapping>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_id_format: Option<String>,
+}
+
+impl UpdateSsoProviderParams {
+    /// Start an update from the provider's current domains, so that
+    /// [`add_domain`](Self::add_domain) and [`remove_domain`](Self::remove_domain)
+    /// only touch the domains you name.
+    pub fn from_provider(provider: &SsoProvider) -> Self {
+        UpdateSsoProviderParams {
+            domains: Some(provider.domains.iter().map(|d| d.domain.clone()).collect()),
+            ..Default::default()
+        }
+    }
+
+    /// Add a domain to the provider
+    pub fn add_domain(mut self, domain: impl Into<String>) -> Self {
+        let domain = domain.into();
+        let domains = self.domains.get_or_insert_with(Vec::new);
+
+        if !domains.contains(&domain) {
+            domains.push(domain);
+        }
+
+        self
+    }
+
+    /// Remove a domain from the provider
+    pub fn remove_domain(mut self, domain: &str) -> Self {
+        if let Some(domains) = self.domains.as_mut() {
+            domains.retain(|d| d != domain);
+        }
+
+        self
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub(crate) struct SsoProvidersResponse {
+    pub(crate) items: Vec<SsoProvider>,
+}
+
+/// A third-party client registered with the project's OAuth 2.1 server
+#[derive(Default, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct OAuthClient {
+    pub client_id: String,
+    pub client_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Only returned when the client is created or its secret is regenerated
+    pub client_secret: Option<String>,
+    pub client_type: OAuthClientType,
+    pub token_endpoint_auth_method: String,
+    pub registration_type: OAuthClientRegistrationType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo_uri: Option<String>,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<OAuthClientGrantType>,
+    pub response_types: Vec<OAuthClientResponseType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl fmt::Debug for OAuthClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuthClient")
+            .field("client_id", &self.client_id)
+            .field("client_name", &self.client_name)
+            .field("client_secret", &redact(&self.client_secret))
+            .field("client_type", &self.client_type)
+            .field("token_endpoint_auth_method", &self.token_endpoint_auth_method)
+            .field("registration_type", &self.registration_type)
+            .field("client_uri", &self.client_uri)
+            .field("logo_uri", &self.logo_uri)
+            .field("redirect_uris", &self.redirect_uris)
+            .field("grant_types", &self.grant_types)
+            .field("response_types", &self.response_types)
+            .field("scope", &self.scope)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .finish()
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuthClientType {
+    Public,
+    #[default]
+    Confidential,
+    /// A client type this version of the crate doesn't know about
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuthClientRegistrationType {
+    Dynamic,
+    #[default]
+    Manual,
+    /// A registration type this version of the crate doesn't know about
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuthClientGrantType {
+    AuthorizationCode,
+    RefreshToken,
+    /// A grant type this version of the crate doesn't know about
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuthClientResponseType {
+    Code,
+    /// A response type this version of the crate doesn't know about
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct CreateOAuthClientParams {
+    /// Human readable name shown to users on the consent screen
+    pub client_name: String,
+    /// URIs the authorization server may redirect to after authorization
+    pub redirect_uris: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// URL of the client's home page
+    pub client_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// URL of the client's logo
+    pub logo_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Defaults to `authorization_code` and `refresh_token`
+    pub grant_types: Option<Vec<OAuthClientGrantType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Defaults to `code`
+    pub response_types: Option<Vec<OAuthClientResponseType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Space separated list of scopes the client may request
+    pub scope: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub(crate) struct OAuthClientsResponse {
+    pub(crate) clients: Vec<OAuthClient>,
+}
+
+/// Which page of the project's users to list, the server defaults to the first 50
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[non_exhaustive]
+pub struct ListUsersParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Starts at 1
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+}
+
+impl ListUsersParams {
+    /// The page to list, starting at 1
+    pub fn page(mut self, page: u32) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    /// How many users to list per page
+    pub fn per_page(mut self, per_page: u32) -> Self {
+        self.per_page = Some(per_page);
+        self
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub(crate) struct UsersResponse {
+    pub(crate) users: Vec<User>,
+}
+
+/// The kind of email link to generate
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GenerateLinkType {
+    /// Confirms a new user, requires a password
+    Signup,
+    /// Invites a new user
+    Invite,
+    Magiclink,
+    Recovery,
+    /// Confirms an email change from the current address
+    EmailChangeCurrent,
+    /// Confirms an email change from the new address
+    EmailChangeNew,
+}
+
+/// Generate an email link without sending the email
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[non_exhaustive]
+pub struct GenerateLinkParams {
+    #[serde(rename = "type")]
+    pub link_type: GenerateLinkType,
+    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Required for [`GenerateLinkType::Signup`]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Required for the email change link types
+    pub new_email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Metadata to store in `auth.users.raw_user_meta_data` of new users
+    pub data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect_to: Option<String>,
+}
+
+impl GenerateLinkParams {
+    /// Generate a `link_type` link for `email`
+    pub fn new(link_type: GenerateLinkType, email: impl Into<String>) -> Self {
+        GenerateLinkParams {
+            link_type,
+            email: email.into(),
+            password: None,
+            new_email: None,
+            data: None,
+            redirect_to: None,
+        }
+    }
+
+    /// The new user's password, required for [`GenerateLinkType::Signup`]
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// The address to change to, required for the email change link types
+    pub fn new_email(mut self, new_email: impl Into<String>) -> Self {
+        self.new_email = Some(new_email.into());
+        self
+    }
+
+    /// Metadata to store in `auth.users.raw_user_meta_data` of new users
+    pub fn data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Where to send the user after they opened the link
+    pub fn redirect_to(mut self, redirect_to: impl Into<String>) -> Self {
+        self.redirect_to = Some(redirect_to.into());
+        self
+    }
+}
+
+// Implement custom Debug to avoid exposing the password
+impl fmt::Debug for GenerateLinkParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GenerateLinkParams")
+            .field("link_type", &self.link_type)
+            .field("email", &self.email)
+            .field("password", &redact(&self.password))
+            .field("new_email", &self.new_email)
+            .field("data", &self.data)
+            .field("redirect_to", &self.redirect_to)
+            .finish()
+    }
+}
+
+/// A generated email link and the user it was generated for
+#[derive(Default, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct GeneratedLink {
+    /// The link the email would have contained
+    pub action_link: String,
+    /// The one time password the email would have contained
+    pub email_otp: String,
+    /// Verifies the link with [`VerifyTokenHashParams`]
+    pub hashed_token: String,
+    pub verification_type: String,
+    pub redirect_to: String,
+    #[serde(flatten)]
+    pub user: User,
+}
+
+// Implement custom Debug to avoid exposing the link's tokens
+impl fmt::Debug for GeneratedLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeneratedLink")
+            .field("action_link", &REDACTED)
+            .field("email_otp", &REDACTED)
+            .field("hashed_token", &REDACTED)
+            .field("verification_type", &self.verification_type)
+            .field("redirect_to", &self.redirect_to)
+            .field("user", &self.user)
+            .finish()
+    }
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
@@ -655,13 +2591,31 @@ pub struct Headers {
     pub x_robots_tag: String,
 }
 
+/// Shown instead of secrets in `Debug` output
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
+/// [`REDACTED`] if `secret` is set
+pub(crate) fn redact<T>(secret: &Option<T>) -> Option<&'static str> {
+    secret.as_ref().map(|_| REDACTED)
+}
+
+/// Deserialize `null` like a missing field
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
 // Implement custom Debug to avoid exposing sensitive information
 impl fmt::Debug for AuthClient {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("AuthClient")
             .field("project_url", &self.project_url())
-            .field("api_key", &"[REDACTED]")
-            .field("jwt_secret", &"[REDACTED]")
+            .field("api_key", &REDACTED)
+            .field("jwt_secret", &REDACTED)
+            .field("key_role", &self.key_role)
             .finish()
     }
 }
@@ -0,0 +1,128 @@
+/*!
+OpenTelemetry tracing of requests to Supabase Auth, enabled with the `otel` feature.
+
+Every request an [`AuthClient`](crate::models::AuthClient) sends, retries included, is recorded
+as a client span by the tracer of the global tracer provider, with the
+[HTTP semantic conventions](https://opentelemetry.io/docs/specs/semconv/http/http-spans/). The
+span is a child of the current OpenTelemetry context, and a W3C `traceparent` header (and
+`tracestate` if any) is attached to the request, so auth calls appear in distributed traces
+alongside the rest of the request path.
+
+Query strings are left out of the recorded URLs as they may hold tokens, and ids in paths are
+replaced with `{id}` to keep span names low cardinality, e.g. `GET /auth/v1/admin/users/{id}`.
+
+Without a global tracer provider, spans aren't recorded, but the `traceparent` of the current
+context is still propagated.
+
+# Example
+
+```rust
+use opentelemetry::context::FutureExt;
+
+let provider = SdkTracerProvider::builder()
+    .with_batch_exporter(exporter)
+    .build();
+opentelemetry::global::set_tracer_provider(provider);
+
+// Recorded as `POST /auth/v1/token`
+let session = auth_client
+    .login_with_email(&demo_email, &demo_password)
+    .with_context(Context::current())
+    .await?;
+```
+*/
+
+use opentelemetry::{
+    global,
+    trace::{SpanKind, Status, TraceContextExt, Tracer},
+    Context, KeyValue,
+};
+use reqwest::{header::HeaderValue, Request, Response};
+
+use crate::{
+    error::Error,
+    transport::{route, HttpTransport},
+};
+
+/// The name of the tracer spans are recorded with
+pub const INSTRUMENTATION_SCOPE: &str = "supabase-auth";
+
+/// Execute `request` with `transport` in a client span, propagating its context
+pub(crate) async fn execute(
+    transport: &dyn HttpTransport,
+    mut request: Request,
+) -> Result<Response, Error> {
+    let url = request.url();
+
+    let mut attributes = vec![
+        KeyValue::new("http.request.method", request.method().to_string()),
+        KeyValue::new(
+            "url.full",
+            format!("{}{}", url.origin().ascii_serialization(), url.path()),
+        ),
+    ];
+    if let Some(host) = url.host_str() {
+        attributes.push(KeyValue::new("server.address", host.to_string()));
+    }
+    if let Some(port) = url.port_or_known_default() {
+        attributes.push(KeyValue::new("server.port", i64::from(port)));
+    }
+
+    let tracer = global::tracer(INSTRUMENTATION_SCOPE);
+    let span = tracer
+        .span_builder(format!("{} {}", request.method(), route(url)))
+        .with_kind(SpanKind::Client)
+        .with_attributes(attributes)
+        .start_with_context(&tracer, &Context::current());
+    let cx = Context::current_with_span(span);
+
+    let span_context = cx.span().span_context().clone();
+    if span_context.is_valid() {
+        let traceparent = format!(
+            "00-{:032x}-{:016x}-{:02x}",
+            span_context.trace_id(),
+            span_context.span_id(),
+            span_context.trace_flags().to_u8()
+        );
+        let tracestate = span_context.trace_state().header();
+
+        let headers = request.headers_mut();
+        if let Ok(value) = HeaderValue::from_str(&traceparent) {
+            headers.insert("traceparent", value);
+        }
+        if let Ok(value) = HeaderValue::from_str(&tracestate) {
+            if !tracestate.is_empty() {
+                headers.insert("tracestate", value);
+            }
+        }
+    }
+
+    let result = transport.execute(request).await;
+
+    let span = cx.span();
+    match &result {
+        Ok(response) => {
+            let status = response.status();
+            span.set_attribute(KeyValue::new(
+                "http.response.status_code",
+                i64::from(status.as_u16()),
+            ));
+            if status.is_client_error() || status.is_server_error() {
+                span.set_attribute(KeyValue::new("error.type", status.as_u16().to_string()));
+                span.set_status(Status::error(status.to_string()));
+            }
+        }
+        Err(error) => {
+            let error_type = match error {
+                Error::NetworkError(error) if error.is_timeout() => "timeout",
+                Error::NetworkError(error) if error.is_connect() => "connect",
+                _ => "_OTHER",
+            };
+            span.set_attribute(KeyValue::new("error.type", error_type));
+            span.set_status(Status::error(error.to_string()));
+        }
+    }
+    span.end();
+
+    result
+}
@@ -0,0 +1,221 @@
+/*!
+A cache of clients for many Supabase projects.
+
+Control planes managing hundreds of projects need a client per project, but not a connection pool
+per project. [`AuthClientPool`] creates clients on first use with
+[`AuthClient::for_project`], so they all share the HTTP transport and settings of one base
+client, and evicts the least recently used ones once it is full.
+
+# Example
+
+```rust
+let base = AuthClient::builder()
+    .project_url("https://example.supabase.co")
+    .api_key("unused")
+    .retry_policy(RetryPolicy::default())
+    .build()?;
+
+let pool = AuthClientPool::new(base)
+    .with_capacity(500)
+    .with_idle_timeout(Duration::from_secs(3600));
+
+let auth_client = pool.get_or_insert_with(&project_ref, || {
+    let project = projects.find(&project_ref)?;
+
+    Ok(ProjectCredentials::hosted(&project_ref, project.service_role_key))
+})?;
+```
+*/
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::Error,
+    models::{AuthClient, REDACTED},
+};
+
+/// How many clients a pool keeps by default
+const DEFAULT_CAPACITY: usize = 256;
+
+/// What a pool needs to know to create a client for a project
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ProjectCredentials {
+    pub project_url: String,
+    pub api_key: String,
+    /// Leave empty to verify HS256 tokens with the server instead of locally
+    pub jwt_secret: String,
+}
+
+impl fmt::Debug for ProjectCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProjectCredentials")
+            .field("project_url", &self.project_url)
+            .field("api_key", &REDACTED)
+            .field("jwt_secret", &REDACTED)
+            .finish()
+    }
+}
+
+impl ProjectCredentials {
+    pub fn new(project_url: impl Into<String>, api_key: impl Into<String>) -> Self {
+        ProjectCredentials {
+            project_url: project_url.into(),
+            api_key: api_key.into(),
+            jwt_secret: String::new(),
+        }
+    }
+
+    /// Credentials of a project hosted by Supabase, at `https://<project_ref>.supabase.co`
+    pub fn hosted(project_ref: &str, api_key: impl Into<String>) -> Self {
+        Self::new(format!("https://{}.supabase.co", project_ref), api_key)
+    }
+
+    pub fn with_jwt_secret(mut self, jwt_secret: impl Into<String>) -> Self {
+        self.jwt_secret = jwt_secret.into();
+        self
+    }
+}
+
+/// Clients for many projects, keyed by project ref and sharing one transport
+///
+/// Clones share the cached clients.
+#[derive(Debug, Clone)]
+pub struct AuthClientPool {
+    base: AuthClient,
+    capacity: usize,
+    idle_timeout: Option<Duration>,
+    clients: Arc<Mutex<HashMap<String, PooledClient>>>,
+}
+
+#[derive(Debug)]
+struct PooledClient {
+    client: AuthClient,
+    last_used: Instant,
+}
+
+impl AuthClientPool {
+    /// Create a pool whose clients copy the transport and settings of `base`. Keeps up to 256
+    /// clients.
+    pub fn new(base: AuthClient) -> Self {
+        AuthClientPool {
+            base,
+            capacity: DEFAULT_CAPACITY,
+            idle_timeout: None,
+            clients: Arc::default(),
+        }
+    }
+
+    /// Set how many clients are kept before the least recently used one is evicted
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Evict clients which weren't used for `idle_timeout`
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// The cached client for `project_ref`, if there is one
+    pub fn get(&self, project_ref: &str) -> Option<AuthClient> {
+        let mut clients = self.lock();
+        self.evict_idle(&mut clients);
+
+        let pooled = clients.get_mut(project_ref)?;
+        pooled.last_used = Instant::now();
+
+        Some(pooled.client.clone())
+    }
+
+    /// The client for `project_ref`, created with the credentials returned by `credentials` if
+    /// it isn't cached
+    ///
+    /// `credentials` is only called on a cache miss. Its errors are returned as is, and nothing
+    /// is cached.
+    pub fn get_or_insert_with(
+        &self,
+        project_ref: &str,
+        credentials: impl FnOnce() -> Result<ProjectCredentials, Error>,
+    ) -> Result<AuthClient, Error> {
+        if let Some(client) = self.get(project_ref) {
+            return Ok(client);
+        }
+
+        let credentials = credentials()?;
+        let client = self.base.for_project(
+            credentials.project_url,
+            credentials.api_key,
+            credentials.jwt_secret,
+        );
+
+        let mut clients = self.lock();
+
+        // Another caller may have created the client in the meantime, keep theirs so that
+        // everybody shares one JWKS cache and rate limiter
+        if let Some(pooled) = clients.get_mut(project_ref) {
+            pooled.last_used = Instant::now();
+            return Ok(pooled.client.clone());
+        }
+
+        if clients.len() >= self.capacity {
+            let least_recently_used = clients
+                .iter()
+                .min_by_key(|(_, pooled)| pooled.last_used)
+                .map(|(project_ref, _)| project_ref.clone());
+
+            if let Some(project_ref) = least_recently_used {
+                clients.remove(&project_ref);
+            }
+        }
+
+        clients.insert(
+            project_ref.to_string(),
+            PooledClient {
+                client: client.clone(),
+                last_used: Instant::now(),
+            },
+        );
+
+        Ok(client)
+    }
+
+    /// Evict the client for `project_ref`, e.g. after its keys were rotated
+    pub fn remove(&self, project_ref: &str) -> Option<AuthClient> {
+        self.lock().remove(project_ref).map(|pooled| pooled.client)
+    }
+
+    /// Evict all clients
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// How many clients are cached
+    pub fn len(&self) -> usize {
+        let mut clients = self.lock();
+        self.evict_idle(&mut clients);
+
+        clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, PooledClient>> {
+        self.clients
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn evict_idle(&self, clients: &mut HashMap<String, PooledClient>) {
+        if let Some(idle_timeout) = self.idle_timeout {
+            clients.retain(|_, pooled| pooled.last_used.elapsed() < idle_timeout);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+/*!
+Client-side rate limiting, to stay below the rate limits of Supabase Auth.
+
+Supabase Auth rejects requests with `429 Too Many Requests` once a project exceeds its rate
+limits, most of which are low for endpoints that send emails or text messages. Batch scripts and
+test suites can instead have the client pace itself with [`RateLimits`]: each class of endpoint
+gets a token bucket, and requests wait for a token before they are sent.
+
+# Example
+
+```rust
+let auth_client = AuthClient::builder()
+    .project_url(project_url)
+    .api_key(api_key)
+    .rate_limits(RateLimits {
+        otp: Some(RateLimit::per_hour(30)),
+        sign_up: Some(RateLimit::per_hour(30)),
+        ..Default::default()
+    })
+    .build()
+    .unwrap();
+```
+*/
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tokio::time::sleep;
+
+/// Allow `requests` requests every `per`
+///
+/// Up to `requests` requests are sent right away, after that requests are spaced evenly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub per: Duration,
+}
+
+impl RateLimit {
+    pub fn new(requests: u32, per: Duration) -> Self {
+        RateLimit { requests, per }
+    }
+
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(60))
+    }
+
+    pub fn per_hour(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(3600))
+    }
+}
+
+/// Rate limits for each class of endpoint, unlimited if `None`
+///
+/// A request only counts against the limit of its own class.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimits {
+    /// Endpoints sending a one time password or link by email or SMS: `/otp`, `/magiclink`,
+    /// `/recover`, and `/resend`
+    pub otp: Option<RateLimit>,
+    /// `/signup`
+    pub sign_up: Option<RateLimit>,
+    /// All other endpoints
+    pub generic: Option<RateLimit>,
+}
+
+/// The token buckets of an [`AuthClient`](crate::models::AuthClient), shared by its clones
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limits: RateLimits,
+    otp: Option<Bucket>,
+    sign_up: Option<Bucket>,
+    generic: Option<Bucket>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limits: RateLimits) -> Self {
+        RateLimiter {
+            limits,
+            otp: limits.otp.map(Bucket::new),
+            sign_up: limits.sign_up.map(Bucket::new),
+            generic: limits.generic.map(Bucket::new),
+        }
+    }
+
+    pub(crate) fn limits(&self) -> RateLimits {
+        self.limits
+    }
+
+    /// Wait until a request to `path` may be sent
+    pub(crate) async fn acquire(&self, path: &str) {
+        let endpoint = path.trim_end_matches('/').rsplit('/').next().unwrap_or("");
+
+        let bucket = match endpoint {
+            "otp" | "magiclink" | "recover" | "resend" => &self.otp,
+            "signup" => &self.sign_up,
+            _ => &self.generic,
+        };
+
+        if let Some(bucket) = bucket {
+            let wait = bucket.reserve();
+
+            if !wait.is_zero() {
+                sleep(wait).await;
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    /// Tokens left, negative when requests are waiting for tokens which haven't been refilled yet
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit) -> Self {
+        Bucket {
+            limit,
+            state: Mutex::new(BucketState {
+                tokens: limit.requests as f64,
+                updated_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take a token, returning how long to wait until it is available
+    fn reserve(&self) -> Duration {
+        let capacity = self.limit.requests.max(1) as f64;
+        let refill_rate = capacity / self.limit.per.as_secs_f64();
+
+        let mut state = self.state.lock().unwrap_or_else(|error| error.into_inner());
+
+        let now = Instant::now();
+        let refilled = now.duration_since(state.updated_at).as_secs_f64() * refill_rate;
+        state.tokens = (state.tokens + refilled).min(capacity) - 1.0;
+        state.updated_at = now;
+
+        if state.tokens >= 0.0 || !refill_rate.is_finite() {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens / refill_rate)
+        }
+    }
+}
@@ -0,0 +1,429 @@
+/*!
+Record and replay HTTP interactions for deterministic tests, enabled with the `recording`
+feature.
+
+A [`RecordingTransport`] is an [`HttpTransport`] that either sends requests through another
+transport and writes each request and response to a JSON fixture, or answers requests from a
+fixture recorded earlier without touching the network. Record a test once against a real
+project, commit the fixture, and CI replays it in milliseconds.
+
+Secrets are scrubbed before anything is written: the body and query fields named in
+[`SCRUBBED_FIELDS`] and the `Set-Cookie` header are replaced with `"[REDACTED]"`, request headers
+aren't recorded at all, and only the path and query of URLs are kept. Anything else, like email
+addresses or the project reference, can be scrubbed with [`RecordingTransport::scrub_text`].
+Since replayed tokens are redacted, they can't be verified locally.
+
+Requests are matched by method, path and query, in the order they were recorded, so flows that
+call the same endpoint repeatedly replay faithfully.
+
+# Example
+
+```rust
+// Replays `tests/fixtures/login.json`, or records it when it doesn't exist yet or
+// SUPABASE_AUTH_RECORD is set
+let transport = RecordingTransport::auto("tests/fixtures/login.json", reqwest::Client::new())?
+    .scrub_text(&demo_email, "demo@example.com");
+
+let auth_client = AuthClient::builder()
+    .project_url(project_url)
+    .api_key(api_key)
+    .transport(transport)
+    .build()?;
+
+let session = auth_client.login_with_email(&demo_email, &demo_password).await?;
+```
+*/
+
+use std::{
+    collections::BTreeMap,
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use reqwest::{Request, Response, ResponseBuilderExt, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    error::Error,
+    models::REDACTED,
+    transport::{HttpTransport, TransportFuture},
+};
+
+/// Setting this environment variable makes [`RecordingTransport::auto`] record even when the
+/// fixture exists, to refresh it
+pub const RECORD_ENV_VAR: &str = "SUPABASE_AUTH_RECORD";
+
+/// JSON body and query fields whose values are replaced with `"[REDACTED]"` in fixtures
+pub const SCRUBBED_FIELDS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "provider_token",
+    "provider_refresh_token",
+    "id_token",
+    "password",
+    "token",
+    "token_hash",
+    "code_verifier",
+    "auth_code",
+    "nonce",
+    "hashed_token",
+    "action_link",
+    "email_otp",
+    "secret",
+    "client_secret",
+    "qr_code",
+    "uri",
+];
+
+/// Response headers that are scrubbed, or dropped because they don't hold for replayed bodies
+const SCRUBBED_HEADERS: &[&str] = &["set-cookie"];
+const DROPPED_HEADERS: &[&str] = &["content-length", "content-encoding", "transfer-encoding"];
+
+/// Whether a [`RecordingTransport`] records or replays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordMode {
+    Record,
+    Replay,
+}
+
+/// Records requests to a fixture or replays them from one, see the [module docs](self)
+pub struct RecordingTransport {
+    mode: RecordMode,
+    path: PathBuf,
+    inner: Option<Arc<dyn HttpTransport>>,
+    replacements: Vec<(String, String)>,
+    state: Mutex<Fixture>,
+}
+
+impl fmt::Debug for RecordingTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordingTransport")
+            .field("mode", &self.mode)
+            .field("path", &self.path)
+            .field("inner", &self.inner)
+            .field("replacements", &self.replacements.len())
+            .finish()
+    }
+}
+
+impl RecordingTransport {
+    /// Send requests through `inner` and write them to the fixture at `path`, replacing it
+    pub fn record(path: impl Into<PathBuf>, inner: impl HttpTransport + 'static) -> Self {
+        RecordingTransport {
+            mode: RecordMode::Record,
+            path: path.into(),
+            inner: Some(Arc::new(inner)),
+            replacements: Vec::new(),
+            state: Mutex::new(Fixture::default()),
+        }
+    }
+
+    /// Answer requests from the fixture at `path`
+    ///
+    /// Fails with [`Error::FixtureError`] if the fixture can't be read.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let fixture = Fixture::load(&path)?;
+
+        Ok(RecordingTransport {
+            mode: RecordMode::Replay,
+            path,
+            inner: None,
+            replacements: Vec::new(),
+            state: Mutex::new(fixture),
+        })
+    }
+
+    /// Replay the fixture at `path` if it exists, record it with `inner` otherwise
+    ///
+    /// Records regardless when the [`RECORD_ENV_VAR`] environment variable is set.
+    pub fn auto(
+        path: impl Into<PathBuf>,
+        inner: impl HttpTransport + 'static,
+    ) -> Result<Self, Error> {
+        let path = path.into();
+
+        if env::var_os(RECORD_ENV_VAR).is_some() || !path.exists() {
+            return Ok(RecordingTransport::record(path, inner));
+        }
+
+        RecordingTransport::replay(path)
+    }
+
+    /// Replace `text` with `replacement` in recorded URLs, headers and bodies, e.g. an email
+    /// address or the project reference
+    pub fn scrub_text(mut self, text: impl Into<String>, replacement: impl Into<String>) -> Self {
+        let text = text.into();
+        if !text.is_empty() {
+            self.replacements.push((text, replacement.into()));
+        }
+        self
+    }
+
+    pub fn mode(&self) -> RecordMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    async fn record_interaction(
+        &self,
+        inner: &dyn HttpTransport,
+        request: Request,
+    ) -> Result<Response, Error> {
+        let recorded_request = RecordedRequest {
+            method: request.method().to_string(),
+            url: self.scrub_url(request.url()),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|body| self.scrub_body(body))
+                .unwrap_or_default(),
+        };
+        let url = request.url().clone();
+
+        let response = inner.execute(request).await?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let final_url = response.url().clone();
+        let bytes = response.bytes().await?;
+
+        let recorded_response = RecordedResponse {
+            status: status.as_u16(),
+            headers: headers
+                .iter()
+                .filter(|(name, _)| !DROPPED_HEADERS.contains(&name.as_str()))
+                .map(|(name, value)| {
+                    let value = if SCRUBBED_HEADERS.contains(&name.as_str()) {
+                        REDACTED.to_string()
+                    } else {
+                        self.scrub_text_in(&String::from_utf8_lossy(value.as_bytes()))
+                    };
+                    (name.to_string(), value)
+                })
+                .collect(),
+            redirected_to: (final_url != url).then(|| self.scrub_text_in(final_url.as_str())),
+            body: self.scrub_body(&bytes),
+        };
+
+        {
+            let mut fixture = self.state.lock().unwrap();
+            fixture.interactions.push(Interaction {
+                request: recorded_request,
+                response: recorded_response,
+                replayed: false,
+            });
+            fixture.save(&self.path)?;
+        }
+
+        let mut builder = http::Response::builder().status(status).url(final_url);
+        if let Some(response_headers) = builder.headers_mut() {
+            *response_headers = headers;
+        }
+
+        Ok(builder
+            .body(bytes)
+            .map_err(|error| Error::FixtureError(error.to_string()))?
+            .into())
+    }
+
+    fn replay_interaction(&self, request: &Request) -> Result<Response, Error> {
+        let method = request.method().to_string();
+        let url = self.scrub_url(request.url());
+
+        let mut fixture = self.state.lock().unwrap();
+        let interaction = fixture
+            .interactions
+            .iter_mut()
+            .find(|interaction| {
+                !interaction.replayed
+                    && interaction.request.method == method
+                    && interaction.request.url == url
+            })
+            .ok_or_else(|| {
+                Error::FixtureError(format!(
+                    "no recorded response for {method} {url} in {}",
+                    self.path.display()
+                ))
+            })?;
+        interaction.replayed = true;
+
+        let response = &interaction.response;
+        let final_url = match &response.redirected_to {
+            Some(redirected_to) => Url::parse(redirected_to).map_err(|_| Error::ParseUrlError)?,
+            None => request.url().clone(),
+        };
+
+        let mut builder = http::Response::builder()
+            .status(response.status)
+            .url(final_url);
+        for (name, value) in &response.headers {
+            builder = builder.header(name, value);
+        }
+
+        Ok(builder
+            .body(response.body.to_bytes())
+            .map_err(|error| Error::FixtureError(error.to_string()))?
+            .into())
+    }
+
+    /// The path and query of `url`, with secret query values scrubbed
+    fn scrub_url(&self, url: &Url) -> String {
+        let mut scrubbed = url.path().to_string();
+
+        if url.query().is_some() {
+            let query = url
+                .query_pairs()
+                .map(|(name, value)| {
+                    let value = if SCRUBBED_FIELDS.contains(&name.as_ref()) {
+                        REDACTED.into()
+                    } else {
+                        value
+                    };
+                    form_urlencoded::Serializer::new(String::new())
+                        .append_pair(&name, &value)
+                        .finish()
+                })
+                .collect::<Vec<_>>()
+                .join("&");
+            scrubbed.push('?');
+            scrubbed.push_str(&query);
+        }
+
+        self.scrub_text_in(&scrubbed)
+    }
+
+    fn scrub_body(&self, body: &[u8]) -> Body {
+        let text = self.scrub_text_in(&String::from_utf8_lossy(body));
+
+        match serde_json::from_str::<Value>(&text) {
+            Ok(mut json) => {
+                scrub_fields(&mut json);
+                Body {
+                    json: Some(json),
+                    text: String::new(),
+                }
+            }
+            Err(_) => Body { json: None, text },
+        }
+    }
+
+    fn scrub_text_in(&self, text: &str) -> String {
+        self.replacements
+            .iter()
+            .fold(text.to_string(), |text, (from, to)| text.replace(from, to))
+    }
+}
+
+impl HttpTransport for RecordingTransport {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            match (&self.mode, &self.inner) {
+                (RecordMode::Record, Some(inner)) => {
+                    self.record_interaction(inner.as_ref(), request).await
+                }
+                _ => self.replay_interaction(&request),
+            }
+        })
+    }
+}
+
+/// Replace the values of [`SCRUBBED_FIELDS`] anywhere in `json`
+fn scrub_fields(json: &mut Value) {
+    match json {
+        Value::Object(object) => {
+            for (name, value) in object.iter_mut() {
+                if SCRUBBED_FIELDS.contains(&name.as_str()) && !value.is_null() {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    scrub_fields(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(scrub_fields),
+        _ => {}
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Fixture {
+    interactions: Vec<Interaction>,
+}
+
+impl Fixture {
+    fn load(path: &Path) -> Result<Self, Error> {
+        let contents = fs::read(path).map_err(|error| {
+            Error::FixtureError(format!("can't read {}: {error}", path.display()))
+        })?;
+
+        serde_json::from_slice(&contents).map_err(|error| {
+            Error::FixtureError(format!("can't parse {}: {error}", path.display()))
+        })
+    }
+
+    fn save(&self, path: &Path) -> Result<(), Error> {
+        let write = || {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, serde_json::to_vec_pretty(self)?)
+        };
+
+        write().map_err(|error: std::io::Error| {
+            Error::FixtureError(format!("can't write {}: {error}", path.display()))
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+    /// Whether the interaction was replayed already, so each one answers only once
+    #[serde(skip)]
+    replayed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    /// The path and query, without the project URL
+    url: String,
+    #[serde(flatten)]
+    body: Body,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    /// Where the server redirected the request to, if anywhere
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    redirected_to: Option<String>,
+    #[serde(flatten)]
+    body: Body,
+}
+
+/// A body, kept as JSON when it is JSON so fixtures stay readable
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Body {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    json: Option<Value>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    text: String,
+}
+
+impl Body {
+    fn to_bytes(&self) -> Vec<u8> {
+        match &self.json {
+            Some(json) => json.to_string().into_bytes(),
+            None => self.text.clone().into_bytes(),
+        }
+    }
+}
@@ -0,0 +1,334 @@
+/*!
+Keeps a [`Session`] fresh in the background.
+
+[`SessionRefresher`] spawns a tokio task which exchanges the refresh token for a new session
+shortly before the access token expires. A small random jitter is added to the refresh time so
+that many processes started together don't all hit the auth server at the same moment. Both can
+be tuned with a [`RefreshPolicy`].
+
+# Example
+
+```rust
+let session = auth_client
+    .login_with_email(&demo_email, &demo_password)
+    .await
+    .unwrap();
+
+let refresher = SessionRefresher::spawn(auth_client.clone(), session);
+
+// Always returns a session that is valid, or about to be refreshed
+let access_token = refresher.current_session().access_token;
+
+refresher.shutdown().await;
+```
+*/
+
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    fmt,
+    hash::{BuildHasher, Hasher},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use tokio::{
+    sync::{oneshot, watch, OnceCell},
+    task::JoinHandle,
+    time::sleep,
+};
+
+use crate::{
+    error::Error,
+    models::{AuthClient, RefreshToken, Session},
+};
+
+/// When [`SessionRefresher`] renews a session
+///
+/// The session is refreshed `refresh_margin` before it expires, minus a random delay of up to
+/// `max_jitter`. For sessions too short lived for these, e.g. with a JWT expiry below the margin,
+/// the margin is capped at half the session's lifetime and the jitter at a quarter of it, so that
+/// the session is still refreshed in its second half rather than right away.
+///
+/// # Example
+/// ```
+/// let policy = RefreshPolicy {
+///     refresh_margin: Duration::from_secs(300),
+///     ..Default::default()
+/// };
+///
+/// let refresher = SessionRefresher::spawn_with_policy(auth_client, session, policy);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshPolicy {
+    /// How long before expiry the session is refreshed. Defaults to 60 seconds.
+    pub refresh_margin: Duration,
+    /// Upper bound of the random delay subtracted from the refresh time. Defaults to 10 seconds,
+    /// set to zero to disable jitter.
+    pub max_jitter: Duration,
+    /// How long to wait before retrying a refresh that failed for a transient reason. Defaults to
+    /// 5 seconds.
+    pub retry_interval: Duration,
+}
+
+impl Default for RefreshPolicy {
+    fn default() -> Self {
+        RefreshPolicy {
+            refresh_margin: Duration::from_secs(60),
+            max_jitter: Duration::from_secs(10),
+            retry_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RefreshPolicy {
+    /// Time to wait until `session` should be refreshed
+    fn refresh_delay(&self, session: &Session) -> Duration {
+        // Sessions which don't say how long they last get the full margin
+        let lifetime = match u64::try_from(session.expires_in) {
+            Ok(seconds) if seconds > 0 => Duration::from_secs(seconds),
+            _ => Duration::MAX,
+        };
+
+        session
+            .time_until_expiry()
+            .saturating_sub(self.refresh_margin.min(lifetime / 2))
+            .saturating_sub(jitter(self.max_jitter.min(lifetime / 4)))
+    }
+}
+
+/// Handle to a background task that refreshes a session before it expires
+///
+/// Dropping the handle stops the task. Use [`SessionRefresher::shutdown`] to stop it and wait
+/// for an in-flight refresh to finish.
+#[derive(Debug)]
+pub struct SessionRefresher {
+    session: watch::Receiver<Session>,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl SessionRefresher {
+    /// Spawn the refresh task on the current tokio runtime, using the default [`RefreshPolicy`]
+    ///
+    /// # Panics
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn(client: AuthClient, session: Session) -> Self {
+        Self::spawn_with_policy(client, session, RefreshPolicy::default())
+    }
+
+    /// Spawn the refresh task on the current tokio runtime, refreshing according to `policy`
+    ///
+    /// # Panics
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn_with_policy(client: AuthClient, session: Session, policy: RefreshPolicy) -> Self {
+        let (session_tx, session_rx) = watch::channel(session);
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let task = tokio::spawn(refresh_loop(client, policy, session_tx, shutdown_rx));
+
+        SessionRefresher {
+            session: session_rx,
+            shutdown: Some(shutdown_tx),
+            task: Some(task),
+        }
+    }
+
+    /// The most recent session
+    pub fn current_session(&self) -> Session {
+        self.session.borrow().clone()
+    }
+
+    /// Subscribe to session changes. The receiver is notified after every successful refresh.
+    pub fn subscribe(&self) -> watch::Receiver<Session> {
+        self.session.clone()
+    }
+
+    /// Whether the refresh task has stopped, either because of a shutdown or because the
+    /// refresh token was rejected by the server
+    pub fn is_finished(&self) -> bool {
+        self.task.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    /// Stop the refresh task and wait for it to exit
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for SessionRefresher {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+async fn refresh_loop(
+    client: AuthClient,
+    policy: RefreshPolicy,
+    session_tx: watch::Sender<Session>,
+    mut shutdown_rx: oneshot::Receiver<()>,
+) {
+    let mut delay = policy.refresh_delay(&session_tx.borrow());
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown_rx => return,
+            _ = sleep(delay) => {}
+        }
+
+        let refresh_token = session_tx.borrow().refresh_token.clone();
+
+        match client.refresh_session(&refresh_token).await {
+            Ok(session) => {
+                delay = policy.refresh_delay(&session);
+                session_tx.send_replace(session);
+            }
+            // The refresh token was rejected, retrying won't help
+            Err(Error::RefreshTokenAlreadyUsed) => return,
+            Err(Error::AuthError { status, .. }) if status.is_client_error() => return,
+            Err(Error::RateLimited { retry_after, .. }) => {
+                delay = retry_after.unwrap_or(policy.retry_interval)
+            }
+            Err(_) => delay = policy.retry_interval,
+        }
+    }
+}
+
+/// Coalesces concurrent refreshes of the same session into a single request
+///
+/// GoTrue rotates refresh tokens, so when several tasks holding the same session refresh at
+/// once, all but the first request fail. `SingleFlightRefresh` sends one request per refresh
+/// token and hands its result, the new session or the error, to everyone who asked with the same
+/// token while it was in flight. Refreshes of different tokens don't wait for each other.
+///
+/// Callers arriving after a refresh settled with an old token get the session it produced, as
+/// long as that session hasn't expired.
+///
+/// Clones share the same state.
+///
+/// # Example
+/// ```
+/// let refresh = SingleFlightRefresh::new(auth_client);
+///
+/// // Both calls resolve to the same new session, only one request is sent
+/// let (a, b) = tokio::join!(
+///     refresh.refresh(&session.refresh_token),
+///     refresh.refresh(&session.refresh_token),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct SingleFlightRefresh {
+    client: AuthClient,
+    flights: Arc<Mutex<Flights>>,
+}
+
+/// The result of a refresh, shared by everyone waiting for it
+type Flight = Arc<OnceCell<Result<Session, Error>>>;
+
+#[derive(Default)]
+struct Flights {
+    /// Refreshes in flight, by the refresh token being exchanged
+    in_flight: HashMap<RefreshToken, Flight>,
+    /// Sessions of settled refreshes, by the refresh token exchanged for them, until they expire
+    refreshed: HashMap<RefreshToken, Session>,
+}
+
+impl fmt::Debug for SingleFlightRefresh {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SingleFlightRefresh")
+            .field("client", &self.client)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SingleFlightRefresh {
+    /// Create a new SingleFlightRefresh using `client` to perform refreshes
+    pub fn new(client: AuthClient) -> Self {
+        SingleFlightRefresh {
+            client,
+            flights: Arc::default(),
+        }
+    }
+
+    /// Exchange `refresh_token` for a new session, or wait for an in-flight exchange of the
+    /// same token and return its result
+    ///
+    /// Errors which can't be copied to the waiting callers, e.g. network errors, reach them as
+    /// [`Error::RefreshFailed`].
+    pub async fn refresh(&self, refresh_token: &RefreshToken) -> Result<Session, Error> {
+        let flight = {
+            let mut flights = self.lock();
+            flights.refreshed.retain(|_, session| !session.is_expired());
+
+            if let Some(session) = flights.refreshed.get(refresh_token) {
+                return Ok(session.clone());
+            }
+
+            flights
+                .in_flight
+                .entry(refresh_token.clone())
+                .or_default()
+                .clone()
+        };
+
+        // Only the caller sending the request gets its result as is
+        let mut own = None;
+        let own_result = &mut own;
+        let shared = flight
+            .get_or_init(|| async move {
+                let result = self.client.refresh_session(refresh_token).await;
+                let shared = share(&result);
+                *own_result = Some(result);
+                shared
+            })
+            .await;
+
+        let Some(result) = own else {
+            return share(shared);
+        };
+
+        let mut flights = self.lock();
+        if flights
+            .in_flight
+            .get(refresh_token)
+            .is_some_and(|settled| Arc::ptr_eq(settled, &flight))
+        {
+            flights.in_flight.remove(refresh_token);
+        }
+        if let Ok(session) = &result {
+            flights
+                .refreshed
+                .insert(refresh_token.clone(), session.clone());
+        }
+
+        result
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Flights> {
+        self.flights
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+/// A copy of the result of a refresh for another caller
+fn share(result: &Result<Session, Error>) -> Result<Session, Error> {
+    match result {
+        Ok(session) => Ok(session.clone()),
+        Err(error) => Err(error.shared_copy()),
+    }
+}
+
+/// A random duration between zero and `max`
+pub(crate) fn jitter(max: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+
+    Duration::from_millis(random % (max.as_millis() as u64 + 1))
+}
@@ -0,0 +1,125 @@
+/*!
+Opt-in retries for requests that failed for a transient reason.
+
+Supabase Auth answers `429 Too Many Requests` when a rate limit is hit, and the API gateway may
+answer `5xx` while the service restarts. With a [`RetryPolicy`] set on the client, idempotent
+requests which fail this way are retried with exponential backoff, waiting as long as the
+server asks for in its `Retry-After` header.
+
+# Example
+
+```rust
+let auth_client = AuthClient::builder()
+    .project_url(project_url)
+    .api_key(api_key)
+    .retry_policy(RetryPolicy::default())
+    .build()
+    .unwrap();
+```
+*/
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    Method, StatusCode,
+};
+
+use crate::refresher::jitter;
+
+/// Which failed requests are retried, and how long to wait in between
+///
+/// The defaults retry `GET`, `HEAD`, `PUT`, `DELETE`, and `OPTIONS` requests up to 3 times in
+/// total when they fail with `429`, `500`, `502`, `503`, or `504`, or can't connect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// How many times a request is sent at most, including the first attempt
+    pub max_attempts: u32,
+    /// How long to wait before the first retry. Doubles with every further retry.
+    pub initial_backoff: Duration,
+    /// Upper bound of the wait between attempts. A response asking to wait longer than this in
+    /// its `Retry-After` header is returned without retrying.
+    pub max_backoff: Duration,
+    /// Shorten each wait by a random amount of up to half of it, so that clients which failed
+    /// together don't retry together
+    pub jitter: bool,
+    /// Response statuses which are retried
+    pub retry_statuses: Vec<StatusCode>,
+    /// Request methods which are retried. Only include methods which are safe to repeat.
+    pub retry_methods: Vec<Method>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+            retry_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_methods: vec![
+                Method::GET,
+                Method::HEAD,
+                Method::PUT,
+                Method::DELETE,
+                Method::OPTIONS,
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub(crate) fn retries_method(&self, method: &Method) -> bool {
+        self.max_attempts > 1 && self.retry_methods.contains(method)
+    }
+
+    pub(crate) fn retries_status(&self, status: StatusCode) -> bool {
+        self.retry_statuses.contains(&status)
+    }
+
+    /// How long to wait before sending attempt number `attempt + 1`, or `None` to give up
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        if let Some(retry_after) = retry_after {
+            return (retry_after <= self.max_backoff).then_some(retry_after);
+        }
+
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            Some(backoff - jitter(backoff / 2))
+        } else {
+            Some(backoff)
+        }
+    }
+}
+
+/// The wait requested by a `Retry-After` header, given either in seconds or as an HTTP date
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
@@ -0,0 +1,348 @@
+/*!
+Persistence for sessions.
+
+A [`SessionStore`] saves the current session somewhere it can be loaded from later, e.g. so a
+CLI tool stays signed in across invocations. Pass one to
+[`ManagedAuthClient::with_store`](crate::managed::ManagedAuthClient::with_store) to have sessions
+saved after every sign in and refresh, and cleared on logout.
+
+Available stores:
+
+- [`MemorySessionStore`], keeps the session in memory
+- `FileSessionStore`, a JSON file readable only by the current user (requires the `file-store` feature)
+- `EncryptedSessionStore`, encrypts the session before handing it to another store (requires the `encrypted-store` feature)
+- `KeyringSessionStore`, the operating system's credential store (requires the `keyring-store` feature)
+*/
+
+use std::{fmt, sync::RwLock};
+
+use crate::{error::Error, models::Session};
+
+#[cfg(feature = "encrypted-store")]
+pub use encrypted::EncryptedSessionStore;
+#[cfg(feature = "file-store")]
+pub use file::FileSessionStore;
+#[cfg(feature = "keyring-store")]
+pub use keyring::KeyringSessionStore;
+
+/// Somewhere a session can be saved and loaded from
+pub trait SessionStore: fmt::Debug + Send + Sync {
+    /// Load the saved session, `None` if nothing is saved
+    fn load(&self) -> Result<Option<Session>, Error>;
+
+    /// Save `session`, replacing any previously saved session
+    fn save(&self, session: &Session) -> Result<(), Error>;
+
+    /// Remove the saved session
+    fn clear(&self) -> Result<(), Error>;
+}
+
+/// Somewhere the serialized session can be saved as bytes
+///
+/// Every `PayloadStore` is a [`SessionStore`] which saves the session as JSON. Implement this
+/// instead of [`SessionStore`] to make a store usable as the inner store of wrappers such as
+/// `EncryptedSessionStore`.
+pub trait PayloadStore: fmt::Debug + Send + Sync {
+    /// Load the saved payload, `None` if nothing is saved
+    fn load_payload(&self) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Save `payload`, replacing any previously saved payload
+    fn save_payload(&self, payload: &[u8]) -> Result<(), Error>;
+
+    /// Remove the saved payload
+    fn clear_payload(&self) -> Result<(), Error>;
+}
+
+impl<T: PayloadStore> SessionStore for T {
+    fn load(&self) -> Result<Option<Session>, Error> {
+        match self.load_payload()? {
+            Some(payload) => Ok(Some(serde_json::from_slice(&payload)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn save(&self, session: &Session) -> Result<(), Error> {
+        self.save_payload(&serde_json::to_vec(session)?)
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        self.clear_payload()
+    }
+}
+
+/// Keeps the session in memory
+#[derive(Debug, Default)]
+pub struct MemorySessionStore {
+    payload: RwLock<Option<Vec<u8>>>,
+}
+
+impl MemorySessionStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PayloadStore for MemorySessionStore {
+    fn load_payload(&self) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.payload.read().unwrap_or_else(|e| e.into_inner()).clone())
+    }
+
+    fn save_payload(&self, payload: &[u8]) -> Result<(), Error> {
+        *self.payload.write().unwrap_or_else(|e| e.into_inner()) = Some(payload.to_vec());
+
+        Ok(())
+    }
+
+    fn clear_payload(&self) -> Result<(), Error> {
+        *self.payload.write().unwrap_or_else(|e| e.into_inner()) = None;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "file-store")]
+mod file {
+    use std::{
+        fs::{self, OpenOptions},
+        io::{ErrorKind, Write},
+        path::{Path, PathBuf},
+        process,
+    };
+
+    use super::PayloadStore;
+    use crate::error::Error;
+
+    /// Saves the session as JSON in a file
+    ///
+    /// Writes are atomic: the session is written to a temporary file next to the target, which
+    /// is then renamed over it. On Unix the file is created with `0600` permissions.
+    ///
+    /// # Example
+    /// ```
+    /// let store = FileSessionStore::new(home_dir.join(".my-cli/session.json"));
+    ///
+    /// let auth_client = ManagedAuthClient::with_store(AuthClient::new_from_env()?, store)?;
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct FileSessionStore {
+        path: PathBuf,
+    }
+
+    impl FileSessionStore {
+        /// Create a store saving the session at `path`
+        pub fn new(path: impl Into<PathBuf>) -> Self {
+            FileSessionStore { path: path.into() }
+        }
+
+        /// Location of the session file
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        fn temp_path(&self) -> PathBuf {
+            let mut file_name = self.path.file_name().unwrap_or_default().to_os_string();
+            file_name.push(format!(".tmp-{}", process::id()));
+
+            self.path.with_file_name(file_name)
+        }
+    }
+
+    impl PayloadStore for FileSessionStore {
+        fn load_payload(&self) -> Result<Option<Vec<u8>>, Error> {
+            match fs::read(&self.path) {
+                Ok(contents) => Ok(Some(contents)),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        }
+
+        fn save_payload(&self, payload: &[u8]) -> Result<(), Error> {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let temp_path = self.temp_path();
+
+            let mut options = OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+            let result = options.open(&temp_path).and_then(|mut file| {
+                file.write_all(payload)?;
+                file.sync_all()
+            });
+
+            if let Err(e) = result.and_then(|_| fs::rename(&temp_path, &self.path)) {
+                let _ = fs::remove_file(&temp_path);
+                return Err(e.into());
+            }
+
+            Ok(())
+        }
+
+        fn clear_payload(&self) -> Result<(), Error> {
+            match fs::remove_file(&self.path) {
+                Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "encrypted-store")]
+mod encrypted {
+    use chacha20poly1305::{
+        aead::{Aead, AeadCore, KeyInit, OsRng},
+        ChaCha20Poly1305, Key, Nonce,
+    };
+
+    use super::PayloadStore;
+    use crate::error::Error;
+
+    const NONCE_LEN: usize = 12;
+
+    /// Encrypts the payload of an inner store with ChaCha20-Poly1305
+    ///
+    /// Useful where no OS keyring is available, such as containers and headless servers. A new
+    /// random nonce is generated for every save and stored in front of the ciphertext.
+    ///
+    /// Keep the key out of the store's location, e.g. in a secret injected through the environment.
+    ///
+    /// # Example
+    /// ```
+    /// let key: [u8; 32] = load_key_from_secret_manager();
+    ///
+    /// let store = EncryptedSessionStore::new(FileSessionStore::new("/var/lib/my-app/session"), key);
+    ///
+    /// let auth_client = ManagedAuthClient::with_store(AuthClient::new_from_env()?, store)?;
+    /// ```
+    pub struct EncryptedSessionStore<S> {
+        inner: S,
+        cipher: ChaCha20Poly1305,
+    }
+
+    impl<S: PayloadStore> EncryptedSessionStore<S> {
+        /// Wrap `inner`, encrypting with the 256 bit `key`
+        pub fn new(inner: S, key: [u8; 32]) -> Self {
+            EncryptedSessionStore {
+                inner,
+                cipher: ChaCha20Poly1305::new(Key::from_slice(&key)),
+            }
+        }
+
+        /// The wrapped store
+        pub fn inner(&self) -> &S {
+            &self.inner
+        }
+    }
+
+    // Implement custom Debug to avoid exposing the key
+    impl<S: std::fmt::Debug> std::fmt::Debug for EncryptedSessionStore<S> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("EncryptedSessionStore")
+                .field("inner", &self.inner)
+                .field("key", &"[REDACTED]")
+                .finish()
+        }
+    }
+
+    impl<S: PayloadStore> PayloadStore for EncryptedSessionStore<S> {
+        fn load_payload(&self) -> Result<Option<Vec<u8>>, Error> {
+            let Some(payload) = self.inner.load_payload()? else {
+                return Ok(None);
+            };
+
+            if payload.len() < NONCE_LEN {
+                return Err(Error::DecryptionError);
+            }
+
+            let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+
+            self.cipher
+                .decrypt(Nonce::from_slice(nonce), ciphertext)
+                .map(Some)
+                .map_err(|_| Error::DecryptionError)
+        }
+
+        fn save_payload(&self, payload: &[u8]) -> Result<(), Error> {
+            let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+            let ciphertext = self
+                .cipher
+                .encrypt(&nonce, payload)
+                .map_err(|_| Error::InternalError)?;
+
+            let mut encrypted = nonce.to_vec();
+            encrypted.extend_from_slice(&ciphertext);
+
+            self.inner.save_payload(&encrypted)
+        }
+
+        fn clear_payload(&self) -> Result<(), Error> {
+            self.inner.clear_payload()
+        }
+    }
+}
+
+#[cfg(feature = "keyring-store")]
+mod keyring {
+    use std::io;
+
+    use keyring::Entry;
+
+    use super::PayloadStore;
+    use crate::error::Error;
+
+    /// Saves the session in the operating system's credential store
+    ///
+    /// That is the Keychain on macOS, the Credential Manager on Windows, and the Secret Service
+    /// (GNOME Keyring, KWallet) on Linux, the secure storage desktop apps such as Tauri apps
+    /// keep secrets in.
+    ///
+    /// # Example
+    /// ```
+    /// let store = KeyringSessionStore::new("com.example.app", "supabase-session")?;
+    ///
+    /// let auth_client = ManagedAuthClient::with_store(AuthClient::new_from_env()?, store)?;
+    /// ```
+    #[derive(Debug)]
+    pub struct KeyringSessionStore {
+        entry: Entry,
+    }
+
+    impl KeyringSessionStore {
+        /// Create a store saving the session as the credential of `user` for `service`,
+        /// usually the app's identifier
+        pub fn new(service: &str, user: &str) -> Result<Self, Error> {
+            Ok(KeyringSessionStore {
+                entry: Entry::new(service, user).map_err(io::Error::other)?,
+            })
+        }
+    }
+
+    impl PayloadStore for KeyringSessionStore {
+        fn load_payload(&self) -> Result<Option<Vec<u8>>, Error> {
+            match self.entry.get_secret() {
+                Ok(secret) => Ok(Some(secret)),
+                Err(keyring::Error::NoEntry) => Ok(None),
+                Err(e) => Err(io::Error::other(e).into()),
+            }
+        }
+
+        fn save_payload(&self, payload: &[u8]) -> Result<(), Error> {
+            self.entry
+                .set_secret(payload)
+                .map_err(|e| io::Error::other(e).into())
+        }
+
+        fn clear_payload(&self) -> Result<(), Error> {
+            match self.entry.delete_credential() {
+                Err(keyring::Error::NoEntry) => Ok(()),
+                result => result.map_err(|e| io::Error::other(e).into()),
+            }
+        }
+    }
+}
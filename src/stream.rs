@@ -0,0 +1,257 @@
+/*!
+Streaming of large admin responses.
+
+[`AuthClient::admin_list_users`](crate::models::AuthClient::admin_list_users) buffers the whole
+page before decoding it, which with `per_page` in the thousands means megabytes per page.
+[`AuthClient::admin_list_users_stream`](crate::models::AuthClient::admin_list_users_stream)
+instead returns a [`UserStream`], which decodes the users one at a time as the body arrives, so
+memory stays flat at about one user and one network chunk.
+
+# Example
+
+```rust
+let params = ListUsersParams::default().per_page(10_000);
+let mut users = auth_client.admin_list_users_stream(params).await?;
+
+while let Some(user) = users.next().await {
+    let user = user?;
+    writer.serialize(&user.email)?;
+}
+```
+*/
+
+use std::fmt;
+
+use reqwest::Response;
+use serde::de;
+use serde_json::Value;
+
+use crate::{error::Error, models::User};
+
+/// The users of an admin user listing, decoded as the response body arrives
+///
+/// Created by
+/// [`AuthClient::admin_list_users_stream`](crate::models::AuthClient::admin_list_users_stream).
+pub struct UserStream {
+    response: Option<Response>,
+    scanner: Scanner,
+}
+
+impl UserStream {
+    pub(crate) fn new(response: Response) -> Self {
+        UserStream {
+            response: Some(response),
+            scanner: Scanner::default(),
+        }
+    }
+
+    /// The next user, `None` once all users were returned
+    ///
+    /// Fails with [`Error::NetworkError`] if the body couldn't be read, and with
+    /// [`Error::ParseError`] if it isn't a user listing. The stream ends after an error.
+    pub async fn next(&mut self) -> Option<Result<User, Error>> {
+        loop {
+            match self.scanner.next_user() {
+                Scan::User(user) => {
+                    let user = serde_json::from_slice(user).map_err(Error::from);
+                    self.scanner.consume();
+                    if user.is_err() {
+                        self.response = None;
+                    }
+                    return Some(user);
+                }
+                Scan::End => {
+                    self.response = None;
+                    return None;
+                }
+                Scan::NeedMore => {}
+            }
+
+            let response = self.response.as_mut()?;
+            match response.chunk().await {
+                Ok(Some(chunk)) => self.scanner.extend(&chunk),
+                Ok(None) => {
+                    self.response = None;
+                    return self.scanner.finish().map(Err);
+                }
+                Err(error) => {
+                    self.response = None;
+                    return Some(Err(error.into()));
+                }
+            }
+        }
+    }
+
+    /// Collect the remaining users
+    pub async fn collect(mut self) -> Result<Vec<User>, Error> {
+        let mut users = Vec::new();
+        while let Some(user) = self.next().await {
+            users.push(user?);
+        }
+
+        Ok(users)
+    }
+}
+
+impl fmt::Debug for UserStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserStream")
+            .field("finished", &self.response.is_none())
+            .finish()
+    }
+}
+
+/// What [`Scanner::next_user`] found in the buffered body
+enum Scan<'a> {
+    /// The JSON of the next user
+    User(&'a [u8]),
+    /// The `users` array ended
+    End,
+    /// The buffered body ends before the next user does
+    NeedMore,
+}
+
+/// Where the scanner is in a `{"users": [...], ...}` body
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum Phase {
+    /// Looking for the `users` key in the top level object
+    #[default]
+    Seek,
+    /// Between the users of the array
+    Array,
+    /// Past the end of the array
+    Done,
+}
+
+/// Finds the users of a user listing in a body which arrives in chunks
+///
+/// Only the bytes of the user being scanned are buffered, earlier ones are dropped.
+#[derive(Debug, Default)]
+struct Scanner {
+    buffer: Vec<u8>,
+    /// How far `buffer` was scanned
+    position: usize,
+    phase: Phase,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Where the string being scanned starts
+    string_start: usize,
+    /// Whether the next string in the top level object is a key
+    expect_key: bool,
+    /// Whether the last key of the top level object was `users`
+    users_key: bool,
+    /// Where the user being scanned starts
+    user_start: Option<usize>,
+    /// The end of the user returned last, to drop from `buffer`
+    consumed: usize,
+}
+
+impl Scanner {
+    fn extend(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Drop the bytes of the user returned last
+    fn consume(&mut self) {
+        self.buffer.drain(..self.consumed);
+        self.position -= self.consumed;
+        self.consumed = 0;
+    }
+
+    fn next_user(&mut self) -> Scan<'_> {
+        while self.position < self.buffer.len() {
+            let byte = self.buffer[self.position];
+            let index = self.position;
+            self.position += 1;
+
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                    if self.phase == Phase::Seek && self.depth == 1 && self.expect_key {
+                        self.users_key = &self.buffer[self.string_start..index] == b"users";
+                        self.expect_key = false;
+                    }
+                }
+                continue;
+            }
+
+            match (self.phase, byte) {
+                (_, b'"') => {
+                    self.in_string = true;
+                    self.string_start = index + 1;
+                }
+                (Phase::Seek, b'[') if self.depth == 1 && self.users_key => {
+                    self.phase = Phase::Array;
+                    self.depth = 0;
+                    self.buffer.drain(..self.position);
+                    self.position = 0;
+                }
+                (Phase::Seek, b'{' | b'[') => {
+                    self.depth += 1;
+                    if self.depth == 1 {
+                        self.expect_key = true;
+                    }
+                }
+                (Phase::Seek, b'}' | b']') => self.depth = self.depth.saturating_sub(1),
+                (Phase::Seek, b',') if self.depth == 1 => self.expect_key = true,
+                (Phase::Array, b']') if self.depth == 0 => {
+                    self.phase = Phase::Done;
+                    self.buffer.clear();
+                    self.position = 0;
+                    return Scan::End;
+                }
+                (Phase::Array, b'{' | b'[') => {
+                    if self.depth == 0 {
+                        self.user_start = Some(index);
+                    }
+                    self.depth += 1;
+                }
+                (Phase::Array, b'}' | b']') => {
+                    self.depth = self.depth.saturating_sub(1);
+                    if self.depth == 0 {
+                        if let Some(start) = self.user_start.take() {
+                            self.consumed = self.position;
+                            return Scan::User(&self.buffer[start..self.position]);
+                        }
+                    }
+                }
+                (Phase::Array, b',') if self.depth == 0 => {
+                    self.buffer.drain(..self.position);
+                    self.position = 0;
+                }
+                _ => {}
+            }
+        }
+
+        match self.phase {
+            Phase::Done => Scan::End,
+            _ => Scan::NeedMore,
+        }
+    }
+
+    /// The error for a body which ended before the `users` array did, if it did
+    fn finish(&mut self) -> Option<Error> {
+        let body = match (self.phase, self.user_start) {
+            (Phase::Done, _) => return None,
+            (Phase::Array, Some(start)) => &self.buffer[start..],
+            (Phase::Array, None) => &b"["[..],
+            (Phase::Seek, _) => &self.buffer[..],
+        };
+
+        let error = match self.phase {
+            Phase::Seek => match serde_json::from_slice::<Value>(body) {
+                Ok(_) => de::Error::missing_field("users"),
+                Err(error) => error,
+            },
+            _ => serde_json::from_slice::<User>(body).err()?,
+        };
+        self.phase = Phase::Done;
+
+        Some(error.into())
+    }
+}
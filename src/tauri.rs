@@ -0,0 +1,135 @@
+/*!
+Helpers for desktop apps built with [Tauri](https://tauri.app), enabled with the `tauri` feature.
+
+- [`KeyringSessionStore`] keeps the session in the operating system's credential store.
+- [`DeepLinkOAuth`] signs in with an OAuth provider in the system browser, which then redirects
+  to the app's custom URL scheme, e.g. `com.example.app://auth/callback`. The PKCE flow is used,
+  so the session itself never appears in the URL.
+
+Neither depends on Tauri, so they work with other desktop toolkits too. Open the URL with
+`tauri-plugin-opener` and receive the redirect with `tauri-plugin-deep-link`.
+
+# Example
+
+```rust
+let store = KeyringSessionStore::new("com.example.app", "supabase-session")?;
+let auth_client = ManagedAuthClient::with_store(AuthClient::new_from_env()?, store)?;
+
+let oauth = DeepLinkOAuth::start(
+    auth_client.client(),
+    Provider::Github,
+    "com.example.app://auth/callback",
+    None,
+)?;
+app.opener().open_url(oauth.url().as_str(), None::<&str>)?;
+
+app.deep_link().on_open_url(move |event| {
+    for url in event.urls() {
+        if oauth.matches(&url) {
+            let session = oauth.complete(auth_client.client(), &url).await?;
+            auth_client.replace_session(session)?;
+        }
+    }
+});
+```
+*/
+
+use std::fmt;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub use crate::store::KeyringSessionStore;
+use crate::{
+    error::Error,
+    models::{AuthClient, LoginWithOAuthOptions, Provider, Session, REDACTED},
+};
+
+/// An OAuth sign in waiting for the provider to redirect back to the app
+///
+/// Keep it until the deep link arrives, it holds the PKCE code verifier the session is
+/// exchanged with.
+#[derive(Clone)]
+pub struct DeepLinkOAuth {
+    url: Url,
+    redirect_to: Url,
+    code_verifier: String,
+}
+
+impl DeepLinkOAuth {
+    /// Start signing in with `provider`, redirecting to `redirect_to` afterwards
+    ///
+    /// `redirect_to` has to be in the project's allowed redirect URLs.
+    pub fn start(
+        auth_client: &AuthClient,
+        provider: Provider,
+        redirect_to: &str,
+        options: Option<LoginWithOAuthOptions>,
+    ) -> Result<Self, Error> {
+        let redirect_to = Url::parse(redirect_to).map_err(|_| Error::ParseUrlError)?;
+
+        // 64 random hex characters, within the 43 to 128 allowed by RFC 7636
+        let code_verifier = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+
+        let options = options
+            .unwrap_or_default()
+            .redirect_to(redirect_to.as_str())
+            .query_param("code_challenge", code_challenge)
+            .query_param("code_challenge_method", "s256");
+
+        let response = auth_client.login_with_oauth(provider, Some(options))?;
+
+        Ok(DeepLinkOAuth {
+            url: response.url,
+            redirect_to,
+            code_verifier,
+        })
+    }
+
+    /// The URL to open in the system browser
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Whether `url` is the redirect this sign in is waiting for
+    pub fn matches(&self, url: &Url) -> bool {
+        url.scheme() == self.redirect_to.scheme()
+            && url.host_str() == self.redirect_to.host_str()
+            && url.path() == self.redirect_to.path()
+    }
+
+    /// Exchange the code the deep link `url` carries for a session
+    ///
+    /// Errors the provider or Supabase Auth redirected with are returned as
+    /// [`Error::AuthError`], see [`Error::from_redirect_url`]. Fails with
+    /// [`Error::ParseUrlError`] if `url` carries neither an error nor a code.
+    pub async fn complete(&self, auth_client: &AuthClient, url: &Url) -> Result<Session, Error> {
+        if let Some(error) = Error::from_redirect_url(url) {
+            return Err(error);
+        }
+
+        let code = url
+            .query_pairs()
+            .find(|(name, _)| name == "code")
+            .map(|(_, code)| code.into_owned())
+            .ok_or(Error::ParseUrlError)?;
+
+        auth_client
+            .exchange_code_for_session(&code, &self.code_verifier)
+            .await
+    }
+}
+
+// Implement custom Debug to avoid exposing the code verifier
+impl fmt::Debug for DeepLinkOAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeepLinkOAuth")
+            .field("url", &self.url)
+            .field("redirect_to", &self.redirect_to)
+            .field("code_verifier", &REDACTED)
+            .finish()
+    }
+}
@@ -0,0 +1,360 @@
+/*!
+A mock Supabase Auth server for tests, enabled with the `testing` feature.
+
+[`MockGoTrue`] runs a [wiremock](https://docs.rs/wiremock) server on a random local port and
+answers with the JSON GoTrue returns, so auth flows can be tested without a Supabase project.
+Access tokens are signed with [`JWT_SECRET`], which the [`MockGoTrue::client`] is configured
+with, so they pass [`AuthClient::verify_access_token`] too.
+
+Endpoints answer only once they are mocked, other requests get a `404`. Mocked errors take
+precedence over mocked successes for the same endpoint.
+
+# Example
+
+```rust
+#[tokio::test]
+async fn signs_in() {
+    let gotrue = MockGoTrue::start().await;
+    gotrue.mock_login("demo@example.com").await;
+
+    let session = gotrue
+        .client()
+        .login_with_email("demo@example.com", "password")
+        .await
+        .unwrap();
+
+    assert_eq!(session.user.email, "demo@example.com");
+}
+```
+*/
+
+use std::{collections::HashMap, sync::Mutex};
+
+use chrono::{SecondsFormat, Utc};
+use jsonwebtoken::{EncodingKey, Header};
+use serde_json::{json, Value};
+use uuid::Uuid;
+use wiremock::{
+    matchers::{body_partial_json, method, path, query_param},
+    Mock, MockServer, Request, ResponseTemplate,
+};
+
+use crate::{
+    error::ErrorCode,
+    models::{AuthClient, Session, User, AUTH_V1},
+};
+
+/// The JWT secret the mock server signs access tokens with, the default of local Supabase
+pub const JWT_SECRET: &str = "super-secret-jwt-token-with-at-least-32-characters-long";
+
+/// How long mocked access tokens are valid, in seconds
+const EXPIRES_IN: i64 = 3600;
+
+/// A mock Supabase Auth server, see the [module docs](self)
+#[derive(Debug)]
+pub struct MockGoTrue {
+    server: MockServer,
+    /// Ids of the users sessions were mocked for, so each email keeps its id
+    users: Mutex<HashMap<String, Uuid>>,
+}
+
+impl MockGoTrue {
+    /// Start a server with nothing mocked
+    pub async fn start() -> Self {
+        MockGoTrue {
+            server: MockServer::start().await,
+            users: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The project URL of the server
+    pub fn url(&self) -> String {
+        self.server.uri()
+    }
+
+    /// The underlying server, to mount mocks this type doesn't provide
+    pub fn server(&self) -> &MockServer {
+        &self.server
+    }
+
+    /// A client for the server, with an anon key and [`JWT_SECRET`]
+    pub fn client(&self) -> AuthClient {
+        let now = Utc::now().timestamp();
+        let anon_key = sign(&json!({
+            "iss": "supabase-demo",
+            "role": "anon",
+            "iat": now,
+            "exp": now + 10 * 365 * 24 * 3600,
+        }));
+
+        AuthClient::new(self.url(), anon_key, JWT_SECRET)
+    }
+
+    /// A session of the user with `email`, as returned by the mocked endpoints
+    pub fn session(&self, email: &str) -> Session {
+        serde_json::from_value(self.session_json(email, "password"))
+            .expect("mocked sessions are valid")
+    }
+
+    /// The user with `email`, as returned by the mocked endpoints
+    pub fn user(&self, email: &str) -> User {
+        serde_json::from_value(self.user_json(email)).expect("mocked users are valid")
+    }
+
+    /// Sign ins with `email` and any password succeed
+    pub async fn mock_login(&self, email: &str) {
+        Mock::given(method("POST"))
+            .and(path(format!("{AUTH_V1}/token")))
+            .and(query_param("grant_type", "password"))
+            .and(body_partial_json(json!({ "email": email })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(self.session_json(email, "password")),
+            )
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Sign ins fail with `invalid_credentials`, like a wrong password does
+    pub async fn mock_invalid_credentials(&self) {
+        Mock::given(method("POST"))
+            .and(path(format!("{AUTH_V1}/token")))
+            .and(query_param("grant_type", "password"))
+            .respond_with(error(
+                400,
+                ErrorCode::InvalidCredentials,
+                "Invalid login credentials",
+            ))
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Refreshing any session returns a new session of the user with `email`
+    pub async fn mock_refresh(&self, email: &str) {
+        let session = self.session_json(email, "token_refresh");
+
+        Mock::given(method("POST"))
+            .and(path(format!("{AUTH_V1}/token")))
+            .and(query_param("grant_type", "refresh_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(session))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Signing up with `email` signs the new user in, like with email confirmation disabled
+    pub async fn mock_sign_up(&self, email: &str) {
+        Mock::given(method("POST"))
+            .and(path(format!("{AUTH_V1}/signup")))
+            .and(body_partial_json(json!({ "email": email })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(self.session_json(email, "password")),
+            )
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Signing up with `email` sends a confirmation email, like with email confirmation enabled
+    pub async fn mock_sign_up_with_confirmation(&self, email: &str) {
+        let mut user = self.user_json(email);
+        user["confirmation_sent_at"] = json!(timestamp());
+        for field in ["email_confirmed_at", "confirmed_at", "last_sign_in_at"] {
+            user.as_object_mut().unwrap().remove(field);
+        }
+
+        Mock::given(method("POST"))
+            .and(path(format!("{AUTH_V1}/signup")))
+            .and(body_partial_json(json!({ "email": email })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(user))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Sending OTPs and magic links succeeds, SMS get a message id
+    pub async fn mock_otp(&self) {
+        let respond = |request: &Request| {
+            let body = serde_json::from_slice::<Value>(&request.body).unwrap_or_default();
+
+            match body.get("phone") {
+                Some(_) => ResponseTemplate::new(200).set_body_json(json!({
+                    "message_id": format!("SM{}", Uuid::new_v4().simple()),
+                })),
+                None => ResponseTemplate::new(200).set_body_json(json!({})),
+            }
+        };
+
+        for endpoint in ["otp", "magiclink"] {
+            Mock::given(method("POST"))
+                .and(path(format!("{AUTH_V1}/{endpoint}")))
+                .respond_with(respond)
+                .mount(&self.server)
+                .await;
+        }
+    }
+
+    /// Verifying any OTP signs in the user with `email`
+    pub async fn mock_verify_otp(&self, email: &str) {
+        Mock::given(method("POST"))
+            .and(path(format!("{AUTH_V1}/verify")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(self.session_json(email, "otp")))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Verifying OTPs fails with `otp_expired`, like a wrong or expired code does
+    pub async fn mock_invalid_otp(&self) {
+        Mock::given(method("POST"))
+            .and(path(format!("{AUTH_V1}/verify")))
+            .respond_with(error(
+                403,
+                ErrorCode::OtpExpired,
+                "Token has expired or is invalid",
+            ))
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Getting the user of any access token returns the user with `email`
+    pub async fn mock_user(&self, email: &str) {
+        Mock::given(method("GET"))
+            .and(path(format!("{AUTH_V1}/user")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(self.user_json(email)))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Signing out succeeds
+    pub async fn mock_logout(&self) {
+        Mock::given(method("POST"))
+            .and(path(format!("{AUTH_V1}/logout")))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Requests to `endpoint`, e.g. `"/token"`, are rate limited
+    pub async fn mock_rate_limited(&self, endpoint: &str) {
+        self.mock_error(
+            endpoint,
+            429,
+            ErrorCode::OverRequestRateLimit,
+            "Request rate limit reached",
+        )
+        .await
+    }
+
+    /// Requests to `endpoint`, e.g. `"/signup"`, fail with `status` and `code`
+    pub async fn mock_error(&self, endpoint: &str, status: u16, code: ErrorCode, message: &str) {
+        Mock::given(path(format!("{AUTH_V1}{endpoint}")))
+            .respond_with(error(status, code, message))
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    fn user_id(&self, email: &str) -> Uuid {
+        *self
+            .users
+            .lock()
+            .unwrap()
+            .entry(email.to_string())
+            .or_insert_with(Uuid::new_v4)
+    }
+
+    fn user_json(&self, email: &str) -> Value {
+        let id = self.user_id(email);
+        let now = timestamp();
+
+        json!({
+            "id": id,
+            "aud": "authenticated",
+            "role": "authenticated",
+            "email": email,
+            "email_confirmed_at": now,
+            "phone": "",
+            "confirmed_at": now,
+            "last_sign_in_at": now,
+            "app_metadata": { "provider": "email", "providers": ["email"] },
+            "user_metadata": {
+                "email": email,
+                "email_verified": true,
+                "phone_verified": false,
+                "sub": id,
+            },
+            "identities": [{
+                "identity_id": Uuid::new_v4(),
+                "id": id,
+                "user_id": id,
+                "identity_data": {
+                    "email": email,
+                    "email_verified": false,
+                    "phone_verified": false,
+                    "sub": id,
+                },
+                "provider": "email",
+                "last_sign_in_at": now,
+                "created_at": now,
+                "updated_at": now,
+                "email": email,
+            }],
+            "created_at": now,
+            "updated_at": now,
+            "is_anonymous": false,
+        })
+    }
+
+    /// A session as GoTrue returns it, signed in with the `amr` method `method`
+    fn session_json(&self, email: &str, method: &str) -> Value {
+        let user = self.user_json(email);
+        let now = Utc::now().timestamp();
+
+        let access_token = sign(&json!({
+            "iss": format!("{}{AUTH_V1}", self.url()),
+            "sub": user["id"],
+            "aud": "authenticated",
+            "exp": now + EXPIRES_IN,
+            "iat": now,
+            "email": email,
+            "phone": "",
+            "app_metadata": user["app_metadata"],
+            "user_metadata": user["user_metadata"],
+            "role": "authenticated",
+            "aal": "aal1",
+            "amr": [{ "method": method, "timestamp": now }],
+            "session_id": Uuid::new_v4(),
+            "is_anonymous": false,
+        }));
+
+        json!({
+            "access_token": access_token,
+            "token_type": "bearer",
+            "expires_in": EXPIRES_IN,
+            "expires_at": now + EXPIRES_IN,
+            "refresh_token": &Uuid::new_v4().simple().to_string()[..12],
+            "user": user,
+        })
+    }
+}
+
+/// An error response in GoTrue's format
+fn error(status: u16, code: ErrorCode, message: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_json(json!({
+        "code": status,
+        "error_code": code.as_str(),
+        "msg": message,
+    }))
+}
+
+fn sign(claims: &Value) -> String {
+    jsonwebtoken::encode(
+        &Header::default(),
+        claims,
+        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+    )
+    .expect("mocked tokens can be signed")
+}
+
+/// The current time, formatted like GoTrue does
+fn timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true)
+}
@@ -0,0 +1,91 @@
+/*!
+A [tonic](https://docs.rs/tonic) interceptor verifying Supabase access tokens, enabled with the
+`tonic` feature.
+
+[`SupabaseInterceptor`] reads the token from the `authorization: Bearer` metadata of every call
+and verifies it locally with the project's JWT secret, see [`AuthClient::verify_access_token`].
+The verified [`Claims`](crate::models::Claims) and the
+[`AccessToken`](crate::models::AccessToken) are inserted into the request extensions. Calls
+without a valid token fail with `Unauthenticated` and never reach the service.
+
+Interceptors can't wait for the server, so projects signing their tokens with asymmetric keys
+should put [`SupabaseAuthLayer`](crate::tower::SupabaseAuthLayer) from the `tower` feature in
+front of their tonic server instead, which fetches the signing keys as needed.
+
+# Example
+
+```rust
+let interceptor = SupabaseInterceptor::new(auth_client)?;
+
+Server::builder()
+    .add_service(GreeterServer::with_interceptor(greeter, interceptor))
+    .serve(address)
+    .await?;
+
+// In the service
+let claims = request.extensions().get::<Claims>().unwrap();
+```
+*/
+
+use tonic::{service::Interceptor, Request, Status};
+
+use crate::{client::parse_bearer, error::Error, models::AuthClient};
+
+/// Verifies the access token of every call, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct SupabaseInterceptor {
+    client: AuthClient,
+    allow_anonymous: bool,
+}
+
+impl SupabaseInterceptor {
+    /// Verify access tokens with the JWT secret of `client`
+    ///
+    /// Fails if the client has no JWT secret, since tokens couldn't be verified without waiting
+    /// for the server.
+    pub fn new(client: AuthClient) -> Result<Self, Error> {
+        if client.jwt_secret.is_empty() {
+            return Err(Error::MissingConfiguration("jwt_secret"));
+        }
+
+        Ok(SupabaseInterceptor {
+            client,
+            allow_anonymous: false,
+        })
+    }
+
+    /// Let calls without a token through, without claims in their extensions. Calls with an
+    /// invalid token are still rejected.
+    pub fn allow_anonymous(mut self, allow_anonymous: bool) -> Self {
+        self.allow_anonymous = allow_anonymous;
+        self
+    }
+}
+
+impl Interceptor for SupabaseInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(value) = request.metadata().get("authorization") else {
+            if self.allow_anonymous {
+                return Ok(request);
+            }
+
+            return Err(Status::unauthenticated("missing access token"));
+        };
+
+        let access_token = value
+            .to_str()
+            .ok()
+            .and_then(parse_bearer)
+            .ok_or_else(|| Status::unauthenticated("malformed authorization metadata"))?;
+
+        let claims = self
+            .client
+            .verify_access_token(&access_token)
+            .map_err(|_| Status::unauthenticated("invalid access token"))?;
+
+        request.extensions_mut().insert(access_token);
+        request.extensions_mut().insert(claims);
+
+        Ok(request)
+    }
+}
@@ -0,0 +1,146 @@
+/*!
+A [tower](https://docs.rs/tower) middleware verifying Supabase access tokens, enabled with the
+`tower` feature.
+
+[`SupabaseAuthLayer`] works with any service taking an [`http::Request`], so it can sit in front
+of axum, tonic, and plain hyper services alike. For every request it verifies the access token
+from the `Authorization: Bearer` header, or else the `@supabase/ssr` auth cookie, with
+[`AuthClient::get_claims`]. The verified [`Claims`] and the [`AccessToken`] are inserted into the
+request extensions. Requests without a valid token are answered with `401 Unauthorized` and never
+reach the inner service. If the token couldn't be verified because Supabase Auth is unreachable or
+failing, they are answered with `503 Service Unavailable` instead, so that an outage doesn't look
+like being signed out.
+
+# Example
+
+```rust
+let app = Router::new()
+    .route("/me", get(|Extension(claims): Extension<Claims>| async move { claims.email }))
+    .layer(SupabaseAuthLayer::new(auth_client));
+```
+*/
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use http::{header::WWW_AUTHENTICATE, HeaderValue, Request, Response, StatusCode};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{
+    error::Error,
+    models::{AccessToken, AuthClient, Claims},
+};
+
+/// Wraps services in [`SupabaseAuth`]
+#[derive(Debug, Clone)]
+pub struct SupabaseAuthLayer {
+    client: AuthClient,
+    allow_anonymous: bool,
+}
+
+impl SupabaseAuthLayer {
+    /// Verify access tokens with `client`
+    pub fn new(client: AuthClient) -> Self {
+        SupabaseAuthLayer {
+            client,
+            allow_anonymous: false,
+        }
+    }
+
+    /// Let requests without a token through, without claims in their extensions. Requests with an
+    /// invalid token are still rejected.
+    pub fn allow_anonymous(mut self, allow_anonymous: bool) -> Self {
+        self.allow_anonymous = allow_anonymous;
+        self
+    }
+}
+
+impl<S> Layer<S> for SupabaseAuthLayer {
+    type Service = SupabaseAuth<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SupabaseAuth {
+            inner,
+            client: self.client.clone(),
+            allow_anonymous: self.allow_anonymous,
+        }
+    }
+}
+
+/// A service which only passes requests with a valid access token on to `inner`, created by
+/// [`SupabaseAuthLayer`]
+#[derive(Debug, Clone)]
+pub struct SupabaseAuth<S> {
+    inner: S,
+    client: AuthClient,
+    allow_anonymous: bool,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for SupabaseAuth<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    ReqBody: Send + 'static,
+    ResBody: Default,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        // The clone may not be ready, so call the service that was polled and keep the clone
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let client = self.client.clone();
+        let allow_anonymous = self.allow_anonymous;
+
+        Box::pin(async move {
+            match verify(&client, request.headers()).await {
+                Ok((access_token, claims)) => {
+                    request.extensions_mut().insert(access_token);
+                    request.extensions_mut().insert(claims);
+                }
+                Err(Error::NotAuthenticated) if allow_anonymous => {}
+                Err(error) if error.is_unavailable() => return Ok(unavailable()),
+                Err(_) => return Ok(unauthorized()),
+            }
+
+            inner.call(request).await
+        })
+    }
+}
+
+/// The access token a request was made with, and its verified claims
+async fn verify(
+    client: &AuthClient,
+    headers: &http::HeaderMap,
+) -> Result<(AccessToken, Claims), Error> {
+    let access_token = client.access_token_from_headers(headers)?;
+    let claims = client.get_claims(&access_token).await?;
+
+    Ok((access_token, claims))
+}
+
+fn unauthorized<B: Default>() -> Response<B> {
+    let mut response = Response::new(B::default());
+    *response.status_mut() = StatusCode::UNAUTHORIZED;
+    response
+        .headers_mut()
+        .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+
+    response
+}
+
+fn unavailable<B: Default>() -> Response<B> {
+    let mut response = Response::new(B::default());
+    *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    response
+}
@@ -0,0 +1,76 @@
+/*!
+The HTTP layer requests to Supabase Auth are sent through.
+
+[`AuthClient`](crate::models::AuthClient) builds every request with `reqwest`, then hands it to
+an [`HttpTransport`] to execute. By default that is the client's [`reqwest::Client`], but any
+backend can be plugged in with
+[`AuthClientBuilder::transport`](crate::builder::AuthClientBuilder::transport), e.g. a test
+double answering with canned responses, or an adapter for another HTTP library.
+
+Responses are returned as [`reqwest::Response`], which can be created from any
+[`http::Response`](https://docs.rs/http/latest/http/response/struct.Response.html).
+
+# Example
+
+```rust
+#[derive(Debug)]
+struct Healthy;
+
+impl HttpTransport for Healthy {
+    fn execute(&self, _request: Request) -> TransportFuture<'_> {
+        Box::pin(async {
+            let response = http::Response::builder()
+                .status(200)
+                .body(r#"{"version":"v2","name":"GoTrue","description":""}"#)
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+let auth_client = AuthClient::builder()
+    .project_url("http://localhost:54321")
+    .api_key(api_key)
+    .transport(Healthy)
+    .build()?;
+```
+*/
+
+use std::{fmt::Debug, future::Future, pin::Pin};
+
+use reqwest::{Client, Request, Response};
+
+use crate::error::Error;
+
+/// The future returned by [`HttpTransport::execute`]
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<Response, Error>> + Send + 'a>>;
+
+/// Executes the requests of an [`AuthClient`](crate::models::AuthClient)
+///
+/// Implementations only send the request and return the response. Headers, timeouts, retries,
+/// and rate limits are applied by the client, a transport should honor
+/// [`Request::timeout`] though. Connection failures should be reported as
+/// [`Error::NetworkError`] so that they are retried.
+pub trait HttpTransport: Debug + Send + Sync {
+    fn execute(&self, request: Request) -> TransportFuture<'_>;
+}
+
+impl HttpTransport for Client {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move { Ok(Client::execute(self, request).await?) })
+    }
+}
+
+/// The path of `url` with ids replaced by `{id}`, a low cardinality name for the endpoint
+#[cfg(any(feature = "otel", feature = "metrics"))]
+pub(crate) fn route(url: &reqwest::Url) -> String {
+    url.path()
+        .split('/')
+        .map(|segment| match segment.parse::<uuid::Uuid>() {
+            Ok(_) => "{id}",
+            Err(_) => segment,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,79 @@
+/*!
+An [`AuthClient`] bound to a single user's access token.
+
+Request handlers usually extract the access token once and then make several calls on behalf of
+the same user. [`UserClient`] holds on to the token so it doesn't need to be passed to every call.
+
+# Example
+
+```rust
+let user_client = auth_client.with_token(&access_token);
+
+let user = user_client.get_user().await.unwrap();
+
+user_client.logout(None).await.unwrap();
+```
+*/
+
+use std::fmt;
+
+use crate::{
+    error::Error,
+    models::{AccessToken, AuthClient, LogoutScope, UpdatedUser, User},
+};
+
+/// An [`AuthClient`] which acts on behalf of the user owning `access_token`
+///
+/// Created with [`AuthClient::with_token`]. Unlike
+/// [`ManagedAuthClient`](crate::managed::ManagedAuthClient), the token is never refreshed.
+#[derive(Clone)]
+pub struct UserClient {
+    client: AuthClient,
+    access_token: AccessToken,
+}
+
+impl fmt::Debug for UserClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserClient")
+            .field("client", &self.client)
+            .field("access_token", &self.access_token)
+            .finish()
+    }
+}
+
+impl UserClient {
+    /// Bind `client` to `access_token`
+    pub fn new(client: AuthClient, access_token: impl Into<AccessToken>) -> Self {
+        UserClient {
+            client,
+            access_token: access_token.into(),
+        }
+    }
+
+    /// The underlying stateless client
+    pub fn client(&self) -> &AuthClient {
+        &self.client
+    }
+
+    /// The access token used for every request
+    pub fn access_token(&self) -> &AccessToken {
+        &self.access_token
+    }
+
+    /// Return the user owning the access token. See [`AuthClient::get_user`].
+    pub async fn get_user(&self) -> Result<User, Error> {
+        self.client.get_user(&self.access_token).await
+    }
+
+    /// Update the user owning the access token. See [`AuthClient::update_user`].
+    pub async fn update_user(&self, updated_user: UpdatedUser) -> Result<User, Error> {
+        self.client
+            .update_user(updated_user, &self.access_token)
+            .await
+    }
+
+    /// Log out the user owning the access token. See [`AuthClient::logout`].
+    pub async fn logout(&self, scope: Option<LogoutScope>) -> Result<(), Error> {
+        self.client.logout(scope, &self.access_token).await
+    }
+}
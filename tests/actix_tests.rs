@@ -0,0 +1,134 @@
+#![cfg(feature = "actix")]
+
+mod common;
+
+use actix_web::{
+    http::StatusCode,
+    test::{self, TestRequest},
+    web, App, FromRequest, HttpMessage, ResponseError,
+};
+
+use supabase_auth::{
+    actix::{AuthRejection, SupabaseAuth, SupabaseClaims},
+    error::Error,
+    models::Claims,
+};
+
+use common::{access_token, auth_client};
+
+async fn me(SupabaseClaims(claims): SupabaseClaims) -> String {
+    claims.email
+}
+
+async fn whoami(claims: Option<web::ReqData<Claims>>) -> String {
+    match claims {
+        Some(claims) => claims.email.clone(),
+        None => "anonymous".to_string(),
+    }
+}
+
+#[actix_web::test]
+async fn claims_are_extracted_from_the_http_request() {
+    let request = TestRequest::default()
+        .app_data(web::Data::new(auth_client()))
+        .insert_header(("authorization", format!("Bearer {}", access_token())))
+        .to_http_request();
+
+    let SupabaseClaims(claims) = SupabaseClaims::extract(&request).await.unwrap();
+    assert_eq!(claims.email, "demo@example.com");
+}
+
+#[actix_web::test]
+async fn claims_verified_by_the_middleware_are_reused() {
+    let verified = TestRequest::default()
+        .app_data(web::Data::new(auth_client()))
+        .insert_header(("authorization", format!("Bearer {}", access_token())))
+        .to_http_request();
+    let SupabaseClaims(claims) = SupabaseClaims::extract(&verified).await.unwrap();
+
+    let request = TestRequest::default().to_http_request();
+    request.extensions_mut().insert(claims);
+
+    // No client is registered, so this only succeeds if the extensions are used
+    let SupabaseClaims(claims) = SupabaseClaims::extract(&request).await.unwrap();
+    assert_eq!(claims.email, "demo@example.com");
+}
+
+#[actix_web::test]
+async fn missing_tokens_and_clients_are_rejected() {
+    let request = TestRequest::default()
+        .app_data(web::Data::new(auth_client()))
+        .to_http_request();
+    let rejection = SupabaseClaims::extract(&request).await.unwrap_err();
+    assert!(matches!(rejection, AuthRejection(Error::NotAuthenticated)));
+    assert_eq!(rejection.status_code(), StatusCode::UNAUTHORIZED);
+    let response = rejection.error_response();
+    assert_eq!(
+        response.headers().get("www-authenticate").unwrap(),
+        "Bearer"
+    );
+
+    let request = TestRequest::default()
+        .insert_header(("authorization", format!("Bearer {}", access_token())))
+        .to_http_request();
+    let rejection = SupabaseClaims::extract(&request).await.unwrap_err();
+    assert_eq!(rejection.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[actix_web::test]
+async fn middleware_injects_claims_and_rejects_invalid_tokens() {
+    let app = test::init_service(
+        App::new()
+            .wrap(SupabaseAuth::new(auth_client()))
+            .route("/me", web::get().to(me))
+            .route("/whoami", web::get().to(whoami)),
+    )
+    .await;
+
+    let request = TestRequest::get()
+        .uri("/whoami")
+        .insert_header(("authorization", format!("Bearer {}", access_token())))
+        .to_request();
+    let body = test::call_and_read_body(&app, request).await;
+    assert_eq!(body, "demo@example.com");
+
+    // `SupabaseClaims` reuses the claims without needing a client in the app data
+    let request = TestRequest::get()
+        .uri("/me")
+        .insert_header(("authorization", format!("Bearer {}", access_token())))
+        .to_request();
+    let body = test::call_and_read_body(&app, request).await;
+    assert_eq!(body, "demo@example.com");
+
+    let request = TestRequest::get().uri("/whoami").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let request = TestRequest::get()
+        .uri("/whoami")
+        .insert_header(("authorization", "Bearer not-a-jwt"))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn middleware_can_allow_anonymous_requests() {
+    let app = test::init_service(
+        App::new()
+            .wrap(SupabaseAuth::new(auth_client()).allow_anonymous(true))
+            .route("/whoami", web::get().to(whoami)),
+    )
+    .await;
+
+    let request = TestRequest::get().uri("/whoami").to_request();
+    let body = test::call_and_read_body(&app, request).await;
+    assert_eq!(body, "anonymous");
+
+    let request = TestRequest::get()
+        .uri("/whoami")
+        .insert_header(("authorization", "Bearer not-a-jwt"))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use supabase_auth::{
+    api::AuthApi,
+    error::Error,
+    models::{AccessToken, AuthClient},
+};
+
+#[cfg(feature = "mockall")]
+/// Code under test, depending on the trait rather than the client
+async fn greet(auth: &dyn AuthApi, access_token: &AccessToken) -> Result<String, Error> {
+    let user = auth.get_user(access_token).await?;
+
+    Ok(format!("Hello, {}!", user.email))
+}
+
+#[test]
+fn the_client_is_usable_as_a_trait_object() {
+    let auth: Arc<dyn AuthApi> = Arc::new(AuthClient::new(
+        "https://abcdefghijklmnop.supabase.co",
+        "api_key",
+        "",
+    ));
+
+    assert_eq!(auth.project_url(), "https://abcdefghijklmnop.supabase.co");
+    assert!(matches!(
+        auth.access_token_from_headers(&Default::default()),
+        Err(Error::NotAuthenticated)
+    ));
+}
+
+#[tokio::test]
+async fn futures_outlive_their_arguments() {
+    let auth: Arc<dyn AuthApi> = Arc::new(AuthClient::new("http://127.0.0.1:9", "api_key", ""));
+
+    let future = {
+        let access_token = AccessToken::new("expired");
+        auth.get_user(&access_token)
+    };
+
+    let result = tokio::spawn(future).await.unwrap();
+    assert!(matches!(result, Err(Error::NetworkError(_))));
+}
+
+#[cfg(feature = "mockall")]
+#[tokio::test]
+async fn services_can_depend_on_a_mocked_client() {
+    use supabase_auth::{api::MockAuthApi, models::User};
+
+    let mut auth = MockAuthApi::new();
+    auth.expect_get_user()
+        .withf(|token| token.as_str() == "valid")
+        .returning(|_| {
+            Box::pin(async {
+                Ok(User {
+                    email: "demo@example.com".to_string(),
+                    ..Default::default()
+                })
+            })
+        });
+
+    let greeting = greet(&auth, &AccessToken::new("valid")).await.unwrap();
+
+    assert_eq!(greeting, "Hello, demo@example.com!");
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "axum")]
+
+mod common;
+
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::{header::COOKIE, request::Parts, Request, StatusCode},
+    response::IntoResponse,
+};
+use chrono::Utc;
+
+use supabase_auth::{
+    axum::{AuthRejection, SupabaseClaims},
+    cookies::{self, CookieOptions},
+    error::Error,
+    models::{AuthClient, Session},
+};
+
+use common::{access_token, auth_client};
+
+fn parts(header: Option<(&str, String)>) -> Parts {
+    let mut request = Request::builder().uri("/me");
+    if let Some((name, value)) = header {
+        request = request.header(name, value);
+    }
+
+    request.body(()).unwrap().into_parts().0
+}
+
+#[tokio::test]
+async fn claims_are_extracted_from_request_parts() {
+    let mut parts = parts(Some((
+        "authorization",
+        format!("Bearer {}", access_token()),
+    )));
+
+    let SupabaseClaims(claims) =
+        <SupabaseClaims as FromRequestParts<AuthClient>>::from_request_parts(
+            &mut parts,
+            &auth_client(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(claims.email, "demo@example.com");
+}
+
+#[tokio::test]
+async fn claims_are_extracted_from_the_auth_cookie() {
+    let auth_client = auth_client();
+    let session = Session {
+        access_token: access_token().into(),
+        refresh_token: "refresh".into(),
+        expires_at: Utc::now() + chrono::TimeDelta::hours(1),
+        ..Default::default()
+    };
+
+    let cookie_name = cookies::cookie_name(auth_client.project_url()).unwrap();
+    let cookie_header =
+        cookies::build_session_cookies(&cookie_name, &session, &CookieOptions::default())
+            .unwrap()
+            .iter()
+            .map(|set_cookie| set_cookie.split(';').next().unwrap().to_string())
+            .collect::<Vec<_>>()
+            .join("; ");
+    let mut parts = parts(Some((COOKIE.as_str(), cookie_header)));
+
+    let SupabaseClaims(claims) =
+        <SupabaseClaims as FromRequestParts<AuthClient>>::from_request_parts(
+            &mut parts,
+            &auth_client,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(claims.email, "demo@example.com");
+}
+
+#[tokio::test]
+async fn requests_without_a_token_are_rejected() {
+    let result = <SupabaseClaims as FromRequestParts<AuthClient>>::from_request_parts(
+        &mut parts(None),
+        &auth_client(),
+    )
+    .await;
+
+    let Err(rejection) = result else {
+        panic!("expected a rejection");
+    };
+    assert!(matches!(rejection, AuthRejection(Error::NotAuthenticated)));
+
+    let response = rejection.into_response();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()["www-authenticate"], "Bearer");
+
+    let claims = <SupabaseClaims as OptionalFromRequestParts<AuthClient>>::from_request_parts(
+        &mut parts(None),
+        &auth_client(),
+    )
+    .await
+    .unwrap();
+    assert!(claims.is_none());
+}
+
+#[tokio::test]
+async fn invalid_tokens_are_rejected() {
+    let mut parts = parts(Some(("authorization", "Bearer not-a-jwt".to_string())));
+
+    let rejection = <SupabaseClaims as OptionalFromRequestParts<AuthClient>>::from_request_parts(
+        &mut parts,
+        &auth_client(),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(rejection.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "blocking")]
+
+use std::sync::{Arc, Mutex};
+
+use reqwest::Request;
+
+use supabase_auth::{
+    blocking,
+    error::Error,
+    models::AuthClient,
+    transport::{HttpTransport, TransportFuture},
+};
+
+const HEALTH: &str = r#"{"version":"v2.170.0","name":"GoTrue","description":"GoTrue is a user registration and authentication API"}"#;
+
+/// Answers every request with `status` and the health response, recording the paths
+#[derive(Debug, Clone)]
+struct FakeTransport {
+    status: u16,
+    paths: Arc<Mutex<Vec<String>>>,
+}
+
+impl HttpTransport for FakeTransport {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            self.paths
+                .lock()
+                .unwrap()
+                .push(request.url().path().to_string());
+
+            let response = http::Response::builder()
+                .status(self.status)
+                .body(HEALTH)
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+fn blocking_client(status: u16) -> (blocking::AuthClient, Arc<Mutex<Vec<String>>>) {
+    let transport = FakeTransport {
+        status,
+        paths: Arc::default(),
+    };
+    let paths = transport.paths.clone();
+
+    let auth_client = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(transport)
+        .build()
+        .unwrap();
+
+    (auth_client.into(), paths)
+}
+
+#[test]
+fn requests_block_until_they_are_done() {
+    let (auth_client, paths) = blocking_client(200);
+
+    let health = auth_client.get_health().unwrap();
+    assert_eq!(health.version, "v2.170.0");
+
+    // Clones share the runtime
+    let clone = auth_client.clone();
+    std::thread::spawn(move || clone.get_health().unwrap())
+        .join()
+        .unwrap();
+
+    assert_eq!(
+        *paths.lock().unwrap(),
+        ["/auth/v1/health", "/auth/v1/health"]
+    );
+}
+
+#[test]
+fn errors_are_returned_like_the_async_client() {
+    let (auth_client, _) = blocking_client(500);
+
+    let error = auth_client.get_settings().unwrap_err();
+    assert!(matches!(error, Error::AuthError { .. }));
+}
@@ -0,0 +1,336 @@
+use std::time::{Duration, Instant};
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Proxy,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use supabase_auth::{
+    error::Error,
+    models::{AuthClient, EnvConfig},
+};
+
+#[test]
+fn build_with_shared_http_client() {
+    let http_client = reqwest::Client::new();
+
+    let auth_client = AuthClient::builder()
+        .http_client(http_client)
+        .project_url("http://localhost:54321")
+        .api_key("api_key")
+        .jwt_secret("jwt_secret")
+        .build()
+        .unwrap();
+
+    assert_eq!(auth_client.project_url(), "http://localhost:54321");
+    assert_eq!(auth_client.api_key(), "api_key");
+    assert_eq!(auth_client.jwt_secret(), "jwt_secret");
+}
+
+#[test]
+fn jwt_secret_is_optional() {
+    let auth_client = AuthClient::builder()
+        .project_url("http://localhost:54321")
+        .api_key("api_key")
+        .build()
+        .unwrap();
+
+    assert_eq!(auth_client.jwt_secret(), "");
+}
+
+#[test]
+fn missing_configuration_is_rejected() {
+    let result = AuthClient::builder().api_key("api_key").build();
+    assert!(matches!(
+        result,
+        Err(Error::MissingConfiguration("project_url"))
+    ));
+
+    let result = AuthClient::builder()
+        .project_url("http://localhost:54321")
+        .build();
+    assert!(matches!(
+        result,
+        Err(Error::MissingConfiguration("api_key"))
+    ));
+
+    let result = AuthClient::builder()
+        .project_url("not a url")
+        .api_key("api_key")
+        .build();
+    assert!(matches!(result, Err(Error::ParseUrlError)));
+}
+
+/// Accept a single request, answer it with `404 Not Found`, and return its raw text
+async fn capture_request() -> (String, tokio::sync::oneshot::Receiver<String>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (request_tx, request_rx) = tokio::sync::oneshot::channel();
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buffer = vec![0; 8192];
+        let read = socket.read(&mut buffer).await.unwrap();
+
+        socket
+            .write_all(b"HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+
+        let _ = request_tx.send(String::from_utf8_lossy(&buffer[..read]).to_lowercase());
+    });
+
+    (url, request_rx)
+}
+
+#[tokio::test]
+async fn default_and_per_call_headers_are_sent() {
+    let (url, request) = capture_request().await;
+
+    let auth_client = AuthClient::builder()
+        .project_url(url)
+        .api_key("api_key")
+        .header(
+            HeaderName::from_static("x-client-info"),
+            HeaderValue::from_static("my-app/1.0"),
+        )
+        .build()
+        .unwrap();
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-tenant-id", HeaderValue::from_static("acme"));
+
+    let _ = auth_client.with_headers(headers).get_health().await;
+
+    let request = request.await.unwrap();
+    assert!(request.contains("x-client-info: my-app/1.0"));
+    assert!(request.contains("x-tenant-id: acme"));
+    assert!(request.contains("apikey: api_key"));
+}
+
+#[tokio::test]
+async fn hung_requests_time_out() {
+    // Accepts connections but never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            sockets.push(socket);
+        }
+    });
+
+    let auth_client = AuthClient::builder()
+        .project_url(url)
+        .api_key("api_key")
+        .timeout(Duration::from_secs(60))
+        .build()
+        .unwrap();
+
+    let started = Instant::now();
+    let result = auth_client
+        .with_timeout(Duration::from_millis(100))
+        .get_health()
+        .await;
+
+    assert!(matches!(result, Err(Error::NetworkError(error)) if error.is_timeout()));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn requests_go_through_the_proxy() {
+    let (proxy_url, request) = capture_request().await;
+
+    let auth_client = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .proxy(Proxy::http(proxy_url).unwrap())
+        .build()
+        .unwrap();
+
+    let _ = auth_client.get_health().await;
+
+    let request = request.await.unwrap();
+    assert!(request.starts_with("get http://project.supabase.invalid/auth/v1/health"));
+}
+
+#[test]
+fn proxy_and_http_client_conflict() {
+    let result = AuthClient::builder()
+        .http_client(reqwest::Client::new())
+        .project_url("http://localhost:54321")
+        .api_key("api_key")
+        .proxy(Proxy::all("http://localhost:3128").unwrap())
+        .build();
+
+    assert!(matches!(
+        result,
+        Err(Error::ConflictingConfiguration("proxy"))
+    ));
+}
+
+#[tokio::test]
+async fn pool_settings_apply_to_the_created_client() {
+    let (url, request) = capture_request().await;
+
+    let auth_client = AuthClient::builder()
+        .project_url(url)
+        .api_key("api_key")
+        .pool_max_idle_per_host(8)
+        .pool_idle_timeout(Duration::from_secs(30))
+        .tcp_keepalive(Duration::from_secs(15))
+        .build()
+        .unwrap();
+
+    let _ = auth_client.get_health().await;
+
+    assert!(request.await.unwrap().starts_with("get /auth/v1/health "));
+}
+
+#[test]
+fn pool_settings_and_http_client_conflict() {
+    let result = AuthClient::builder()
+        .http_client(reqwest::Client::new())
+        .project_url("http://localhost:54321")
+        .api_key("api_key")
+        .pool_max_idle_per_host(8)
+        .build();
+
+    assert!(matches!(
+        result,
+        Err(Error::ConflictingConfiguration("pool_max_idle_per_host"))
+    ));
+}
+
+#[tokio::test]
+async fn self_hosted_auth_path_without_api_key() {
+    let (url, request) = capture_request().await;
+
+    let auth_client = AuthClient::builder()
+        .project_url(url)
+        .auth_path("gotrue/")
+        .without_api_key()
+        .build()
+        .unwrap();
+
+    assert_eq!(auth_client.auth_path(), "/gotrue");
+
+    let _ = auth_client.get_health().await;
+
+    let request = request.await.unwrap();
+    assert!(request.starts_with("get /gotrue/health "));
+    assert!(!request.contains("apikey"));
+}
+
+#[test]
+fn client_from_custom_environment_variables() {
+    std::env::set_var("ENV_CONFIG_TEST_URL", "http://localhost:54321");
+    std::env::set_var("ENV_CONFIG_TEST_ANON_KEY", "anon_key");
+
+    let auth_client = AuthClient::new_from_env_with(EnvConfig {
+        url_var: "ENV_CONFIG_TEST_URL".to_string(),
+        api_key_var: "ENV_CONFIG_TEST_ANON_KEY".to_string(),
+        jwt_secret_var: None,
+    })
+    .unwrap();
+
+    assert_eq!(auth_client.project_url(), "http://localhost:54321");
+    assert_eq!(auth_client.api_key(), "anon_key");
+    assert_eq!(auth_client.jwt_secret(), "");
+
+    let result = AuthClient::new_from_env_with(EnvConfig {
+        url_var: "ENV_CONFIG_TEST_URL".to_string(),
+        api_key_var: "ENV_CONFIG_TEST_MISSING_KEY".to_string(),
+        jwt_secret_var: None,
+    });
+    assert!(matches!(
+        result,
+        Err(Error::InvalidEnvironment { missing, invalid })
+            if missing == ["ENV_CONFIG_TEST_MISSING_KEY"] && invalid.is_empty()
+    ));
+}
+
+#[test]
+fn environment_errors_name_every_variable() {
+    std::env::set_var("ENV_ERRORS_TEST_URL", "not a url");
+    std::env::set_var("ENV_ERRORS_TEST_ANON_KEY", "");
+
+    let result = AuthClient::new_from_env_with(EnvConfig {
+        url_var: "ENV_ERRORS_TEST_URL".to_string(),
+        api_key_var: "ENV_ERRORS_TEST_ANON_KEY".to_string(),
+        jwt_secret_var: Some("ENV_ERRORS_TEST_JWT_SECRET".to_string()),
+    });
+
+    match result {
+        Err(error @ Error::InvalidEnvironment { .. }) => {
+            assert_eq!(
+                error.to_string(),
+                "Environment Variables Missing: [\"ENV_ERRORS_TEST_ANON_KEY\", \"ENV_ERRORS_TEST_JWT_SECRET\"], Invalid: [\"ENV_ERRORS_TEST_URL\"]"
+            );
+        }
+        other => panic!("Expected InvalidEnvironment, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn client_info_identifies_library_and_application() {
+    let (url, request) = capture_request().await;
+    let _ = AuthClient::new(url, "api_key", "").get_health().await;
+
+    let request = request.await.unwrap();
+    assert!(request.contains(&format!(
+        "x-client-info: supabase-auth-rs/{}\r\n",
+        env!("CARGO_PKG_VERSION")
+    )));
+
+    let (url, request) = capture_request().await;
+    let auth_client = AuthClient::builder()
+        .project_url(url)
+        .api_key("api_key")
+        .client_info("my-app/1.2.0")
+        .build()
+        .unwrap();
+    let _ = auth_client.get_health().await;
+
+    let request = request.await.unwrap();
+    assert!(request.contains(&format!(
+        "x-client-info: supabase-auth-rs/{} my-app/1.2.0\r\n",
+        env!("CARGO_PKG_VERSION")
+    )));
+}
+
+#[tokio::test]
+async fn for_project_rebinds_url_and_key() {
+    let (url, request) = capture_request().await;
+
+    let auth_client = AuthClient::builder()
+        .project_url("http://localhost:54321")
+        .api_key("api_key")
+        .jwt_secret("jwt_secret")
+        .header(
+            HeaderName::from_static("x-tenant-proxy"),
+            HeaderValue::from_static("edge-1"),
+        )
+        .build()
+        .unwrap();
+
+    let tenant_client = auth_client.for_project(url, "tenant_api_key", "");
+
+    assert_eq!(tenant_client.api_key(), "tenant_api_key");
+    assert_eq!(tenant_client.jwt_secret(), "");
+    assert_eq!(auth_client.api_key(), "api_key");
+
+    let _ = tenant_client.get_health().await;
+
+    let request = request.await.unwrap();
+    assert!(request.contains("apikey: tenant_api_key"));
+    assert!(request.contains("x-tenant-proxy: edge-1"));
+}
+
+#[test]
+fn auth_client_can_be_shared_across_tasks() {
+    fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+
+    assert_shareable::<AuthClient>();
+}
@@ -0,0 +1,180 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use reqwest::{Request, ResponseBuilderExt, StatusCode};
+
+use supabase_auth::{
+    circuit_breaker::{CircuitBreaker, CircuitState},
+    error::Error,
+    models::AuthClient,
+    transport::{HttpTransport, TransportFuture},
+};
+
+/// Answers the n-th request with the n-th status of `statuses`, repeating the last one
+#[derive(Debug, Clone)]
+struct ScriptedTransport {
+    statuses: Vec<u16>,
+    requests: Arc<AtomicUsize>,
+}
+
+impl ScriptedTransport {
+    fn new(statuses: Vec<u16>) -> Self {
+        ScriptedTransport {
+            statuses,
+            requests: Arc::default(),
+        }
+    }
+
+    fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+impl HttpTransport for ScriptedTransport {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let request_number = self.requests.fetch_add(1, Ordering::SeqCst);
+            let status = self.statuses[request_number.min(self.statuses.len() - 1)];
+
+            let response = http::Response::builder()
+                .status(status)
+                .url(request.url().clone())
+                .body("{}")
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+fn client(transport: &ScriptedTransport, circuit_breaker: CircuitBreaker) -> AuthClient {
+    AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(transport.clone())
+        .circuit_breaker(circuit_breaker)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn circuit_opens_after_consecutive_failures() {
+    let transport = ScriptedTransport::new(vec![503]);
+    let auth_client = client(
+        &transport,
+        CircuitBreaker {
+            failure_threshold: 2,
+            open_duration: Duration::from_secs(3600),
+            ..Default::default()
+        },
+    );
+
+    for _ in 0..2 {
+        let error = auth_client.get_health().await.unwrap_err();
+        assert_eq!(error.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    }
+    assert_eq!(auth_client.circuit_state(), Some(CircuitState::Open));
+
+    // Clones share the circuit
+    let result = auth_client.clone().get_health().await;
+    assert!(matches!(
+        result,
+        Err(Error::CircuitOpen { retry_after }) if retry_after > Duration::from_secs(3500)
+    ));
+    assert_eq!(transport.requests(), 2);
+}
+
+#[tokio::test]
+async fn successes_reset_the_failure_count() {
+    let transport = ScriptedTransport::new(vec![503, 200, 503]);
+    let auth_client = client(
+        &transport,
+        CircuitBreaker {
+            failure_threshold: 2,
+            ..Default::default()
+        },
+    );
+
+    assert!(auth_client.get_health().await.is_err());
+    auth_client.get_health().await.unwrap();
+    assert!(auth_client.get_health().await.is_err());
+
+    assert_eq!(auth_client.circuit_state(), Some(CircuitState::Closed));
+}
+
+#[tokio::test]
+async fn rejected_requests_dont_open_the_circuit() {
+    let transport = ScriptedTransport::new(vec![400]);
+    let auth_client = client(
+        &transport,
+        CircuitBreaker {
+            failure_threshold: 1,
+            ..Default::default()
+        },
+    );
+
+    assert!(auth_client.get_health().await.is_err());
+    assert!(auth_client.get_health().await.is_err());
+
+    assert_eq!(auth_client.circuit_state(), Some(CircuitState::Closed));
+    assert_eq!(transport.requests(), 2);
+}
+
+#[tokio::test]
+async fn successful_probe_closes_the_circuit() {
+    let transport = ScriptedTransport::new(vec![503, 200]);
+    let auth_client = client(
+        &transport,
+        CircuitBreaker {
+            failure_threshold: 1,
+            open_duration: Duration::from_millis(50),
+            half_open_probes: 1,
+        },
+    );
+
+    assert!(auth_client.get_health().await.is_err());
+    assert!(matches!(
+        auth_client.get_health().await,
+        Err(Error::CircuitOpen { .. })
+    ));
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(auth_client.circuit_state(), Some(CircuitState::HalfOpen));
+
+    auth_client.get_health().await.unwrap();
+    assert_eq!(auth_client.circuit_state(), Some(CircuitState::Closed));
+    assert_eq!(transport.requests(), 2);
+}
+
+#[tokio::test]
+async fn failed_probe_reopens_the_circuit() {
+    let transport = ScriptedTransport::new(vec![503]);
+    let auth_client = client(
+        &transport,
+        CircuitBreaker {
+            failure_threshold: 1,
+            open_duration: Duration::from_millis(50),
+            half_open_probes: 1,
+        },
+    );
+
+    assert!(auth_client.get_health().await.is_err());
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    let error = auth_client.get_health().await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    assert_eq!(auth_client.circuit_state(), Some(CircuitState::Open));
+    assert_eq!(transport.requests(), 2);
+}
+
+#[test]
+fn clients_without_a_circuit_breaker_have_no_state() {
+    let auth_client = AuthClient::new("http://project.supabase.invalid", "api_key", "");
+
+    assert_eq!(auth_client.circuit_state(), None);
+}
@@ -1,11 +1,14 @@
 use core::time;
-use std::{collections::HashMap, env, thread};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, COOKIE};
+use std::{env, thread};
 
 use supabase_auth::{
-    error::Error,
+    error::{Error, ErrorCode},
     models::{
-        AuthClient, EmailSignUpResult, LoginEmailOtpParams, LoginWithOAuthOptions, LoginWithSSO,
-        LogoutScope, ResendParams, ResetPasswordOptions, SignUpWithPasswordOptions, UpdatedUser,
+        AuthClient, CreateOAuthClientParams, EmailSignUpResult, GenerateLinkParams,
+        GenerateLinkType, ListUsersParams, LoginEmailOtpParams, LoginWithOAuthOptions,
+        LoginWithSSO, LogoutScope, Patch, ResendParams, ResetPasswordOptions,
+        SignUpWithPasswordOptions, UpdateSsoProviderParams, UpdatedUser,
     },
 };
 
@@ -20,6 +23,45 @@ async fn create_client_test_valid() {
     assert!(*auth_client.project_url() == env::var("SUPABASE_URL").unwrap())
 }
 
+fn api_key_with_role(role: &str) -> String {
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &serde_json::json!({ "iss": "supabase", "ref": "demo", "role": role }),
+        &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+    )
+    .unwrap()
+}
+
+#[test]
+fn key_role_is_read_from_api_key() {
+    let anon = AuthClient::new("http://localhost:54321", api_key_with_role("anon"), "");
+    assert!(anon.key_role().is_some_and(|role| role.is_anon()));
+
+    let service = AuthClient::new(
+        "http://localhost:54321",
+        api_key_with_role("service_role"),
+        "",
+    );
+    assert!(service
+        .key_role()
+        .is_some_and(|role| role.is_service_role()));
+
+    let secret = AuthClient::new("http://localhost:54321", "sb_secret_abc123", "");
+    assert!(secret.key_role().is_some_and(|role| role.is_service_role()));
+
+    let unknown = AuthClient::new("http://localhost:54321", "not-a-jwt", "");
+    assert!(unknown.key_role().is_none());
+}
+
+#[tokio::test]
+async fn admin_methods_require_service_role_key() {
+    let auth_client = AuthClient::new("http://127.0.0.1:9", api_key_with_role("anon"), "");
+
+    let result = auth_client.admin_list_sso_providers().await;
+
+    assert!(matches!(result, Err(Error::ServiceRoleKeyRequired)))
+}
+
 #[tokio::test]
 async fn test_login_with_email() {
     let auth_client = create_test_client();
@@ -46,7 +88,8 @@ async fn test_login_with_email_invalid() {
         .login_with_email(demo_email, demo_password)
         .await
     {
-        Err(Error::AuthError { message, .. }) => {
+        Err(Error::AuthError { code, message, .. }) => {
+            assert_eq!(code, Some(ErrorCode::InvalidCredentials));
             assert!(message.contains("Invalid login credentials"));
         }
         other => panic!("Expected AuthError, got {:?}", other),
@@ -67,11 +110,9 @@ async fn sign_up_with_email_test_valid() {
         "name": format!("test" )
     });
 
-    let options = SignUpWithPasswordOptions {
-        data: Some(data),
-        email_redirect_to: Some("https://www.thisisnotarealdomain.com".to_string()),
-        ..Default::default()
-    };
+    let options = SignUpWithPasswordOptions::default()
+        .data(data)
+        .email_redirect_to("https://www.thisisnotarealdomain.com");
 
     let result = auth_client
         .sign_up_with_email_and_password(demo_email.as_ref(), demo_password, Some(options))
@@ -100,6 +141,7 @@ async fn sign_up_with_email_test_valid() {
 }
 
 #[tokio::test]
+#[allow(clippy::unnecessary_unwrap)]
 async fn send_login_email_with_magic_link() {
     let auth_client = create_test_client();
 
@@ -121,6 +163,7 @@ async fn send_login_email_with_magic_link() {
 }
 
 #[tokio::test]
+#[allow(clippy::unnecessary_unwrap)]
 async fn send_email_with_otp() {
     let auth_client = create_test_client();
 
@@ -130,10 +173,7 @@ async fn send_email_with_otp() {
         "otp": format!("test" )
     });
 
-    let options = LoginEmailOtpParams {
-        data: Some(data),
-        ..Default::default()
-    };
+    let options = LoginEmailOtpParams::default().data(data);
 
     let response = auth_client
         .send_email_with_otp(&demo_email, Some(options))
@@ -151,6 +191,7 @@ async fn send_email_with_otp() {
 }
 
 #[tokio::test]
+#[allow(clippy::assertions_on_constants)]
 async fn send_email_with_otp_with_create_user_false() {
     let auth_client = create_test_client();
 
@@ -162,11 +203,9 @@ async fn send_email_with_otp_with_create_user_false() {
         "otp": format!("test" )
     });
 
-    let options = LoginEmailOtpParams {
-        data: Some(data),
-        should_create_user: Some(false),
-        ..Default::default()
-    };
+    let options = LoginEmailOtpParams::default()
+        .data(data)
+        .should_create_user(false);
 
     let response = auth_client
         .send_email_with_otp(&demo_email, Some(options))
@@ -176,7 +215,10 @@ async fn send_email_with_otp_with_create_user_false() {
     let one_minute = time::Duration::from_secs(60);
     thread::sleep(one_minute);
 
-    if let Err(Error::AuthError{status, message}) = response {
+    if let Err(Error::AuthError {
+        status, message, ..
+    }) = response
+    {
         assert_eq!(status.as_u16(), 422);
         assert!(message.contains("not allowed for otp"));
     } else {
@@ -188,17 +230,13 @@ async fn send_email_with_otp_with_create_user_false() {
 fn login_with_oauth_test() {
     let auth_client = create_test_client();
 
-    let mut params = HashMap::new();
-    params.insert("key".to_string(), "value".to_string());
-    params.insert("second_key".to_string(), "second_value".to_string());
-    params.insert("third_key".to_string(), "third_value".to_string());
-
-    let options = LoginWithOAuthOptions {
-        query_params: Some(params),
-        redirect_to: Some("localhost".to_string()),
-        scopes: Some("repo gist notifications".to_string()),
-        skip_browser_redirect: Some(true),
-    };
+    let options = LoginWithOAuthOptions::default()
+        .query_param("key", "value")
+        .query_param("second_key", "second_value")
+        .query_param("third_key", "third_value")
+        .redirect_to("localhost")
+        .scopes("repo gist notifications")
+        .skip_browser_redirect(true);
 
     let response =
         auth_client.login_with_oauth(supabase_auth::models::Provider::Github, Some(options));
@@ -215,17 +253,13 @@ fn login_with_oauth_test() {
 fn sign_up_with_oauth_test() {
     let auth_client = create_test_client();
 
-    let mut params = HashMap::new();
-    params.insert("key".to_string(), "value".to_string());
-    params.insert("second_key".to_string(), "second_value".to_string());
-    params.insert("third_key".to_string(), "third_value".to_string());
-
-    let options = LoginWithOAuthOptions {
-        query_params: Some(params),
-        redirect_to: Some("localhost".to_string()),
-        scopes: Some("repo gist notifications".to_string()),
-        skip_browser_redirect: Some(true),
-    };
+    let options = LoginWithOAuthOptions::default()
+        .query_param("key", "value")
+        .query_param("second_key", "second_value")
+        .query_param("third_key", "third_value")
+        .redirect_to("localhost")
+        .scopes("repo gist notifications")
+        .skip_browser_redirect(true);
 
     let response =
         auth_client.sign_up_with_oauth(supabase_auth::models::Provider::Github, Some(options));
@@ -238,6 +272,7 @@ fn sign_up_with_oauth_test() {
 }
 
 #[test]
+#[allow(clippy::unnecessary_unwrap)]
 fn login_with_oauth_no_options_test() {
     let auth_client = create_test_client();
 
@@ -268,6 +303,7 @@ fn login_with_oauth_no_options_test() {
 }
 
 #[tokio::test]
+#[allow(clippy::unnecessary_unwrap)]
 async fn get_user_test() {
     let auth_client = create_test_client();
 
@@ -292,6 +328,42 @@ async fn get_user_test() {
 }
 
 #[tokio::test]
+async fn get_user_from_headers_test() {
+    let auth_client = create_test_client();
+
+    let demo_email = env::var("DEMO_EMAIL").unwrap();
+    let demo_password = env::var("DEMO_PASSWORD").unwrap();
+
+    let session = auth_client
+        .login_with_email(&demo_email, &demo_password)
+        .await
+        .unwrap();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", session.access_token.as_str())).unwrap(),
+    );
+
+    let request_user = auth_client.get_user_from_headers(&headers).await.unwrap();
+
+    assert!(request_user.user.email == demo_email && request_user.refreshed_session.is_none())
+}
+
+#[tokio::test]
+async fn get_user_from_headers_requires_credentials() {
+    let auth_client = AuthClient::new("http://localhost:54321", "api_key", "jwt_secret");
+
+    let mut headers = HeaderMap::new();
+    headers.insert(COOKIE, HeaderValue::from_static("other=1"));
+
+    let result = auth_client.get_user_from_headers(&headers).await;
+
+    assert!(matches!(result, Err(Error::NotAuthenticated)))
+}
+
+#[tokio::test]
+#[allow(clippy::unnecessary_unwrap)]
 async fn update_user_test() {
     let auth_client = create_test_client();
 
@@ -310,9 +382,10 @@ async fn update_user_test() {
     });
 
     let updated_user = UpdatedUser {
-        email: Some(demo_email.clone()),
-        password: Some("qqqqwwww".to_string()),
-        data: Some(data),
+        email: Patch::Set(demo_email.clone()),
+        password: Patch::Set("qqqqwwww".to_string()),
+        data: Patch::Set(data),
+        ..Default::default()
     };
 
     let first_response = auth_client
@@ -349,9 +422,9 @@ async fn update_user_test() {
 
     // Return the user to original condition
     let original_user = UpdatedUser {
-        email: Some(demo_email),
-        password: Some("qwerqwer".to_string()),
-        data: None,
+        email: Patch::Set(demo_email),
+        password: Patch::Set("qwerqwer".to_string()),
+        ..Default::default()
     };
 
     let second_response = auth_client
@@ -383,16 +456,96 @@ async fn exchange_token_for_session() {
     assert!(new_session.user.email == demo_email)
 }
 
+#[tokio::test]
+async fn reused_refresh_token_is_rejected() {
+    let auth_client = create_test_client();
+
+    let demo_email = env::var("DEMO_EMAIL").unwrap();
+    let demo_password = env::var("DEMO_PASSWORD").unwrap();
+
+    let original_session = auth_client
+        .login_with_email(&demo_email, &demo_password)
+        .await
+        .unwrap();
+
+    auth_client
+        .refresh_session(&original_session.refresh_token)
+        .await
+        .unwrap();
+
+    // GoTrue accepts a reused token within the 10 second reuse interval
+    tokio::time::sleep(std::time::Duration::from_secs(11)).await;
+
+    let reused = auth_client
+        .refresh_session(&original_session.refresh_token)
+        .await;
+
+    assert!(matches!(reused, Err(Error::RefreshTokenAlreadyUsed)))
+}
+
+#[tokio::test]
+async fn with_token_get_user_test() {
+    let auth_client = create_test_client();
+
+    let demo_email = env::var("DEMO_EMAIL").unwrap();
+    let demo_password = env::var("DEMO_PASSWORD").unwrap();
+
+    let session = auth_client
+        .login_with_email(&demo_email, &demo_password)
+        .await
+        .unwrap();
+
+    let user_client = auth_client.with_token(&session.access_token);
+
+    assert_eq!(user_client.access_token(), &session.access_token);
+
+    let user = user_client.get_user().await.unwrap();
+
+    assert!(user.email == demo_email)
+}
+
+#[tokio::test]
+async fn set_session_test() {
+    let auth_client = create_test_client();
+
+    let demo_email = env::var("DEMO_EMAIL").unwrap();
+    let demo_password = env::var("DEMO_PASSWORD").unwrap();
+
+    let original_session = auth_client
+        .login_with_email(&demo_email, &demo_password)
+        .await
+        .unwrap();
+
+    let session = auth_client
+        .set_session(
+            &original_session.access_token,
+            &original_session.refresh_token,
+        )
+        .await
+        .unwrap();
+
+    assert!(session.user.email == demo_email && session.expires_at == original_session.expires_at)
+}
+
+#[tokio::test]
+async fn set_session_rejects_malformed_token() {
+    let auth_client = AuthClient::new("http://localhost:54321", "api_key", "jwt_secret");
+
+    let result = auth_client
+        .set_session(&"not-a-jwt".into(), &"refresh".into())
+        .await;
+
+    assert!(matches!(result, Err(Error::WrongToken)))
+}
+
 #[tokio::test]
 async fn reset_password_for_email_test() {
     let auth_client = create_test_client();
 
     let demo_email = env::var("DEMO_EMAIL").unwrap();
 
-    let options = ResetPasswordOptions {
-        email_redirect_to: Some("https://www.thisisnotarealdomain.com".to_string()),
-        ..Default::default()
-    };
+    let options =
+        ResetPasswordOptions::default().email_redirect_to("https://www.thisisnotarealdomain.com");
 
     let response = auth_client
         .reset_password_for_email(&demo_email, Some(options))
@@ -406,6 +559,7 @@ async fn reset_password_for_email_test() {
 }
 
 #[tokio::test]
+#[allow(clippy::unnecessary_unwrap)]
 async fn resend_email_test() {
     let auth_client = create_test_client();
 
@@ -476,11 +630,7 @@ async fn logout_test() {
 async fn test_sso_login() {
     let auth_client = create_test_client();
     let demo_domain = env::var("DEMO_DOMAIN").unwrap();
-    let params = LoginWithSSO {
-        domain: Some(demo_domain),
-        options: None,
-        provider_id: None,
-    };
+    let params = LoginWithSSO::domain(demo_domain);
 
     let url = auth_client.sso(params).await.unwrap();
 
@@ -489,6 +639,149 @@ async fn test_sso_login() {
     assert!(url.to_string().len() > 1);
 }
 
+#[tokio::test]
+#[ignore = "SSO Requires Pro plan"]
+async fn get_saml_metadata_test() {
+    let auth_client = create_test_client();
+
+    let metadata = auth_client.get_saml_metadata().await.unwrap();
+
+    assert!(metadata.contains("EntityDescriptor"))
+}
+
+#[tokio::test]
+#[ignore = "SSO Requires Pro plan"]
+async fn admin_list_sso_providers_test() {
+    let auth_client = create_test_client();
+
+    // NOTE: Requires admin permissions to manage SSO providers
+    let providers = auth_client.admin_list_sso_providers().await.unwrap();
+
+    assert!(!providers.is_empty())
+}
+
+#[tokio::test]
+#[ignore = "SSO Requires Pro plan"]
+async fn admin_get_sso_provider_test() {
+    let auth_client = create_test_client();
+
+    let providers = auth_client.admin_list_sso_providers().await.unwrap();
+    let first = providers.first().unwrap();
+
+    let provider = auth_client.admin_get_sso_provider(first.id).await.unwrap();
+
+    assert!(provider.id == first.id && provider.saml.is_some())
+}
+
+#[tokio::test]
+#[ignore = "SSO Requires Pro plan"]
+async fn admin_update_sso_provider_test() {
+    let auth_client = create_test_client();
+    let demo_domain = env::var("DEMO_DOMAIN").unwrap();
+
+    let providers = auth_client.admin_list_sso_providers().await.unwrap();
+    let original = providers.first().unwrap();
+
+    let extra_domain = format!("{}.{}", uuid::Uuid::now_v7(), demo_domain);
+    let params = UpdateSsoProviderParams::from_provider(original).add_domain(&extra_domain);
+
+    let updated = auth_client
+        .admin_update_sso_provider(original.id, params)
+        .await
+        .unwrap();
+
+    assert!(updated.domains.iter().any(|d| d.domain == extra_domain));
+
+    // Return the provider to original condition
+    let params = UpdateSsoProviderParams::from_provider(&updated).remove_domain(&extra_domain);
+
+    let restored = auth_client
+        .admin_update_sso_provider(original.id, params)
+        .await
+        .unwrap();
+
+    assert!(restored.domains.len() == original.domains.len())
+}
+
+#[tokio::test]
+#[ignore = "SSO Requires Pro plan"]
+async fn admin_delete_sso_provider_test() {
+    let auth_client = create_test_client();
+    let provider_id = env::var("DEMO_DELETABLE_SSO_PROVIDER_ID").unwrap();
+
+    let deleted = auth_client
+        .admin_delete_sso_provider(provider_id.parse().unwrap())
+        .await
+        .unwrap();
+
+    let lookup = auth_client.admin_get_sso_provider(deleted.id).await;
+
+    assert!(lookup.is_err())
+}
+
+#[tokio::test]
+#[ignore = "Requires the OAuth server to be enabled"]
+async fn admin_oauth_client_lifecycle_test() {
+    let auth_client = create_test_client();
+
+    let params = CreateOAuthClientParams {
+        client_name: format!("client__{}", uuid::Uuid::now_v7()),
+        redirect_uris: vec!["https://www.thisisnotarealdomain.com/callback".to_string()],
+        ..Default::default()
+    };
+
+    let created = auth_client.admin_create_oauth_client(params).await.unwrap();
+
+    let fetched = auth_client
+        .admin_get_oauth_client(&created.client_id)
+        .await
+        .unwrap();
+
+    assert!(fetched.client_name == created.client_name);
+
+    let regenerated = auth_client
+        .admin_regenerate_oauth_client_secret(&created.client_id)
+        .await
+        .unwrap();
+
+    assert!(regenerated.client_secret != created.client_secret);
+
+    auth_client
+        .admin_delete_oauth_client(&created.client_id)
+        .await
+        .unwrap();
+
+    let clients = auth_client.admin_list_oauth_clients().await.unwrap();
+
+    assert!(!clients.iter().any(|c| c.client_id == created.client_id))
+}
+
+#[tokio::test]
+async fn admin_list_users_test() {
+    let auth_client = create_test_client();
+
+    // NOTE: Requires admin permissions to list users
+    let users = auth_client
+        .admin_list_users(ListUsersParams::default().per_page(1))
+        .await
+        .unwrap();
+
+    assert!(users.len() == 1)
+}
+
+#[tokio::test]
+async fn admin_generate_link_test() {
+    let auth_client = create_test_client();
+
+    let demo_email = env::var("DEMO_EMAIL").unwrap();
+
+    // NOTE: Requires admin permissions to generate links
+    let params = GenerateLinkParams::new(GenerateLinkType::Magiclink, &demo_email);
+    let link = auth_client.admin_generate_link(params).await.unwrap();
+
+    assert!(link.user.email == demo_email && link.action_link.contains(&link.hashed_token))
+}
+
 #[tokio::test]
 async fn invite_by_email_test() {
     let auth_client = create_test_client();
@@ -0,0 +1,55 @@
+//! Fixtures shared by the integration tests which verify HS256 access tokens
+
+#![allow(dead_code)]
+
+use chrono::Utc;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use serde_json::{json, Value};
+
+use supabase_auth::models::AuthClient;
+
+/// The JWT secret of the project behind [`auth_client`]
+pub const SECRET: &str = "super-secret-jwt-token-with-at-least-32-characters-long";
+
+/// A client for a local project, which verifies access tokens signed with [`SECRET`]
+pub fn auth_client() -> AuthClient {
+    AuthClient::new("http://localhost:54321", "api_key", SECRET)
+}
+
+/// Signs `claims` with HS256 and `secret`
+pub fn sign(claims: &Value, secret: &str) -> String {
+    encode(
+        &Header::default(),
+        claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .unwrap()
+}
+
+/// The claims of `demo@example.com`, expiring `exp_offset` seconds from now
+pub fn claims(exp_offset: i64, aud: &str) -> Value {
+    let now = Utc::now().timestamp();
+
+    json!({
+        "sub": "01936a4e-7b5c-7d1e-9c3b-2a1f0e9d8c7b",
+        "aud": aud,
+        "exp": now + exp_offset,
+        "iat": now,
+        "iss": "http://localhost:54321/auth/v1",
+        "role": "authenticated",
+        "email": "demo@example.com",
+        "phone": "",
+        "aal": "aal1",
+        "amr": [{ "method": "password", "timestamp": now }],
+        "session_id": "01936a4e-7b5c-7d1e-9c3b-2a1f0e9d8c7c",
+        "is_anonymous": false,
+        "app_metadata": { "provider": "email", "providers": ["email"] },
+        "user_metadata": {},
+        "tenant_id": "01936a4e-7b5c-7d1e-9c3b-2a1f0e9d8c7d"
+    })
+}
+
+/// A valid access token of `demo@example.com`, signed with [`SECRET`]
+pub fn access_token() -> String {
+    sign(&claims(3600, "authenticated"), SECRET)
+}
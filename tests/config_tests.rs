@@ -0,0 +1,90 @@
+#![cfg(feature = "config")]
+
+use std::time::Duration;
+
+use supabase_auth::{config::ConfigFile, error::Error};
+
+const CONFIG: &str = r#"
+[profiles.local]
+project_url = "http://localhost:54321"
+api_key = "local_anon_key"
+jwt_secret = "local_jwt_secret"
+
+[profiles.self-hosted]
+project_url = "http://localhost:9999"
+auth_path = ""
+timeout_secs = 2.5
+
+[profiles.prod]
+project_url = "https://example.supabase.co"
+api_key = "prod_anon_key"
+timeout_secs = 10
+
+[profiles.prod.retry]
+max_attempts = 4
+initial_backoff_secs = 0.5
+
+[profiles.prod.refresh]
+refresh_margin_secs = 120
+"#;
+
+#[test]
+fn build_client_from_profile() {
+    let config: ConfigFile = CONFIG.parse().unwrap();
+
+    let local = config.profile("local").unwrap().build().unwrap();
+    assert_eq!(local.project_url(), "http://localhost:54321");
+    assert_eq!(local.api_key(), "local_anon_key");
+    assert_eq!(local.jwt_secret(), "local_jwt_secret");
+    assert_eq!(local.auth_path(), "/auth/v1");
+
+    let self_hosted = config.profile("self-hosted").unwrap();
+    assert_eq!(self_hosted.timeout_secs, Some(2.5));
+    assert_eq!(self_hosted.build().unwrap().auth_path(), "");
+}
+
+#[test]
+fn refresh_policy_from_profile() {
+    let config: ConfigFile = CONFIG.parse().unwrap();
+
+    let policy = config.profile("prod").unwrap().refresh_policy().unwrap();
+    assert_eq!(policy.refresh_margin, Duration::from_secs(120));
+    assert_eq!(policy.retry_interval, Duration::from_secs(5));
+
+    let retry = config.profile("prod").unwrap().retry.clone().unwrap();
+    assert_eq!(retry.max_attempts, Some(4));
+    assert_eq!(retry.initial_backoff_secs, Some(0.5));
+}
+
+#[test]
+fn unknown_profile_is_rejected() {
+    let config: ConfigFile = CONFIG.parse().unwrap();
+
+    assert!(matches!(
+        config.profile("staging"),
+        Err(Error::UnknownProfile(name)) if name == "staging"
+    ));
+}
+
+#[test]
+fn invalid_config_is_rejected() {
+    let result = "[profiles.local]\nproject_url = \"http://localhost:54321\"\ntimeout = 5\n"
+        .parse::<ConfigFile>();
+    assert!(matches!(result, Err(Error::InvalidConfig(_))));
+
+    let config: ConfigFile =
+        "[profiles.local]\nproject_url = \"http://localhost:54321\"\ntimeout_secs = -1\n"
+            .parse()
+            .unwrap();
+    assert!(matches!(
+        config.profile("local").unwrap().build(),
+        Err(Error::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn missing_file_is_reported() {
+    let result = ConfigFile::load("does/not/exist.toml");
+
+    assert!(matches!(result, Err(Error::InvalidConfig(_))));
+}
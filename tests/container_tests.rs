@@ -0,0 +1,72 @@
+#![cfg(feature = "testcontainers")]
+
+use supabase_auth::{
+    container::{GoTrueContainer, USER_EMAIL, USER_PASSWORD},
+    error::{Error, ErrorCode},
+    models::{ListUsersParams, LogoutScope},
+};
+
+#[tokio::test]
+async fn the_seeded_user_signs_in_refreshes_and_signs_out() {
+    let gotrue = GoTrueContainer::start().await;
+    let auth_client = gotrue.client();
+
+    let session = auth_client
+        .login_with_email(USER_EMAIL, USER_PASSWORD)
+        .await
+        .unwrap();
+    assert_eq!(session.user.id, gotrue.user().id);
+
+    let claims = auth_client
+        .verify_access_token(&session.access_token)
+        .unwrap();
+    assert_eq!(claims.email, USER_EMAIL);
+
+    let refreshed = auth_client
+        .refresh_session(&session.refresh_token)
+        .await
+        .unwrap();
+    let user = auth_client.get_user(&refreshed.access_token).await.unwrap();
+    assert_eq!(user.email, USER_EMAIL);
+
+    auth_client
+        .logout(Some(LogoutScope::Global), &refreshed.access_token)
+        .await
+        .unwrap();
+    assert!(auth_client
+        .refresh_session(&refreshed.refresh_token)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn wrong_passwords_are_rejected() {
+    let gotrue = GoTrueContainer::start().await;
+
+    let result = gotrue
+        .client()
+        .login_with_email(USER_EMAIL, "wrong-password")
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(Error::AuthError {
+            code: Some(ErrorCode::InvalidCredentials),
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn admins_list_the_seeded_user() {
+    let gotrue = GoTrueContainer::start().await;
+
+    let users = gotrue
+        .admin_client()
+        .admin_list_users(ListUsersParams::default())
+        .await
+        .unwrap();
+
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].id, gotrue.user().id);
+}
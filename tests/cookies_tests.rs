@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use supabase_auth::{
+    cookies::{self, CookieOptions, MAX_CHUNK_SIZE},
+    models::Session,
+};
+
+fn test_session(metadata_size: usize) -> Session {
+    let mut session = Session {
+        access_token: "access".into(),
+        refresh_token: "refresh".into(),
+        token_type: "bearer".to_string(),
+        expires_in: 3600,
+        expires_at: DateTime::from_timestamp(Utc::now().timestamp() + 3600, 0).unwrap(),
+        ..Default::default()
+    };
+
+    session
+        .user
+        .user_metadata
+        .custom
+        .insert("padding".to_string(), "x".repeat(metadata_size).into());
+
+    session
+}
+
+/// Turn `Set-Cookie` values into the `Cookie` header a browser would send back
+fn to_cookie_header(set_cookies: &[String]) -> String {
+    set_cookies
+        .iter()
+        .map(|c| c.split(';').next().unwrap())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[test]
+fn cookie_name_from_project_url() {
+    assert_eq!(
+        cookies::cookie_name("https://abcdefgh.supabase.co").unwrap(),
+        "sb-abcdefgh-auth-token"
+    );
+}
+
+#[test]
+fn single_cookie_round_trip() {
+    let session = test_session(10);
+
+    let set_cookies =
+        cookies::build_session_cookies("sb-test-auth-token", &session, &CookieOptions::default())
+            .unwrap();
+
+    assert_eq!(set_cookies.len(), 1);
+    assert!(set_cookies[0].starts_with("sb-test-auth-token=base64-"));
+    assert!(set_cookies[0].contains("; Path=/; Max-Age=34560000; SameSite=Lax"));
+
+    let header = format!("other=1; {}", to_cookie_header(&set_cookies));
+    let parsed = cookies::parse_session_cookie("sb-test-auth-token", &header)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(parsed, session);
+}
+
+#[test]
+fn chunked_cookie_round_trip() {
+    let session = test_session(MAX_CHUNK_SIZE * 2);
+
+    let set_cookies =
+        cookies::build_session_cookies("sb-test-auth-token", &session, &CookieOptions::default())
+            .unwrap();
+
+    assert!(set_cookies.len() >= 3);
+    assert!(set_cookies[0].starts_with("sb-test-auth-token.0="));
+
+    let header = to_cookie_header(&set_cookies);
+    let parsed = cookies::parse_session_cookie("sb-test-auth-token", &header)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(parsed, session);
+
+    let removal =
+        cookies::build_removal_cookies("sb-test-auth-token", &header, &CookieOptions::default());
+
+    assert_eq!(removal.len(), set_cookies.len());
+    assert!(removal.iter().all(|c| c.contains("Max-Age=0")));
+}
+
+#[test]
+fn plain_json_cookie() {
+    let session = test_session(10);
+    let json = session.to_supabase_js_json().unwrap();
+    let encoded = json.replace('"', "%22").replace(' ', "%20");
+
+    let header = format!("sb-test-auth-token={}", encoded);
+    let parsed = cookies::parse_session_cookie("sb-test-auth-token", &header)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(parsed, session);
+    assert!(cookies::parse_session_cookie("sb-test-auth-token", "other=1")
+        .unwrap()
+        .is_none());
+}
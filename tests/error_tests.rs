@@ -0,0 +1,485 @@
+use std::time::Duration;
+
+use reqwest::{Request, ResponseBuilderExt, StatusCode, Url};
+
+use supabase_auth::{
+    error::{Error, ErrorCode, SupabaseHTTPError},
+    models::{AuthClient, Session, WeakPassword, WeakPasswordReason},
+    transport::{HttpTransport, TransportFuture},
+};
+
+/// Answers every request with `status`, `headers`, and `body`
+#[derive(Debug)]
+struct Respond {
+    status: u16,
+    headers: &'static [(&'static str, &'static str)],
+    body: &'static str,
+}
+
+impl HttpTransport for Respond {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let mut response = http::Response::builder()
+                .status(self.status)
+                .url(request.url().clone());
+            for (name, value) in self.headers {
+                response = response.header(*name, *value);
+            }
+
+            Ok(response.body(self.body).unwrap().into())
+        })
+    }
+}
+
+fn client_answering(status: u16, body: &'static str) -> AuthClient {
+    client_answering_with_headers(status, &[], body)
+}
+
+fn client_answering_with_headers(
+    status: u16,
+    headers: &'static [(&'static str, &'static str)],
+    body: &'static str,
+) -> AuthClient {
+    AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(Respond {
+            status,
+            headers,
+            body,
+        })
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn error_codes_round_trip() {
+    assert_eq!(
+        ErrorCode::from("email_not_confirmed"),
+        ErrorCode::EmailNotConfirmed
+    );
+    assert_eq!(
+        ErrorCode::OverEmailSendRateLimit.as_str(),
+        "over_email_send_rate_limit"
+    );
+    assert_eq!(ErrorCode::UserBanned.to_string(), "user_banned");
+
+    let code: ErrorCode = serde_json::from_str(r#""identity_already_exists""#).unwrap();
+    assert_eq!(code, ErrorCode::IdentityAlreadyExists);
+    assert_eq!(
+        serde_json::to_string(&code).unwrap(),
+        r#""identity_already_exists""#
+    );
+}
+
+#[test]
+fn unknown_error_codes_are_kept() {
+    let code = ErrorCode::from("brand_new_code");
+
+    assert_eq!(code, ErrorCode::Unknown("brand_new_code".to_string()));
+    assert_eq!(code.as_str(), "brand_new_code");
+}
+
+#[test]
+fn http_error_exposes_code() {
+    let error: SupabaseHTTPError = serde_json::from_str(
+        r#"{"code":400,"error_code":"invalid_credentials","msg":"Invalid login credentials"}"#,
+    )
+    .unwrap();
+
+    assert_eq!(error.code(), Some(ErrorCode::InvalidCredentials));
+}
+
+#[tokio::test]
+async fn auth_errors_carry_the_error_code() {
+    let auth_client = client_answering(
+        400,
+        r#"{"code":400,"error_code":"email_not_confirmed","msg":"Email not confirmed"}"#,
+    );
+
+    let result = auth_client
+        .login_with_email("demo@example.com", "password")
+        .await;
+
+    match result {
+        Err(Error::AuthError {
+            status,
+            code,
+            message,
+            ..
+        }) => {
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(code, Some(ErrorCode::EmailNotConfirmed));
+            assert_eq!(message, "Email not confirmed");
+        }
+        other => panic!("Expected AuthError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn errors_without_code_have_none() {
+    let auth_client = client_answering(502, "Bad Gateway");
+
+    let result = auth_client.get_health().await;
+
+    assert!(matches!(
+        result,
+        Err(Error::AuthError { code: None, message, .. }) if message == "Bad Gateway"
+    ));
+}
+
+#[test]
+fn error_bodies_of_every_shape_are_parsed() {
+    let shapes = [
+        (
+            r#"{"code":400,"error_code":"invalid_credentials","msg":"Invalid login credentials"}"#,
+            400,
+            Some(ErrorCode::InvalidCredentials),
+            "Invalid login credentials",
+        ),
+        (
+            r#"{"code":"invalid_credentials","message":"Invalid login credentials"}"#,
+            0,
+            Some(ErrorCode::InvalidCredentials),
+            "Invalid login credentials",
+        ),
+        (
+            r#"{"error":"invalid_grant","error_description":"Invalid Refresh Token"}"#,
+            0,
+            Some(ErrorCode::Unknown("invalid_grant".to_string())),
+            "Invalid Refresh Token",
+        ),
+        (
+            r#"{"message":"No API key found in request"}"#,
+            0,
+            None,
+            "No API key found in request",
+        ),
+        (r#"{"error":"not found"}"#, 0, None, "not found"),
+        (
+            r#"{"code":404,"msg":"","message":"User not found"}"#,
+            404,
+            None,
+            "User not found",
+        ),
+    ];
+
+    for (body, status, code, message) in shapes {
+        let error: SupabaseHTTPError = serde_json::from_str(body).unwrap();
+
+        assert_eq!(error.code, status, "{}", body);
+        assert_eq!(error.code(), code, "{}", body);
+        assert_eq!(error.message, message, "{}", body);
+    }
+}
+
+#[test]
+fn error_bodies_without_message_are_rejected() {
+    assert!(serde_json::from_str::<SupabaseHTTPError>(r#"{"code":500}"#).is_err());
+    assert!(serde_json::from_str::<SupabaseHTTPError>(r#"{"msg":"  "}"#).is_err());
+}
+
+#[tokio::test]
+async fn oauth_error_bodies_are_parsed() {
+    let auth_client = client_answering(
+        400,
+        r#"{"error":"invalid_grant","error_description":"Invalid Refresh Token: Not Found"}"#,
+    );
+
+    let result = auth_client.get_health().await;
+
+    match result {
+        Err(Error::AuthError {
+            status,
+            code,
+            message,
+            ..
+        }) => {
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(code, Some(ErrorCode::Unknown("invalid_grant".to_string())));
+            assert_eq!(message, "Invalid Refresh Token: Not Found");
+        }
+        other => panic!("Expected AuthError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn empty_error_bodies_have_a_message() {
+    let auth_client = client_answering(503, "");
+
+    let result = auth_client.get_health().await;
+
+    assert!(matches!(
+        result,
+        Err(Error::AuthError { status, message, .. })
+            if status == StatusCode::SERVICE_UNAVAILABLE && message == "Service Unavailable"
+    ));
+}
+
+#[tokio::test]
+async fn unexpected_json_error_bodies_are_kept() {
+    let auth_client = client_answering(500, r#"{"unexpected":true}"#);
+
+    let result = auth_client.get_health().await;
+
+    assert!(matches!(
+        result,
+        Err(Error::AuthError { message, .. }) if message == r#"{"unexpected":true}"#
+    ));
+}
+
+#[tokio::test]
+async fn auth_errors_carry_status_and_headers() {
+    let auth_client = client_answering_with_headers(
+        429,
+        &[("retry-after", "30"), ("x-sb-request-id", "req-123")],
+        r#"{"code":429,"error_code":"over_request_rate_limit","msg":"Request rate limit reached"}"#,
+    );
+
+    let error = auth_client
+        .login_with_email("demo@example.com", "password")
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.status(), Some(StatusCode::TOO_MANY_REQUESTS));
+    assert_eq!(error.code(), Some(&ErrorCode::OverRequestRateLimit));
+
+    let headers = error.headers().unwrap();
+    assert_eq!(headers["retry-after"], "30");
+    assert_eq!(headers["x-sb-request-id"], "req-123");
+
+    assert!(matches!(
+        error,
+        Error::RateLimited { retry_after: Some(retry_after), .. }
+            if retry_after == Duration::from_secs(30)
+    ));
+}
+
+#[tokio::test]
+async fn rate_limited_without_retry_after() {
+    let auth_client = client_answering(
+        429,
+        r#"{"code":429,"error_code":"over_email_send_rate_limit","msg":"Email rate limit exceeded"}"#,
+    );
+
+    let result = auth_client
+        .send_login_email_with_magic_link("demo@example.com")
+        .await;
+
+    match result {
+        Err(Error::RateLimited {
+            retry_after,
+            code,
+            message,
+            ..
+        }) => {
+            assert_eq!(retry_after, None);
+            assert_eq!(code, Some(ErrorCode::OverEmailSendRateLimit));
+            assert_eq!(message, "Email rate limit exceeded");
+        }
+        other => panic!("Expected RateLimited, got {:?}", other),
+    }
+}
+
+#[test]
+fn other_errors_have_no_status() {
+    assert_eq!(Error::WrongToken.status(), None);
+    assert!(Error::WrongToken.headers().is_none());
+}
+
+#[tokio::test]
+async fn weak_passwords_are_explained() {
+    let auth_client = client_answering(
+        422,
+        r#"{"code":422,"error_code":"weak_password","msg":"Password should be at least 8 characters.","weak_password":{"reasons":["length","pwned","future_reason"]}}"#,
+    );
+
+    let result = auth_client
+        .sign_up_with_email_and_password("demo@example.com", "abc", None)
+        .await;
+
+    match result {
+        Err(Error::WeakPassword {
+            status,
+            reasons,
+            message,
+            ..
+        }) => {
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(
+                reasons,
+                vec![
+                    WeakPasswordReason::Length,
+                    WeakPasswordReason::Pwned,
+                    WeakPasswordReason::Unknown
+                ]
+            );
+            assert_eq!(message, "Password should be at least 8 characters.");
+        }
+        other => panic!("Expected WeakPassword, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn weak_password_errors_keep_the_response() {
+    let auth_client = client_answering_with_headers(
+        422,
+        &[("sb-request-id", "request-id")],
+        r#"{"code":422,"error_code":"weak_password","msg":"Password should be at least 8 characters.","weak_password":{"reasons":["length"]}}"#,
+    );
+
+    let error = auth_client
+        .sign_up_with_email_and_password("demo@example.com", "abc", None)
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.status(), Some(StatusCode::UNPROCESSABLE_ENTITY));
+    assert_eq!(error.code(), Some(&ErrorCode::WeakPassword));
+    assert_eq!(error.request_id(), Some("request-id"));
+}
+
+#[test]
+fn sessions_report_weak_passwords() {
+    let session: Session = serde_json::from_str(
+        r#"{
+            "access_token": "access",
+            "token_type": "bearer",
+            "expires_in": 3600,
+            "refresh_token": "refresh",
+            "user": {
+                "id": "01936a4e-7b5c-7d1e-9c3b-2a1f0e9d8c7b",
+                "aud": "authenticated",
+                "role": "authenticated",
+                "email": "demo@example.com",
+                "phone": "",
+                "app_metadata": {},
+                "user_metadata": {},
+                "identities": [],
+                "created_at": "2024-01-01T00:00:00Z",
+                "updated_at": "2024-01-01T00:00:00Z",
+                "is_anonymous": false
+            },
+            "weak_password": {
+                "reasons": ["characters"],
+                "message": "Password should contain a digit"
+            }
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        session.weak_password,
+        Some(WeakPassword {
+            reasons: vec![WeakPasswordReason::Characters],
+            message: Some("Password should contain a digit".to_string()),
+        })
+    );
+}
+
+#[tokio::test]
+async fn undecodable_responses_keep_the_body() {
+    let auth_client = client_answering(200, r#"{"version":2}"#);
+
+    let result = auth_client.get_health().await;
+
+    match result {
+        Err(error @ Error::DecodeError { .. }) => {
+            assert!(std::error::Error::source(&error)
+                .is_some_and(|source| source.is::<serde_json::Error>()));
+            assert!(matches!(
+                error,
+                Error::DecodeError { endpoint, body, .. }
+                    if endpoint == "/auth/v1/health" && body == r#"{"version":2}"#
+            ));
+        }
+        other => panic!("Expected DecodeError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn network_errors_have_a_source() {
+    let auth_client = AuthClient::builder()
+        .project_url("http://127.0.0.1:1")
+        .api_key("api_key")
+        .build()
+        .unwrap();
+
+    let error = auth_client.get_health().await.unwrap_err();
+
+    assert!(matches!(error, Error::NetworkError(_)));
+    assert!(std::error::Error::source(&error).is_some_and(|source| source.is::<reqwest::Error>()));
+}
+
+#[tokio::test]
+async fn undecodable_bodies_are_capped() {
+    let body: &'static str = Box::leak(format!("[{}]", "1,".repeat(5000)).into_boxed_str());
+    let auth_client = client_answering(200, body);
+
+    let result = auth_client.get_settings().await;
+
+    assert!(matches!(
+        result,
+        Err(Error::DecodeError { endpoint, body, .. })
+            if endpoint == "/auth/v1/settings" && body.len() == 4096
+    ));
+}
+
+#[tokio::test]
+async fn undecodable_bodies_are_capped_at_a_character_boundary() {
+    let body: &'static str = Box::leak(format!("[\"x{}\"", "é".repeat(3000)).into_boxed_str());
+    let auth_client = client_answering(200, body);
+
+    let result = auth_client.get_settings().await;
+
+    assert!(matches!(
+        result,
+        Err(Error::DecodeError { body, .. })
+            if body.len() == 4095 && body.ends_with('é')
+    ));
+}
+
+#[test]
+fn redirect_errors_in_the_fragment_are_parsed() {
+    let url = Url::parse(
+        "https://example.com/callback#error=access_denied&error_code=otp_expired&error_description=Email+link+is+invalid+or+has+expired",
+    )
+    .unwrap();
+
+    match Error::from_redirect_url(&url) {
+        Some(Error::AuthError {
+            status,
+            code,
+            message,
+            ..
+        }) => {
+            assert_eq!(status, StatusCode::FORBIDDEN);
+            assert_eq!(code, Some(ErrorCode::OtpExpired));
+            assert_eq!(message, "Email link is invalid or has expired");
+        }
+        other => panic!("Expected AuthError, got {:?}", other),
+    }
+}
+
+#[test]
+fn redirect_errors_in_the_query_are_parsed() {
+    let url = Url::parse(
+        "https://example.com/callback?error=server_error&error_code=500&error_description=Unable%20to%20exchange%20external%20code",
+    )
+    .unwrap();
+
+    let error = Error::from_redirect_url(&url).unwrap();
+
+    assert_eq!(error.status(), Some(StatusCode::INTERNAL_SERVER_ERROR));
+    assert_eq!(error.code(), None);
+    assert!(matches!(
+        error,
+        Error::AuthError { message, .. } if message == "Unable to exchange external code"
+    ));
+}
+
+#[test]
+fn redirects_without_errors_are_ignored() {
+    let url = Url::parse("https://example.com/callback?code=abc#access_token=token").unwrap();
+
+    assert!(Error::from_redirect_url(&url).is_none());
+}
@@ -0,0 +1,331 @@
+#![cfg(feature = "test-utils")]
+
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use serde_json::json;
+use supabase_auth::{
+    error::{Error, ErrorCode},
+    fake::FakeSession,
+    fake_server::{FakeAuthClient, FakeAuthServer},
+    managed::ManagedAuthClient,
+    models::{
+        LogoutScope, OtpType, Patch, SignUpWithPasswordOptions, UpdatedUser, VerifyEmailOtpParams,
+        VerifyMobileOtpParams, VerifyOtpParams,
+    },
+};
+
+const EMAIL: &str = "demo@example.com";
+const PASSWORD: &str = "password";
+
+fn error_code<T>(result: Result<T, Error>) -> Option<ErrorCode> {
+    match result {
+        Err(error) => error.code().cloned(),
+        Ok(_) => None,
+    }
+}
+
+#[tokio::test]
+async fn users_sign_up_and_sign_in() {
+    let fake = FakeAuthClient::new();
+    let auth_client = fake.client();
+
+    let result = auth_client
+        .sign_up_with_email_and_password(
+            EMAIL,
+            PASSWORD,
+            Some(SignUpWithPasswordOptions::default().data(json!({ "name": "Demo" }))),
+        )
+        .await
+        .unwrap();
+    let session = result
+        .into_session()
+        .expect("emails are confirmed right away");
+    assert_eq!(session.user.email, EMAIL);
+    assert_eq!(session.user.user_metadata.name.as_deref(), Some("Demo"));
+
+    let claims = auth_client
+        .verify_access_token(&session.access_token)
+        .unwrap();
+    assert_eq!(claims.sub, session.user.id);
+
+    let session = auth_client.login_with_email(EMAIL, PASSWORD).await.unwrap();
+    let user = auth_client.get_user(&session.access_token).await.unwrap();
+    assert_eq!(user.id, fake.server().user(EMAIL).unwrap().id);
+
+    let result = auth_client
+        .sign_up_with_email_and_password(EMAIL, PASSWORD, None)
+        .await;
+    assert_eq!(error_code(result), Some(ErrorCode::UserAlreadyExists));
+
+    let result = auth_client
+        .sign_up_with_email_and_password("other@example.com", "short", None)
+        .await;
+    assert!(matches!(result, Err(Error::WeakPassword { .. })));
+
+    let result = auth_client.login_with_email(EMAIL, "wrong-password").await;
+    assert_eq!(error_code(result), Some(ErrorCode::InvalidCredentials));
+}
+
+#[tokio::test]
+async fn refresh_tokens_rotate() {
+    let server = FakeAuthServer::new();
+    server.add_user(EMAIL, PASSWORD);
+    let fake = server.client();
+    let auth_client = fake.client();
+
+    let session = auth_client.login_with_email(EMAIL, PASSWORD).await.unwrap();
+    let refreshed = auth_client
+        .refresh_session(&session.refresh_token)
+        .await
+        .unwrap();
+    assert_ne!(refreshed.refresh_token, session.refresh_token);
+
+    let result = auth_client.refresh_session(&session.refresh_token).await;
+    assert!(matches!(result, Err(Error::RefreshTokenAlreadyUsed)));
+
+    auth_client
+        .refresh_session(&refreshed.refresh_token)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn signing_out_revokes_sessions_by_scope() {
+    let server = FakeAuthServer::new();
+    let user = server.add_user(EMAIL, PASSWORD);
+    let fake = server.client();
+    let auth_client = fake.client();
+
+    let first = auth_client.login_with_email(EMAIL, PASSWORD).await.unwrap();
+    let second = auth_client.login_with_email(EMAIL, PASSWORD).await.unwrap();
+    assert_eq!(server.active_sessions(user.id), 2);
+
+    auth_client
+        .logout(Some(LogoutScope::Local), &second.access_token)
+        .await
+        .unwrap();
+    assert_eq!(server.active_sessions(user.id), 1);
+
+    let result = auth_client.get_user(&second.access_token).await;
+    assert_eq!(error_code(result), Some(ErrorCode::SessionNotFound));
+    let result = auth_client.refresh_session(&second.refresh_token).await;
+    assert_eq!(error_code(result), Some(ErrorCode::RefreshTokenNotFound));
+
+    auth_client.logout(None, &first.access_token).await.unwrap();
+    assert_eq!(server.active_sessions(user.id), 0);
+}
+
+#[tokio::test]
+async fn otps_are_issued_and_verified() {
+    let fake = FakeAuthClient::new();
+    let auth_client = fake.client();
+
+    auth_client.send_email_with_otp(EMAIL, None).await.unwrap();
+    let otp = fake.server().otp(EMAIL).unwrap();
+    assert_eq!(otp.len(), 6);
+
+    let result = auth_client
+        .verify_otp(VerifyOtpParams::Email(VerifyEmailOtpParams {
+            email: EMAIL.to_string(),
+            token: "000000".to_string(),
+            otp_type: OtpType::Email,
+            ..Default::default()
+        }))
+        .await;
+    assert_eq!(error_code(result), Some(ErrorCode::OtpExpired));
+
+    let session = auth_client
+        .verify_otp(VerifyOtpParams::Email(VerifyEmailOtpParams {
+            email: EMAIL.to_string(),
+            token: otp,
+            otp_type: OtpType::Email,
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    assert!(session.user.email_confirmed_at.is_some());
+    assert!(fake.server().otp(EMAIL).is_none());
+
+    let response = auth_client.send_sms_with_otp("+15555550100").await.unwrap();
+    assert!(response.message_id.is_some());
+    let session = auth_client
+        .verify_otp(VerifyOtpParams::Mobile(VerifyMobileOtpParams {
+            phone: "+15555550100".to_string(),
+            token: fake.server().otp("+15555550100").unwrap(),
+            otp_type: OtpType::Sms,
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    assert_eq!(session.user.phone, "+15555550100");
+    assert_eq!(session.user.providers(), vec!["phone"]);
+}
+
+#[tokio::test]
+async fn unconfirmed_users_confirm_before_signing_in() {
+    let server = FakeAuthServer::new().require_email_confirmation(true);
+    let fake = server.client();
+    let auth_client = fake.client();
+
+    let result = auth_client
+        .sign_up_with_email_and_password(EMAIL, PASSWORD, None)
+        .await
+        .unwrap();
+    assert!(result.needs_confirmation());
+    let confirmation = result.confirmation().unwrap();
+    assert_eq!(confirmation.email.as_deref(), Some(EMAIL));
+    assert_eq!(result.user_id(), confirmation.id);
+
+    let result = auth_client.login_with_email(EMAIL, PASSWORD).await;
+    assert_eq!(error_code(result), Some(ErrorCode::EmailNotConfirmed));
+
+    auth_client
+        .verify_otp(VerifyOtpParams::Email(VerifyEmailOtpParams {
+            email: EMAIL.to_string(),
+            token: server.otp(EMAIL).unwrap(),
+            otp_type: OtpType::Signup,
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    auth_client.login_with_email(EMAIL, PASSWORD).await.unwrap();
+}
+
+#[tokio::test]
+async fn users_update_their_password() {
+    let server = FakeAuthServer::new();
+    server.add_user(EMAIL, PASSWORD);
+    let fake = server.client();
+    let auth_client = fake.client();
+
+    let session = auth_client.login_with_email(EMAIL, PASSWORD).await.unwrap();
+    auth_client
+        .update_user(
+            UpdatedUser {
+                password: Patch::Set("new-password".to_string()),
+                ..Default::default()
+            },
+            &session.access_token,
+        )
+        .await
+        .unwrap();
+
+    assert!(auth_client.login_with_email(EMAIL, PASSWORD).await.is_err());
+    auth_client
+        .login_with_email(EMAIL, "new-password")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn users_update_their_metadata() {
+    let server = FakeAuthServer::new();
+    server.add_user(EMAIL, PASSWORD);
+    let fake = server.client();
+    let auth_client = fake.client();
+
+    let session = auth_client.login_with_email(EMAIL, PASSWORD).await.unwrap();
+    let update = |updated_user| auth_client.update_user(updated_user, &session.access_token);
+
+    update(UpdatedUser::default().data(json!({ "theme": "dark", "nickname": "demo" })))
+        .await
+        .unwrap();
+    let user = update(UpdatedUser::default().remove_data("nickname"))
+        .await
+        .unwrap();
+
+    assert_eq!(user.user_metadata.custom.get("theme"), Some(&json!("dark")));
+    assert_eq!(user.user_metadata.custom.get("nickname"), None);
+}
+
+#[tokio::test]
+async fn anonymous_users_sign_in() {
+    let fake = FakeAuthClient::new();
+
+    let session = fake.client().login_anonymously(None).await.unwrap();
+
+    assert!(session.user.is_anonymous);
+    assert_eq!(fake.server().users().len(), 1);
+}
+
+#[tokio::test]
+async fn expired_access_tokens_are_rejected_without_a_request() {
+    let fake = FakeAuthClient::new();
+    let user = fake.server().add_user(EMAIL, PASSWORD);
+    let expired = FakeSession::new().user(user).expired().build();
+
+    let requests = Arc::new(Mutex::new(0));
+    let counter = requests.clone();
+    let auth_client = fake
+        .client()
+        .on_response(move |_| *counter.lock().unwrap() += 1);
+
+    let result = auth_client.get_user(&expired.access_token).await;
+    assert!(matches!(
+        result,
+        Err(Error::TokenExpired { expired_at }) if expired_at < Utc::now()
+    ));
+    let result = auth_client.logout(None, &expired.access_token).await;
+    assert!(matches!(result, Err(Error::TokenExpired { .. })));
+
+    assert_eq!(*requests.lock().unwrap(), 0);
+}
+
+#[tokio::test]
+async fn managed_clients_refresh_expired_sessions_before_calling() {
+    let fake = FakeAuthClient::new();
+    fake.server().add_user(EMAIL, PASSWORD);
+    let mut session = fake
+        .client()
+        .login_with_email(EMAIL, PASSWORD)
+        .await
+        .unwrap();
+
+    let expired = FakeSession::new()
+        .user(session.user.clone())
+        .expired()
+        .build();
+    session.access_token = expired.access_token;
+    session.expires_at = expired.expires_at;
+
+    let paths = Arc::new(Mutex::new(Vec::new()));
+    let recorded = paths.clone();
+    let auth_client = fake
+        .client()
+        .on_response(move |meta| recorded.lock().unwrap().push(meta.path.clone()));
+    let managed = ManagedAuthClient::with_session(auth_client, session);
+
+    let user = managed.get_user().await.unwrap();
+
+    assert_eq!(user.email, EMAIL);
+    assert!(!managed.session().unwrap().is_expired());
+    assert_eq!(*paths.lock().unwrap(), ["/auth/v1/token", "/auth/v1/user"]);
+}
+
+#[tokio::test]
+async fn managed_clients_dont_refresh_revoked_sessions() {
+    let fake = FakeAuthClient::new();
+    fake.server().add_user(EMAIL, PASSWORD);
+    let session = fake
+        .client()
+        .login_with_email(EMAIL, PASSWORD)
+        .await
+        .unwrap();
+    fake.client()
+        .logout(Some(LogoutScope::Local), &session.access_token)
+        .await
+        .unwrap();
+
+    let paths = Arc::new(Mutex::new(Vec::new()));
+    let recorded = paths.clone();
+    let auth_client = fake
+        .client()
+        .on_response(move |meta| recorded.lock().unwrap().push(meta.path.clone()));
+    let managed = ManagedAuthClient::with_session(auth_client, session);
+
+    let result = managed.get_user().await;
+
+    assert_eq!(error_code(result), Some(ErrorCode::SessionNotFound));
+    assert_eq!(*paths.lock().unwrap(), ["/auth/v1/user"]);
+}
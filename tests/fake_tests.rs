@@ -0,0 +1,74 @@
+#![cfg(feature = "test-utils")]
+
+use std::time::Duration;
+
+use serde_json::json;
+use supabase_auth::{
+    fake::{FakeSession, FakeUser, JWT_SECRET},
+    models::{AuthClient, Session, User},
+};
+
+#[test]
+fn fake_users_are_confirmed_email_users() {
+    let user = User::fake();
+
+    assert!(user.email.ends_with("@example.com"));
+    assert_eq!(user.role, "authenticated");
+    assert!(user.email_confirmed_at.is_some());
+    assert_eq!(user.providers(), vec!["email"]);
+    assert_eq!(user.identities[0].user_id, user.id);
+    assert_ne!(User::fake().id, user.id);
+}
+
+#[test]
+fn fake_users_can_be_overridden() {
+    let user = FakeUser::new()
+        .email("admin@example.com")
+        .role("service_role")
+        .app_metadata("roles", json!(["admin"]))
+        .build();
+
+    assert_eq!(user.email, "admin@example.com");
+    assert_eq!(user.role, "service_role");
+    assert_eq!(user.app_metadata.custom["roles"], json!(["admin"]));
+    assert_eq!(
+        user.user_metadata.email.as_deref(),
+        Some("admin@example.com")
+    );
+
+    let anonymous = FakeUser::new().anonymous().build();
+    assert!(anonymous.is_anonymous);
+    assert!(anonymous.identities.is_empty());
+}
+
+#[test]
+fn fake_sessions_verify_with_the_jwt_secret() {
+    let session = FakeSession::new().email("demo@example.com").build();
+    let auth_client = AuthClient::new("http://127.0.0.1:54321", "api_key", JWT_SECRET);
+
+    let claims = auth_client
+        .verify_access_token(&session.access_token)
+        .unwrap();
+
+    assert_eq!(claims.sub, session.user.id);
+    assert_eq!(claims.email, "demo@example.com");
+    assert!(!session.is_expired());
+    assert!(!Session::fake().refresh_token.is_empty());
+}
+
+#[test]
+fn fake_sessions_can_expire() {
+    let session = FakeSession::new()
+        .expires_in(Duration::from_secs(300))
+        .build();
+    assert_eq!(session.expires_in, 300);
+    assert!(session.expires_within(Duration::from_secs(600)));
+
+    let expired = FakeSession::new().expired().build();
+    assert!(expired.is_expired());
+
+    let auth_client = AuthClient::new("http://127.0.0.1:54321", "api_key", JWT_SECRET);
+    assert!(auth_client
+        .verify_access_token(&expired.access_token)
+        .is_err());
+}
@@ -0,0 +1,125 @@
+#![cfg(feature = "leptos")]
+
+use chrono::{DateTime, Utc};
+use http::{header::SET_COOKIE, Request};
+use leptos::{
+    prelude::{provide_context, Owner},
+    reactive::computed::ScopedFuture,
+};
+use leptos_axum::ResponseOptions;
+
+use supabase_auth::{
+    cookies::{self, CookieOptions},
+    error::Error,
+    leptos::{clear_session, get_session, set_session},
+    models::{AuthClient, Session},
+};
+
+const PROJECT_URL: &str = "https://abcdefghijklmnop.supabase.co";
+
+fn auth_client() -> AuthClient {
+    AuthClient::new(PROJECT_URL, "api_key", "")
+}
+
+fn session() -> Session {
+    let mut session = Session {
+        access_token: "access".into(),
+        refresh_token: "refresh".into(),
+        token_type: "bearer".to_string(),
+        expires_in: 3600,
+        expires_at: DateTime::from_timestamp(Utc::now().timestamp() + 3600, 0).unwrap(),
+        ..Default::default()
+    };
+    session.user.email = "demo@example.com".to_string();
+
+    session
+}
+
+/// An owner with the context `leptos_axum` provides to server functions, which also run their
+/// futures in it
+fn owner(cookie: Option<String>) -> (Owner, ResponseOptions) {
+    let mut request = Request::builder().uri("/api/whoami");
+    if let Some(cookie) = cookie {
+        request = request.header("cookie", cookie);
+    }
+
+    let owner = Owner::new();
+    let response = ResponseOptions::default();
+    owner.with(|| {
+        provide_context(request.body(()).unwrap().into_parts().0);
+        provide_context(response.clone());
+    });
+
+    (owner, response)
+}
+
+fn set_cookies(response: &ResponseOptions) -> Vec<String> {
+    response
+        .0
+        .read()
+        .unwrap()
+        .headers
+        .get_all(SET_COOKIE)
+        .iter()
+        .map(|value| value.to_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn session_is_read_from_the_request_cookies() {
+    let name = cookies::cookie_name(PROJECT_URL).unwrap();
+    let cookie = cookies::build_session_cookies(&name, &session(), &CookieOptions::default())
+        .unwrap()
+        .remove(0);
+    let cookie = cookie.split(';').next().unwrap().to_string();
+
+    let (owner, response) = owner(Some(cookie));
+    let auth_client = auth_client();
+    let session = owner
+        .with(|| ScopedFuture::new(get_session(&auth_client)))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(session.user.email, "demo@example.com");
+    assert!(set_cookies(&response).is_empty());
+
+    let (owner, _) = self::owner(None);
+    let session = owner
+        .with(|| ScopedFuture::new(get_session(&auth_client)))
+        .await
+        .unwrap();
+    assert!(session.is_none());
+}
+
+#[test]
+fn sessions_are_written_and_cleared_with_set_cookie_headers() {
+    let (owner, response) = owner(Some("sb-abcdefghijklmnop-auth-token.1=stale".to_string()));
+
+    owner.with(|| {
+        provide_context(CookieOptions {
+            secure: true,
+            ..Default::default()
+        });
+        set_session(&auth_client(), &session()).unwrap();
+    });
+
+    let cookies = set_cookies(&response);
+    assert!(cookies[0].starts_with("sb-abcdefghijklmnop-auth-token.1=; "));
+    assert!(cookies[1].starts_with("sb-abcdefghijklmnop-auth-token=base64-"));
+    assert!(cookies[1].ends_with("; Secure"));
+
+    let (owner, response) = self::owner(Some("sb-abcdefghijklmnop-auth-token=value".to_string()));
+    owner.with(|| clear_session(&auth_client())).unwrap();
+
+    let cookies = set_cookies(&response);
+    assert_eq!(cookies.len(), 1);
+    assert!(cookies[0].contains("Max-Age=0"));
+}
+
+#[test]
+fn helpers_need_the_leptos_axum_context() {
+    let result = Owner::new().with(|| set_session(&auth_client(), &session()));
+
+    assert!(matches!(result, Err(Error::MissingConfiguration(_))));
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "logging")]
+
+use std::sync::{Arc, Mutex};
+
+use reqwest::{Method, Request, StatusCode};
+use supabase_auth::{
+    error::{Error, ErrorCode},
+    logging::RequestLog,
+    meta::SB_REQUEST_ID,
+    models::{AuthClient, ResetPasswordOptions},
+    transport::{HttpTransport, TransportFuture},
+};
+
+const INVALID_CREDENTIALS: &str =
+    r#"{"code":400,"error_code":"invalid_credentials","msg":"Invalid login credentials"}"#;
+
+/// Rejects every request with invalid credentials
+#[derive(Debug)]
+struct Rejecting;
+
+impl HttpTransport for Rejecting {
+    fn execute(&self, _request: Request) -> TransportFuture<'_> {
+        Box::pin(async {
+            let response = http::Response::builder()
+                .status(400)
+                .header(SB_REQUEST_ID, "request-1")
+                .body(INVALID_CREDENTIALS)
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+#[tokio::test]
+async fn rejected_requests_are_logged_with_their_error_code() {
+    let logged = Arc::new(Mutex::new(Vec::<RequestLog>::new()));
+    let entries = logged.clone();
+    let auth_client = AuthClient::builder()
+        .project_url("https://abcdefghijklmnop.supabase.co")
+        .api_key("api_key")
+        .transport(Rejecting)
+        .logger(move |entry| entries.lock().unwrap().push(entry.clone()))
+        .build()
+        .unwrap();
+
+    let error = auth_client
+        .login_with_email("user@example.com", "wrong-password")
+        .await
+        .unwrap_err();
+
+    // The error is still decoded from the body the logger read
+    assert_eq!(error.code(), Some(&ErrorCode::InvalidCredentials));
+
+    let logged = logged.lock().unwrap();
+    let entry = &logged[0];
+    assert_eq!(entry.method, Method::POST);
+    assert_eq!(entry.path, "/auth/v1/token");
+    assert_eq!(entry.query.as_deref(), Some("grant_type=password"));
+    assert_eq!(entry.status, Some(StatusCode::BAD_REQUEST));
+    assert_eq!(entry.error_code.as_deref(), Some("invalid_credentials"));
+    assert_eq!(entry.request_id.as_deref(), Some("request-1"));
+    assert!(entry
+        .to_string()
+        .starts_with("POST /auth/v1/token?grant_type=password 400 invalid_credentials "));
+}
+
+#[tokio::test]
+async fn sensitive_query_values_and_failed_connections_are_logged_redacted() {
+    let logged = Arc::new(Mutex::new(Vec::<RequestLog>::new()));
+    let entries = logged.clone();
+    let auth_client = AuthClient::builder()
+        .project_url("http://127.0.0.1:1")
+        .api_key("api_key")
+        .logger(move |entry| entries.lock().unwrap().push(entry.clone()))
+        .build()
+        .unwrap();
+
+    let mut options = ResetPasswordOptions::default();
+    options.email_redirect_to = Some("https://app.example.com/reset?secret=1".to_string());
+    let result = auth_client
+        .reset_password_for_email("user@example.com", Some(options))
+        .await;
+
+    assert!(matches!(result, Err(Error::NetworkError(_))));
+
+    let logged = logged.lock().unwrap();
+    let entry = &logged[0];
+    assert_eq!(entry.status, None);
+    assert_eq!(entry.error_code.as_deref(), Some("connect"));
+    assert_eq!(entry.query.as_deref(), Some("redirect_to=[REDACTED]"));
+    assert!(!entry.to_string().contains("secret"));
+}
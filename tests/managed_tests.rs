@@ -0,0 +1,181 @@
+use std::{
+    env,
+    sync::{Arc, Mutex},
+};
+
+use reqwest::{Request, ResponseBuilderExt, StatusCode};
+
+use supabase_auth::{
+    error::Error,
+    events::{AuthEvent, AuthStateChange},
+    managed::ManagedAuthClient,
+    models::{AuthClient, Session},
+    transport::{HttpTransport, TransportFuture},
+};
+
+/// Answers every request with `401 Unauthorized`, recording the paths requested
+#[derive(Debug, Clone, Default)]
+struct Unauthorized {
+    paths: Arc<Mutex<Vec<String>>>,
+}
+
+impl HttpTransport for Unauthorized {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            self.paths
+                .lock()
+                .unwrap()
+                .push(request.url().path().to_string());
+
+            let response = http::Response::builder()
+                .status(401)
+                .url(request.url().clone())
+                .body(r#"{"message":"Invalid authentication credentials"}"#)
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+#[tokio::test]
+async fn managed_client_requires_session() {
+    let auth_client = ManagedAuthClient::new(AuthClient::new(
+        "http://localhost:54321",
+        "api_key",
+        "jwt_secret",
+    ));
+
+    assert!(auth_client.session().is_none());
+    assert!(matches!(
+        auth_client.get_user().await,
+        Err(Error::NotAuthenticated)
+    ));
+    assert!(matches!(
+        auth_client.refresh_session().await,
+        Err(Error::MissingRefreshToken)
+    ));
+}
+
+#[tokio::test]
+async fn managed_client_only_refreshes_expired_tokens() {
+    let transport = Unauthorized::default();
+    let auth_client = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+    let session: Session = serde_json::from_value(serde_json::json!({
+        "access_token": "access",
+        "token_type": "bearer",
+        "expires_in": 3600,
+        "refresh_token": "refresh",
+        "user": {
+            "id": "01936a4e-7b5c-7d1e-9c3b-2a1f0e9d8c7b",
+            "aud": "authenticated",
+            "role": "authenticated",
+            "email": "demo@example.com",
+            "phone": "",
+            "app_metadata": {},
+            "user_metadata": {},
+            "identities": [],
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "is_anonymous": false
+        }
+    }))
+    .unwrap();
+    let managed = ManagedAuthClient::with_session(auth_client, session);
+
+    let error = managed.get_user().await.unwrap_err();
+
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED));
+    assert_eq!(*transport.paths.lock().unwrap(), ["/auth/v1/user"]);
+}
+
+#[tokio::test]
+async fn managed_client_tracks_session() {
+    let auth_client = ManagedAuthClient::new(AuthClient::new_from_env().unwrap());
+
+    let demo_email = env::var("DEMO_EMAIL").unwrap();
+    let demo_password = env::var("DEMO_PASSWORD").unwrap();
+
+    auth_client
+        .login_with_email(&demo_email, &demo_password)
+        .await
+        .unwrap();
+
+    let user = auth_client.get_user().await.unwrap();
+
+    assert!(user.email == demo_email);
+
+    let refreshed = auth_client.refresh_session().await.unwrap();
+
+    assert!(auth_client.session().unwrap().access_token == refreshed.access_token);
+
+    auth_client.logout(None).await.unwrap();
+
+    assert!(auth_client.session().is_none())
+}
+
+#[test]
+fn auth_state_change_callbacks() {
+    let auth_client = ManagedAuthClient::new(AuthClient::new(
+        "http://localhost:54321",
+        "api_key",
+        "jwt_secret",
+    ));
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+
+    let subscription = auth_client.on_auth_state_change(move |event, session| {
+        recorded
+            .lock()
+            .unwrap()
+            .push((event, session.map(|s| s.access_token.clone())));
+    });
+
+    let session = Session {
+        access_token: "access".into(),
+        ..Default::default()
+    };
+
+    auth_client.replace_session(session).unwrap();
+    auth_client.clear_session().unwrap();
+
+    subscription.unsubscribe();
+
+    auth_client.clear_session().unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            (AuthEvent::SignedIn, Some("access".into())),
+            (AuthEvent::SignedOut, None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn auth_state_change_broadcast() {
+    let auth_client = ManagedAuthClient::new(AuthClient::new(
+        "http://localhost:54321",
+        "api_key",
+        "jwt_secret",
+    ));
+
+    let mut first = auth_client.subscribe_auth_events();
+    let mut second = auth_client.subscribe_auth_events();
+
+    auth_client.clear_session().unwrap();
+
+    let expected = AuthStateChange {
+        event: AuthEvent::SignedOut,
+        session: None,
+    };
+
+    assert_eq!(first.recv().await.unwrap(), expected);
+    assert_eq!(second.recv().await.unwrap(), expected);
+}
@@ -0,0 +1,128 @@
+use std::sync::{
+    atomic::{AtomicU16, Ordering},
+    Arc, Mutex,
+};
+
+use reqwest::{Method, Request, StatusCode};
+use supabase_auth::{
+    meta::{ResponseMeta, SB_REQUEST_ID},
+    models::{AuthClient, RefreshToken},
+    transport::{HttpTransport, TransportFuture},
+};
+
+const HEALTH: &str = r#"{"version":"v2.170.0","name":"GoTrue","description":""}"#;
+
+const INVALID_REFRESH_TOKEN: &str =
+    r#"{"code":400,"error_code":"refresh_token_not_found","msg":"Invalid Refresh Token"}"#;
+
+/// Answers with `status`, tagging each response with a numbered request id
+#[derive(Debug, Default, Clone)]
+struct Tagged {
+    requests: Arc<AtomicU16>,
+}
+
+impl HttpTransport for Tagged {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let number = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+            let (status, body) = match request.url().path() {
+                "/auth/v1/health" => (200, HEALTH),
+                _ => (400, INVALID_REFRESH_TOKEN),
+            };
+
+            let response = http::Response::builder()
+                .status(status)
+                .header(SB_REQUEST_ID, format!("request-{number}"))
+                .body(body)
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+fn builder() -> supabase_auth::builder::AuthClientBuilder {
+    AuthClient::builder()
+        .project_url("https://abcdefghijklmnop.supabase.co")
+        .api_key("api_key")
+        .transport(Tagged::default())
+}
+
+fn recorder() -> (
+    Arc<Mutex<Vec<ResponseMeta>>>,
+    impl Fn(&ResponseMeta) + Send + Sync + 'static,
+) {
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let hook = {
+        let recorded = recorded.clone();
+        move |meta: &ResponseMeta| recorded.lock().unwrap().push(meta.clone())
+    };
+
+    (recorded, hook)
+}
+
+#[tokio::test]
+async fn hooks_receive_the_request_id_of_each_call() {
+    let (recorded, hook) = recorder();
+    let auth_client = builder().build().unwrap();
+
+    auth_client.on_response(hook).get_health().await.unwrap();
+    auth_client.get_health().await.unwrap();
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded.len(), 1, "the original client has no hook");
+    assert_eq!(recorded[0].method, Method::GET);
+    assert_eq!(recorded[0].path, "/auth/v1/health");
+    assert_eq!(recorded[0].status, StatusCode::OK);
+    assert_eq!(recorded[0].request_id.as_deref(), Some("request-1"));
+}
+
+#[tokio::test]
+async fn builder_hooks_run_before_per_call_hooks_for_every_call() {
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let (first, second) = (order.clone(), order.clone());
+    let auth_client = builder()
+        .on_response(move |meta| {
+            first
+                .lock()
+                .unwrap()
+                .push(("builder", meta.request_id.clone()))
+        })
+        .build()
+        .unwrap();
+
+    auth_client.get_health().await.unwrap();
+    auth_client
+        .on_response(move |meta| {
+            second
+                .lock()
+                .unwrap()
+                .push(("call", meta.request_id.clone()))
+        })
+        .get_health()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *order.lock().unwrap(),
+        [
+            ("builder", Some("request-1".to_string())),
+            ("builder", Some("request-2".to_string())),
+            ("call", Some("request-2".to_string())),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn rejected_requests_carry_the_request_id() {
+    let (recorded, hook) = recorder();
+    let auth_client = builder().on_response(hook).build().unwrap();
+
+    let error = auth_client
+        .refresh_session(&RefreshToken::new("unknown"))
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.request_id(), Some("request-1"));
+    assert_eq!(recorded.lock().unwrap()[0].status, StatusCode::BAD_REQUEST);
+}
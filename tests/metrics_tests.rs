@@ -0,0 +1,158 @@
+#![cfg(feature = "metrics")]
+
+use metrics_util::{
+    debugging::{DebugValue, DebuggingRecorder},
+    CompositeKey, MetricKind,
+};
+use reqwest::Request;
+use supabase_auth::{
+    error::Error,
+    metrics::{ERRORS_TOTAL, REFRESH_FAILURES_TOTAL, REQUESTS_TOTAL, REQUEST_DURATION_SECONDS},
+    models::{AuthClient, RefreshToken},
+    transport::{HttpTransport, TransportFuture},
+};
+
+const HEALTH: &str = r#"{"version":"v2.170.0","name":"GoTrue","description":""}"#;
+
+const REFRESH_TOKEN_ALREADY_USED: &str = r#"{"code":400,"error_code":"refresh_token_already_used","msg":"Invalid Refresh Token: Already Used"}"#;
+
+/// Answers every request with `status` and `body`
+#[derive(Debug, Clone)]
+struct Fixed {
+    status: u16,
+    body: &'static str,
+}
+
+impl HttpTransport for Fixed {
+    fn execute(&self, _request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let response = http::Response::builder()
+                .status(self.status)
+                .body(self.body)
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+fn client(status: u16, body: &'static str) -> AuthClient {
+    AuthClient::builder()
+        .project_url("https://abcdefghijklmnop.supabase.co")
+        .api_key("api_key")
+        .transport(Fixed { status, body })
+        .build()
+        .unwrap()
+}
+
+/// Run `test` with a recorder local to this thread, returning what it recorded
+fn record<F: std::future::Future>(test: F) -> Vec<(CompositeKey, DebugValue)> {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    metrics::with_local_recorder(&recorder, || runtime.block_on(test));
+
+    snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| (key, value))
+        .collect()
+}
+
+fn value<'a>(
+    recorded: &'a [(CompositeKey, DebugValue)],
+    kind: MetricKind,
+    name: &str,
+    labels: &[(&str, &str)],
+) -> Option<&'a DebugValue> {
+    recorded
+        .iter()
+        .find(|(key, _)| {
+            key.kind() == kind
+                && key.key().name() == name
+                && labels.iter().all(|(label, value)| {
+                    key.key()
+                        .labels()
+                        .any(|l| l.key() == *label && l.value() == *value)
+                })
+        })
+        .map(|(_, value)| value)
+}
+
+#[test]
+fn requests_are_counted_and_timed_by_endpoint() {
+    let recorded = record(async {
+        let auth_client = client(200, HEALTH);
+        auth_client.get_health().await.unwrap();
+        auth_client.get_health().await.unwrap();
+    });
+
+    let labels = [
+        ("method", "GET"),
+        ("endpoint", "/auth/v1/health"),
+        ("status", "200"),
+    ];
+    assert_eq!(
+        value(&recorded, MetricKind::Counter, REQUESTS_TOTAL, &labels),
+        Some(&DebugValue::Counter(2))
+    );
+
+    let Some(DebugValue::Histogram(durations)) = value(
+        &recorded,
+        MetricKind::Histogram,
+        REQUEST_DURATION_SECONDS,
+        &labels[..2],
+    ) else {
+        panic!("the durations were recorded");
+    };
+    assert_eq!(durations.len(), 2);
+}
+
+#[test]
+fn rejected_requests_and_refresh_failures_are_counted_by_code() {
+    let recorded = record(async {
+        let auth_client = client(400, REFRESH_TOKEN_ALREADY_USED);
+        let error = auth_client
+            .refresh_session(&RefreshToken::new("used"))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::RefreshTokenAlreadyUsed));
+    });
+
+    assert_eq!(
+        value(
+            &recorded,
+            MetricKind::Counter,
+            REQUESTS_TOTAL,
+            &[
+                ("method", "POST"),
+                ("endpoint", "/auth/v1/token"),
+                ("status", "400")
+            ],
+        ),
+        Some(&DebugValue::Counter(1))
+    );
+    assert_eq!(
+        value(
+            &recorded,
+            MetricKind::Counter,
+            ERRORS_TOTAL,
+            &[("status", "400"), ("code", "refresh_token_already_used")],
+        ),
+        Some(&DebugValue::Counter(1))
+    );
+    assert_eq!(
+        value(
+            &recorded,
+            MetricKind::Counter,
+            REFRESH_FAILURES_TOTAL,
+            &[("reason", "refresh_token_already_used")],
+        ),
+        Some(&DebugValue::Counter(1))
+    );
+}
@@ -0,0 +1,770 @@
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use serde::Deserialize;
+use supabase_auth::{
+    error::Error,
+    models::{
+        AccessToken, AuthClient, AuthServerHealth, AuthServerSettings, FactorStatus, FactorType,
+        GenerateLinkParams, GenerateLinkType, GeneratedLink, Identity, InviteParams,
+        ListUsersParams, LoginEmailOtpParams, LoginWithOAuthOptions, LoginWithSSO, OAuthClient,
+        OAuthClientGrantType, OAuthClientRegistrationType, OAuthClientResponseType,
+        OAuthClientType, OtpType, Patch, Provider, RefreshToken, ResendParams, SSOLoginOptions,
+        ServerVersion, Session, SsoIdentifier, UpdatedUser, User, VerifyOtpParams,
+    },
+};
+
+#[test]
+fn login_with_sso_serializes_single_identifier() {
+    let params = LoginWithSSO::domain("example.com");
+
+    let body = serde_json::to_value(&params).unwrap();
+
+    assert_eq!(body, serde_json::json!({ "domain": "example.com" }));
+
+    let provider_id = uuid::Uuid::now_v7();
+    let params = LoginWithSSO {
+        identifier: SsoIdentifier::ProviderId(provider_id),
+        options: Some(SSOLoginOptions::default().redirect_to("https://example.com/welcome")),
+    };
+
+    let body = serde_json::to_value(&params).unwrap();
+
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "provider_id": provider_id,
+            "redirect_to": "https://example.com/welcome",
+        })
+    );
+}
+
+fn session_json(expires_at: Option<i64>) -> serde_json::Value {
+    let mut session = serde_json::json!({
+        "access_token": "access",
+        "token_type": "bearer",
+        "expires_in": 3600,
+        "refresh_token": "refresh",
+        "user": serde_json::to_value(supabase_auth::models::User::default()).unwrap(),
+    });
+
+    if let Some(expires_at) = expires_at {
+        session["expires_at"] = expires_at.into();
+    }
+
+    session
+}
+
+#[test]
+fn session_expires_at_computed_from_expires_in() {
+    let session: Session = serde_json::from_value(session_json(None)).unwrap();
+
+    let expected = Utc::now() + TimeDelta::seconds(3600);
+
+    assert!((session.expires_at - expected).num_seconds().abs() <= 1);
+    assert!(!session.is_expired());
+    assert!(!session.expires_within(Duration::from_secs(60)));
+    assert!(session.expires_within(Duration::from_secs(7200)));
+}
+
+#[test]
+fn session_expiry_helpers() {
+    let expired_at = Utc::now().timestamp() - 10;
+    let session: Session = serde_json::from_value(session_json(Some(expired_at))).unwrap();
+
+    assert_eq!(session.expires_at.timestamp(), expired_at);
+    assert!(session.is_expired());
+    assert!(session.expires_within(Duration::ZERO));
+    assert_eq!(session.time_until_expiry(), Duration::ZERO);
+
+    let round_trip = serde_json::to_value(&session).unwrap();
+
+    assert_eq!(round_trip["expires_at"], expired_at);
+}
+
+#[test]
+fn supabase_js_session_round_trip() {
+    let expires_at = Utc::now().timestamp() + 3600;
+    let session: Session = serde_json::from_value(session_json(Some(expires_at))).unwrap();
+
+    let json = session.to_supabase_js_json().unwrap();
+    let stored: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(stored["expires_at"], expires_at);
+    assert!(stored.get("provider_token").is_none());
+
+    assert_eq!(Session::from_supabase_js_json(&json).unwrap(), session);
+}
+
+#[test]
+fn supabase_js_legacy_session() {
+    let expires_at = Utc::now().timestamp() + 1800;
+
+    let mut current_session = session_json(None);
+    current_session["accessToken"] = current_session["access_token"].take();
+    current_session
+        .as_object_mut()
+        .unwrap()
+        .remove("access_token");
+
+    let stored = serde_json::json!({
+        "currentSession": current_session,
+        "expiresAt": expires_at,
+    });
+
+    let session = Session::from_supabase_js_json(&stored.to_string()).unwrap();
+
+    assert_eq!(session.access_token, "access");
+    assert_eq!(session.expires_at.timestamp(), expires_at);
+}
+
+#[test]
+fn option_builders_set_fields() {
+    let options = LoginEmailOtpParams::default()
+        .email_redirect_to("https://example.com/welcome")
+        .should_create_user(false);
+
+    assert_eq!(
+        serde_json::to_value(&options).unwrap(),
+        serde_json::json!({
+            "captcha_token": null,
+            "data": null,
+            "email_redirect_to": "https://example.com/welcome",
+            "create_user": false,
+        })
+    );
+
+    let options = LoginWithOAuthOptions::default()
+        .query_param("prompt", "consent")
+        .query_param("access_type", "offline")
+        .scopes("email");
+
+    let query_params = options.query_params.unwrap();
+    assert_eq!(query_params.len(), 2);
+    assert_eq!(query_params["prompt"], "consent");
+    assert_eq!(options.scopes.as_deref(), Some("email"));
+}
+
+#[test]
+fn params_are_built_from_borrowed_strings() {
+    assert_eq!(
+        serde_json::to_value(VerifyOtpParams::email(
+            "user@example.com",
+            "123456",
+            OtpType::Email
+        ))
+        .unwrap(),
+        serde_json::json!({"email": "user@example.com", "token": "123456", "type": "email"})
+    );
+    assert_eq!(
+        serde_json::to_value(VerifyOtpParams::token_hash("hash", OtpType::Recovery)).unwrap(),
+        serde_json::json!({"token_hash": "hash", "type": "recovery"})
+    );
+    assert_eq!(
+        serde_json::to_value(ResendParams::new(OtpType::Signup, "user@example.com")).unwrap(),
+        serde_json::json!({"type": "signup", "email": "user@example.com"})
+    );
+    assert_eq!(
+        InviteParams::new("user@example.com")
+            .data(serde_json::json!({"team": "a"}))
+            .data,
+        Some(serde_json::json!({"team": "a"}))
+    );
+}
+
+#[test]
+fn oauth_urls_include_the_options() {
+    let auth_client = AuthClient::new("https://abcdefghijklmnop.supabase.co", "api_key", "");
+    let options = LoginWithOAuthOptions::default()
+        .redirect_to("https://example.com/callback")
+        .query_param("prompt", "consent");
+
+    let response = auth_client
+        .login_with_oauth(Provider::Github, Some(options))
+        .unwrap();
+
+    let query: Vec<(String, String)> = response.url.query_pairs().into_owned().collect();
+    assert_eq!(
+        query,
+        [
+            ("provider".to_string(), "github".to_string()),
+            (
+                "redirect_to".to_string(),
+                "https://example.com/callback".to_string()
+            ),
+            ("prompt".to_string(), "consent".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn user_metadata_as_custom_type() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Plan {
+        Free,
+        Pro,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Profile {
+        full_name: String,
+        plan: Plan,
+        seats: u32,
+    }
+
+    let user: User = serde_json::from_value(serde_json::json!({
+        "id": "7e3d5d6c-9a5c-4bd6-8f0c-8e5a4b1a2c3d",
+        "aud": "authenticated",
+        "role": "authenticated",
+        "email": "demo@example.com",
+        "phone": "",
+        "app_metadata": {},
+        "user_metadata": { "full_name": "Demo User", "plan": "pro", "seats": 3 },
+        "identities": [],
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+        "is_anonymous": false,
+    }))
+    .unwrap();
+
+    let profile: Profile = user.metadata_as().unwrap();
+    assert_eq!(
+        profile,
+        Profile {
+            full_name: "Demo User".to_string(),
+            plan: Plan::Pro,
+            seats: 3,
+        }
+    );
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Billing {
+        stripe_customer_id: String,
+    }
+
+    assert!(matches!(
+        user.metadata_as::<Billing>(),
+        Err(Error::ParseError(_))
+    ));
+}
+
+#[test]
+fn app_metadata_keeps_custom_fields() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Tenancy {
+        provider: String,
+        tenant_id: String,
+        roles: Vec<String>,
+    }
+
+    let user: User = serde_json::from_value(serde_json::json!({
+        "id": "7e3d5d6c-9a5c-4bd6-8f0c-8e5a4b1a2c3d",
+        "aud": "authenticated",
+        "role": "authenticated",
+        "email": "demo@example.com",
+        "phone": "",
+        "app_metadata": {
+            "provider": "email",
+            "providers": ["email", "github"],
+            "tenant_id": "acme",
+            "roles": ["admin"],
+        },
+        "user_metadata": {},
+        "identities": [],
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+        "is_anonymous": false,
+    }))
+    .unwrap();
+
+    assert_eq!(user.app_metadata.provider.as_deref(), Some("email"));
+    assert_eq!(
+        user.app_metadata.providers,
+        Some(vec!["email".to_string(), "github".to_string()])
+    );
+    assert_eq!(user.app_metadata.custom["tenant_id"], "acme");
+
+    let tenancy: Tenancy = user.app_metadata_as().unwrap();
+    assert_eq!(
+        tenancy,
+        Tenancy {
+            provider: "email".to_string(),
+            tenant_id: "acme".to_string(),
+            roles: vec!["admin".to_string()],
+        }
+    );
+
+    let json = serde_json::to_value(&user.app_metadata).unwrap();
+    assert_eq!(json["roles"], serde_json::json!(["admin"]));
+}
+
+#[test]
+fn admin_params_serialize_only_set_fields() {
+    let params = ListUsersParams::default().page(2);
+    assert_eq!(
+        serde_json::to_value(&params).unwrap(),
+        serde_json::json!({ "page": 2 })
+    );
+
+    let params = GenerateLinkParams::new(GenerateLinkType::EmailChangeNew, "old@example.com")
+        .new_email("new@example.com");
+    assert_eq!(
+        serde_json::to_value(&params).unwrap(),
+        serde_json::json!({
+            "type": "email_change_new",
+            "email": "old@example.com",
+            "new_email": "new@example.com",
+        })
+    );
+
+    let params = params.password("hunter2");
+    assert!(!format!("{params:?}").contains("hunter2"));
+}
+
+#[test]
+fn generated_link_includes_the_user() {
+    let link: GeneratedLink = serde_json::from_value(serde_json::json!({
+        "action_link": "https://example.supabase.co/auth/v1/verify?token=abc123&type=magiclink",
+        "email_otp": "123456",
+        "hashed_token": "abc123",
+        "verification_type": "magiclink",
+        "redirect_to": "http://localhost:3000",
+        "id": "7e3d5d6c-9a5c-4bd6-8f0c-8e5a4b1a2c3d",
+        "aud": "authenticated",
+        "role": "authenticated",
+        "email": "demo@example.com",
+        "phone": "",
+        "app_metadata": {},
+        "user_metadata": {},
+        "identities": [],
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+        "is_anonymous": false,
+    }))
+    .unwrap();
+
+    assert_eq!(link.hashed_token, "abc123");
+    assert_eq!(link.user.email, "demo@example.com");
+
+    let debug = format!("{link:?}");
+    assert!(!debug.contains("abc123") && !debug.contains("123456"));
+}
+
+#[test]
+fn unknown_fields_are_preserved() {
+    let mut json = session_json(Some(1_700_000_000));
+    json["refresh_token_expires_at"] = 1_800_000_000.into();
+    json["user"]["passkeys"] = serde_json::json!([{ "id": "passkey" }]);
+
+    let session: Session = serde_json::from_value(json).unwrap();
+
+    assert_eq!(session.extra["refresh_token_expires_at"], 1_800_000_000);
+    assert_eq!(session.user.extra["passkeys"][0]["id"], "passkey");
+    assert!(!session.extra.contains_key("access_token"));
+
+    let round_trip = serde_json::to_value(&session).unwrap();
+    assert_eq!(round_trip["refresh_token_expires_at"], 1_800_000_000);
+    assert_eq!(round_trip["user"]["passkeys"][0]["id"], "passkey");
+
+    let settings: AuthServerSettings = serde_json::from_value(serde_json::json!({
+        "external": serde_json::to_value(supabase_auth::models::External::default()).unwrap(),
+        "disable_signup": false,
+        "mailer_autoconfirm": false,
+        "phone_autoconfirm": false,
+        "sms_provider": "twilio",
+        "saml_enabled": false,
+        "passkey_enabled": true,
+    }))
+    .unwrap();
+
+    assert_eq!(settings.extra["passkey_enabled"], true);
+}
+
+#[test]
+fn identity_ids_are_uuids() {
+    let user_id = uuid::Uuid::now_v7();
+    let identity_id = uuid::Uuid::now_v7();
+
+    let identity: Identity = serde_json::from_value(serde_json::json!({
+        "identity_id": identity_id,
+        "id": "1234567",
+        "user_id": user_id,
+        "identity_data": {
+            "email_verified": true,
+            "phone_verified": false,
+            "sub": "1234567",
+        },
+        "provider": "github",
+        "last_sign_in_at": "2024-01-01T00:00:00Z",
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+    }))
+    .unwrap();
+
+    assert_eq!(identity.identity_id, identity_id);
+    assert_eq!(identity.user_id, user_id);
+    assert_eq!(identity.id, "1234567");
+}
+
+#[test]
+fn user_timestamps_are_typed() {
+    let user: User = serde_json::from_value(serde_json::json!({
+        "id": "7e3d5d6c-9a5c-4bd6-8f0c-8e5a4b1a2c3d",
+        "aud": "authenticated",
+        "role": "authenticated",
+        "email": "demo@example.com",
+        "phone": "",
+        "last_sign_in_at": "2024-03-01T12:30:00.123456Z",
+        "banned_until": "2999-01-01T00:00:00Z",
+        "app_metadata": {},
+        "user_metadata": {},
+        "identities": [],
+        "created_at": "2024-01-01T00:00:00+00:00",
+        "updated_at": "2024-02-01T00:00:00Z",
+        "is_anonymous": false,
+    }))
+    .unwrap();
+
+    assert_eq!(user.created_at.timestamp(), 1_704_067_200);
+    assert!(user.updated_at > user.created_at);
+    assert!(user.last_sign_in_at.unwrap() > user.updated_at);
+    assert!(user.is_banned());
+
+    let json = serde_json::to_value(&user).unwrap();
+    assert_eq!(json["created_at"], "2024-01-01T00:00:00Z");
+}
+
+#[test]
+fn debug_output_redacts_secrets() {
+    let mut session: Session = serde_json::from_value(session_json(None)).unwrap();
+    session.access_token = "secret-access-token".into();
+    session.refresh_token = "secret-refresh-token".into();
+    session.provider_token = Some("secret-provider-token".to_string());
+
+    let debug = format!("{:?}", session);
+    assert!(!debug.contains("secret-"));
+    assert!(debug.contains("[REDACTED]"));
+
+    let updated_user = UpdatedUser {
+        password: Patch::Set("secret-password".to_string()),
+        ..Default::default()
+    };
+    assert!(!format!("{:?}", updated_user).contains("secret-"));
+
+    let builder = AuthClient::builder()
+        .project_url("http://localhost:54321")
+        .api_key("secret-api-key")
+        .jwt_secret("secret-jwt-secret");
+    assert!(!format!("{:?}", builder).contains("secret-"));
+
+    let auth_client = builder.build().unwrap();
+    assert!(!format!("{:?}", auth_client.with_token("secret-access-token")).contains("secret-"));
+}
+
+#[test]
+fn tokens_serialize_as_plain_strings() {
+    let session: Session = serde_json::from_value(session_json(None)).unwrap();
+    let json = serde_json::to_value(&session).unwrap();
+
+    assert_eq!(json["access_token"], session.access_token.as_str());
+    assert_eq!(json["refresh_token"], session.refresh_token.as_str());
+
+    let access_token = AccessToken::from("secret-access-token");
+    assert_eq!(access_token, "secret-access-token");
+    assert_eq!(format!("{:?}", access_token), "AccessToken(\"[REDACTED]\")");
+
+    let refresh_token: RefreshToken = serde_json::from_str("\"secret-refresh-token\"").unwrap();
+    assert_eq!(refresh_token.into_string(), "secret-refresh-token");
+}
+
+#[test]
+fn user_status_fields() {
+    let user: User = serde_json::from_value(serde_json::json!({
+        "id": "7e3d5d6c-9a5c-4bd6-8f0c-8e5a4b1a2c3d",
+        "aud": "authenticated",
+        "role": "authenticated",
+        "email": "",
+        "phone": "",
+        "app_metadata": {},
+        "user_metadata": {},
+        "identities": [],
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+    }))
+    .unwrap();
+
+    assert!(!user.is_anonymous);
+    assert!(!user.is_confirmed());
+    assert!(!user.is_deleted());
+
+    let user: User = serde_json::from_value(serde_json::json!({
+        "id": "7e3d5d6c-9a5c-4bd6-8f0c-8e5a4b1a2c3d",
+        "aud": "authenticated",
+        "role": "authenticated",
+        "email": "demo@example.com",
+        "phone": "",
+        "invited_at": "2024-01-01T00:00:00Z",
+        "email_confirmed_at": "2024-01-02T00:00:00Z",
+        "deleted_at": "2024-02-01T00:00:00Z",
+        "app_metadata": {},
+        "user_metadata": {},
+        "identities": [],
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+        "is_anonymous": true,
+    }))
+    .unwrap();
+
+    assert!(user.is_anonymous);
+    assert!(user.is_confirmed());
+    assert!(user.is_deleted());
+    assert!(user.invited_at.is_some());
+    assert!(!user.extra.contains_key("deleted_at"));
+}
+
+#[test]
+fn user_identities_are_typed() {
+    let user: User = serde_json::from_value(serde_json::json!({
+        "id": "7e3d5d6c-9a5c-4bd6-8f0c-8e5a4b1a2c3d",
+        "aud": "authenticated",
+        "role": "authenticated",
+        "email": "demo@example.com",
+        "phone": "",
+        "app_metadata": {},
+        "user_metadata": {},
+        "identities": [{
+            "identity_id": "5b2a1c4e-3f6d-4e8a-9b7c-1d2e3f4a5b6c",
+            "id": "1234567",
+            "user_id": "7e3d5d6c-9a5c-4bd6-8f0c-8e5a4b1a2c3d",
+            "identity_data": {
+                "email": "demo@example.com",
+                "sub": "1234567",
+                "avatar_url": "https://example.com/avatar.png",
+            },
+            "provider": "github",
+            "last_sign_in_at": null,
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+        }],
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+    }))
+    .unwrap();
+
+    assert_eq!(user.providers(), vec!["github"]);
+
+    let github = user.identity("github").unwrap();
+    assert!(github.last_sign_in_at.is_none());
+    assert_eq!(
+        github.identity_data.extra["avatar_url"],
+        "https://example.com/avatar.png"
+    );
+    assert!(user.identity("google").is_none());
+
+    let user: User = serde_json::from_value(serde_json::json!({
+        "id": "7e3d5d6c-9a5c-4bd6-8f0c-8e5a4b1a2c3d",
+        "aud": "authenticated",
+        "role": "authenticated",
+        "email": "",
+        "phone": "",
+        "app_metadata": {},
+        "user_metadata": {},
+        "identities": null,
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+    }))
+    .unwrap();
+
+    assert!(user.identities.is_empty());
+}
+
+#[test]
+fn user_factors_are_typed() {
+    let user: User = serde_json::from_value(serde_json::json!({
+        "id": "7e3d5d6c-9a5c-4bd6-8f0c-8e5a4b1a2c3d",
+        "aud": "authenticated",
+        "role": "authenticated",
+        "email": "demo@example.com",
+        "phone": "",
+        "app_metadata": {},
+        "user_metadata": {},
+        "identities": [],
+        "factors": [
+            {
+                "id": "0f5c3b1e-8a7d-4c6b-9e2f-1a3b5c7d9e0f",
+                "friendly_name": "Phone app",
+                "factor_type": "totp",
+                "status": "verified",
+                "created_at": "2024-01-01T00:00:00Z",
+                "updated_at": "2024-01-01T00:00:00Z",
+            },
+            {
+                "id": "1a2b3c4d-5e6f-4a8b-9c0d-1e2f3a4b5c6d",
+                "factor_type": "passkey",
+                "status": "unverified",
+                "created_at": "2024-01-01T00:00:00Z",
+                "updated_at": "2024-01-01T00:00:00Z",
+            },
+        ],
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+    }))
+    .unwrap();
+
+    assert!(user.has_verified_factor());
+    assert_eq!(user.verified_factors().count(), 1);
+    assert_eq!(user.factors[0].factor_type, FactorType::Totp);
+    assert_eq!(user.factors[1].factor_type, FactorType::Unknown);
+    assert_eq!(user.factors[1].status, FactorStatus::Unverified);
+}
+
+#[test]
+fn settings_are_typed() {
+    let settings: AuthServerSettings = serde_json::from_value(serde_json::json!({
+        "external": {
+            "email": true,
+            "github": true,
+            "google": false,
+            "solana": true,
+        },
+        "disable_signup": false,
+        "mailer_autoconfirm": true,
+        "phone_autoconfirm": false,
+        "sms_provider": "",
+        "mfa_enabled": true,
+        "saml_enabled": false,
+        "external_labels": { "saml": "Acme SSO" },
+    }))
+    .unwrap();
+
+    assert!(settings.mfa_enabled);
+    assert!(settings.mailer_autoconfirm);
+    assert_eq!(settings.external_labels["saml"], "Acme SSO");
+    assert!(settings.external.github);
+    assert!(settings.external.is_enabled("solana"));
+    assert!(!settings.external.is_enabled("google"));
+    assert_eq!(
+        settings.external.enabled(),
+        vec!["email", "github", "solana"]
+    );
+    assert!(settings.extra.is_empty());
+}
+
+#[test]
+fn health_versions_are_parsed() {
+    let health: AuthServerHealth = serde_json::from_value(serde_json::json!({
+        "version": "v2.170.0",
+        "name": "GoTrue",
+        "description": "GoTrue is a user registration and authentication API",
+    }))
+    .unwrap();
+
+    let version = health.server_version().unwrap();
+    assert_eq!(version, ServerVersion::new(2, 170, 0));
+    assert!(version > ServerVersion::new(2, 99, 12));
+    assert_eq!(version.to_string(), "v2.170.0");
+
+    assert_eq!(
+        "2.171.1-rc.3".parse::<ServerVersion>().unwrap(),
+        ServerVersion::new(2, 171, 1)
+    );
+    assert!(matches!(
+        "unknown".parse::<ServerVersion>(),
+        Err(Error::InvalidVersion(version)) if version == "unknown"
+    ));
+
+    let health: AuthServerHealth = serde_json::from_value(serde_json::json!({
+        "version": "master",
+        "name": "GoTrue",
+    }))
+    .unwrap();
+    assert!(health.server_version().is_none());
+}
+
+#[test]
+fn updated_users_only_send_changed_fields() {
+    let updated_user = UpdatedUser {
+        email: Patch::Set("new@example.com".to_string()),
+        phone: Patch::Clear,
+        ..Default::default()
+    }
+    .data(serde_json::json!({ "theme": "dark" }))
+    .remove_data("nickname");
+
+    assert_eq!(
+        serde_json::to_value(&updated_user).unwrap(),
+        serde_json::json!({
+            "email": "new@example.com",
+            "phone": null,
+            "data": { "theme": "dark", "nickname": null },
+        })
+    );
+    assert_eq!(
+        serde_json::to_string(&UpdatedUser::default()).unwrap(),
+        "{}"
+    );
+
+    let parsed: UpdatedUser =
+        serde_json::from_value(serde_json::json!({ "email": "new@example.com", "phone": null }))
+            .unwrap();
+    assert_eq!(parsed.email, Patch::Set("new@example.com".to_string()));
+    assert_eq!(parsed.phone, Patch::Clear);
+    assert_eq!(parsed.password, Patch::Unchanged);
+}
+
+#[test]
+fn oauth_clients_with_new_grant_types_are_parsed() {
+    let client: OAuthClient = serde_json::from_value(serde_json::json!({
+        "client_id": "client-id",
+        "client_name": "Demo",
+        "client_type": "confidential",
+        "token_endpoint_auth_method": "client_secret_basic",
+        "registration_type": "manual",
+        "redirect_uris": ["https://example.com/callback"],
+        "grant_types": ["authorization_code", "client_credentials"],
+        "response_types": ["code", "token"],
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z"
+    }))
+    .unwrap();
+
+    assert_eq!(
+        client.grant_types,
+        [
+            OAuthClientGrantType::AuthorizationCode,
+            OAuthClientGrantType::Unknown
+        ]
+    );
+    assert_eq!(
+        client.response_types,
+        [
+            OAuthClientResponseType::Code,
+            OAuthClientResponseType::Unknown
+        ]
+    );
+}
+
+#[test]
+fn oauth_clients_with_new_client_and_registration_types_are_parsed() {
+    let client: OAuthClient = serde_json::from_value(serde_json::json!({
+        "client_id": "client-id",
+        "client_name": "Demo",
+        "client_type": "first_party",
+        "token_endpoint_auth_method": "client_secret_basic",
+        "registration_type": "imported",
+        "redirect_uris": ["https://example.com/callback"],
+        "grant_types": ["authorization_code"],
+        "response_types": ["code"],
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z"
+    }))
+    .unwrap();
+
+    assert_eq!(client.client_type, OAuthClientType::Unknown);
+    assert_eq!(
+        client.registration_type,
+        OAuthClientRegistrationType::Unknown
+    );
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "otel")]
+
+use std::sync::{Arc, Mutex, OnceLock};
+
+use opentelemetry::{
+    context::FutureExt,
+    global,
+    trace::{SpanKind, Status, TraceContextExt, Tracer},
+    Context, KeyValue, Value,
+};
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+use reqwest::Request;
+use supabase_auth::{
+    models::AuthClient,
+    transport::{HttpTransport, TransportFuture},
+};
+
+const HEALTH: &str = r#"{"version":"v2.170.0","name":"GoTrue","description":""}"#;
+
+/// Answers with `status`, recording the `traceparent` header of each request
+#[derive(Debug, Clone)]
+struct Traced {
+    status: u16,
+    traceparents: Arc<Mutex<Vec<String>>>,
+}
+
+impl HttpTransport for Traced {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            if let Some(traceparent) = request.headers().get("traceparent") {
+                let traceparent = traceparent.to_str().unwrap().to_string();
+                self.traceparents.lock().unwrap().push(traceparent);
+            }
+
+            let response = http::Response::builder()
+                .status(self.status)
+                .body(HEALTH)
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+/// The exporter of the global tracer provider, which is installed once per test binary
+fn exporter() -> &'static InMemorySpanExporter {
+    static EXPORTER: OnceLock<InMemorySpanExporter> = OnceLock::new();
+
+    EXPORTER.get_or_init(|| {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        global::set_tracer_provider(provider);
+        exporter
+    })
+}
+
+fn client(status: u16) -> (AuthClient, Traced) {
+    let transport = Traced {
+        status,
+        traceparents: Arc::default(),
+    };
+    let auth_client = AuthClient::builder()
+        .project_url("https://abcdefghijklmnop.supabase.co")
+        .api_key("api_key")
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+
+    (auth_client, transport)
+}
+
+fn finished_span(name: &str, trace_id: opentelemetry::trace::TraceId) -> SpanData {
+    exporter()
+        .get_finished_spans()
+        .unwrap()
+        .into_iter()
+        .find(|span| span.name == name && span.span_context.trace_id() == trace_id)
+        .unwrap_or_else(|| panic!("{name} was recorded"))
+}
+
+fn attribute(span: &SpanData, key: &str) -> Option<Value> {
+    span.attributes
+        .iter()
+        .find(|attribute| attribute.key.as_str() == key)
+        .map(|attribute| attribute.value.clone())
+}
+
+#[tokio::test]
+async fn requests_are_traced_as_children_of_the_current_context() {
+    exporter();
+    let (auth_client, transport) = client(200);
+
+    let tracer = global::tracer("tests");
+    let parent = tracer.start("handle request");
+    let cx = Context::current_with_span(parent);
+    let parent_context = cx.span().span_context().clone();
+
+    auth_client.get_health().with_context(cx).await.unwrap();
+
+    let span = finished_span("GET /auth/v1/health", parent_context.trace_id());
+    assert_eq!(span.parent_span_id, parent_context.span_id());
+    assert_eq!(span.span_kind, SpanKind::Client);
+    assert_eq!(
+        attribute(&span, "http.response.status_code"),
+        Some(Value::I64(200))
+    );
+    assert_eq!(
+        attribute(&span, "url.full"),
+        Some("https://abcdefghijklmnop.supabase.co/auth/v1/health".into())
+    );
+    assert!(span.attributes.contains(&KeyValue::new(
+        "server.address",
+        "abcdefghijklmnop.supabase.co"
+    )));
+
+    let traceparents = transport.traceparents.lock().unwrap();
+    assert_eq!(
+        traceparents[0],
+        format!(
+            "00-{:032x}-{:016x}-01",
+            parent_context.trace_id(),
+            span.span_context.span_id()
+        )
+    );
+}
+
+#[tokio::test]
+async fn failed_requests_are_marked_as_errors() {
+    exporter();
+    let (auth_client, _) = client(503);
+
+    let tracer = global::tracer("tests");
+    let cx = Context::current_with_span(tracer.start("handle request"));
+    let trace_id = cx.span().span_context().trace_id();
+
+    assert!(auth_client.get_health().with_context(cx).await.is_err());
+
+    let span = finished_span("GET /auth/v1/health", trace_id);
+    assert!(matches!(span.status, Status::Error { .. }));
+    assert_eq!(attribute(&span, "error.type"), Some("503".into()));
+}
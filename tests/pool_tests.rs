@@ -0,0 +1,101 @@
+#![allow(clippy::result_large_err)]
+
+use std::time::Duration;
+
+use supabase_auth::{
+    error::Error,
+    models::AuthClient,
+    pool::{AuthClientPool, ProjectCredentials},
+};
+
+fn pool() -> AuthClientPool {
+    AuthClientPool::new(AuthClient::new("http://localhost:54321", "base_key", ""))
+}
+
+#[test]
+fn clients_are_created_once() {
+    let pool = pool();
+    let mut created = 0;
+
+    for _ in 0..3 {
+        let client = pool
+            .get_or_insert_with("abcdefgh", || {
+                created += 1;
+                Ok(ProjectCredentials::hosted("abcdefgh", "project_key"))
+            })
+            .unwrap();
+
+        assert_eq!(client.project_url(), "https://abcdefgh.supabase.co");
+        assert_eq!(client.api_key(), "project_key");
+    }
+
+    assert_eq!(created, 1);
+    assert_eq!(pool.len(), 1);
+}
+
+#[test]
+fn least_recently_used_client_is_evicted() {
+    let pool = pool().with_capacity(2);
+    let credentials = |project_ref: &str| {
+        let project_ref = project_ref.to_string();
+        move || Ok(ProjectCredentials::hosted(&project_ref, "key"))
+    };
+
+    pool.get_or_insert_with("first", credentials("first"))
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(5));
+    pool.get_or_insert_with("second", credentials("second"))
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(5));
+
+    // Using the first client makes the second one the least recently used
+    assert!(pool.get("first").is_some());
+    std::thread::sleep(Duration::from_millis(5));
+
+    pool.get_or_insert_with("third", credentials("third"))
+        .unwrap();
+
+    assert_eq!(pool.len(), 2);
+    assert!(pool.get("first").is_some());
+    assert!(pool.get("second").is_none());
+    assert!(pool.get("third").is_some());
+}
+
+#[test]
+fn idle_clients_are_evicted() {
+    let pool = pool().with_idle_timeout(Duration::from_millis(10));
+
+    pool.get_or_insert_with("abcdefgh", || {
+        Ok(ProjectCredentials::hosted("abcdefgh", "key"))
+    })
+    .unwrap();
+    std::thread::sleep(Duration::from_millis(20));
+
+    assert!(pool.get("abcdefgh").is_none());
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn credential_errors_are_not_cached() {
+    let pool = pool();
+
+    let result =
+        pool.get_or_insert_with("missing", || Err(Error::UnknownProfile("missing".into())));
+
+    assert!(matches!(result, Err(Error::UnknownProfile(_))));
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn removed_clients_are_recreated() {
+    let pool = pool();
+    let credentials =
+        || Ok(ProjectCredentials::new("http://localhost:9999", "key").with_jwt_secret("secret"));
+
+    pool.get_or_insert_with("local", credentials).unwrap();
+    assert!(pool.remove("local").is_some());
+    assert!(pool.get("local").is_none());
+
+    let client = pool.get_or_insert_with("local", credentials).unwrap();
+    assert_eq!(client.jwt_secret(), "secret");
+}
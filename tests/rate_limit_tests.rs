@@ -0,0 +1,132 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use supabase_auth::{
+    models::AuthClient,
+    rate_limit::{RateLimit, RateLimits},
+};
+
+/// Answer every request with `204 No Content`, counting them
+async fn serve_no_content() -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0; 8192];
+            let _ = socket.read(&mut buffer).await.unwrap();
+
+            counter.fetch_add(1, Ordering::SeqCst);
+
+            socket
+                .write_all(
+                    b"HTTP/1.1 204 No Content\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                )
+                .await
+                .unwrap();
+        }
+    });
+
+    (url, requests)
+}
+
+fn rate_limited_client(url: String, rate_limits: RateLimits) -> AuthClient {
+    AuthClient::builder()
+        .project_url(url)
+        .api_key("api_key")
+        .rate_limits(rate_limits)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn requests_beyond_the_burst_are_paced() {
+    let (url, requests) = serve_no_content().await;
+    let auth_client = rate_limited_client(
+        url,
+        RateLimits {
+            otp: Some(RateLimit::new(2, Duration::from_millis(400))),
+            ..Default::default()
+        },
+    );
+
+    let started_at = Instant::now();
+
+    for _ in 0..2 {
+        auth_client
+            .send_login_email_with_magic_link("demo@example.com")
+            .await
+            .unwrap();
+    }
+    assert!(started_at.elapsed() < Duration::from_millis(200));
+
+    auth_client
+        .send_login_email_with_magic_link("demo@example.com")
+        .await
+        .unwrap();
+    assert!(started_at.elapsed() >= Duration::from_millis(200));
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn limits_apply_to_their_endpoint_class_only() {
+    let (url, requests) = serve_no_content().await;
+    let auth_client = rate_limited_client(
+        url,
+        RateLimits {
+            sign_up: Some(RateLimit::per_hour(1)),
+            ..Default::default()
+        },
+    );
+
+    let started_at = Instant::now();
+
+    for _ in 0..5 {
+        auth_client
+            .send_login_email_with_magic_link("demo@example.com")
+            .await
+            .unwrap();
+    }
+
+    assert!(started_at.elapsed() < Duration::from_secs(1));
+    assert_eq!(requests.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn limits_are_shared_by_clones() {
+    let (url, _) = serve_no_content().await;
+    let auth_client = rate_limited_client(
+        url,
+        RateLimits {
+            otp: Some(RateLimit::new(1, Duration::from_millis(300))),
+            ..Default::default()
+        },
+    );
+
+    let started_at = Instant::now();
+
+    auth_client
+        .send_login_email_with_magic_link("demo@example.com")
+        .await
+        .unwrap();
+    auth_client
+        .clone()
+        .send_login_email_with_magic_link("demo@example.com")
+        .await
+        .unwrap();
+
+    assert!(started_at.elapsed() >= Duration::from_millis(300));
+}
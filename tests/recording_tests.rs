@@ -0,0 +1,126 @@
+#![cfg(feature = "recording")]
+
+use std::{fs, path::PathBuf};
+
+use reqwest::Request;
+use serde_json::json;
+use supabase_auth::{
+    error::Error,
+    models::{AuthClient, RefreshToken},
+    recording::{RecordMode, RecordingTransport},
+    transport::{HttpTransport, TransportFuture},
+};
+
+const EMAIL: &str = "jane@company.example";
+
+/// Answers every request with the same session, like a real project would for a sign in
+#[derive(Debug)]
+struct Project;
+
+impl HttpTransport for Project {
+    fn execute(&self, _request: Request) -> TransportFuture<'_> {
+        Box::pin(async {
+            let session = json!({
+                "access_token": "secret-access-token",
+                "token_type": "bearer",
+                "expires_in": 3600,
+                "expires_at": 4102444800_i64,
+                "refresh_token": "secret-refresh-token",
+                "user": {
+                    "id": "3f0fa2ae-3d38-4cf0-b5c7-7bd6b3c6f0e8",
+                    "aud": "authenticated",
+                    "role": "authenticated",
+                    "email": EMAIL,
+                    "phone": "",
+                    "app_metadata": {},
+                    "user_metadata": {},
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "updated_at": "2024-01-01T00:00:00Z",
+                },
+            });
+            let response = http::Response::builder()
+                .status(200)
+                .header("content-type", "application/json")
+                .header("set-cookie", "sb-session=secret")
+                .body(session.to_string())
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+fn fixture_path() -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("supabase-auth-{}", uuid::Uuid::now_v7()))
+        .join("login.json")
+}
+
+fn client(transport: RecordingTransport) -> AuthClient {
+    AuthClient::builder()
+        .project_url("https://abcdefghijklmnop.supabase.co")
+        .api_key("api_key")
+        .transport(transport)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn recorded_fixtures_are_scrubbed() {
+    let path = fixture_path();
+    let transport =
+        RecordingTransport::record(&path, Project).scrub_text(EMAIL, "demo@example.com");
+    assert_eq!(transport.mode(), RecordMode::Record);
+
+    let session = client(transport)
+        .login_with_email(EMAIL, "hunter2")
+        .await
+        .unwrap();
+    assert_eq!(session.access_token.as_str(), "secret-access-token");
+
+    let fixture = fs::read_to_string(&path).unwrap();
+    for secret in ["secret", "hunter2", EMAIL, "abcdefghijklmnop", "api_key"] {
+        assert!(!fixture.contains(secret), "{secret} was recorded");
+    }
+    assert!(fixture.contains("/auth/v1/token?grant_type=password"));
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn fixtures_replay_without_the_network() {
+    let path = fixture_path();
+    client(RecordingTransport::record(&path, Project))
+        .login_with_email(EMAIL, "hunter2")
+        .await
+        .unwrap();
+
+    let transport = RecordingTransport::auto(&path, Project).unwrap();
+    assert_eq!(transport.mode(), RecordMode::Replay);
+    let auth_client = client(transport);
+
+    let session = auth_client
+        .login_with_email(EMAIL, "any password")
+        .await
+        .unwrap();
+    assert_eq!(session.user.email, EMAIL);
+    assert!(session.refresh_token.as_str().contains("REDACTED"));
+
+    // Each interaction answers once, and unrecorded requests fail
+    let result = auth_client.login_with_email(EMAIL, "hunter2").await;
+    assert!(matches!(result, Err(Error::FixtureError(_))));
+
+    let result = auth_client
+        .refresh_session(&RefreshToken::new("refresh"))
+        .await;
+    assert!(matches!(result, Err(Error::FixtureError(_))));
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn missing_fixtures_cant_be_replayed() {
+    let result = RecordingTransport::replay(fixture_path());
+
+    assert!(matches!(result, Err(Error::FixtureError(_))));
+}
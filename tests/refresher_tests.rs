@@ -0,0 +1,283 @@
+use std::{
+    env,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use chrono::{TimeDelta, Utc};
+use reqwest::{Request, ResponseBuilderExt, StatusCode};
+
+use supabase_auth::{
+    models::{AuthClient, Session},
+    refresher::{RefreshPolicy, SessionRefresher, SingleFlightRefresh},
+    transport::{HttpTransport, TransportFuture},
+};
+
+/// Answers refresh token grants with a new session lasting `expires_in` seconds after a delay,
+/// counting them, or with `status` if it isn't a success
+#[derive(Debug, Clone)]
+struct SlowRefresh {
+    refreshes: Arc<AtomicUsize>,
+    in_flight: Arc<AtomicUsize>,
+    max_in_flight: Arc<AtomicUsize>,
+    expires_in: i64,
+    status: u16,
+}
+
+impl SlowRefresh {
+    fn new(expires_in: i64) -> Self {
+        SlowRefresh {
+            refreshes: Arc::default(),
+            in_flight: Arc::default(),
+            max_in_flight: Arc::default(),
+            expires_in,
+            status: 200,
+        }
+    }
+
+    fn failing(status: u16) -> Self {
+        SlowRefresh {
+            status,
+            ..SlowRefresh::new(3600)
+        }
+    }
+
+    fn refreshes(&self) -> usize {
+        self.refreshes.load(Ordering::SeqCst)
+    }
+
+    fn client(&self) -> AuthClient {
+        AuthClient::builder()
+            .project_url("http://project.supabase.invalid")
+            .api_key("api_key")
+            .transport(self.clone())
+            .build()
+            .unwrap()
+    }
+}
+
+impl HttpTransport for SlowRefresh {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            assert_eq!(request.url().path(), "/auth/v1/token");
+            assert_eq!(request.url().query(), Some("grant_type=refresh_token"));
+            let refresh = self.refreshes.fetch_add(1, Ordering::SeqCst);
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            if self.status != 200 {
+                let response = http::Response::builder()
+                    .status(self.status)
+                    .url(request.url().clone())
+                    .body(r#"{"code":503,"msg":"Service Unavailable"}"#)
+                    .unwrap();
+
+                return Ok(response.into());
+            }
+
+            let session = serde_json::json!({
+                "access_token": format!("access-{refresh}"),
+                "token_type": "bearer",
+                "expires_in": self.expires_in,
+                "refresh_token": format!("refresh-{refresh}"),
+                "user": {
+                    "id": "01936a4e-7b5c-7d1e-9c3b-2a1f0e9d8c7b",
+                    "aud": "authenticated",
+                    "role": "authenticated",
+                    "email": "demo@example.com",
+                    "phone": "",
+                    "app_metadata": {},
+                    "user_metadata": {},
+                    "identities": [],
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "updated_at": "2024-01-01T00:00:00Z",
+                    "is_anonymous": false
+                }
+            });
+            let response = http::Response::builder()
+                .status(200)
+                .url(request.url().clone())
+                .body(session.to_string())
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+fn far_future_session() -> Session {
+    Session {
+        access_token: "access".into(),
+        refresh_token: "refresh".into(),
+        expires_in: 3600,
+        expires_at: Utc::now() + TimeDelta::seconds(3600),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn refresher_returns_session_and_shuts_down() {
+    let auth_client = AuthClient::new("http://localhost:54321", "api_key", "jwt_secret");
+    let session = far_future_session();
+
+    let refresher = SessionRefresher::spawn(auth_client, session.clone());
+
+    assert_eq!(refresher.current_session(), session);
+    assert!(!refresher.is_finished());
+
+    refresher.shutdown().await;
+}
+
+#[tokio::test]
+async fn refresher_accepts_custom_policy() {
+    let auth_client = AuthClient::new("http://localhost:54321", "api_key", "jwt_secret");
+    let session = far_future_session();
+
+    let policy = RefreshPolicy {
+        refresh_margin: Duration::from_secs(300),
+        max_jitter: Duration::ZERO,
+        ..Default::default()
+    };
+
+    let refresher = SessionRefresher::spawn_with_policy(auth_client, session.clone(), policy);
+
+    assert_eq!(refresher.current_session(), session);
+    assert!(!refresher.is_finished());
+
+    refresher.shutdown().await;
+}
+
+#[tokio::test]
+async fn short_lived_sessions_are_refreshed_in_their_second_half() {
+    // The JWT expiry is below the default refresh margin of a minute
+    let transport = SlowRefresh::new(30);
+    let session = Session {
+        expires_in: 30,
+        expires_at: Utc::now(),
+        ..far_future_session()
+    };
+
+    let refresher = SessionRefresher::spawn(transport.client(), session);
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    assert_eq!(transport.refreshes(), 1);
+    assert_eq!(
+        refresher.current_session().access_token.as_str(),
+        "access-0"
+    );
+
+    refresher.shutdown().await;
+}
+
+#[test]
+fn default_refresh_policy_renews_a_minute_early() {
+    let policy = RefreshPolicy::default();
+
+    assert_eq!(policy.refresh_margin, Duration::from_secs(60));
+    assert_eq!(policy.max_jitter, Duration::from_secs(10));
+    assert_eq!(policy.retry_interval, Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn concurrent_refreshes_send_one_request() {
+    let transport = SlowRefresh::new(3600);
+    let session = far_future_session();
+
+    let refresh = SingleFlightRefresh::new(transport.client());
+
+    let (first, second) = tokio::join!(
+        refresh.refresh(&session.refresh_token),
+        refresh.refresh(&session.refresh_token),
+    );
+
+    let (first, second) = (first.unwrap(), second.unwrap());
+    assert_eq!(first.access_token, second.access_token);
+    assert_eq!(first.access_token.as_str(), "access-0");
+    assert_eq!(transport.refreshes(), 1);
+}
+
+#[tokio::test]
+async fn concurrent_refreshes_share_the_error() {
+    let transport = SlowRefresh::failing(503);
+    let refresh = SingleFlightRefresh::new(transport.client());
+    let refresh_token = far_future_session().refresh_token;
+
+    let (first, second) = tokio::join!(
+        refresh.refresh(&refresh_token),
+        refresh.refresh(&refresh_token),
+    );
+
+    for result in [first, second] {
+        assert_eq!(
+            result.unwrap_err().status(),
+            Some(StatusCode::SERVICE_UNAVAILABLE)
+        );
+    }
+    assert_eq!(transport.refreshes(), 1);
+
+    // Failed refreshes aren't kept, the next caller tries again
+    assert!(refresh.refresh(&refresh_token).await.is_err());
+    assert_eq!(transport.refreshes(), 2);
+}
+
+#[tokio::test]
+async fn refreshes_of_different_tokens_run_concurrently() {
+    let transport = SlowRefresh::new(3600);
+    let refresh = SingleFlightRefresh::new(transport.client());
+
+    let (first_token, second_token) = ("first".into(), "second".into());
+
+    let (first, second) = tokio::join!(
+        refresh.refresh(&first_token),
+        refresh.refresh(&second_token),
+    );
+
+    assert_ne!(first.unwrap().access_token, second.unwrap().access_token);
+    assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn expired_sessions_of_settled_refreshes_are_not_returned() {
+    let transport = SlowRefresh::new(3600);
+    let refresh = SingleFlightRefresh::new(transport.client());
+    let refresh_token = far_future_session().refresh_token;
+
+    let first = refresh.refresh(&refresh_token).await.unwrap();
+    let late = refresh.refresh(&refresh_token).await.unwrap();
+    assert_eq!(first.access_token, late.access_token);
+    assert_eq!(transport.refreshes(), 1);
+
+    let transport = SlowRefresh::new(0);
+    let refresh = SingleFlightRefresh::new(transport.client());
+
+    refresh.refresh(&refresh_token).await.unwrap();
+    refresh.refresh(&refresh_token).await.unwrap();
+    assert_eq!(transport.refreshes(), 2);
+}
+
+#[tokio::test]
+async fn concurrent_refreshes_share_one_session() {
+    let auth_client = AuthClient::new_from_env().unwrap();
+
+    let demo_email = env::var("DEMO_EMAIL").unwrap();
+    let demo_password = env::var("DEMO_PASSWORD").unwrap();
+
+    let session = auth_client
+        .login_with_email(&demo_email, &demo_password)
+        .await
+        .unwrap();
+
+    let refresh = SingleFlightRefresh::new(auth_client);
+
+    let (first, second) = tokio::join!(
+        refresh.refresh(&session.refresh_token),
+        refresh.refresh(&session.refresh_token),
+    );
+
+    assert!(first.unwrap().access_token == second.unwrap().access_token)
+}
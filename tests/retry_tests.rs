@@ -0,0 +1,134 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use reqwest::StatusCode;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use supabase_auth::{error::Error, models::AuthClient, retry::RetryPolicy};
+
+const HEALTH: &str = r#"{"version":"v2.0.0","name":"GoTrue","description":"GoTrue is a user registration and authentication API"}"#;
+
+/// Answer the n-th request with the n-th response of `responses`, repeating the last one
+async fn serve_responses(responses: Vec<&'static str>) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0; 8192];
+            let _ = socket.read(&mut buffer).await.unwrap();
+
+            let index = counter
+                .fetch_add(1, Ordering::SeqCst)
+                .min(responses.len() - 1);
+
+            socket.write_all(responses[index].as_bytes()).await.unwrap();
+        }
+    });
+
+    (url, requests)
+}
+
+fn status(status: u16, extra_headers: &str) -> &'static str {
+    Box::leak(
+        format!(
+            "HTTP/1.1 {} Error\r\n{}content-length: 0\r\nconnection: close\r\n\r\n",
+            status, extra_headers
+        )
+        .into_boxed_str(),
+    )
+}
+
+fn healthy() -> &'static str {
+    Box::leak(
+        format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            HEALTH.len(),
+            HEALTH
+        )
+        .into_boxed_str(),
+    )
+}
+
+fn retrying_client(url: String) -> AuthClient {
+    AuthClient::builder()
+        .project_url(url)
+        .api_key("api_key")
+        .retry_policy(RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..Default::default()
+        })
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried() {
+    let (url, requests) = serve_responses(vec![
+        status(503, ""),
+        status(429, "retry-after: 0\r\n"),
+        healthy(),
+    ])
+    .await;
+
+    let health = retrying_client(url).get_health().await.unwrap();
+
+    assert_eq!(health.name, "GoTrue");
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn retries_stop_after_max_attempts() {
+    let (url, requests) = serve_responses(vec![status(503, "")]).await;
+
+    let result = retrying_client(url).get_health().await;
+
+    assert!(matches!(
+        result,
+        Err(Error::AuthError { status, .. }) if status == StatusCode::SERVICE_UNAVAILABLE
+    ));
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn long_retry_after_is_not_waited_for() {
+    let (url, requests) = serve_responses(vec![status(429, "retry-after: 3600\r\n")]).await;
+
+    let result = retrying_client(url).get_health().await;
+
+    assert!(result.is_err());
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn non_idempotent_requests_are_not_retried() {
+    let (url, requests) = serve_responses(vec![status(503, ""), status(204, "")]).await;
+
+    let result = retrying_client(url)
+        .send_login_email_with_magic_link("demo@example.com")
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn requests_are_not_retried_by_default() {
+    let (url, requests) = serve_responses(vec![status(503, ""), healthy()]).await;
+
+    let result = AuthClient::new(url, "api_key", "").get_health().await;
+
+    assert!(result.is_err());
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
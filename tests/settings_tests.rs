@@ -0,0 +1,191 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use reqwest::{header::IF_NONE_MATCH, Request, ResponseBuilderExt, StatusCode};
+
+use supabase_auth::{
+    error::Error,
+    models::AuthClient,
+    transport::{HttpTransport, TransportFuture},
+};
+
+const SETTINGS: &str = r#"{"external":{"email":true,"github":true},"disable_signup":false,"mailer_autoconfirm":false,"phone_autoconfirm":false,"sms_provider":"","mfa_enabled":true,"saml_enabled":false}"#;
+
+/// Answers every request with the project's settings, counting the requests
+#[derive(Debug, Default, Clone)]
+struct CountingTransport {
+    requests: Arc<AtomicUsize>,
+}
+
+impl HttpTransport for CountingTransport {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            let response = http::Response::builder()
+                .status(200)
+                .url(request.url().clone())
+                .body(SETTINGS)
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+/// Answers the n-th request with the n-th status of `statuses`, repeating the last one, and records
+/// the `If-None-Match` header of each request
+///
+/// Settings are served with an `ETag` and `cache_control`, and requests for that `ETag` are
+/// answered with `304 Not Modified`.
+#[derive(Debug, Clone)]
+struct ScriptedTransport {
+    statuses: Vec<u16>,
+    cache_control: Option<&'static str>,
+    if_none_match: Arc<Mutex<Vec<Option<String>>>>,
+}
+
+impl ScriptedTransport {
+    fn new(statuses: Vec<u16>) -> Self {
+        ScriptedTransport {
+            statuses,
+            cache_control: None,
+            if_none_match: Arc::default(),
+        }
+    }
+
+    fn requests(&self) -> Vec<Option<String>> {
+        self.if_none_match.lock().unwrap().clone()
+    }
+}
+
+impl HttpTransport for ScriptedTransport {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let if_none_match = request
+                .headers()
+                .get(IF_NONE_MATCH)
+                .map(|etag| etag.to_str().unwrap().to_string());
+
+            let mut requests = self.if_none_match.lock().unwrap();
+            let status = self.statuses[requests.len().min(self.statuses.len() - 1)];
+            requests.push(if_none_match.clone());
+            drop(requests);
+
+            let mut response = http::Response::builder().url(request.url().clone());
+            let body = match status {
+                200 if if_none_match.as_deref() == Some("\"v1\"") => {
+                    response = response.status(304);
+                    ""
+                }
+                200 => {
+                    response = response.status(200).header("etag", "\"v1\"");
+                    SETTINGS
+                }
+                status => {
+                    response = response.status(status);
+                    r#"{"code":500,"error_code":"unexpected_failure","msg":"Unexpected failure"}"#
+                }
+            };
+            if let Some(cache_control) = self.cache_control {
+                response = response.header("cache-control", cache_control);
+            }
+
+            Ok(response.body(body).unwrap().into())
+        })
+    }
+}
+
+fn client(transport: impl HttpTransport + 'static) -> AuthClient {
+    AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(transport)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn settings_are_cached() {
+    let transport = CountingTransport::default();
+    let auth_client = client(transport.clone());
+
+    let settings = auth_client.get_settings_cached().await.unwrap();
+    assert!(settings.external.github);
+
+    // Clones share the cache
+    auth_client.clone().get_settings_cached().await.unwrap();
+    assert_eq!(transport.requests.load(Ordering::SeqCst), 1);
+
+    auth_client.invalidate_settings_cache().await;
+    auth_client.get_settings_cached().await.unwrap();
+    assert_eq!(transport.requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn expired_settings_are_fetched_again() {
+    let transport = CountingTransport::default();
+    let auth_client = client(transport.clone()).with_settings_ttl(Duration::ZERO);
+
+    auth_client.get_settings_cached().await.unwrap();
+    auth_client.get_settings_cached().await.unwrap();
+
+    assert_eq!(transport.requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn expired_settings_are_revalidated_with_etag() {
+    let transport = ScriptedTransport::new(vec![200]);
+    let auth_client = client(transport.clone()).with_settings_ttl(Duration::ZERO);
+
+    let first = auth_client.get_settings_cached().await.unwrap();
+    let second = auth_client.get_settings_cached().await.unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(transport.requests(), [None, Some("\"v1\"".to_string())]);
+}
+
+#[tokio::test]
+async fn max_age_shortens_the_ttl() {
+    let mut transport = ScriptedTransport::new(vec![200]);
+    transport.cache_control = Some("public, max-age=0");
+    let auth_client = client(transport.clone());
+
+    auth_client.get_settings_cached().await.unwrap();
+    auth_client.get_settings_cached().await.unwrap();
+
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn stale_settings_are_served_when_the_server_fails() {
+    let transport = ScriptedTransport::new(vec![200, 503]);
+    let auth_client = client(transport.clone()).with_settings_ttl(Duration::ZERO);
+
+    let settings = auth_client.get_settings_cached().await.unwrap();
+    let stale = auth_client.get_settings_cached().await.unwrap();
+    assert_eq!(settings, stale);
+
+    // Uncached settings can't be served stale
+    let result = client(transport).get_settings_cached().await;
+    assert!(matches!(
+        result,
+        Err(Error::AuthError { status, .. }) if status == StatusCode::SERVICE_UNAVAILABLE
+    ));
+}
+
+#[tokio::test]
+async fn rejected_revalidations_are_not_served_stale() {
+    let transport = ScriptedTransport::new(vec![200, 401]);
+    let auth_client = client(transport.clone()).with_settings_ttl(Duration::ZERO);
+
+    auth_client.get_settings_cached().await.unwrap();
+    let result = auth_client.get_settings_cached().await;
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+use supabase_auth::{
+    managed::ManagedAuthClient,
+    models::{AuthClient, Session},
+    store::{MemorySessionStore, SessionStore},
+};
+
+fn test_session() -> Session {
+    Session {
+        access_token: "access".into(),
+        refresh_token: "refresh".into(),
+        expires_in: 3600,
+        expires_at: DateTime::from_timestamp(Utc::now().timestamp() + 3600, 0).unwrap(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn memory_store_round_trip() {
+    let store = MemorySessionStore::new();
+    let session = test_session();
+
+    assert!(store.load().unwrap().is_none());
+
+    store.save(&session).unwrap();
+
+    assert_eq!(store.load().unwrap(), Some(session));
+
+    store.clear().unwrap();
+
+    assert!(store.load().unwrap().is_none());
+}
+
+#[test]
+fn managed_client_loads_and_saves_through_store() {
+    let store = MemorySessionStore::new();
+    let session = test_session();
+    store.save(&session).unwrap();
+
+    let auth_client = ManagedAuthClient::with_store(
+        AuthClient::new("http://localhost:54321", "api_key", "jwt_secret"),
+        store,
+    )
+    .unwrap();
+
+    assert_eq!(auth_client.session(), Some(session));
+
+    auth_client.clear_session().unwrap();
+
+    assert!(auth_client.session().is_none());
+}
+
+#[cfg(feature = "file-store")]
+#[test]
+fn file_store_round_trip() {
+    use supabase_auth::store::FileSessionStore;
+
+    let path = std::env::temp_dir()
+        .join(format!("supabase-auth-{}", uuid::Uuid::now_v7()))
+        .join("session.json");
+    let store = FileSessionStore::new(&path);
+    let session = test_session();
+
+    assert!(store.load().unwrap().is_none());
+
+    store.save(&session).unwrap();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let loaded = store.load().unwrap().unwrap();
+
+    assert_eq!(loaded.access_token, session.access_token);
+    assert_eq!(loaded.expires_at.timestamp(), session.expires_at.timestamp());
+
+    store.clear().unwrap();
+
+    assert!(store.load().unwrap().is_none());
+
+    std::fs::remove_dir(path.parent().unwrap()).unwrap();
+}
+
+#[cfg(feature = "encrypted-store")]
+#[test]
+fn encrypted_store_round_trip() {
+    use supabase_auth::{
+        error::Error,
+        store::{EncryptedSessionStore, PayloadStore},
+    };
+
+    let store = EncryptedSessionStore::new(MemorySessionStore::new(), [7; 32]);
+    let session = test_session();
+
+    store.save(&session).unwrap();
+
+    let ciphertext = store.inner().load_payload().unwrap().unwrap();
+
+    assert!(!String::from_utf8_lossy(&ciphertext).contains("access"));
+    assert_eq!(store.load().unwrap().unwrap().access_token, session.access_token);
+
+    let wrong_key = EncryptedSessionStore::new(MemorySessionStore::new(), [8; 32]);
+    wrong_key.inner().save_payload(&ciphertext).unwrap();
+
+    assert!(matches!(wrong_key.load(), Err(Error::DecryptionError)));
+}
@@ -0,0 +1,97 @@
+use serde_json::json;
+use supabase_auth::{
+    error::Error,
+    models::{AuthClient, ListUsersParams, User},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+use uuid::Uuid;
+
+/// Serve one response with `body`, sent in chunks of `chunk_size` bytes
+async fn serve(status: u16, body: String, chunk_size: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = [0; 4096];
+        let _ = socket.read(&mut request).await.unwrap();
+
+        let head = format!(
+            "HTTP/1.1 {status} OK\r\ncontent-type: application/json\r\ntransfer-encoding: chunked\r\n\r\n"
+        );
+        socket.write_all(head.as_bytes()).await.unwrap();
+        for chunk in body.as_bytes().chunks(chunk_size) {
+            socket
+                .write_all(format!("{:x}\r\n", chunk.len()).as_bytes())
+                .await
+                .unwrap();
+            socket.write_all(chunk).await.unwrap();
+            socket.write_all(b"\r\n").await.unwrap();
+            socket.flush().await.unwrap();
+        }
+        socket.write_all(b"0\r\n\r\n").await.unwrap();
+    });
+
+    format!("http://{address}")
+}
+
+fn user(email: &str) -> User {
+    User {
+        id: Uuid::now_v7(),
+        email: email.to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn users_are_decoded_as_the_body_arrives() {
+    let mut tricky = user("tricky@example.com");
+    tricky.user_metadata.name = Some(r#"} ] " \ { ["#.to_string());
+    let users = vec![user("first@example.com"), tricky, user("last@example.com")];
+    let body = json!({ "aud": "authenticated", "users": users }).to_string();
+    let auth_client = AuthClient::new(serve(200, body, 7).await, "api_key", "");
+
+    let mut stream = auth_client
+        .admin_list_users_stream(ListUsersParams::default().per_page(3))
+        .await
+        .unwrap();
+
+    let mut streamed = Vec::new();
+    while let Some(user) = stream.next().await {
+        streamed.push(user.unwrap());
+    }
+
+    assert_eq!(streamed, users);
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn truncated_bodies_fail() {
+    let body =
+        json!({ "users": [user("first@example.com"), user("second@example.com")] }).to_string();
+    let truncated = body[..body.len() - 40].to_string();
+    let auth_client = AuthClient::new(serve(200, truncated, 64).await, "api_key", "");
+
+    let stream = auth_client
+        .admin_list_users_stream(ListUsersParams::default())
+        .await
+        .unwrap();
+
+    assert!(matches!(stream.collect().await, Err(Error::ParseError(_))));
+}
+
+#[tokio::test]
+async fn rejected_listings_fail_before_streaming() {
+    let body = r#"{"code":403,"error_code":"not_admin","msg":"User not allowed"}"#.to_string();
+    let auth_client = AuthClient::new(serve(403, body, 16).await, "api_key", "");
+
+    let error = auth_client
+        .admin_list_users_stream(ListUsersParams::default())
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.status().map(|status| status.as_u16()), Some(403));
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "tauri")]
+
+use std::sync::{Arc, Mutex};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use reqwest::{Request, Url};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use supabase_auth::{
+    error::{Error, ErrorCode},
+    models::{AuthClient, Provider, Session},
+    store::{KeyringSessionStore, SessionStore},
+    tauri::DeepLinkOAuth,
+    transport::{HttpTransport, TransportFuture},
+};
+
+const REDIRECT_TO: &str = "com.example.app://auth/callback";
+
+/// Rejects every request, recording the request bodies
+#[derive(Debug, Default, Clone)]
+struct RecordingTransport {
+    bodies: Arc<Mutex<Vec<Value>>>,
+}
+
+impl HttpTransport for RecordingTransport {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let body = request.body().and_then(|body| body.as_bytes()).unwrap();
+            self.bodies
+                .lock()
+                .unwrap()
+                .push(serde_json::from_slice(body).unwrap());
+
+            let response = http::Response::builder()
+                .status(400)
+                .body(r#"{"code":400,"error_code":"bad_code_verifier","msg":"code challenge does not match previously saved code verifier"}"#)
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+fn auth_client(transport: RecordingTransport) -> AuthClient {
+    AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(transport)
+        .build()
+        .unwrap()
+}
+
+fn query_param(url: &Url, name: &str) -> String {
+    url.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn code_is_exchanged_with_the_pkce_verifier() {
+    let transport = RecordingTransport::default();
+    let auth_client = auth_client(transport.clone());
+
+    let oauth = DeepLinkOAuth::start(&auth_client, Provider::Github, REDIRECT_TO, None).unwrap();
+    assert_eq!(query_param(oauth.url(), "redirect_to"), REDIRECT_TO);
+    assert_eq!(query_param(oauth.url(), "code_challenge_method"), "s256");
+
+    let callback = Url::parse(&format!("{REDIRECT_TO}?code=auth-code")).unwrap();
+    assert!(oauth.matches(&callback));
+    assert!(!oauth.matches(&Url::parse("com.example.app://other").unwrap()));
+
+    let error = oauth.complete(&auth_client, &callback).await.unwrap_err();
+    assert_eq!(error.code(), Some(&ErrorCode::BadCodeVerifier));
+
+    let body = transport.bodies.lock().unwrap().remove(0);
+    assert_eq!(body["auth_code"], "auth-code");
+
+    let verifier = body["code_verifier"].as_str().unwrap();
+    assert!((43..=128).contains(&verifier.len()));
+    assert_eq!(
+        URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())),
+        query_param(oauth.url(), "code_challenge")
+    );
+    assert!(!format!("{oauth:?}").contains(verifier));
+}
+
+#[tokio::test]
+async fn redirect_errors_are_returned_without_a_request() {
+    let transport = RecordingTransport::default();
+    let auth_client = auth_client(transport.clone());
+    let oauth = DeepLinkOAuth::start(&auth_client, Provider::Github, REDIRECT_TO, None).unwrap();
+
+    let callback = Url::parse(&format!(
+        "{REDIRECT_TO}?error=access_denied&error_description=The+user+denied+access"
+    ))
+    .unwrap();
+    let error = oauth.complete(&auth_client, &callback).await.unwrap_err();
+    assert!(matches!(error, Error::AuthError { .. }));
+
+    let callback = Url::parse(REDIRECT_TO).unwrap();
+    let error = oauth.complete(&auth_client, &callback).await.unwrap_err();
+    assert!(matches!(error, Error::ParseUrlError));
+
+    assert!(transport.bodies.lock().unwrap().is_empty());
+}
+
+#[test]
+fn keyring_store_saves_and_clears_the_session() {
+    keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+
+    let store = KeyringSessionStore::new("com.example.app", "supabase-session").unwrap();
+    assert!(store.load().unwrap().is_none());
+
+    let session = Session {
+        access_token: "access".into(),
+        refresh_token: "refresh".into(),
+        ..Default::default()
+    };
+    store.save(&session).unwrap();
+    assert_eq!(store.load().unwrap().unwrap().access_token, "access");
+
+    store.clear().unwrap();
+    store.clear().unwrap();
+    assert!(store.load().unwrap().is_none());
+}
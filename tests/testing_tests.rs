@@ -0,0 +1,121 @@
+#![cfg(feature = "testing")]
+
+use supabase_auth::{
+    error::{Error, ErrorCode},
+    models::{EmailSignUpResult, LoginEmailOtpParams, VerifyEmailOtpParams, VerifyOtpParams},
+    testing::MockGoTrue,
+};
+
+const EMAIL: &str = "demo@example.com";
+
+#[tokio::test]
+async fn mocked_sessions_are_signed_with_the_jwt_secret() {
+    let gotrue = MockGoTrue::start().await;
+    gotrue.mock_login(EMAIL).await;
+    gotrue.mock_refresh(EMAIL).await;
+    gotrue.mock_user(EMAIL).await;
+
+    let auth_client = gotrue.client();
+    let session = auth_client
+        .login_with_email(EMAIL, "password")
+        .await
+        .unwrap();
+
+    let claims = auth_client
+        .verify_access_token(&session.access_token)
+        .unwrap();
+    assert_eq!(claims.sub, session.user.id);
+    assert_eq!(claims.email, EMAIL);
+
+    let refreshed = auth_client
+        .refresh_session(&session.refresh_token)
+        .await
+        .unwrap();
+    assert_eq!(refreshed.user.id, session.user.id);
+    assert_ne!(refreshed.refresh_token, session.refresh_token);
+
+    let user = auth_client.get_user(&session.access_token).await.unwrap();
+    assert_eq!(user.id, session.user.id);
+    assert_eq!(user.providers(), vec!["email"]);
+}
+
+#[tokio::test]
+async fn sign_ups_return_a_session_or_a_confirmation() {
+    let gotrue = MockGoTrue::start().await;
+    gotrue.mock_sign_up(EMAIL).await;
+    gotrue
+        .mock_sign_up_with_confirmation("unconfirmed@example.com")
+        .await;
+
+    let auth_client = gotrue.client();
+
+    let result = auth_client
+        .sign_up_with_email_and_password(EMAIL, "password", None)
+        .await
+        .unwrap();
+    assert!(matches!(result, EmailSignUpResult::SessionResult(_)));
+
+    let result = auth_client
+        .sign_up_with_email_and_password("unconfirmed@example.com", "password", None)
+        .await
+        .unwrap();
+    assert!(matches!(result, EmailSignUpResult::ConfirmationResult(_)));
+}
+
+#[tokio::test]
+async fn otps_are_sent_and_verified() {
+    let gotrue = MockGoTrue::start().await;
+    gotrue.mock_otp().await;
+    gotrue.mock_verify_otp(EMAIL).await;
+
+    let auth_client = gotrue.client();
+
+    let response = auth_client
+        .send_email_with_otp(EMAIL, Some(LoginEmailOtpParams::default()))
+        .await
+        .unwrap();
+    assert!(response.message_id.is_none());
+
+    let response = auth_client.send_sms_with_otp("+15555550100").await.unwrap();
+    assert!(response.message_id.is_some());
+
+    let session = auth_client
+        .verify_otp(VerifyOtpParams::Email(VerifyEmailOtpParams {
+            email: EMAIL.to_string(),
+            token: "123456".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    assert_eq!(session.user.email, EMAIL);
+}
+
+#[tokio::test]
+async fn mocked_errors_take_precedence() {
+    let gotrue = MockGoTrue::start().await;
+    gotrue.mock_login(EMAIL).await;
+    gotrue.mock_invalid_credentials().await;
+    gotrue.mock_rate_limited("/signup").await;
+
+    let auth_client = gotrue.client();
+
+    let result = auth_client.login_with_email(EMAIL, "wrong").await;
+    assert!(matches!(
+        result,
+        Err(Error::AuthError {
+            code: Some(ErrorCode::InvalidCredentials),
+            ..
+        })
+    ));
+
+    let result = auth_client
+        .sign_up_with_email_and_password(EMAIL, "password", None)
+        .await;
+    assert!(matches!(
+        result,
+        Err(Error::RateLimited {
+            code: Some(ErrorCode::OverRequestRateLimit),
+            ..
+        })
+    ));
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "tonic")]
+
+mod common;
+
+use tonic::{service::Interceptor, Code, Request};
+
+use supabase_auth::{
+    error::Error,
+    models::{AccessToken, AuthClient, Claims},
+    tonic::SupabaseInterceptor,
+};
+
+use common::{access_token, auth_client};
+
+fn interceptor() -> SupabaseInterceptor {
+    SupabaseInterceptor::new(auth_client()).unwrap()
+}
+
+fn request(authorization: Option<String>) -> Request<()> {
+    let mut request = Request::new(());
+    if let Some(authorization) = authorization {
+        request
+            .metadata_mut()
+            .insert("authorization", authorization.parse().unwrap());
+    }
+
+    request
+}
+
+#[test]
+fn verified_claims_are_inserted_into_extensions() {
+    let request = interceptor()
+        .call(request(Some(format!("Bearer {}", access_token()))))
+        .unwrap();
+
+    let claims = request.extensions().get::<Claims>().unwrap();
+    assert_eq!(claims.email, "demo@example.com");
+    assert!(request.extensions().get::<AccessToken>().is_some());
+}
+
+#[test]
+fn the_bearer_scheme_is_case_insensitive() {
+    let request = interceptor()
+        .call(request(Some(format!("bearer {}", access_token()))))
+        .unwrap();
+
+    assert!(request.extensions().get::<Claims>().is_some());
+}
+
+#[test]
+fn calls_without_a_valid_token_are_unauthenticated() {
+    let status = interceptor().call(request(None)).unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let status = interceptor()
+        .call(request(Some("Bearer not-a-jwt".to_string())))
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let status = interceptor()
+        .call(request(Some(access_token())))
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let status = interceptor()
+        .call(request(Some("Bearer ".to_string())))
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+}
+
+#[test]
+fn anonymous_calls_can_be_allowed() {
+    let mut interceptor = interceptor().allow_anonymous(true);
+
+    let anonymous = interceptor.call(request(None)).unwrap();
+    assert!(anonymous.extensions().get::<Claims>().is_none());
+
+    let status = interceptor
+        .call(request(Some("Bearer not-a-jwt".to_string())))
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+}
+
+#[test]
+fn a_jwt_secret_is_required() {
+    let result = SupabaseInterceptor::new(AuthClient::new("http://localhost:54321", "api_key", ""));
+
+    assert!(matches!(
+        result,
+        Err(Error::MissingConfiguration("jwt_secret"))
+    ));
+}
@@ -0,0 +1,167 @@
+#![cfg(feature = "tower")]
+
+mod common;
+
+use std::{
+    convert::Infallible,
+    future::{ready, Ready},
+    task::{Context, Poll},
+};
+
+use http::{Request, Response, StatusCode};
+use reqwest::ResponseBuilderExt;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use supabase_auth::{
+    models::{AccessToken, AuthClient, Claims},
+    tower::SupabaseAuthLayer,
+    transport::{HttpTransport, TransportFuture},
+};
+
+use common::{access_token, auth_client};
+
+/// Answers with the email of the verified claims, or `anonymous`
+#[derive(Debug, Clone)]
+struct Echo;
+
+impl Service<Request<()>> for Echo {
+    type Response = Response<String>;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<()>) -> Self::Future {
+        assert_eq!(
+            request.extensions().get::<Claims>().is_some(),
+            request.extensions().get::<AccessToken>().is_some()
+        );
+
+        let body = match request.extensions().get::<Claims>() {
+            Some(claims) => claims.email.clone(),
+            None => "anonymous".to_string(),
+        };
+
+        ready(Ok(Response::new(body)))
+    }
+}
+
+fn layer() -> SupabaseAuthLayer {
+    SupabaseAuthLayer::new(auth_client())
+}
+
+fn request(authorization: Option<String>) -> Request<()> {
+    let mut request = Request::builder().uri("/me");
+    if let Some(authorization) = authorization {
+        request = request.header("authorization", authorization);
+    }
+
+    request.body(()).unwrap()
+}
+
+#[tokio::test]
+async fn verified_claims_are_passed_on() {
+    let mut service = layer().layer(Echo);
+
+    let response = service
+        .call(request(Some(format!("Bearer {}", access_token()))))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.body(), "demo@example.com");
+}
+
+#[tokio::test]
+async fn the_bearer_scheme_is_case_insensitive() {
+    let mut service = layer().layer(Echo);
+
+    let response = service
+        .call(request(Some(format!("bearer {}", access_token()))))
+        .await
+        .unwrap();
+
+    assert_eq!(response.body(), "demo@example.com");
+}
+
+#[tokio::test]
+async fn requests_without_a_valid_token_are_rejected() {
+    let mut service = layer().layer(Echo);
+
+    let response = service.call(request(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()["www-authenticate"], "Bearer");
+
+    let response = service
+        .call(request(Some("Bearer not-a-jwt".to_string())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = service
+        .call(request(Some("Bearer ".to_string())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn anonymous_requests_can_be_allowed() {
+    let mut service = layer().allow_anonymous(true).layer(Echo);
+
+    let response = service.call(request(None)).await.unwrap();
+    assert_eq!(response.body(), "anonymous");
+
+    let response = service
+        .call(request(Some("Bearer ".to_string())))
+        .await
+        .unwrap();
+    assert_eq!(response.body(), "anonymous");
+
+    let response = service
+        .call(request(Some("Bearer not-a-jwt".to_string())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+/// Answers every request with `503 Service Unavailable`
+#[derive(Debug)]
+struct Outage;
+
+impl HttpTransport for Outage {
+    fn execute(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let response = Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .url(request.url().clone())
+                .body("")
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+#[tokio::test]
+async fn auth_server_outages_are_reported_as_unavailable() {
+    // Without a JWT secret, tokens are checked by fetching their user from the server
+    let auth_client = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(Outage)
+        .build()
+        .unwrap();
+    let mut service = SupabaseAuthLayer::new(auth_client).layer(Echo);
+
+    let response = service
+        .call(request(Some(format!("Bearer {}", access_token()))))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(!response.headers().contains_key("www-authenticate"));
+}
@@ -0,0 +1,283 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reqwest::Request;
+use serde_json::{json, Value};
+
+use supabase_auth::{
+    error::Error,
+    models::{
+        AuthClient, CompatibilityWarning, LoginWithPasswordOptions, ServerVersion,
+        MIN_SUPPORTED_VERSION,
+    },
+    retry::RetryPolicy,
+    transport::{HttpTransport, TransportFuture},
+};
+
+const HEALTH: &str = r#"{"version":"v2.0.0","name":"GoTrue","description":"GoTrue is a user registration and authentication API"}"#;
+
+/// Answers requests with canned responses, recording the requests
+#[derive(Debug, Default, Clone)]
+struct FakeTransport {
+    statuses: Arc<Mutex<Vec<u16>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeTransport {
+    fn answering(statuses: &[u16]) -> Self {
+        FakeTransport {
+            statuses: Arc::new(Mutex::new(statuses.iter().rev().copied().collect())),
+            ..Default::default()
+        }
+    }
+}
+
+impl HttpTransport for FakeTransport {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            self.requests.lock().unwrap().push(format!(
+                "{} {}",
+                request.method(),
+                request.url().path()
+            ));
+
+            let status = self.statuses.lock().unwrap().pop().unwrap_or(200);
+            let response = http::Response::builder()
+                .status(status)
+                .body(HEALTH)
+                .unwrap();
+
+            Ok(response.into())
+        })
+    }
+}
+
+#[tokio::test]
+async fn requests_are_sent_through_the_transport() {
+    let transport = FakeTransport::default();
+
+    let auth_client = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+
+    let health = auth_client.get_health().await.unwrap();
+
+    assert_eq!(health.name, "GoTrue");
+    assert_eq!(
+        *transport.requests.lock().unwrap(),
+        vec!["GET /auth/v1/health"]
+    );
+}
+
+#[tokio::test]
+async fn oauth_client_ids_are_encoded_in_the_path() {
+    let transport = FakeTransport::answering(&[404]);
+
+    let auth_client = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+
+    let _ = auth_client.admin_get_oauth_client("../users?x#y").await;
+    let dot_dot = auth_client.admin_delete_oauth_client("..").await;
+
+    assert!(matches!(dot_dot, Err(Error::ParseUrlError)));
+    assert_eq!(
+        *transport.requests.lock().unwrap(),
+        vec!["GET /auth/v1/admin/oauth/clients/..%2Fusers%3Fx%23y"]
+    );
+}
+
+#[tokio::test]
+async fn transport_responses_are_retried() {
+    let transport = FakeTransport::answering(&[503, 503]);
+
+    let auth_client = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(transport.clone())
+        .retry_policy(RetryPolicy {
+            jitter: false,
+            initial_backoff: std::time::Duration::from_millis(1),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+
+    auth_client.get_health().await.unwrap();
+
+    assert_eq!(transport.requests.lock().unwrap().len(), 3);
+}
+
+#[test]
+fn transport_and_proxy_conflict() {
+    let result = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(FakeTransport::default())
+        .proxy(reqwest::Proxy::all("http://localhost:3128").unwrap())
+        .build();
+
+    assert!(matches!(
+        result,
+        Err(Error::ConflictingConfiguration("proxy"))
+    ));
+}
+
+#[tokio::test]
+async fn transport_errors_are_returned() {
+    #[derive(Debug)]
+    struct Unreachable;
+
+    impl HttpTransport for Unreachable {
+        fn execute(&self, _request: Request) -> TransportFuture<'_> {
+            Box::pin(async { Err(Error::InternalError) })
+        }
+    }
+
+    let auth_client = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(Unreachable)
+        .build()
+        .unwrap();
+
+    let result = auth_client.get_health().await;
+
+    assert!(matches!(result, Err(Error::InternalError)));
+}
+
+#[tokio::test]
+async fn login_options_are_sent_with_the_credentials() {
+    #[derive(Debug, Default, Clone)]
+    struct BodyRecorder(Arc<Mutex<Vec<Value>>>);
+
+    impl HttpTransport for BodyRecorder {
+        fn execute(&self, request: Request) -> TransportFuture<'_> {
+            Box::pin(async move {
+                let body = request.body().and_then(|body| body.as_bytes()).unwrap();
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(serde_json::from_slice(body).unwrap());
+
+                let response = http::Response::builder().status(400).body("").unwrap();
+                Ok(response.into())
+            })
+        }
+    }
+
+    let transport = BodyRecorder::default();
+    let auth_client = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+
+    let options = LoginWithPasswordOptions::default().captcha_token("captcha");
+    let _ = auth_client
+        .login_with_email("demo@example.com", "password")
+        .await;
+    let _ = auth_client
+        .login_with_email_with_options("demo@example.com", "password", options.clone())
+        .await;
+    let _ = auth_client
+        .login_with_phone_with_options("+15555550100", "password", options)
+        .await;
+
+    assert_eq!(
+        *transport.0.lock().unwrap(),
+        [
+            json!({ "email": "demo@example.com", "password": "password" }),
+            json!({
+                "email": "demo@example.com",
+                "password": "password",
+                "gotrue_meta_security": { "captcha_token": "captcha" },
+            }),
+            json!({
+                "phone": "+15555550100",
+                "password": "password",
+                "gotrue_meta_security": { "captcha_token": "captcha" },
+            }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn wait_until_healthy_polls_until_the_server_responds() {
+    let transport = FakeTransport::answering(&[503, 503]);
+
+    let auth_client = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+
+    let health = auth_client
+        .wait_until_healthy(Duration::from_secs(5), Duration::from_millis(1))
+        .await
+        .unwrap();
+
+    assert_eq!(health.name, "GoTrue");
+    assert_eq!(transport.requests.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn wait_until_healthy_gives_up_after_the_timeout() {
+    let transport = FakeTransport::answering(&[503; 1000]);
+
+    let auth_client = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(transport)
+        .build()
+        .unwrap();
+
+    let result = auth_client
+        .wait_until_healthy(Duration::from_millis(50), Duration::from_millis(10))
+        .await;
+
+    let Err(Error::Unhealthy {
+        last_error: Some(last_error),
+        ..
+    }) = result
+    else {
+        panic!("expected Error::Unhealthy, got {result:?}");
+    };
+    assert!(matches!(*last_error, Error::AuthError { status, .. } if status == 503));
+}
+
+#[tokio::test]
+async fn outdated_servers_are_reported() {
+    let auth_client = AuthClient::builder()
+        .project_url("http://project.supabase.invalid")
+        .api_key("api_key")
+        .transport(FakeTransport::default())
+        .build()
+        .unwrap();
+
+    let warnings = auth_client.check_compatibility().await.unwrap();
+
+    assert_eq!(
+        warnings[0],
+        CompatibilityWarning::Unsupported {
+            version: ServerVersion::new(2, 0, 0),
+            minimum: MIN_SUPPORTED_VERSION,
+        }
+    );
+    assert!(warnings[1..]
+        .iter()
+        .all(|warning| matches!(warning, CompatibilityWarning::MissingChange { .. })));
+    assert!(warnings[1]
+        .to_string()
+        .starts_with("GoTrue v2.0.0 predates"));
+}